@@ -1,14 +1,14 @@
-use std::{path::PathBuf, env};
+use std::{collections::HashMap, path::{Path, PathBuf}, env, sync::atomic::{AtomicU64, Ordering}};
 
-use pandoc::{OutputKind, InputKind, PandocOutput, PandocError};
+use pandoc::{OutputKind, InputKind, PandocOutput, PandocError, InputFormat, OutputFormat, PandocOption};
 use tokio::{fs::{File, self}, io::AsyncWriteExt, io::AsyncBufRead};
 use tonic::{Request, Response, Status};
-use sea_orm::{entity::*, query::*};
-
+use sea_orm::{entity::*, query::*, sea_query::Expr};
+use docuvault_events::publish_event;
 
 use crate::{AppState, entity, error::GlobalError, grpc::upload::{upload_client::UploadClient, UploadRequest, PreUploadRequest}};
 
-use self::convert::{convert_server::Convert, ConvertRequest, ConvertResponse};
+use self::convert::{convert_server::Convert, ConvertRequest, ConvertResponse, ImportRequest, ImportResponse};
 
 pub mod convert {
     use tonic::include_proto;
@@ -51,6 +51,158 @@ fn pandoc_creation(req: &ConvertRequest) -> Result<PandocOutput, PandocError> {
     pandoc.execute() 
 }
 
+// converted with pandoc and stored in the file server, the object id of the result
+async fn produce(req: &ConvertRequest) -> Result<String, String> {
+    pandoc_creation(req).map_err(|e| e.to_string())?;
+    let bytes = fs::read(format!("output.{}",extension(req.c_type))).await.map_err(|e| e.to_string())?;
+
+    let file_proxy_addr = env::var("FILE_PROXY_ADDR").map_err(|_| "file proxy addr is not set.".to_string())?;
+    let mut upload_client = UploadClient::connect(file_proxy_addr).await.map_err(|e| e.to_string())?;
+    let res = upload_client.pre_upload(Request::new(PreUploadRequest{
+        name: format!("{}.{}",req.title,extension(req.c_type)), 
+        docuser_id: req.docuser_id,
+        ftype: ftype(req.c_type).to_owned(),
+        size: bytes.len() as u64,
+        data: bytes,
+        object_id: None,
+    })).await.map_err(|e| e.to_string())?.into_inner();
+    upload_client.upload(Request::new(UploadRequest { object_id: res.object_id.clone(), doc_id: req.doc_id })).await.map_err(|e| e.to_string())?;
+    Ok(res.object_id)
+}
+
+pub(crate) fn import_format(i_type: i32) -> Option<(InputFormat, &'static str)> {
+    match i_type {
+        0 => Some((InputFormat::Docx, "docx")),
+        1 => Some((InputFormat::Html, "html")),
+        2 => Some((InputFormat::Odt, "odt")),
+        3 => Some((InputFormat::Rst, "rst")),
+        4 => Some((InputFormat::Latex, "tex")),
+        _ => None,
+    }
+}
+fn media_ftype(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "emf" => "image/emf",
+        "wmf" => "image/wmf",
+        _ => "application/octet-stream",
+    }
+}
+
+// every import works in a directory of its own, removed once it is done
+static IMPORTS: AtomicU64 = AtomicU64::new(0);
+
+fn files(dir: &Path, found: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => files(&path, found)?,
+            false => found.push(path),
+        }
+    }
+    Ok(())
+}
+
+/*
+ * pandoc from the given format to github flavored markdown, embedded images are written to <dir>/media.
+ * the markdown links to them by that path, the paths of the images found are returned along with it.
+ * the input is untrusted: pandoc runs sandboxed(--sandbox, pandoc 3), it reads no file but the input
+ * and fetches nothing, images linked by a path or url are left as links and never extracted.
+ */
+pub(crate) fn pandoc_import(dir: &Path, input: &Path, format: InputFormat) -> Result<(String, Vec<PathBuf>), String> {
+    let media_dir = dir.join("media");
+    let mut pandoc = pandoc::new();
+    pandoc.set_input(InputKind::Files(vec![input.to_path_buf()]));
+    pandoc.set_input_format(format, vec![]);
+    pandoc.arg("sandbox", "true");
+    pandoc.set_output_format(OutputFormat::Other("gfm".to_string()), vec![]);
+    pandoc.add_option(PandocOption::ExtractMedia(media_dir.clone()));
+    pandoc.set_output(OutputKind::Pipe);
+    let markdown = match pandoc.execute().map_err(|e| e.to_string())? {
+        PandocOutput::ToBuffer(markdown) => markdown,
+        _ => return Err("pandoc did not write to the pipe".to_string()),
+    };
+    let mut media = Vec::new();
+    files(&media_dir, &mut media).map_err(|e| e.to_string())?;
+    Ok((markdown, media))
+}
+
+/*
+ * links to the extracted images replaced with the file server's. only whole targets are replaced,
+ * of markdown links and images(](<path>) and of html src attributes(src="<path>"), as pandoc writes them.
+ */
+pub(crate) fn rewrite_media(markdown: &str, uploaded: &[(String, String)]) -> String {
+    let targets = uploaded.iter().map(|(path, object_id)| (path.as_str(), object_id.as_str())).collect::<HashMap<_, _>>();
+    let mut rewritten = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+    loop {
+        let next = ["](", "src=\""].iter()
+            .filter_map(|opening| rest.find(opening).map(|at| at + opening.len()))
+            .min();
+        let start = match next {
+            Some(start) => start,
+            None => break,
+        };
+        rewritten.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c: char| matches!(c, ')' | '"' | ' ' | '\n')).unwrap_or(rest.len());
+        match targets.get(&rest[..end]) {
+            Some(object_id) => rewritten.push_str(&format!("file/{}", object_id)),
+            None => rewritten.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    rewritten.push_str(rest);
+    rewritten
+}
+
+async fn import(dir: &Path, req: ImportRequest) -> Result<ImportResponse, Status> {
+    let (format, extension) = import_format(req.i_type).ok_or(Status::invalid_argument("unsupported import type"))?;
+    fs::create_dir_all(dir).await.map_err(|err| Status::from(GlobalError::from(err)))?;
+    let input = dir.join(format!("source.{}", extension));
+    fs::write(&input, &req.data).await.map_err(|err| Status::from(GlobalError::from(err)))?;
+
+    // pandoc blocks until it is done
+    let cloned_dir = dir.to_path_buf();
+    let (markdown, media) = tokio::task::spawn_blocking(move || pandoc_import(&cloned_dir, &input, format))
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::invalid_argument(format!("import failed: {}", e)))?;
+
+    let mut uploaded = Vec::new();
+    if !media.is_empty() {
+        let file_proxy_addr = env::var("FILE_PROXY_ADDR").map_err(|_| Status::internal("file proxy addr is not set."))?;
+        let mut upload_client = UploadClient::connect(file_proxy_addr).await.map_err(|e| Status::internal(e.to_string()))?;
+        for path in media {
+            let data = fs::read(&path).await.map_err(|err| Status::from(GlobalError::from(err)))?;
+            let res = upload_client.pre_upload(Request::new(PreUploadRequest {
+                name: path.file_name().and_then(|name| name.to_str()).unwrap_or("image").to_string(),
+                docuser_id: req.docuser_id,
+                ftype: media_ftype(&path).to_owned(),
+                size: data.len() as u64,
+                data,
+                object_id: None,
+            })).await?.into_inner();
+            uploaded.push((path.to_string_lossy().to_string(), res.object_id));
+        }
+    }
+    let object_ids = uploaded.iter().map(|(_, object_id)| object_id.clone()).collect();
+    Ok(ImportResponse {
+        markdown: rewrite_media(&markdown, &uploaded),
+        object_ids,
+    })
+}
+
 #[tonic::async_trait]
 impl Convert for ConvertService {
     async fn convert(&self, request: Request<ConvertRequest>) -> Result<Response<ConvertResponse>, Status> {
@@ -63,75 +215,67 @@ impl Convert for ConvertService {
             status: Set(0),
             ..Default::default()
         }.insert(&self.state.db_conn).await.map_err(|err|GlobalError::from(err));
-        let mut convertres = match convertres {
-            Ok(model) => model.into_active_model(),
+        let (convert_id, mut convertres) = match convertres {
+            Ok(model) => (model.id, model.into_active_model()),
             Err(e) => return Err(e.into()),
         };
         
         let db_conn = self.state.db_conn.clone();
+        let redis_conn = self.state.redis_conn.clone();
 
         tokio::spawn(async move {
-            match pandoc_creation(&req) {
-                Ok(_) => {
-                    match fs::read(format!("output.{}",extension(req.c_type))).await {
-                        Ok(bytes) => {
-                            let file_proxy_addr = env::var("FILE_PROXY_ADDR").expect("file proxy addr is not set.");
-                            let mut upload_client = UploadClient::connect(file_proxy_addr).await.unwrap();
-
-                            match upload_client.pre_upload(Request::new(PreUploadRequest{
-                                name: format!("{}.{}",req.title,extension(req.c_type)), 
-                                docuser_id: req.docuser_id,
-                                ftype: ftype(req.c_type).to_owned(),
-                                size: bytes.len() as u64,
-                                data: bytes,
-                                object_id: None,
-                            })).await {
-                                Ok(res) => {
-                                    let res = res.into_inner();
-                                    match upload_client.upload(Request::new(UploadRequest { object_id: res.object_id.clone(), doc_id: req.doc_id })).await {
-                                        Ok(_) => {
-                                            convertres.data = Set(Some(res.object_id));
-                                            convertres.status = Set(1);
-                                            convertres.update(&db_conn).await.unwrap();
-                                        },
-                                        Err(e) => {
-                                            dbg!(e);
-                                            convertres.status = Set(2);
-                                            convertres.update(&db_conn).await.unwrap();
-                                        }
-                                    }
-
-                                },
-                                Err(e) => {
-                                    dbg!(e);
-                                    convertres.status = Set(2);
-                                    convertres.update(&db_conn).await.unwrap();
-                                }
-                            }
-
-
-                        },
-                        Err(e) => {
-                            dbg!(e);
-                            convertres.status = Set(2);
-                            convertres.update(&db_conn).await.unwrap();
-                        }
-                    }
-                }
+            let event = match produce(&req).await {
+                Ok(object_id) => {
+                    convertres.data = Set(Some(object_id));
+                    convertres.status = Set(1);
+                    "conversion_finished"
+                },
                 Err(e) => {
                     dbg!(e);
                     convertres.status = Set(2);
-                    convertres.update(&db_conn).await.unwrap();
+                    "conversion_failed"
                 }
-
-            }
-
+            };
+            // a result that could not be recorded leaves the row failed, as far as the database lets
+            let event = match convertres.update(&db_conn).await.map_err(GlobalError::from) {
+                Ok(_) => event,
+                Err(_) => {
+                    let res = entity::convert::Entity::update_many()
+                        .col_expr(entity::convert::Column::Status, Expr::value(2))
+                        .filter(entity::convert::Column::Id.eq(convert_id))
+                        .exec(&db_conn)
+                        .await;
+                    if let Err(e) = res {
+                        dbg!(e);
+                    }
+                    "conversion_failed"
+                },
+            };
+            // a conversion runs once per document and type, the row id tells the event apart
+            let id = format!("conversion-{}-{}-{}", req.doc_id, req.c_type, convert_id);
+            publish_event(&redis_conn, req.docuser_id, format!(r#"{{"id":"{}","type":"{}","doc_id":{},"c_type":{}}}"#, id, event, req.doc_id, req.c_type)).await;
         });
 
         return Ok(Response::new(ConvertResponse {
             msg: "hello".to_owned(),
         }));
     }
+
+    /*
+     * runs while the caller waits, the images are pre uploaded only.
+     * the caller creates the document and fixes them to it through Upload.
+     */
+    async fn import(&self, request: Request<ImportRequest>) -> Result<Response<ImportResponse>, Status> {
+        let req = request.into_inner();
+        let dir = env::temp_dir().join(format!("docuvault-import-{}-{}", std::process::id(), IMPORTS.fetch_add(1, Ordering::Relaxed)));
+        let res = import(&dir, req).await;
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir).await {
+                dbg!(e);
+            }
+        }
+        Ok(Response::new(res?))
+    }
 }
 
 
@@ -5,7 +5,9 @@ mod tests {
     use tokio::time::sleep;
     use tonic::{transport::Server, Request};
 
-    use crate::{AppState, db, apis::convert::{ConvertService, convert::{convert_server::ConvertServer, convert_client::ConvertClient, ConvertRequest}}};
+    use pandoc::InputFormat;
+
+    use crate::{AppState, db, apis::convert::{ConvertService, pandoc_import, rewrite_media, import_format, convert::{convert_server::ConvertServer, convert_client::ConvertClient, ConvertRequest}}};
     static INIT: Once = Once::new();
     fn initialize() {
         INIT.call_once(|| {
@@ -67,4 +69,42 @@ mod tests {
         //initialize();     
     }
 
+    // the input is untrusted, images linked by a local path or an url are neither read nor fetched
+    #[test]
+    fn import_sandbox_test(){
+        let dir = env::temp_dir().join(format!("docuvault-import-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("source.html");
+        std::fs::write(&input, r#"<p>hello</p><img src="/etc/hostname"><img src="http://169.254.169.254/latest/meta-data/image.png">"#).unwrap();
+        let res = pandoc_import(&dir, &input, InputFormat::Html);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (markdown, media) = res.unwrap();
+        assert!(media.is_empty());
+        assert!(markdown.contains("](/etc/hostname)"));
+        assert!(markdown.contains("](http://169.254.169.254/latest/meta-data/image.png)"));
+    }
+
+    #[test]
+    fn rewrite_media_test(){
+        let uploaded = vec![
+            ("/tmp/i/media/image1.png".to_owned(), "ab".to_owned()),
+            ("/tmp/i/media/image10.png".to_owned(), "cd".to_owned()),
+        ];
+        let markdown = "![](/tmp/i/media/image1.png)\n\n[link](/tmp/i/media/image10.png \"title\")\n\n<img src=\"/tmp/i/media/image10.png\" style=\"width:2in\" />\n\n`/tmp/i/media/image1.png` stays as written, so does ![](/tmp/i/media/image2.png)\n";
+        assert_eq!(
+            rewrite_media(markdown, &uploaded),
+            "![](file/ab)\n\n[link](file/cd \"title\")\n\n<img src=\"file/cd\" style=\"width:2in\" />\n\n`/tmp/i/media/image1.png` stays as written, so does ![](/tmp/i/media/image2.png)\n",
+        );
+        assert_eq!(rewrite_media("no links](", &uploaded), "no links](");
+    }
+
+    #[test]
+    fn import_format_test(){
+        let format = |i_type| import_format(i_type).map(|(format, extension)| (format.to_string(), extension));
+        assert_eq!(format(0), Some(("docx".to_owned(), "docx")));
+        assert_eq!(format(1), Some(("html".to_owned(), "html")));
+        assert_eq!(format(4), Some(("latex".to_owned(), "tex")));
+        assert_eq!(format(5), None);
+    }
+
 }
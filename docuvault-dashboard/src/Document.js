@@ -49,6 +49,7 @@ const Convert = styled(Button)(({ theme }) => ({
 
 export default function Document({ setToastOpen }) {
     const access_token = useSelector((state) => state.auth.access_token);
+    const scope_ids = useSelector((state) => state.resource.scope_ids.map((elem) => elem[0]));
     const dispatch = useDispatch();
     const { isSuccess, publish_token, msg } = useLoaderData();
     const [document, setDocument] = useState({});
@@ -101,6 +102,27 @@ export default function Document({ setToastOpen }) {
             extension: "json",
         }
     ];
+    // converted files are served through a share link of their own c_type
+    let open_converted = async (c_type) => {
+        try {
+            const res = await axios.post("http://localhost:8000/document/publish",
+                {
+                    doc_id: document.id,
+                    scope_ids: scope_ids,
+                    c_type,
+                },
+                {
+                    headers: {
+                        Authorization: `Bearer ${access_token}`,
+                    },
+                    withCredentials: true,
+                });
+            window.location.href = `http://localhost:8000/document/${res.data.publish_token}`;
+        } catch (e) {
+            dispatch(opfail({ msg: "publish failed" }));
+            setToastOpen(true);
+        }
+    }
     let convert_request = async (c_type) => {
         try {
             const res = await axios.post("http://localhost:8000/document/convert", 
@@ -149,7 +171,7 @@ export default function Document({ setToastOpen }) {
                             color="success"
                             onClick={(e) => {
                                 e.preventDefault();
-                                window.location.href = `http://localhost:8000/file/${target.object_id}`;
+                                open_converted(target.c_type);
                             }}
                             >
                             {target.extension}
@@ -106,7 +106,7 @@ function List({ setToastOpen }) {
                 .filter(([key, value]) => value == true)
                 .map(([key, value]) => Number(key));
             let res = await axios.post(
-                "http://localhost:8000/resource/tag",
+                "http://localhost:8000/resource/v2/tag",
                 {
                     scope_ids: req,
                 },
@@ -118,7 +118,7 @@ function List({ setToastOpen }) {
                 }
             );
             let tmptags = {};
-            res.data.forEach((elem) => {
+            res.data.items.forEach((elem) => {
                 tmptags[`${elem.id}`] = {
                     name: elem.value,
                     status: true,
@@ -151,7 +151,7 @@ function List({ setToastOpen }) {
             }
 
             let res = await axios.post(
-                "http://localhost:8000/resource/list",
+                "http://localhost:8000/resource/v2/list",
                 body
                 ,
                 {
@@ -162,7 +162,7 @@ function List({ setToastOpen }) {
                 }
             );
 
-            setList(res.data);
+            setList(res.data.items);
             dispatch(oppass({ msg: "document list fetch success" }));
             setToastOpen(true);
         } catch (e) {
@@ -28,7 +28,7 @@ function SequenceList({ setToastOpen }) {
         const get_sequence_list = async () => {
             try {
                 let res = await axios.post(
-                    "http://localhost:8000/resource/sequence/list",
+                    "http://localhost:8000/resource/v2/sequence/list",
                     {
                         scope_ids,
                         seq_id: Number(seq_id),
@@ -40,8 +40,8 @@ function SequenceList({ setToastOpen }) {
                         withCredentials: true,
                     }
                 );
-                listRef.current = res.data;
-                setList(res.data);
+                listRef.current = res.data.items;
+                setList(res.data.items);
                 dispatch(oppass({ msg: "document list fetch success" }));
                 setToastOpen(true);
             } catch (e) {
@@ -0,0 +1,22 @@
+use bb8::Pool;
+use bb8_redis::RedisConnectionManager;
+use redis::AsyncCommands;
+
+/*
+ * server events of docuvault-file and docuvault-convert for docuvault-main to stream to the user
+ * (see its modules::events). they are published on events:<docuser_id> like the ones of docuvault-main.
+ * data is the json of the event, a failed publish is only logged.
+ */
+pub async fn publish_event(redis_conn: &Pool<RedisConnectionManager>, docuser_id: i32, data: String) {
+    let mut con = match redis_conn.get().await {
+        Ok(con) => con,
+        Err(e) => {
+            dbg!(e);
+            return;
+        }
+    };
+    let res: Result<(), _> = con.publish(format!("events:{}", docuser_id), data).await;
+    if let Err(e) = res {
+        dbg!(e);
+    }
+}
@@ -1,3 +1,7 @@
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt, stream};
+use tokio::io::AsyncReadExt;
 use tonic::{Request, Response, Status};
 use sea_orm::{entity::*, query::*};
 use sea_orm::EntityTrait;
@@ -11,6 +15,8 @@ pub mod download {
     include_proto!("download");
 }
 
+const CHUNK_SIZE: usize = 64 * 1024;
+
 #[derive(Debug)]
 pub struct DownloadService {
     pub state: AppState,
@@ -18,6 +24,8 @@ pub struct DownloadService {
 
 #[tonic::async_trait]
 impl Download for DownloadService {
+    type DownloadStreamStream = Pin<Box<dyn Stream<Item = Result<DownloadChunk, Status>> + Send>>;
+
     async fn download(&self, request: Request<DownloadRequest>) -> Result<Response<DownloadResponse>, Status> {
         let req = request.into_inner();
         let key = req.object_id;
@@ -59,6 +67,56 @@ impl Download for DownloadService {
             data,
         }))
     }
-}
 
+    // large files are not cached, they are read from disk chunk by chunk
+    async fn download_stream(&self, request: Request<DownloadRequest>) -> Result<Response<Self::DownloadStreamStream>, Status> {
+        let key = request.into_inner().object_id;
+        let mut file_schema = DocFile::new(RedisSchemaHeader{
+            key: key.clone(),
+            con: self.state.redis_conn.clone(),
+            expire_at: None,
+        });
+        file_schema.get_all().await?;
+
+        if file_schema.name.is_some() {
+            let chunk = DownloadChunk {
+                name: file_schema.name.unwrap(),
+                ftype: file_schema.ftype.unwrap(),
+                size: file_schema.size.unwrap(),
+                data: file_schema.data.unwrap(),
+            };
+            return Ok(Response::new(Box::pin(stream::iter(vec![Ok(chunk)]))));
+        }
+
+        let docfile = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::ObjectId.eq(key))
+            .one(&self.state.db_conn)
+            .await.map_err(|err|GlobalError::from(err))?;
+        if docfile.is_none() {
+            return Err(GlobalError::ObjectNotExist.into());
+        }
+        let docfile = docfile.unwrap();
+        let file = tokio::fs::File::open(docfile.uri.unwrap()).await.map_err(|err|GlobalError::from(err))?;
 
+        let head = DownloadChunk {
+            name: docfile.name,
+            ftype: docfile.ftype,
+            size: docfile.size as u64,
+            data: vec![],
+        };
+        let chunks = stream::unfold(Some(file), |file| async move {
+            let mut file = file?;
+            let mut data = vec![0; CHUNK_SIZE];
+            match file.read(&mut data).await {
+                Ok(0) => None,
+                Ok(len) => {
+                    data.truncate(len);
+                    Some((Ok(DownloadChunk { data, ..Default::default() }), Some(file)))
+                },
+                // the stream ends after an error
+                Err(err) => Some((Err(Status::from(GlobalError::from(err))), None)),
+            }
+        });
+        Ok(Response::new(Box::pin(stream::iter(vec![Ok(head)]).chain(chunks))))
+    }
+}
@@ -6,6 +6,8 @@ use sea_orm::{entity::*, query::*};
 use sea_orm::{Set, ActiveModelTrait, EntityTrait};
 use upload::upload_server::Upload;
 
+use docuvault_events::publish_event;
+
 use crate::{AppState, db::schema::redis::{DocFile, RedisSchemaHeader}, entity, error::GlobalError};
 
 use self::upload::{UploadRequest, UploadResponse, PreUploadRequest, PreUploadResponse};
@@ -84,6 +86,12 @@ impl Upload for UploadService {
 
             docfile.update(&db_conn).await.expect("db connection failed");
 
+            let event = match status {
+                1 => "upload_finished",
+                _ => "upload_failed",
+            };
+            publish_event(&redis_conn, req.docuser_id, format!(r#"{{"id":"upload-{}","type":"{}","object_id":"{}"}}"#, object_id, event, object_id)).await;
+
             if req.size < 1024*1024*50 {
                 let mut file_schema = DocFile::new(RedisSchemaHeader{
                     key: object_id.clone(),
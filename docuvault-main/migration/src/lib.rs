@@ -1,12 +1,56 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20230801_000001_add_docorg_meta;
+mod m20230802_000001_create_template;
+mod m20230803_000001_create_comment;
+mod m20230804_000001_create_export_job;
+mod m20230805_000001_create_site;
+mod m20230806_000001_create_share_link;
+mod m20230807_000001_add_share_link_protection;
+mod m20230808_000001_create_theme;
+mod m20230809_000001_add_scope_public;
+mod m20230810_000001_add_render_profile;
+mod m20230811_000001_add_docorg_outline;
+mod m20230812_000001_add_docorg_stats;
+mod m20230813_000001_create_saved_query;
+mod m20230814_000001_create_link_check;
+mod m20230815_000001_create_webhook;
+mod m20230816_000001_create_audit_log;
+mod m20230817_000001_add_lint_config;
+mod m20230818_000001_create_publish_schedule;
+mod m20230819_000001_create_scope_member;
+mod m20230820_000001_add_site_lease;
+mod m20230821_000001_add_saved_query_public;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20230801_000001_add_docorg_meta::Migration),
+            Box::new(m20230802_000001_create_template::Migration),
+            Box::new(m20230803_000001_create_comment::Migration),
+            Box::new(m20230804_000001_create_export_job::Migration),
+            Box::new(m20230805_000001_create_site::Migration),
+            Box::new(m20230806_000001_create_share_link::Migration),
+            Box::new(m20230807_000001_add_share_link_protection::Migration),
+            Box::new(m20230808_000001_create_theme::Migration),
+            Box::new(m20230809_000001_add_scope_public::Migration),
+            Box::new(m20230810_000001_add_render_profile::Migration),
+            Box::new(m20230811_000001_add_docorg_outline::Migration),
+            Box::new(m20230812_000001_add_docorg_stats::Migration),
+            Box::new(m20230813_000001_create_saved_query::Migration),
+            Box::new(m20230814_000001_create_link_check::Migration),
+            Box::new(m20230815_000001_create_webhook::Migration),
+            Box::new(m20230816_000001_create_audit_log::Migration),
+            Box::new(m20230817_000001_add_lint_config::Migration),
+            Box::new(m20230818_000001_create_publish_schedule::Migration),
+            Box::new(m20230819_000001_create_scope_member::Migration),
+            Box::new(m20230820_000001_add_site_lease::Migration),
+            Box::new(m20230821_000001_add_saved_query_public::Migration),
+        ]
     }
 }
@@ -0,0 +1,48 @@
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // custom front matter keys, queried with jsonb operators(@>, ?)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::Meta).json_binary())
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("CREATE INDEX IF NOT EXISTS idx_docorg_meta ON docorg USING GIN (meta)")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_docorg_meta")
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .drop_column(Docorg::Meta)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Meta,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Template::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Template::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Template::DocuserId).integer().not_null())
+                    // null: private template of docuser, otherwise shared with the scope
+                    .col(ColumnDef::new(Template::ScopeId).integer())
+                    .col(ColumnDef::new(Template::Name).string().not_null())
+                    .col(ColumnDef::new(Template::Raw).string().not_null())
+                    // default values applied to the instantiated document
+                    .col(ColumnDef::new(Template::Tags).json_binary().not_null())
+                    .col(ColumnDef::new(Template::ScopeIds).json_binary().not_null())
+                    .col(ColumnDef::new(Template::SeqId).integer())
+                    .col(ColumnDef::new(Template::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Template::Table, Template::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Template::Table, Template::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Template::Table, Template::SeqId)
+                        .to(Sequence::Table, Sequence::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Template::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Template {
+    Table,
+    Id,
+    DocuserId,
+    ScopeId,
+    Name,
+    Raw,
+    Tags,
+    ScopeIds,
+    SeqId,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Sequence {
+    Table,
+    Id,
+}
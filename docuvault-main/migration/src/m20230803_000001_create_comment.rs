@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CommentThread::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CommentThread::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CommentThread::DocorgId).integer().not_null())
+                    .col(ColumnDef::new(CommentThread::DocuserId).integer().not_null())
+                    // both null: thread on the whole document
+                    .col(ColumnDef::new(CommentThread::HeadingPath).json_binary())
+                    .col(ColumnDef::new(CommentThread::Quote).string())
+                    //0: open, 1: resolved
+                    .col(ColumnDef::new(CommentThread::Status).integer().not_null().default(0))
+                    // anchor text no longer exists in the document
+                    .col(ColumnDef::new(CommentThread::Outdated).boolean().not_null().default(false))
+                    .col(ColumnDef::new(CommentThread::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(CommentThread::UpdatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(CommentThread::Table, CommentThread::DocorgId)
+                        .to(Docorg::Table, Docorg::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(CommentThread::Table, CommentThread::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Comment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Comment::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Comment::ThreadId).integer().not_null())
+                    .col(ColumnDef::new(Comment::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(Comment::Body).string().not_null())
+                    .col(ColumnDef::new(Comment::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(Comment::UpdatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Comment::Table, Comment::ThreadId)
+                        .to(CommentThread::Table, CommentThread::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Comment::Table, Comment::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Comment::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CommentThread::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum CommentThread {
+    Table,
+    Id,
+    DocorgId,
+    DocuserId,
+    HeadingPath,
+    Quote,
+    Status,
+    Outdated,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Comment {
+    Table,
+    Id,
+    ThreadId,
+    DocuserId,
+    Body,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExportJob::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ExportJob::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ExportJob::DocuserId).integer().not_null())
+                    // empty: whole account
+                    .col(ColumnDef::new(ExportJob::ScopeIds).json_binary().not_null())
+                    .col(ColumnDef::new(ExportJob::Status).integer().not_null().default(0))
+                    .col(ColumnDef::new(ExportJob::Processed).integer().not_null().default(0))
+                    .col(ColumnDef::new(ExportJob::Total).integer().not_null().default(0))
+                    // object id of the archive, downloadable through /file/:object_id
+                    .col(ColumnDef::new(ExportJob::ObjectId).string())
+                    .col(ColumnDef::new(ExportJob::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(ExportJob::UpdatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ExportJob::Table, ExportJob::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExportJob::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ExportJob {
+    Table,
+    Id,
+    DocuserId,
+    ScopeIds,
+    Status,
+    Processed,
+    Total,
+    ObjectId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
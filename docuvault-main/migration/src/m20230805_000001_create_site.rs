@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Site::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Site::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Site::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(Site::ScopeId).integer().not_null().unique_key())
+                    // rebuild whenever a document of the scope is rendered again
+                    .col(ColumnDef::new(Site::AutoBuild).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Site::Status).integer().not_null().default(0))
+                    // object id of the latest archive, downloadable through /file/:object_id
+                    .col(ColumnDef::new(Site::ObjectId).string())
+                    .col(ColumnDef::new(Site::RequestedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(Site::BuiltAt).timestamp())
+                    .col(ColumnDef::new(Site::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Site::Table, Site::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Site::Table, Site::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Site::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Site {
+    Table,
+    Id,
+    DocuserId,
+    ScopeId,
+    AutoBuild,
+    Status,
+    ObjectId,
+    RequestedAt,
+    BuiltAt,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
@@ -0,0 +1,138 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareLink::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShareLink::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    // random id handed out instead of the publish token
+                    .col(ColumnDef::new(ShareLink::LinkId).string().not_null().unique_key())
+                    .col(ColumnDef::new(ShareLink::DocorgId).integer().not_null())
+                    .col(ColumnDef::new(ShareLink::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(ShareLink::ScopeId).integer().not_null())
+                    .col(ColumnDef::new(ShareLink::CType).integer().not_null())
+                    // null: never expires
+                    .col(ColumnDef::new(ShareLink::ExpiresAt).timestamp())
+                    // null: unlimited
+                    .col(ColumnDef::new(ShareLink::MaxViews).integer())
+                    .col(ColumnDef::new(ShareLink::Views).integer().not_null().default(0))
+                    .col(ColumnDef::new(ShareLink::Revoked).boolean().not_null().default(false))
+                    .col(ColumnDef::new(ShareLink::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ShareLink::Table, ShareLink::DocorgId)
+                        .to(Docorg::Table, Docorg::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ShareLink::Table, ShareLink::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ShareLink::Table, ShareLink::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareAccess::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShareAccess::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ShareAccess::ShareLinkId).integer().not_null())
+                    .col(ColumnDef::new(ShareAccess::Referrer).string())
+                    .col(ColumnDef::new(ShareAccess::AccessedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ShareAccess::Table, ShareAccess::ShareLinkId)
+                        .to(ShareLink::Table, ShareLink::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShareAccess::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ShareLink::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ShareLink {
+    Table,
+    Id,
+    LinkId,
+    DocorgId,
+    DocuserId,
+    ScopeId,
+    CType,
+    ExpiresAt,
+    MaxViews,
+    Views,
+    Revoked,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum ShareAccess {
+    Table,
+    Id,
+    ShareLinkId,
+    Referrer,
+    AccessedAt,
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a link is either open, password protected or limited to email domains
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ShareLink::Table)
+                    // argon2 hash
+                    .add_column_if_not_exists(ColumnDef::new(ShareLink::PasswordHash).string())
+                    // ["example.com", ...]
+                    .add_column_if_not_exists(ColumnDef::new(ShareLink::AllowedDomains).json_binary())
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ShareLink::Table)
+                    .drop_column(ShareLink::PasswordHash)
+                    .drop_column(ShareLink::AllowedDomains)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ShareLink {
+    Table,
+    PasswordHash,
+    AllowedDomains,
+}
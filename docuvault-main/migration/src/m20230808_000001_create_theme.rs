@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Theme::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Theme::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Theme::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(Theme::Name).string().not_null())
+                    // tera template of the page
+                    .col(ColumnDef::new(Theme::Layout).text().not_null())
+                    .col(ColumnDef::new(Theme::Css).text().not_null())
+                    .col(ColumnDef::new(Theme::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(Theme::UpdatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Theme::Table, Theme::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+
+        // null: built-in theme
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Scope::ThemeId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                        .name("fk_scope_theme_id")
+                        .from_tbl(Scope::Table)
+                        .from_col(Scope::ThemeId)
+                        .to_tbl(Theme::Table)
+                        .to_col(Theme::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .drop_foreign_key(Alias::new("fk_scope_theme_id"))
+                    .drop_column(Scope::ThemeId)
+                    .to_owned()
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Theme::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Theme {
+    Table,
+    Id,
+    DocuserId,
+    Name,
+    Layout,
+    Css,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    ThemeId,
+}
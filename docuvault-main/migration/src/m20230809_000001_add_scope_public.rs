@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // public scopes(and their sequences) have feeds anyone can subscribe to
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Scope::Public).boolean().not_null().default(false))
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .drop_column(Scope::Public)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Public,
+}
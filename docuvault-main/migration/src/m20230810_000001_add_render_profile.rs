@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // markdown rendering profile of a user, a scope's profile takes precedence over it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docuser::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Docuser::RenderProfile).json_binary().null())
+                    .to_owned()
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Scope::RenderProfile).json_binary().null())
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Scope::Table)
+                    .drop_column(Scope::RenderProfile)
+                    .to_owned()
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docuser::Table)
+                    .drop_column(Docuser::RenderProfile)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    RenderProfile,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    RenderProfile,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // heading outline computed on save, null for documents saved before(computed on read)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::Outline).json_binary().null())
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .drop_column(Docorg::Outline)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Outline,
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // computed on save, word_count null marks documents the startup backfill has not reached yet
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::WordCount).integer().null())
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::ReadingTime).integer().null())
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::Excerpt).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(Docorg::Cover).string().null())
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Docorg::Table)
+                    .drop_column(Docorg::WordCount)
+                    .drop_column(Docorg::ReadingTime)
+                    .drop_column(Docorg::Excerpt)
+                    .drop_column(Docorg::Cover)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    WordCount,
+    ReadingTime,
+    Excerpt,
+    Cover,
+}
@@ -0,0 +1,160 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedQuery::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SavedQuery::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SavedQuery::DocuserId).integer().not_null())
+                    // null: private to the user, otherwise shared with the scope
+                    .col(ColumnDef::new(SavedQuery::ScopeId).integer())
+                    .col(ColumnDef::new(SavedQuery::Name).string().not_null())
+                    .col(ColumnDef::new(SavedQuery::Filter).json_binary().not_null())
+                    .col(ColumnDef::new(SavedQuery::Pinned).boolean().not_null().default(false))
+                    .col(ColumnDef::new(SavedQuery::Notify).boolean().not_null().default(false))
+                    .col(ColumnDef::new(SavedQuery::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(SavedQuery::UpdatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(SavedQuery::Table, SavedQuery::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(SavedQuery::Table, SavedQuery::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // documents a query has already notified about, a document is reported once per query
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedQueryHit::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(SavedQueryHit::SavedQueryId).integer().not_null())
+                    .col(ColumnDef::new(SavedQueryHit::DocorgId).integer().not_null())
+                    .col(ColumnDef::new(SavedQueryHit::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .primary_key(Index::create().col(SavedQueryHit::SavedQueryId).col(SavedQueryHit::DocorgId))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(SavedQueryHit::Table, SavedQueryHit::SavedQueryId)
+                        .to(SavedQuery::Table, SavedQuery::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(SavedQueryHit::Table, SavedQueryHit::DocorgId)
+                        .to(Docorg::Table, Docorg::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // exports of a saved query instead of whole scopes
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ExportJob::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ExportJob::SavedQueryId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                        .name("fk_export_job_saved_query")
+                        .from_tbl(ExportJob::Table)
+                        .from_col(ExportJob::SavedQueryId)
+                        .to_tbl(SavedQuery::Table)
+                        .to_col(SavedQuery::Id)
+                        .on_delete(ForeignKeyAction::SetNull)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ExportJob::Table)
+                    .drop_foreign_key(Alias::new("fk_export_job_saved_query"))
+                    .drop_column(ExportJob::SavedQueryId)
+                    .to_owned()
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SavedQueryHit::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SavedQuery::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum SavedQuery {
+    Table,
+    Id,
+    DocuserId,
+    ScopeId,
+    Name,
+    Filter,
+    Pinned,
+    Notify,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum SavedQueryHit {
+    Table,
+    SavedQueryId,
+    DocorgId,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum ExportJob {
+    Table,
+    SavedQueryId,
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
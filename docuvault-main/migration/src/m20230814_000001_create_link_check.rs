@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // results of the last link check of a document, replaced on every check
+        manager
+            .create_table(
+                Table::create()
+                    .table(LinkCheck::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LinkCheck::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LinkCheck::DocorgId).integer().not_null())
+                    .col(ColumnDef::new(LinkCheck::Url).text().not_null())
+                    .col(ColumnDef::new(LinkCheck::Kind).string().not_null())
+                    .col(ColumnDef::new(LinkCheck::Line).integer().not_null())
+                    .col(ColumnDef::new(LinkCheck::Status).integer().not_null())
+                    .col(ColumnDef::new(LinkCheck::Detail).string())
+                    .col(ColumnDef::new(LinkCheck::CheckedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(LinkCheck::Table, LinkCheck::DocorgId)
+                        .to(Docorg::Table, Docorg::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LinkCheck::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum LinkCheck {
+    Table,
+    Id,
+    DocorgId,
+    Url,
+    Kind,
+    Line,
+    Status,
+    Detail,
+    CheckedAt,
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Id,
+}
@@ -0,0 +1,144 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Webhook::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Webhook::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Webhook::DocuserId).integer().not_null())
+                    // null: every document of the user, otherwise the documents of the scope
+                    .col(ColumnDef::new(Webhook::ScopeId).integer())
+                    .col(ColumnDef::new(Webhook::Url).text().not_null())
+                    .col(ColumnDef::new(Webhook::Secret).string().not_null())
+                    // event names subscribed to, empty for all
+                    .col(ColumnDef::new(Webhook::Events).json_binary().not_null())
+                    .col(ColumnDef::new(Webhook::Active).boolean().not_null().default(true))
+                    .col(ColumnDef::new(Webhook::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Webhook::Table, Webhook::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(Webhook::Table, Webhook::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // one row per event and subscription, kept as the delivery log
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WebhookDelivery::WebhookId).integer().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Event).string().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Payload).json_binary().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Status).integer().not_null().default(0))
+                    .col(ColumnDef::new(WebhookDelivery::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(WebhookDelivery::NextAttemptAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(WebhookDelivery::ResponseStatus).integer())
+                    .col(ColumnDef::new(WebhookDelivery::Error).string())
+                    .col(ColumnDef::new(WebhookDelivery::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .col(ColumnDef::new(WebhookDelivery::DeliveredAt).timestamp())
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                        .to(Webhook::Table, Webhook::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // the worker looks for due deliveries
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_due")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDelivery::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Webhook::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Webhook {
+    Table,
+    Id,
+    DocuserId,
+    ScopeId,
+    Url,
+    Secret,
+    Events,
+    Active,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    WebhookId,
+    Event,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    ResponseStatus,
+    Error,
+    CreatedAt,
+    DeliveredAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
@@ -0,0 +1,101 @@
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        /*
+         * who changed what and when. no foreign keys, entries outlive what they are about.
+         * target_ids and scope_ids are json arrays, queried with @>
+         */
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditLog::ActorId).integer())
+                    .col(ColumnDef::new(AuditLog::Ip).string())
+                    .col(ColumnDef::new(AuditLog::Action).string().not_null())
+                    .col(ColumnDef::new(AuditLog::TargetType).string().not_null())
+                    .col(ColumnDef::new(AuditLog::TargetIds).json_binary().not_null())
+                    .col(ColumnDef::new(AuditLog::ScopeIds).json_binary().not_null())
+                    .col(ColumnDef::new(AuditLog::Summary).json_binary().not_null())
+                    .col(ColumnDef::new(AuditLog::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_actor")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::ActorId)
+                    .if_not_exists()
+                    .to_owned()
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_created_at")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::CreatedAt)
+                    .if_not_exists()
+                    .to_owned()
+            )
+            .await?;
+
+        let conn = manager.get_connection();
+        conn.execute_unprepared("CREATE INDEX IF NOT EXISTS idx_audit_log_target_ids ON audit_log USING GIN (target_ids)").await?;
+        conn.execute_unprepared("CREATE INDEX IF NOT EXISTS idx_audit_log_scope_ids ON audit_log USING GIN (scope_ids)").await?;
+        // append-only, whoever has access to the database
+        conn.execute_unprepared(
+            "CREATE OR REPLACE FUNCTION audit_log_append_only() RETURNS trigger AS $$ \
+             BEGIN RAISE EXCEPTION 'audit_log is append-only'; END; \
+             $$ LANGUAGE plpgsql"
+        ).await?;
+        conn.execute_unprepared(
+            "CREATE TRIGGER audit_log_no_change BEFORE UPDATE OR DELETE ON audit_log \
+             FOR EACH ROW EXECUTE FUNCTION audit_log_append_only()"
+        ).await?;
+        conn.execute_unprepared(
+            "CREATE TRIGGER audit_log_no_truncate BEFORE TRUNCATE ON audit_log \
+             FOR EACH STATEMENT EXECUTE FUNCTION audit_log_append_only()"
+        ).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).if_exists().to_owned())
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared("DROP FUNCTION IF EXISTS audit_log_append_only()")
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum AuditLog {
+    Table,
+    Id,
+    ActorId,
+    Ip,
+    Action,
+    TargetType,
+    TargetIds,
+    ScopeIds,
+    Summary,
+    CreatedAt,
+}
@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a document published through a share link at publish_at, and revoked at unpublish_at
+        manager
+            .create_table(
+                Table::create()
+                    .table(PublishSchedule::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PublishSchedule::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PublishSchedule::DocorgId).integer().not_null())
+                    .col(ColumnDef::new(PublishSchedule::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(PublishSchedule::ScopeId).integer().not_null())
+                    .col(ColumnDef::new(PublishSchedule::CType).integer().not_null())
+                    .col(ColumnDef::new(PublishSchedule::PublishAt).timestamp().not_null())
+                    .col(ColumnDef::new(PublishSchedule::UnpublishAt).timestamp())
+                    .col(ColumnDef::new(PublishSchedule::Status).integer().not_null().default(0))
+                    // chosen when the publish is first claimed, so a retried publish never makes a second link
+                    .col(ColumnDef::new(PublishSchedule::LinkId).string())
+                    .col(ColumnDef::new(PublishSchedule::ConvertRequested).boolean().not_null().default(false))
+                    // the instance that claimed the row works on it until then
+                    .col(ColumnDef::new(PublishSchedule::LeaseUntil).timestamp())
+                    .col(ColumnDef::new(PublishSchedule::Error).string())
+                    .col(ColumnDef::new(PublishSchedule::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(PublishSchedule::Table, PublishSchedule::DocorgId)
+                        .to(Docorg::Table, Docorg::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(PublishSchedule::Table, PublishSchedule::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(PublishSchedule::Table, PublishSchedule::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // the scheduler looks for due schedules
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_publish_schedule_publish_due")
+                    .table(PublishSchedule::Table)
+                    .col(PublishSchedule::Status)
+                    .col(PublishSchedule::PublishAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_publish_schedule_unpublish_due")
+                    .table(PublishSchedule::Table)
+                    .col(PublishSchedule::Status)
+                    .col(PublishSchedule::UnpublishAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PublishSchedule::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum PublishSchedule {
+    Table,
+    Id,
+    DocorgId,
+    DocuserId,
+    ScopeId,
+    CType,
+    PublishAt,
+    UnpublishAt,
+    Status,
+    LinkId,
+    ConvertRequested,
+    LeaseUntil,
+    Error,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Docorg {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // users a scope is shared with, and the role they have in it(the owner has every role)
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScopeMember::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ScopeMember::ScopeId).integer().not_null())
+                    .col(ColumnDef::new(ScopeMember::DocuserId).integer().not_null())
+                    .col(ColumnDef::new(ScopeMember::Role).integer().not_null().default(0))
+                    .col(ColumnDef::new(ScopeMember::CreatedAt).timestamp().not_null().extra("DEFAULT CURRENT_TIMESTAMP".to_string()))
+                    .primary_key(
+                        Index::create()
+                            .col(ScopeMember::ScopeId)
+                            .col(ScopeMember::DocuserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ScopeMember::Table, ScopeMember::ScopeId)
+                        .to(Scope::Table, Scope::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .from(ScopeMember::Table, ScopeMember::DocuserId)
+                        .to(Docuser::Table, Docuser::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        )
+                    .to_owned(),
+            )
+            .await?;
+        // scopes shared with a user
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_scope_member_docuser_id")
+                    .table(ScopeMember::Table)
+                    .col(ScopeMember::DocuserId)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ScopeMember::Table).if_exists().to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ScopeMember {
+    Table,
+    ScopeId,
+    DocuserId,
+    Role,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Docuser {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Scope {
+    Table,
+    Id,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a running build renews its lease, a site whose lease ran out can be built again
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Site::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Site::LeaseUntil).timestamp())
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Site::Table)
+                    .drop_column(Site::LeaseUntil)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Site {
+    Table,
+    LeaseUntil,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // only public queries are served as feeds without a login
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SavedQuery::Table)
+                    .add_column_if_not_exists(ColumnDef::new(SavedQuery::Public).boolean().not_null().default(false))
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SavedQuery::Table)
+                    .drop_column(SavedQuery::Public)
+                    .to_owned()
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum SavedQuery {
+    Table,
+    Public,
+}
@@ -2,10 +2,15 @@ use once_cell::sync::Lazy;
 use redis::AsyncCommands;
 use sea_orm::{entity::*, query::*};
 
-use crate::{AppState, entity, modules::redis::redis_reset_scopes};
+use crate::{AppState, entity, modules::{redis::redis_reset_scopes, background::{derived, webhooks, schedule}, collab, events}};
 
 
 pub async fn bootstrap(state: AppState) {
     redis_reset_scopes(state.clone()).await;
+    derived::backfill(state.clone());
+    collab::recover(state.clone());
+    events::listen(state.clone());
+    webhooks::worker(state.clone());
+    schedule::worker(state.clone());
 }
 
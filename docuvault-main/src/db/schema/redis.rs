@@ -37,3 +37,9 @@ pub struct File{
     pub size: usize,
     pub data: Vec<u8>,
 }
+
+// one time code mailed to a reader of a domain restricted share link, key: <link_id>:<email>
+#[redis_schema(scope="share_code")]
+pub struct ShareCode{
+    pub code: String,
+}
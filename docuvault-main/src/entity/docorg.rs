@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "docorg")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -14,6 +14,15 @@ pub struct Model {
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub status: i32,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub meta: Option<Json>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub outline: Option<Json>,
+    pub word_count: Option<i32>,
+    pub reading_time: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub excerpt: Option<String>,
+    pub cover: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -30,6 +39,10 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Docuser,
+    #[sea_orm(has_many = "super::comment_thread::Entity")]
+    CommentThread,
+    #[sea_orm(has_many = "super::share_link::Entity")]
+    ShareLink,
 }
 
 impl Related<super::convert::Entity> for Entity {
@@ -77,4 +90,16 @@ impl Related<super::sequence::Entity> for Entity {
     }
 }
 
+impl Related<super::comment_thread::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommentThread.def()
+    }
+}
+
+impl Related<super::share_link::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShareLink.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "docuser")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -12,6 +12,10 @@ pub struct Model {
     pub nickname: String,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub render_profile: Option<Json>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub lint_config: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -24,6 +28,22 @@ pub enum Relation {
     Scope,
     #[sea_orm(has_many = "super::sequence::Entity")]
     Sequence,
+    #[sea_orm(has_many = "super::template::Entity")]
+    Template,
+    #[sea_orm(has_many = "super::comment_thread::Entity")]
+    CommentThread,
+    #[sea_orm(has_many = "super::comment::Entity")]
+    Comment,
+    #[sea_orm(has_many = "super::export_job::Entity")]
+    ExportJob,
+    #[sea_orm(has_many = "super::site::Entity")]
+    Site,
+    #[sea_orm(has_many = "super::share_link::Entity")]
+    ShareLink,
+    #[sea_orm(has_many = "super::theme::Entity")]
+    Theme,
+    #[sea_orm(has_many = "super::saved_query::Entity")]
+    SavedQuery,
 }
 
 impl Related<super::docfile::Entity> for Entity {
@@ -50,4 +70,52 @@ impl Related<super::sequence::Entity> for Entity {
     }
 }
 
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
+impl Related<super::comment_thread::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommentThread.def()
+    }
+}
+
+impl Related<super::comment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comment.def()
+    }
+}
+
+impl Related<super::export_job::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExportJob.def()
+    }
+}
+
+impl Related<super::site::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Site.def()
+    }
+}
+
+impl Related<super::share_link::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShareLink.def()
+    }
+}
+
+impl Related<super::theme::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Theme.def()
+    }
+}
+
+impl Related<super::saved_query::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedQuery.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,6 +2,8 @@
 
 pub mod prelude;
 
+pub mod comment;
+pub mod comment_thread;
 pub mod convert;
 pub mod docfile;
 pub mod docorg;
@@ -9,7 +11,21 @@ pub mod docorg_scope;
 pub mod docorg_sequence;
 pub mod docorg_tag;
 pub mod docuser;
+pub mod export_job;
+pub mod link_check;
+pub mod saved_query;
+pub mod saved_query_hit;
 pub mod scope;
 pub mod scope_sequence;
 pub mod sequence;
+pub mod share_access;
+pub mod share_link;
+pub mod site;
 pub mod tag;
+pub mod template;
+pub mod theme;
+pub mod webhook;
+pub mod webhook_delivery;
+pub mod audit_log;
+pub mod publish_schedule;
+pub mod scope_member;
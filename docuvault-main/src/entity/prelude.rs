@@ -1,5 +1,7 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.7
 
+pub use super::comment::Entity as Comment;
+pub use super::comment_thread::Entity as CommentThread;
 pub use super::convert::Entity as Convert;
 pub use super::docfile::Entity as Docfile;
 pub use super::docorg::Entity as Docorg;
@@ -7,7 +9,21 @@ pub use super::docorg_scope::Entity as DocorgScope;
 pub use super::docorg_sequence::Entity as DocorgSequence;
 pub use super::docorg_tag::Entity as DocorgTag;
 pub use super::docuser::Entity as Docuser;
+pub use super::export_job::Entity as ExportJob;
+pub use super::link_check::Entity as LinkCheck;
+pub use super::saved_query::Entity as SavedQuery;
+pub use super::saved_query_hit::Entity as SavedQueryHit;
 pub use super::scope::Entity as Scope;
 pub use super::scope_sequence::Entity as ScopeSequence;
 pub use super::sequence::Entity as Sequence;
+pub use super::share_access::Entity as ShareAccess;
+pub use super::share_link::Entity as ShareLink;
+pub use super::site::Entity as Site;
 pub use super::tag::Entity as Tag;
+pub use super::template::Entity as Template;
+pub use super::theme::Entity as Theme;
+pub use super::webhook::Entity as Webhook;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::publish_schedule::Entity as PublishSchedule;
+pub use super::scope_member::Entity as ScopeMember;
@@ -2,13 +2,19 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "scope")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub docuser_id: i32,
     pub name: String,
+    pub theme_id: Option<i32>,
+    pub public: bool,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub render_profile: Option<Json>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub lint_config: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -21,6 +27,20 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Docuser,
+    #[sea_orm(
+        belongs_to = "super::theme::Entity",
+        from = "Column::ThemeId",
+        to = "super::theme::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Theme,
+    #[sea_orm(has_many = "super::template::Entity")]
+    Template,
+    #[sea_orm(has_one = "super::site::Entity")]
+    Site,
+    #[sea_orm(has_many = "super::share_link::Entity")]
+    ShareLink,
 }
 
 impl Related<super::docuser::Entity> for Entity {
@@ -47,4 +67,28 @@ impl Related<super::sequence::Entity> for Entity {
     }
 }
 
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
+impl Related<super::site::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Site.def()
+    }
+}
+
+impl Related<super::share_link::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShareLink.def()
+    }
+}
+
+impl Related<super::theme::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Theme.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -21,6 +21,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Docuser,
+    #[sea_orm(has_many = "super::template::Entity")]
+    Template,
 }
 
 impl Related<super::docuser::Entity> for Entity {
@@ -47,4 +49,10 @@ impl Related<super::scope::Entity> for Entity {
     }
 }
 
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,7 +4,7 @@ use axum::extract::FromRef;
 use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
 use common::object::ServiceState;
-use modules::Modules;
+use modules::{Modules, probe};
 use once_cell::sync::Lazy;
 use sea_orm::DatabaseConnection;
 use tokio::sync::Mutex;
@@ -65,7 +65,7 @@ async fn main() {
         db_conn: db_conn.clone(),
         redis_conn: redis_conn.clone(),
         file_proxy_addr: Arc::new(Mutex::new(file_proxy_addr)), 
-        modules: Arc::new(Modules::new(db_conn, redis_conn).await)
+        modules: Arc::new(Modules::new(db_conn, redis_conn, probe::from_env()).await)
     };
 
     
@@ -0,0 +1,394 @@
+use std::{collections::{BTreeMap, HashMap}, io::{Cursor, Read, Write}, ops::Range};
+
+use comrak::{Arena, parse_document, nodes::NodeValue};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use super::markdown::{front_matter, render::Profile};
+
+/*
+ * helpers for moving markdown folders in and out of docuvault as zip archives.
+ * paths inside an archive are always '/' separated and relative to the archive root.
+ */
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // ![label](target "title") and [label](target)
+    Regex::new(r#"(!?\[(?:[^\[\]]|\[[^\[\]]*\])*\]\(\s*)(<[^>]*>|[^)\s]+)((?:\s+(?:"[^"]*"|'[^']*'))?\s*\))"#).unwrap()
+});
+static IMG_TAG_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(<img\b[^>]*?\bsrc\s*=\s*["'])([^"']+)(["'])"#).unwrap()
+});
+
+/*
+ * manifest.json at the root of an exported archive.
+ * documents and attachments are stored as
+ * - documents/<doc_id>-<slug>.md
+ * - files/<object_id>/<name>
+ */
+pub const MANIFEST: &str = "manifest.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: Option<chrono::NaiveDateTime>,
+    #[serde(default)]
+    pub scopes: Vec<ManifestScope>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub sequences: Vec<ManifestSequence>,
+    #[serde(default)]
+    pub documents: Vec<ManifestDocument>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestScope {
+    pub id: i32,
+    pub name: String,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestSequence {
+    pub id: i32,
+    pub title: String,
+    // archive paths of the documents, in sequence order
+    #[serde(default)]
+    pub documents: Vec<String>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestDocument {
+    pub id: i32,
+    pub path: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub scope_ids: Vec<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/*
+ * an archive is expanded in memory, so the uncompressed sizes are capped
+ * whatever the headers claim(zip bombs).
+ */
+pub const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+pub const MAX_TOTAL_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ReadError {
+    Zip(zip::result::ZipError),
+    TooLarge,
+}
+impl From<zip::result::ZipError> for ReadError {
+    fn from(value: zip::result::ZipError) -> Self {
+        Self::Zip(value)
+    }
+}
+impl From<std::io::Error> for ReadError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Zip(value.into())
+    }
+}
+
+pub fn read_zip(bytes: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, ReadError> {
+    read_zip_limited(bytes, MAX_ENTRY_SIZE, MAX_TOTAL_SIZE)
+}
+
+fn read_zip_limited(bytes: &[u8], max_entry: u64, max_total: u64) -> Result<BTreeMap<String, Vec<u8>>, ReadError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let mut entries = BTreeMap::new();
+    let mut total = 0;
+    for idx in 0..archive.len() {
+        let file = archive.by_index(idx)?;
+        if file.is_dir() {
+            continue;
+        }
+        // entries escaping the archive root(../, absolute paths) are ignored
+        let path = match file.enclosed_name() {
+            Some(path) => path.to_string_lossy().replace('\\', "/"),
+            None => continue,
+        };
+        if path.starts_with("__MACOSX/") || path.split('/').any(|part| part.starts_with('.')) {
+            continue;
+        }
+        // one byte past the limit is read to tell a file of exactly the limit from a larger one
+        let limit = max_entry.min(max_total - total);
+        let mut data = Vec::new();
+        file.take(limit + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > limit {
+            return Err(ReadError::TooLarge);
+        }
+        total += data.len() as u64;
+        entries.insert(path, data);
+    }
+    Ok(entries)
+}
+
+pub fn write_zip(entries: &BTreeMap<String, Vec<u8>>) -> Result<Vec<u8>, zip::result::ZipError> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (path, data) in entries {
+        writer.start_file(path.as_str(), options)?;
+        writer.write_all(data)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+// file name friendly form of a title
+pub fn slug(title: &str) -> String {
+    let slug = title.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    match slug.is_empty() {
+        true => "document".to_string(),
+        false => slug.chars().take(60).collect(),
+    }
+}
+
+pub fn is_markdown(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".md") || path.ends_with(".markdown")
+}
+
+pub fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+pub fn parent(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(idx) => &path[..idx],
+        None => "",
+    }
+}
+
+// folder names of a path, used as tags of imported documents
+pub fn folders(path: &str) -> Vec<String> {
+    parent(path).split('/').filter(|part| !part.is_empty()).map(|part| part.to_string()).collect()
+}
+
+pub fn ftype(path: &str) -> &'static str {
+    let extension = path.rsplit('.').next().unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+// external urls, anchors and links already pointing to docuvault objects are left untouched
+pub fn is_local(target: &str) -> bool {
+    !(target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with('/')
+        || target.starts_with("file/")
+        || target.contains("://")
+        || target.starts_with("mailto:")
+        || target.starts_with("data:"))
+}
+
+fn percent_decode(target: &str) -> String {
+    let bytes = target.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            if let Some(Ok(byte)) = target.get(idx + 1..idx + 3).map(|hex| u8::from_str_radix(hex, 16)) {
+                out.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        out.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+// percent encode a path so that it can be used as a markdown link target
+pub fn percent_encode(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+// resolve a relative link target against the folder of the markdown file
+pub fn resolve(base_dir: &str, target: &str) -> Option<String> {
+    let target = target.split(['#', '?']).next().unwrap_or("");
+    let target = percent_decode(target);
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|part| !part.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {},
+            ".." => { parts.pop()?; },
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+// relative path from the folder `from` to the archive path `to`
+pub fn relative(from: &str, to: &str) -> String {
+    let from = from.split('/').filter(|part| !part.is_empty()).collect::<Vec<_>>();
+    let to = to.split('/').filter(|part| !part.is_empty()).collect::<Vec<_>>();
+    let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(to[common..].iter());
+    parts.join("/")
+}
+
+/*
+ * byte ranges of the code of a markdown document, links inside them are text.
+ * code blocks come from the comrak AST, which only knows the line they start at, inline code spans
+ * are the backtick runs closed by a run of the same length.
+ */
+fn code_spans(raw: &str) -> Vec<Range<usize>> {
+    let body = front_matter::strip(raw);
+    let offset = raw.len() - body.len();
+    let line_base = raw[..offset].matches('\n').count();
+    let line_starts = std::iter::once(0).chain(raw.match_indices('\n').map(|(idx, _)| idx + 1)).collect::<Vec<_>>();
+    let line_start = |line: usize| line_starts.get(line).copied().unwrap_or(raw.len());
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &Profile { gfm: true, ..Default::default() }.options());
+    let mut spans = Vec::new();
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        if let NodeValue::CodeBlock(block) = &data.value {
+            let start = line_base + (data.start_line as usize).max(1) - 1;
+            // the fences are lines of the block too, the closing one may be missing at the end
+            let lines = block.literal.iter().filter(|&&byte| byte == b'\n').count() + if block.fenced { 2 } else { 0 };
+            spans.push(line_start(start)..line_start(start + lines));
+        }
+    }
+    spans.sort_by_key(|span| span.start);
+
+    let bytes = raw.as_bytes();
+    let run = |at: usize| bytes[at..].iter().take_while(|&&byte| byte == b'`').count();
+    let mut inline = Vec::new();
+    let mut idx = 0;
+    for block in spans.iter().cloned().chain(std::iter::once(raw.len()..raw.len())) {
+        while idx < block.start {
+            if bytes[idx] != b'`' {
+                idx += 1;
+                continue;
+            }
+            let open = run(idx);
+            let mut close = idx + open;
+            let mut end = None;
+            while close < block.start {
+                if bytes[close] != b'`' {
+                    close += 1;
+                    continue;
+                }
+                let len = run(close);
+                if len == open {
+                    end = Some(close + len);
+                    break;
+                }
+                close += len;
+            }
+            match end {
+                Some(end) => {
+                    inline.push(idx..end);
+                    idx = end;
+                },
+                None => idx += open,
+            }
+        }
+        idx = idx.max(block.end);
+    }
+    spans.extend(inline);
+    spans
+}
+
+/*
+ * rewrite every link/image target of a markdown document, links inside code are kept.
+ * `f` returns the new target, or None to keep the link as is.
+ */
+pub fn rewrite_links<F>(raw: &str, mut f: F) -> String where F: FnMut(&str) -> Option<String> {
+    let spans = code_spans(raw);
+    let res = LINK_REGEX.replace_all(raw, |cap: &Captures| {
+        let start = cap.get(0).map_or(0, |m| m.start());
+        if spans.iter().any(|span| span.contains(&start)) {
+            return cap[0].to_string();
+        }
+        let target = cap[2].trim_start_matches('<').trim_end_matches('>');
+        match f(target) {
+            Some(new_target) => format!("{}{}{}", &cap[1], new_target, &cap[3]),
+            None => cap[0].to_string(),
+        }
+    });
+    let spans = code_spans(&res);
+    IMG_TAG_REGEX.replace_all(&res, |cap: &Captures| {
+        let start = cap.get(0).map_or(0, |m| m.start());
+        if spans.iter().any(|span| span.contains(&start)) {
+            return cap[0].to_string();
+        }
+        match f(&cap[2]) {
+            Some(new_target) => format!("{}{}{}", &cap[1], new_target, &cap[3]),
+            None => cap[0].to_string(),
+        }
+    }).to_string()
+}
+
+// links to docuvault objects(…file/<object_id>) pointed to other objects, the ones missing from object_ids are kept
+pub fn replace_objects(raw: &str, object_ids: &HashMap<String, String>) -> String {
+    rewrite_links(raw, |target| {
+        let idx = target.rfind("file/")? + 5;
+        object_ids.get(&target[idx..]).map(|object_id| format!("{}{}", &target[..idx], object_id))
+    })
+}
+
+#[test]
+fn archive_link_test() {
+    let raw = "![diagram](../assets/a%20b.png \"title\") [doc](other.md) [web](https://a.com) <img src=\"./x.png\">";
+    let res = rewrite_links(raw, |target| {
+        if !is_local(target) || is_markdown(target) {
+            return None;
+        }
+        resolve("notes/infra", target).map(|path| format!("file/{}", path.replace('/', "_")))
+    });
+    assert_eq!(res, "![diagram](file/notes_assets_a b.png \"title\") [doc](other.md) [web](https://a.com) <img src=\"file/notes_infra_x.png\">");
+    assert_eq!(resolve("", "../x.png"), None);
+    assert_eq!(relative("notes/infra", "notes/assets/a.png"), "../assets/a.png");
+    assert_eq!(folders("notes/infra/runbook.md"), vec!["notes", "infra"]);
+    assert_eq!(resolve("documents", &percent_encode("../files/ab/a (1).png")).as_deref(), Some("files/ab/a (1).png"));
+    assert_eq!(slug("Postgres: failover runbook!"), "postgres-failover-runbook");
+    let object_ids = HashMap::from([("a1".to_string(), "b2".to_string())]);
+    assert_eq!(replace_objects("![x](file/a1) [y](/file/a1 \"t\") [z](file/c3) file/a1", &object_ids), "![x](file/b2) [y](/file/b2 \"t\") [z](file/c3) file/a1");
+    // code is kept as written
+    let raw = "---\ntitle: x\n---\n`[a](file/a1)` ``[b](`file/a1`)`` [c](file/a1)\n\n```md\n![d](file/a1) <img src=\"file/a1\">\n```\n\n    [e](file/a1)\n\n<img src=\"file/a1\">\n";
+    assert_eq!(replace_objects(raw, &object_ids), raw.replace("[c](file/a1)", "[c](file/b2)").replace("\n<img src=\"file/a1\">\n", "\n<img src=\"file/b2\">\n"));
+}
+
+#[test]
+fn archive_limit_test() {
+    let entries = BTreeMap::from([
+        ("a.md".to_string(), vec![b'a'; 100]),
+        ("b/c.png".to_string(), vec![0; 100]),
+    ]);
+    let bytes = write_zip(&entries).unwrap();
+    assert_eq!(read_zip(&bytes).unwrap(), entries);
+    assert!(read_zip_limited(&bytes, 100, 200).is_ok());
+    assert!(matches!(read_zip_limited(&bytes, 99, 200), Err(ReadError::TooLarge)));
+    assert!(matches!(read_zip_limited(&bytes, 100, 199), Err(ReadError::TooLarge)));
+}
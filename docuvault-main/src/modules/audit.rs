@@ -0,0 +1,159 @@
+use std::{env, net::SocketAddr};
+
+use once_cell::sync::Lazy;
+use sea_orm::{entity::*, query::*, ConnectionTrait, DbErr};
+use serde_json::Value;
+
+use crate::entity;
+
+/*
+ * append-only audit log of the changes to documents, scopes, sequences, files and sessions.
+ * an entry is recorded with the connection of the change it is about, inside its transaction,
+ * so both are committed or rolled back together. the table itself refuses updates and deletes.
+ */
+
+// users who read every entry(AUDIT_ADMIN_IDS=1,2), everyone else reads the ones of the scopes they own
+static ADMIN_IDS: Lazy<Vec<i32>> = Lazy::new(|| {
+    env::var("AUDIT_ADMIN_IDS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|id| id.trim().parse().ok())
+        .collect()
+});
+
+pub fn is_admin(docuser_id: i32) -> bool {
+    ADMIN_IDS.contains(&docuser_id)
+}
+
+// who made the request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Actor {
+    pub user_id: Option<i32>,
+    pub ip: Option<String>,
+}
+impl Actor {
+    pub fn new(user_id: i32, addr: SocketAddr) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ip: Some(addr.ip().to_string()),
+        }
+    }
+    // requests without a session, e.g. registering
+    pub fn anonymous(addr: SocketAddr) -> Self {
+        Self {
+            user_id: None,
+            ip: Some(addr.ip().to_string()),
+        }
+    }
+    // jobs run on behalf of a user without a request, e.g. scheduled publishing
+    pub fn background(user_id: i32) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ip: None,
+        }
+    }
+    // action is <target type>.<verb>, e.g. document.update
+    pub fn entry(&self, action: &str) -> Entry {
+        Entry {
+            actor_id: self.user_id,
+            ip: self.ip.clone(),
+            action: action.to_string(),
+            target_type: action.split('.').next().unwrap_or_default().to_string(),
+            target_ids: Vec::new(),
+            scope_ids: Vec::new(),
+            summary: Value::Object(Default::default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub actor_id: Option<i32>,
+    pub ip: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_ids: Vec<Value>,
+    pub scope_ids: Vec<i32>,
+    // what changed, never the content itself
+    pub summary: Value,
+}
+impl Entry {
+    pub fn targets<T: Into<Value>>(mut self, target_ids: impl IntoIterator<Item = T>) -> Self {
+        self.target_ids.extend(target_ids.into_iter().map(Into::into));
+        self
+    }
+    // scopes of the targets when the change happened, their owners can read the entry
+    pub fn scopes(mut self, scope_ids: impl IntoIterator<Item = i32>) -> Self {
+        self.scope_ids.extend(scope_ids);
+        self.scope_ids.sort_unstable();
+        self.scope_ids.dedup();
+        self
+    }
+    pub fn summary(mut self, summary: Value) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub async fn record<C: ConnectionTrait>(self, conn: &C) -> Result<(), DbErr> {
+        let model = entity::audit_log::ActiveModel {
+            actor_id: Set(self.actor_id),
+            ip: Set(self.ip),
+            action: Set(self.action),
+            target_type: Set(self.target_type),
+            target_ids: Set(Value::from(self.target_ids)),
+            scope_ids: Set(Value::from(self.scope_ids)),
+            summary: Set(self.summary),
+            ..Default::default()
+        };
+        entity::audit_log::Entity::insert(model).exec(conn).await?;
+        Ok(())
+    }
+}
+
+// scopes of a document as the entry is recorded, inside the transaction of the change
+pub async fn document_scopes<C: ConnectionTrait>(conn: &C, doc_ids: &[i32]) -> Result<Vec<i32>, DbErr> {
+    let scope_ids = entity::docorg_scope::Entity::find()
+        .filter(entity::docorg_scope::Column::DocorgId.is_in(doc_ids.to_vec()))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|docorg_scope| docorg_scope.scope_id)
+        .collect();
+    Ok(scope_ids)
+}
+
+pub const CSV_HEADER: [&str; 9] = ["id", "created_at", "actor_id", "ip", "action", "target_type", "target_ids", "scope_ids", "summary"];
+
+// one line of RFC 4180 csv, fields with separators, quotes or line breaks are quoted
+pub fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields.iter()
+        .map(|field| {
+            let field = field.as_ref();
+            match field.contains([',', '"', '\n', '\r']) {
+                true => format!("\"{}\"", field.replace('"', "\"\"")),
+                false => field.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+#[test]
+fn audit_test() {
+    let addr: SocketAddr = "10.0.0.7:51234".parse().unwrap();
+    let entry = Actor::new(3, addr)
+        .entry("document.delete")
+        .targets([12, 10])
+        .scopes([4, 2, 4]);
+    assert_eq!(entry.actor_id, Some(3));
+    assert_eq!(entry.ip.as_deref(), Some("10.0.0.7"));
+    assert_eq!(entry.target_type, "document");
+    assert_eq!(Value::from(entry.target_ids), serde_json::json!([12, 10]));
+    assert_eq!(entry.scope_ids, vec![2, 4]);
+    assert_eq!(Actor::anonymous(addr).entry("auth.register").targets(["x1"]).target_ids, vec![Value::from("x1")]);
+
+    assert_eq!(csv_line(&CSV_HEADER[..3]), "id,created_at,actor_id\r\n");
+    assert_eq!(csv_line(&["1", "[1,2]", "say \"hi\"", "a\nb"]), "1,\"[1,2]\",\"say \"\"hi\"\"\",\"a\nb\"\r\n");
+}
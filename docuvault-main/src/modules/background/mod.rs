@@ -2,12 +2,12 @@ pub mod conversion {
     use std::{cell::RefCell, io::{BufWriter, Bytes, Cursor}};
 
     use docx::{Docx, document::Paragraph};
-    use sea_orm::{entity::*, query::*, FromQueryResult};
+    use sea_orm::{entity::*, query::*, sea_query::Expr, DbErr, FromQueryResult};
     use comrak::{ComrakOptions, nodes::{AstNode, Ast, NodeValue}, Arena, arena_tree::Node, parse_document};
     use tokio::time::sleep;
     use tonic::Request;
 
-    use crate::{AppState, entity, modules::grpc::upload::{upload_client::UploadClient, UploadRequest, PreUploadRequest}};
+    use crate::{AppState, entity, routes::{error::GlobalError, document::{object::DocumentStatus, error::DocumentError}}, modules::{grpc::upload::{upload_client::UploadClient, UploadRequest, PreUploadRequest}, markdown::{front_matter, render::{self, Profile}}, events::{self, ServerEvent}}};
 
     pub fn extension<'a>(c_type: i32)->&'a str{
         match c_type {
@@ -21,31 +21,140 @@ pub mod conversion {
             _ => "",
         }
     }
+    pub fn mime<'a>(c_type: i32)->&'a str{
+        match c_type {
+            0 | 1 => "text/html; charset=utf-8",
+            2 => "text/plain; charset=utf-8",
+            3 => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            4 => "application/pdf",
+            5 => "application/epub+zip",
+            6 => "application/json",
+            _ => "application/octet-stream",
+        }
+    }
 
 
+    // profile of the first scope of the document that has one, otherwise the owner's
+    pub async fn profile_of(state: &AppState, doc_id: i32) -> Result<Profile, DbErr> {
+        let scope = entity::scope::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::docorg_scope::Relation::Scope.def())
+            .filter(entity::docorg_scope::Column::DocorgId.eq(doc_id))
+            .filter(entity::scope::Column::RenderProfile.is_not_null())
+            .order_by_asc(entity::scope::Column::Id)
+            .one(&state.db_conn)
+            .await?;
+        if let Some(profile) = scope.and_then(|scope| Profile::from_json(scope.render_profile.as_ref())) {
+            return Ok(profile);
+        }
+        let docuser = entity::docuser::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::docorg::Relation::Docuser.def())
+            .filter(entity::docorg::Column::Id.eq(doc_id))
+            .one(&state.db_conn)
+            .await?;
+        Ok(docuser.and_then(|docuser| Profile::from_json(docuser.render_profile.as_ref())).unwrap_or_default())
+    }
+
     pub fn convert_to_html(state: AppState, convert_id: (i32, i32), target: String){
         tokio::spawn(async move {
-            //find ways for logging
-            // let data = comrak::markdown_to_html(&payload.raw.clone(), &ComrakOptions::default());
-            let data = comrak::markdown_to_html(&target, &ComrakOptions::default());
-            let convert = entity::convert::Entity::find_by_id(convert_id)
-                .one(&state.db_conn)
-                .await
-                .expect("convet db error");
-            if convert.is_none() {
-                panic!("convert instance not exists");
-            }
-            let mut convert: entity::convert::ActiveModel = convert.unwrap().into();
-            convert.data = Set(Some(data));
-            convert.status = Set(1);
-            convert.update(&state.db_conn).await.expect("convert db fail");
+            match to_html(&state, convert_id, &target).await {
+                Ok(Some(recorded)) => events::send(&state, recorded).await,
+                Ok(None) => {},
+                Err(e) => {
+                    dbg!(e);
+                    fail(&state, convert_id).await;
+                    return;
+                },
+            }
+
+            // static sites are built from the rendered html
+            super::site::request_for_document(state.clone(), convert_id.0);
+            super::watch::document(state.clone(), convert_id.0);
+        });
+    }
+
+    // the convert row is marked failed(status 2) and the user told so
+    async fn fail(state: &AppState, convert_id: (i32, i32)) {
+        let res = async {
+            let txn = state.db_conn.begin().await?;
+            entity::convert::Entity::update_many()
+                .col_expr(entity::convert::Column::Status, Expr::value(2))
+                .filter(entity::convert::Column::DocorgId.eq(convert_id.0))
+                .filter(entity::convert::Column::CType.eq(convert_id.1))
+                .exec(&txn)
+                .await?;
+            let recorded = events::record_for_document(&txn, convert_id.0, ServerEvent::ConversionFailed { doc_id: convert_id.0, c_type: convert_id.1 }).await?;
+            txn.commit().await?;
+            Ok::<_, DbErr>(recorded)
+        }.await;
+        match res {
+            Ok(Some(recorded)) => events::send(state, recorded).await,
+            Ok(None) => {},
+            Err(e) => {
+                dbg!(e);
+            },
+        }
+    }
+
+    // a convert row removed in the meantime(the document was deleted) is left alone
+    async fn to_html(state: &AppState, convert_id: (i32, i32), target: &str) -> Result<Option<events::Recorded>, GlobalError> {
+        let profile = profile_of(state, convert_id.0).await?;
+        let data = render::render(target, &profile);
+        let txn = state.db_conn.begin().await?;
+        entity::convert::Entity::update_many()
+            .col_expr(entity::convert::Column::Data, Expr::value(Some(data)))
+            .col_expr(entity::convert::Column::Status, Expr::value(1))
+            .filter(entity::convert::Column::DocorgId.eq(convert_id.0))
+            .filter(entity::convert::Column::CType.eq(convert_id.1))
+            .exec(&txn)
+            .await?;
+        let recorded = events::record_for_document(&txn, convert_id.0, ServerEvent::ConversionFinished { doc_id: convert_id.0, c_type: convert_id.1 }).await?;
+        txn.commit().await?;
+        Ok(recorded)
+    }
+
+    /*
+     * html of the documents rendered again after a profile change.
+     * documents are done one after another so that a large scope does not hold the pool.
+     */
+    pub fn rerender(state: AppState, doc_ids: Vec<i32>){
+        tokio::spawn(async move {
+            for doc_id in doc_ids {
+                match rerender_document(&state, doc_id).await {
+                    Ok(true) => super::site::request_for_document(state.clone(), doc_id),
+                    Ok(false) => {},
+                    Err(e) => {
+                        dbg!(e);
+                    },
+                }
+            }
         });
     }
 
+    // false when the document is gone
+    async fn rerender_document(state: &AppState, doc_id: i32) -> Result<bool, GlobalError> {
+        let docorg = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&state.db_conn)
+            .await?;
+        let docorg = match docorg {
+            Some(docorg) => docorg,
+            None => return Ok(false),
+        };
+        let profile = profile_of(state, doc_id).await?;
+        entity::convert::Entity::update_many()
+            .col_expr(entity::convert::Column::Data, Expr::value(Some(render::render(&docorg.raw, &profile))))
+            .col_expr(entity::convert::Column::Status, Expr::value(1))
+            .filter(entity::convert::Column::DocorgId.eq(doc_id))
+            .filter(entity::convert::Column::CType.eq(0))
+            .exec(&state.db_conn)
+            .await?;
+        Ok(true)
+    }
+
     fn parse_raw<'a>(arena: &'a Arena<Node<'a, RefCell<Ast>>>, raw: String) -> &'a Node<'a, RefCell<Ast>> {
         let root = parse_document(
             arena,
-            &raw[..],
+            front_matter::strip(&raw),
             &ComrakOptions::default());
         root
     } 
@@ -95,42 +204,48 @@ pub mod conversion {
     pub async fn convert(state: AppState, convert_id: (i32, i32), c_type: i32){
         let file_proxy_addr = state.file_proxy_addr.lock().await.clone();
         tokio::spawn(async move {
-            let docorg = entity::docorg::Entity::find_by_id(convert_id.0)
-                .one(&state.db_conn)
-                .await.expect("db connection failed");
-
-            let docorg = match docorg { Some(docorg) => docorg,
-                None => return (),
-            };
-            let (data, extension, ftype) = match c_type {
-                2 => (parse_to_txt(docorg.raw), "txt", "text/plain"),
-                3 => (parse_to_docx(docorg.raw), "docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
-                _ => panic!("not supported c_type"),
-            };
-            dbg!(&data);
-            
-            let mut upload_client = UploadClient::connect(file_proxy_addr).await.unwrap();
-            let object_id = upload_client.pre_upload(Request::new(PreUploadRequest{
-                name: format!("{}.{}", docorg.title, extension),
-                ftype: ftype.to_owned(),
-                size: data.len() as u64,
-                docuser_id: docorg.docuser_id,
-                data,
-                object_id: None,
-            })).await.unwrap();
-            let object_id = object_id.into_inner().object_id;
-            let _ = upload_client.upload(Request::new(UploadRequest { object_id: object_id.clone(), doc_id: convert_id.0 })).await;
-
-            let convert = entity::convert::Entity::find_by_id(convert_id)
-                .one(&state.db_conn)
-                .await
-                .expect("convet db error");
-            let mut convert: entity::convert::ActiveModel = convert.unwrap().into();
-            convert.data = Set(Some(object_id));
-            convert.status = Set(1);
-            convert.update(&state.db_conn).await.expect("convert db fail");
+            if let Err(e) = to_file(&state, file_proxy_addr, convert_id, c_type).await {
+                dbg!(e);
+                fail(&state, convert_id).await;
+            }
         });
     }
+
+    async fn to_file(state: &AppState, file_proxy_addr: String, convert_id: (i32, i32), c_type: i32) -> Result<(), GlobalError> {
+        let docorg = entity::docorg::Entity::find_by_id(convert_id.0)
+            .one(&state.db_conn)
+            .await?;
+        let docorg = match docorg {
+            Some(docorg) => docorg,
+            None => return Ok(()),
+        };
+        let (data, extension, ftype) = match c_type {
+            2 => (parse_to_txt(docorg.raw), "txt", "text/plain"),
+            3 => (parse_to_docx(docorg.raw), "docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            _ => return Err(DocumentError::NoMatchingConvertType.into()),
+        };
+
+        let mut upload_client = UploadClient::connect(file_proxy_addr).await?;
+        let object_id = upload_client.pre_upload(Request::new(PreUploadRequest{
+            name: format!("{}.{}", docorg.title, extension),
+            ftype: ftype.to_owned(),
+            size: data.len() as u64,
+            docuser_id: docorg.docuser_id,
+            data,
+            object_id: None,
+        })).await?;
+        let object_id = object_id.into_inner().object_id;
+        upload_client.upload(Request::new(UploadRequest { object_id: object_id.clone(), doc_id: convert_id.0 })).await?;
+
+        entity::convert::Entity::update_many()
+            .col_expr(entity::convert::Column::Data, Expr::value(Some(object_id)))
+            .col_expr(entity::convert::Column::Status, Expr::value(1))
+            .filter(entity::convert::Column::DocorgId.eq(convert_id.0))
+            .filter(entity::convert::Column::CType.eq(convert_id.1))
+            .exec(&state.db_conn)
+            .await?;
+        Ok(())
+    }
 }
 
 pub mod sanitize {
@@ -164,3 +279,1416 @@ pub mod sanitize {
     }
 }
 
+
+pub mod export {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use rand::{distributions::Alphanumeric, Rng};
+    use sea_orm::{entity::*, query::*, sea_query::{Expr, Query}, ConnectionTrait, DbErr};
+    use tonic::Request;
+
+    use crate::{AppState, entity, routes::{error::GlobalError, document::object::DocumentStatus, export::object::ExportStatus}, modules::{archive::{self, Manifest, ManifestDocument, ManifestScope, ManifestSequence}, filter::DocFilter, markdown::front_matter, grpc::{download::{download_client::DownloadClient, DownloadRequest}, upload::{upload_client::UploadClient, PreUploadRequest}}}};
+
+    /*
+     * a running job touches updated_at after every document. a job not touched for STALE_SECONDS
+     * was left behind by a restart or a crash, it is failed so that the user can export again.
+     */
+    const STALE_SECONDS: i64 = 30 * 60;
+
+    pub fn export(state: AppState, job_id: i32){
+        tokio::spawn(async move {
+            // built in its own task, a panic fails the job as well
+            let build_state = state.clone();
+            let (status, object_id) = match tokio::spawn(async move { build(&build_state, job_id).await }).await {
+                Ok(Ok(object_id)) => (ExportStatus::DONE, Some(object_id)),
+                Ok(Err(e)) => {
+                    dbg!(e);
+                    (ExportStatus::FAILED, None)
+                },
+                Err(e) => {
+                    dbg!(e);
+                    (ExportStatus::FAILED, None)
+                },
+            };
+            let job = entity::export_job::ActiveModel {
+                id: Set(job_id),
+                status: Set(status as i32),
+                object_id: Set(object_id),
+                updated_at: Set(chrono::Utc::now().naive_utc()),
+                ..Default::default()
+            };
+            // left to reclaim when it can not be saved
+            if let Err(e) = job.update(&state.db_conn).await {
+                dbg!(e);
+            }
+        });
+    }
+
+    // fails the user's jobs left pending or running by a dead task
+    pub async fn reclaim<C: ConnectionTrait>(conn: &C, docuser_id: i32) -> Result<u64, DbErr> {
+        let stale = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(STALE_SECONDS);
+        let res = entity::export_job::Entity::update_many()
+            .col_expr(entity::export_job::Column::Status, Expr::value(ExportStatus::FAILED as i32))
+            .col_expr(entity::export_job::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+            .filter(entity::export_job::Column::DocuserId.eq(docuser_id))
+            .filter(entity::export_job::Column::Status.is_in([ExportStatus::PENDING as i32, ExportStatus::RUNNING as i32]))
+            .filter(entity::export_job::Column::UpdatedAt.lt(stale))
+            .exec(conn)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    async fn progress(state: &AppState, job_id: i32, processed: i32, total: i32) -> Result<(), GlobalError> {
+        let job = entity::export_job::ActiveModel {
+            id: Set(job_id),
+            status: Set(ExportStatus::RUNNING as i32),
+            processed: Set(processed),
+            total: Set(total),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        job.update(&state.db_conn).await?;
+        Ok(())
+    }
+
+    /*
+     * builds the archive(see modules::archive for the layout) and returns its object id.
+     * the archive can be imported again through /document/import.
+     */
+    async fn build(state: &AppState, job_id: i32) -> Result<String, GlobalError> {
+        let job = entity::export_job::Entity::find_by_id(job_id)
+            .one(&state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+        let scope_ids: Vec<i32> = serde_json::from_value(job.scope_ids.clone()).unwrap_or_default();
+
+        /*
+         * documents, scopes, tags and sequences to export
+         */
+        let select = match job.saved_query_id {
+            Some(query_id) => {
+                let query = entity::saved_query::Entity::find_by_id(query_id)
+                    .one(&state.db_conn)
+                    .await?
+                    .ok_or(GlobalError::InternalServerError)?;
+                DocFilter::from_json(&query.filter).ok_or(GlobalError::InternalServerError)?.select(job.docuser_id)
+            },
+            None => entity::docorg::Entity::find().filter(entity::docorg::Column::DocuserId.eq(job.docuser_id)),
+        };
+        let documents = select
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .apply_if((!scope_ids.is_empty()).then(|| scope_ids.clone()), |query, scope_ids| {
+                query.filter(entity::docorg::Column::Id.in_subquery(
+                    Query::select()
+                        .column(entity::docorg_scope::Column::DocorgId)
+                        .from(entity::docorg_scope::Entity)
+                        .and_where(Expr::col(entity::docorg_scope::Column::ScopeId).is_in(scope_ids))
+                        .to_owned()
+                ))
+            })
+            .order_by_asc(entity::docorg::Column::Id)
+            .all(&state.db_conn)
+            .await?;
+        let doc_ids = documents.iter().map(|document| document.id).collect::<Vec<_>>();
+        let total = documents.len() as i32;
+        progress(state, job_id, 0, total).await?;
+
+        let scopes = entity::scope::Entity::find()
+            .filter(entity::scope::Column::DocuserId.eq(job.docuser_id))
+            .apply_if((!scope_ids.is_empty()).then(|| scope_ids.clone()), |query, scope_ids| {
+                query.filter(entity::scope::Column::Id.is_in(scope_ids))
+            })
+            .all(&state.db_conn)
+            .await?;
+        let doc_scopes = entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::DocorgId.is_in(doc_ids.clone()))
+            .all(&state.db_conn)
+            .await?;
+        let doc_tags = entity::docorg_tag::Entity::find()
+            .filter(entity::docorg_tag::Column::DocorgId.is_in(doc_ids.clone()))
+            .find_also_related(entity::tag::Entity)
+            .all(&state.db_conn)
+            .await?;
+        let doc_seqs = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::DocorgId.is_in(doc_ids.clone()))
+            .order_by_asc(entity::docorg_sequence::Column::Order)
+            .find_also_related(entity::sequence::Entity)
+            .all(&state.db_conn)
+            .await?;
+        let docfiles = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::DocorgId.is_in(doc_ids.clone()))
+            .filter(entity::docfile::Column::IsFixed.eq(true))
+            .all(&state.db_conn)
+            .await?;
+
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for (doc_tag, tag) in &doc_tags {
+            if let Some(tag) = tag {
+                tags.entry(doc_tag.docorg_id).or_default().push(tag.value.clone());
+            }
+        }
+
+        /*
+         * documents and their attachments
+         */
+        let file_proxy_addr = state.file_proxy_addr.lock().await.clone();
+        let mut download_client = DownloadClient::connect(file_proxy_addr.clone()).await?;
+
+        let mut entries = BTreeMap::new();
+        let mut manifest = Manifest {
+            version: 1,
+            exported_at: Some(chrono::Utc::now().naive_utc()),
+            scopes: scopes.iter().map(|scope| ManifestScope { id: scope.id, name: scope.name.clone() }).collect(),
+            ..Default::default()
+        };
+        let mut paths = HashMap::new();
+        for (idx, document) in documents.iter().enumerate() {
+            let path = format!("documents/{}-{}.md", document.id, archive::slug(&document.title));
+            let doc_tags = tags.remove(&document.id).unwrap_or_default();
+
+            // object id -> archive path
+            let mut files = HashMap::new();
+            for docfile in docfiles.iter().filter(|docfile| docfile.docorg_id == Some(document.id)) {
+                let res = download_client.download(Request::new(DownloadRequest {
+                    object_id: docfile.object_id.clone(),
+                })).await?.into_inner();
+                let file_path = format!("files/{}/{}", docfile.object_id, archive::file_name(&res.name));
+                entries.insert(file_path.clone(), res.data);
+                files.insert(docfile.object_id.clone(), file_path);
+            }
+            let raw = archive::rewrite_links(&document.raw, |target| {
+                let object_id = &target[target.rfind("file/")? + 5..];
+                files.get(object_id).map(|file_path| archive::percent_encode(&archive::relative(archive::parent(&path), file_path)))
+            });
+
+            // scopes and sequences are listed in the manifest, ids are meaningless to another account
+            let mut fields = serde_yaml::Mapping::new();
+            fields.insert("title".into(), document.title.clone().into());
+            fields.insert("tags".into(), serde_yaml::Value::Sequence(doc_tags.iter().map(|tag| tag.clone().into()).collect()));
+            fields.insert("created_at".into(), document.created_at.format("%Y-%m-%d %H:%M:%S").to_string().into());
+            let raw = front_matter::set(&raw, fields, &["scopes", "sequence"]).map_err(|_| GlobalError::InternalServerError)?;
+            entries.insert(path.clone(), raw.into_bytes());
+
+            manifest.documents.push(ManifestDocument {
+                id: document.id,
+                path: path.clone(),
+                title: document.title.clone(),
+                tags: doc_tags,
+                scope_ids: doc_scopes.iter().filter(|doc_scope| doc_scope.docorg_id == document.id).map(|doc_scope| doc_scope.scope_id).collect(),
+                created_at: document.created_at,
+                updated_at: document.updated_at,
+                files: files.into_values().collect(),
+            });
+            paths.insert(document.id, path);
+
+            progress(state, job_id, idx as i32 + 1, total).await?;
+        }
+
+        manifest.tags = manifest.documents.iter().flat_map(|document| document.tags.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+        let mut sequences: BTreeMap<i32, ManifestSequence> = BTreeMap::new();
+        for (doc_seq, sequence) in &doc_seqs {
+            let sequence = match sequence {
+                Some(sequence) => sequence,
+                None => continue,
+            };
+            sequences.entry(sequence.id).or_insert_with(|| ManifestSequence {
+                id: sequence.id,
+                title: sequence.title.clone(),
+                documents: Vec::new(),
+            }).documents.extend(paths.get(&doc_seq.docorg_id).cloned());
+        }
+        manifest.sequences = sequences.into_values().collect();
+        entries.insert(archive::MANIFEST.to_string(), serde_json::to_vec_pretty(&manifest).map_err(|_| GlobalError::InternalServerError)?);
+
+        /*
+         * upload the archive, and fix it so that sanitize does not remove it.
+         * the object id is a hash of the name, type and upload second, the random part of the name keeps it unguessable,
+         * the archive is served to its owner through /export/download/:job_id
+         */
+        let data = archive::write_zip(&entries).map_err(|_| GlobalError::InternalServerError)?;
+        let token: String = rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+        let mut upload_client = UploadClient::connect(file_proxy_addr).await?;
+        let object_id = upload_client.pre_upload(Request::new(PreUploadRequest {
+            name: format!("docuvault-export-{}-{}.zip", job_id, token),
+            ftype: "application/zip".to_owned(),
+            size: data.len() as u64,
+            docuser_id: job.docuser_id,
+            data,
+            object_id: None,
+        })).await?.into_inner().object_id;
+
+        entity::docfile::Entity::update_many()
+            .col_expr(entity::docfile::Column::IsFixed, Expr::value(true))
+            .filter(entity::docfile::Column::ObjectId.eq(object_id.clone()))
+            .exec(&state.db_conn)
+            .await?;
+        Ok(object_id)
+    }
+}
+
+pub mod site {
+    use std::collections::HashMap;
+
+    use comrak::ComrakOptions;
+    use sea_orm::{entity::*, query::*, sea_query::Expr, DbErr};
+    use tonic::Request;
+
+    use crate::{AppState, entity, routes::{error::GlobalError, document::object::DocumentStatus, site::object::SiteStatus}, modules::{archive, markdown::front_matter, site::{self, Site, SiteDocument, SiteSequence}, grpc::{download::{download_client::DownloadClient, DownloadRequest}, upload::{upload_client::UploadClient, PreUploadRequest}, delete::{delete_client::DeleteClient, DeleteRequest}}}};
+
+    /*
+     * a build holds the site for LEASE_SECONDS and renews it on every round.
+     * a site still RUNNING once its lease ran out was left by a dead build and is built again.
+     */
+    const LEASE_SECONDS: i64 = 30 * 60;
+
+    /*
+     * ask for a build of the site.
+     * requests made while a build is running are picked up by that build once it is done,
+     * so a burst of document updates ends up in at most one extra build.
+     */
+    pub async fn request(state: &AppState, site_id: i32) -> Result<(), DbErr> {
+        let now = chrono::Utc::now().naive_utc();
+        entity::site::Entity::update_many()
+            .col_expr(entity::site::Column::RequestedAt, Expr::value(now))
+            .filter(entity::site::Column::Id.eq(site_id))
+            .exec(&state.db_conn)
+            .await?;
+        let res = entity::site::Entity::update_many()
+            .col_expr(entity::site::Column::Status, Expr::value(SiteStatus::RUNNING as i32))
+            .col_expr(entity::site::Column::LeaseUntil, Expr::value(Some(now + chrono::Duration::seconds(LEASE_SECONDS))))
+            .filter(entity::site::Column::Id.eq(site_id))
+            .filter(Condition::any()
+                .add(entity::site::Column::Status.ne(SiteStatus::RUNNING as i32))
+                .add(entity::site::Column::LeaseUntil.is_null())
+                .add(entity::site::Column::LeaseUntil.lt(now)))
+            .exec(&state.db_conn)
+            .await?;
+        if res.rows_affected == 1 {
+            build(state.clone(), site_id);
+        }
+        Ok(())
+    }
+
+    // rebuild the auto built sites of every scope of the document
+    pub fn request_for_document(state: AppState, doc_id: i32){
+        tokio::spawn(async move {
+            let sites = entity::site::Entity::find()
+                .filter(entity::site::Column::AutoBuild.eq(true))
+                .join_rev(JoinType::InnerJoin, entity::docorg_scope::Entity::belongs_to(entity::site::Entity)
+                          .from(entity::docorg_scope::Column::ScopeId)
+                          .to(entity::site::Column::ScopeId)
+                          .into())
+                .filter(entity::docorg_scope::Column::DocorgId.eq(doc_id))
+                .all(&state.db_conn)
+                .await;
+            let sites = match sites {
+                Ok(sites) => sites,
+                Err(e) => {
+                    dbg!(e);
+                    return;
+                },
+            };
+            for site in sites {
+                if let Err(e) = request(&state, site.id).await {
+                    dbg!(e);
+                }
+            }
+        });
+    }
+
+    fn build(state: AppState, site_id: i32){
+        tokio::spawn(async move {
+            loop {
+                let started_at = chrono::Utc::now().naive_utc();
+                // a failure to renew leaves the lease to run out, and the site to the next request
+                let renewed = entity::site::Entity::update_many()
+                    .col_expr(entity::site::Column::LeaseUntil, Expr::value(Some(started_at + chrono::Duration::seconds(LEASE_SECONDS))))
+                    .filter(entity::site::Column::Id.eq(site_id))
+                    .exec(&state.db_conn)
+                    .await;
+                if let Err(e) = renewed {
+                    dbg!(e);
+                    break;
+                }
+                // rendered in its own task, a panic fails the build as well
+                let render_state = state.clone();
+                let status = match tokio::spawn(async move {
+                    let object_id = render(&render_state, site_id).await?;
+                    replace_object(&render_state, site_id, object_id).await
+                }).await {
+                    Ok(Ok(())) => SiteStatus::DONE,
+                    Ok(Err(e)) => {
+                        dbg!(e);
+                        SiteStatus::FAILED
+                    },
+                    Err(e) => {
+                        dbg!(e);
+                        SiteStatus::FAILED
+                    },
+                };
+                let res = entity::site::Entity::update_many()
+                    .col_expr(entity::site::Column::Status, Expr::value(status as i32))
+                    .col_expr(entity::site::Column::LeaseUntil, Expr::value(Option::<chrono::NaiveDateTime>::None))
+                    .filter(entity::site::Column::Id.eq(site_id))
+                    .filter(entity::site::Column::RequestedAt.lte(started_at))
+                    .exec(&state.db_conn)
+                    .await;
+                match res {
+                    Ok(res) if res.rows_affected == 1 => break,
+                    Ok(_) => {},
+                    Err(e) => {
+                        dbg!(e);
+                        break;
+                    },
+                }
+                // requested again while building(or the site is gone)
+                match entity::site::Entity::find_by_id(site_id).one(&state.db_conn).await {
+                    Ok(Some(_)) => {},
+                    Ok(None) => break,
+                    Err(e) => {
+                        dbg!(e);
+                        break;
+                    },
+                }
+            }
+        });
+    }
+
+    async fn replace_object(state: &AppState, site_id: i32, object_id: String) -> Result<(), GlobalError> {
+        let site = match entity::site::Entity::find_by_id(site_id).one(&state.db_conn).await? {
+            Some(site) => site,
+            None => return Ok(()),
+        };
+        let previous = site.object_id.clone();
+        let mut site: entity::site::ActiveModel = site.into();
+        site.object_id = Set(Some(object_id));
+        site.built_at = Set(Some(chrono::Utc::now().naive_utc()));
+        site.update(&state.db_conn).await?;
+
+        if let Some(previous) = previous {
+            let file_proxy_addr = state.file_proxy_addr.lock().await.clone();
+            let mut delete_client = DeleteClient::connect(file_proxy_addr).await?;
+            delete_client.delete(Request::new(DeleteRequest { object_ids: vec![previous] })).await?;
+        }
+        Ok(())
+    }
+
+    async fn render(state: &AppState, site_id: i32) -> Result<String, GlobalError> {
+        let site = entity::site::Entity::find_by_id(site_id)
+            .one(&state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+        let scope = entity::scope::Entity::find_by_id(site.scope_id)
+            .one(&state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+
+        let documents = entity::docorg::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::docorg_scope::Relation::Docorg.def())
+            .filter(entity::docorg_scope::Column::ScopeId.eq(scope.id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .order_by_asc(entity::docorg::Column::Id)
+            .all(&state.db_conn)
+            .await?;
+        let doc_ids = documents.iter().map(|document| document.id).collect::<Vec<_>>();
+
+        // comrak html rendered on save, documents without it are rendered here
+        let converts = entity::convert::Entity::find()
+            .filter(entity::convert::Column::DocorgId.is_in(doc_ids.clone()))
+            .filter(entity::convert::Column::CType.eq(0))
+            .filter(entity::convert::Column::Status.eq(1))
+            .all(&state.db_conn)
+            .await?
+            .into_iter()
+            .filter_map(|convert| convert.data.map(|data| (convert.docorg_id, data)))
+            .collect::<HashMap<_, _>>();
+        let doc_tags = entity::docorg_tag::Entity::find()
+            .filter(entity::docorg_tag::Column::DocorgId.is_in(doc_ids.clone()))
+            .find_also_related(entity::tag::Entity)
+            .all(&state.db_conn)
+            .await?;
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for (doc_tag, tag) in doc_tags {
+            if let Some(tag) = tag {
+                tags.entry(doc_tag.docorg_id).or_default().push(tag.value);
+            }
+        }
+
+        let sequences = entity::sequence::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::scope_sequence::Relation::Sequence.def())
+            .filter(entity::scope_sequence::Column::ScopeId.eq(scope.id))
+            .order_by_asc(entity::sequence::Column::Id)
+            .all(&state.db_conn)
+            .await?;
+        let doc_seqs = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::SequenceId.is_in(sequences.iter().map(|sequence| sequence.id).collect::<Vec<_>>()))
+            .order_by_asc(entity::docorg_sequence::Column::Order)
+            .all(&state.db_conn)
+            .await?;
+
+        let docfiles = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::DocorgId.is_in(doc_ids.clone()))
+            .filter(entity::docfile::Column::IsFixed.eq(true))
+            .all(&state.db_conn)
+            .await?;
+
+        let input = Site {
+            title: scope.name.clone(),
+            documents: documents.into_iter().map(|document| SiteDocument {
+                id: document.id,
+                html: match converts.get(&document.id) {
+                    Some(html) => html.clone(),
+                    None => comrak::markdown_to_html(front_matter::strip(&document.raw), &ComrakOptions::default()),
+                },
+                title: document.title,
+                tags: tags.remove(&document.id).unwrap_or_default(),
+                excerpt: document.excerpt,
+                reading_time: document.reading_time,
+                created_at: document.created_at,
+                updated_at: document.updated_at,
+            }).collect(),
+            sequences: sequences.iter().map(|sequence| SiteSequence {
+                title: sequence.title.clone(),
+                doc_ids: doc_seqs.iter().filter(|doc_seq| doc_seq.sequence_id == sequence.id).map(|doc_seq| doc_seq.docorg_id).collect(),
+            }).collect(),
+            files: docfiles.iter().map(|docfile| (docfile.object_id.clone(), archive::file_name(&docfile.name).to_string())).collect(),
+        };
+        let mut entries = site::render(&input).map_err(|e| {
+            dbg!(e);
+            GlobalError::InternalServerError
+        })?;
+
+        /*
+         * copy attachments in
+         */
+        let file_proxy_addr = state.file_proxy_addr.lock().await.clone();
+        let mut download_client = DownloadClient::connect(file_proxy_addr.clone()).await?;
+        for (object_id, name) in &input.files {
+            let res = download_client.download(Request::new(DownloadRequest {
+                object_id: object_id.clone(),
+            })).await?.into_inner();
+            entries.insert(format!("files/{}/{}", object_id, name), res.data);
+        }
+
+        let data = archive::write_zip(&entries).map_err(|_| GlobalError::InternalServerError)?;
+        let mut upload_client = UploadClient::connect(file_proxy_addr).await?;
+        let object_id = upload_client.pre_upload(Request::new(PreUploadRequest {
+            name: format!("{}-site.zip", archive::slug(&scope.name)),
+            ftype: "application/zip".to_owned(),
+            size: data.len() as u64,
+            docuser_id: site.docuser_id,
+            data,
+            object_id: None,
+        })).await?.into_inner().object_id;
+
+        // fixed so that sanitize does not remove it
+        entity::docfile::Entity::update_many()
+            .col_expr(entity::docfile::Column::IsFixed, Expr::value(true))
+            .filter(entity::docfile::Column::ObjectId.eq(object_id.clone()))
+            .exec(&state.db_conn)
+            .await?;
+        Ok(object_id)
+    }
+}
+
+pub mod derived {
+    use redis::AsyncCommands;
+    use sea_orm::{entity::*, query::*};
+
+    use crate::{AppState, entity, routes::{error::GlobalError, document::service::DocumentService}};
+
+    const BATCH: u64 = 100;
+    // one instance backfills at a time, the lock is renewed on every batch
+    const LOCK_KEY: &str = "derived:backfill";
+    const LOCK_TTL: usize = 10 * 60;
+
+    // outline and statistics of documents saved before they were stored
+    pub fn backfill(state: AppState){
+        tokio::spawn(async move {
+            if let Err(e) = run(&state).await {
+                dbg!(e);
+            }
+        });
+    }
+
+    async fn run(state: &AppState) -> Result<(), GlobalError> {
+        let mut con = state.redis_conn.get().await?;
+        let locked: Option<String> = redis::cmd("SET")
+            .arg(LOCK_KEY)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(LOCK_TTL)
+            .query_async(&mut *con)
+            .await?;
+        drop(con);
+        if locked.is_none() {
+            return Ok(());
+        }
+        let res = batches(state).await;
+        // released on failure as well, the next instance to start tries again
+        let mut con = state.redis_conn.get().await?;
+        let _: () = con.del(LOCK_KEY).await?;
+        res
+    }
+
+    async fn batches(state: &AppState) -> Result<(), GlobalError> {
+        loop {
+            let documents = entity::docorg::Entity::find()
+                .filter(entity::docorg::Column::WordCount.is_null())
+                .order_by_asc(entity::docorg::Column::Id)
+                .limit(BATCH)
+                .all(&state.db_conn)
+                .await?;
+            if documents.is_empty() {
+                return Ok(());
+            }
+            for document in documents {
+                let raw = document.raw.clone();
+                let mut document: entity::docorg::ActiveModel = document.into();
+                DocumentService::set_derived(&mut document, &raw);
+                document.update(&state.db_conn).await?;
+            }
+            let mut con = state.redis_conn.get().await?;
+            let _: () = con.expire(LOCK_KEY, LOCK_TTL).await?;
+        }
+    }
+}
+
+pub mod watch {
+    use sea_orm::{entity::*, query::*, sea_query::OnConflict};
+
+    use crate::{AppState, entity, routes::{error::GlobalError, document::object::DocumentStatus}, modules::{filter::DocFilter, mail}};
+
+    /*
+     * mail the author of every saved query with notifications on that the saved document
+     * now matches. a document is reported once per query, see saved_query_hit.
+     */
+    pub fn document(state: AppState, doc_id: i32){
+        tokio::spawn(async move {
+            if let Err(e) = notify(&state, doc_id).await {
+                dbg!(e);
+            }
+        });
+    }
+
+    async fn notify(state: &AppState, doc_id: i32) -> Result<(), GlobalError> {
+        let document = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&state.db_conn)
+            .await?;
+        let document = match document {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+        let queries = entity::saved_query::Entity::find()
+            .filter(entity::saved_query::Column::DocuserId.eq(document.docuser_id))
+            .filter(entity::saved_query::Column::Notify.eq(true))
+            .all(&state.db_conn)
+            .await?;
+        if queries.is_empty() {
+            return Ok(());
+        }
+        let docuser = entity::docuser::Entity::find_by_id(document.docuser_id)
+            .one(&state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+
+        for query in queries {
+            let filter = match DocFilter::from_json(&query.filter) {
+                Some(filter) => filter,
+                None => continue,
+            };
+            let matched = filter.select(query.docuser_id)
+                .filter(entity::docorg::Column::Id.eq(document.id))
+                .count(&state.db_conn)
+                .await?;
+            if matched == 0 {
+                continue;
+            }
+            let hit = entity::saved_query_hit::ActiveModel {
+                saved_query_id: Set(query.id),
+                docorg_id: Set(document.id),
+                ..Default::default()
+            };
+            let inserted = entity::saved_query_hit::Entity::insert(hit)
+                .on_conflict(OnConflict::columns([entity::saved_query_hit::Column::SavedQueryId, entity::saved_query_hit::Column::DocorgId])
+                    .do_nothing()
+                    .to_owned())
+                .exec_without_returning(&state.db_conn)
+                .await?;
+            if inserted == 0 {
+                continue;
+            }
+            let body = format!("\"{}\" has a new match:\n\n{} (document {})\n", query.name, document.title, document.id);
+            if let Err(e) = mail::send(&docuser.email, &format!("[docuvault] {}", query.name), &body).await {
+                dbg!(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub mod links {
+    use std::collections::{HashMap, HashSet};
+
+    use sea_orm::{entity::*, query::*};
+    use tonic::{Code, Request};
+
+    use crate::{AppState, entity, routes::{error::GlobalError, document::object::DocumentStatus, link::object::LinkStatus}, modules::{probe::{self, ProbeError}, markdown::{outline, links::{self, Target}}, grpc::download::{download_client::DownloadClient, DownloadRequest}}};
+
+    /*
+     * check the links of the documents one after another and replace their stored results.
+     * results of attachments and external urls are shared across the documents of one run.
+     */
+    pub fn check(state: AppState, doc_ids: Vec<i32>, external: bool){
+        tokio::spawn(async move {
+            let mut seen: HashMap<Target, Result<(), String>> = HashMap::new();
+            let mut probed: HashMap<String, Result<(), ProbeError>> = HashMap::new();
+            for doc_id in doc_ids {
+                if let Err(e) = check_document(&state, doc_id, external, &mut seen, &mut probed).await {
+                    dbg!(e);
+                }
+            }
+        });
+    }
+
+    async fn check_document(state: &AppState, doc_id: i32, external: bool, seen: &mut HashMap<Target, Result<(), String>>, probed: &mut HashMap<String, Result<(), ProbeError>>) -> Result<(), GlobalError> {
+        let document = match entity::docorg::Entity::find_by_id(doc_id).one(&state.db_conn).await? {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+        let anchors = outline::from_json(document.outline.as_ref(), &document.raw)
+            .into_iter()
+            .map(|heading| heading.anchor)
+            .collect::<HashSet<_>>();
+        let docfiles = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::DocorgId.eq(document.id))
+            .all(&state.db_conn)
+            .await?
+            .into_iter()
+            .map(|docfile| (docfile.object_id, docfile.is_fixed))
+            .collect::<HashMap<_, _>>();
+
+        let mut results = Vec::new();
+        for link in links::links(&document.raw) {
+            let res = match &link.target {
+                Target::Other => continue,
+                Target::External(_) if !external => {
+                    results.push((link, LinkStatus::SKIPPED, None));
+                    continue;
+                },
+                Target::Anchor(anchor) => match anchors.contains(anchor) {
+                    true => Ok(()),
+                    false => Err(format!("no heading #{}", anchor)),
+                },
+                Target::File(object_id) => match docfiles.get(object_id) {
+                    None => Err("not attached to the document".to_string()),
+                    Some(false) => Err("upload not fixed to the document".to_string()),
+                    Some(true) => match seen.get(&link.target) {
+                        Some(res) => res.clone(),
+                        None => {
+                            let res = file_exists(state, object_id).await;
+                            seen.insert(link.target.clone(), res.clone());
+                            res
+                        },
+                    },
+                },
+                Target::External(url) => {
+                    let res = match probed.get(url) {
+                        Some(res) => res.clone(),
+                        None => {
+                            let res = probe::check(state.modules.probe.as_ref(), url).await;
+                            probed.insert(url.clone(), res.clone());
+                            res
+                        },
+                    };
+                    // urls of internal addresses are not requested
+                    match res {
+                        Ok(()) => Ok(()),
+                        Err(ProbeError::Failed(detail)) => Err(detail),
+                        Err(ProbeError::Refused(detail)) => {
+                            results.push((link, LinkStatus::SKIPPED, Some(detail)));
+                            continue;
+                        },
+                    }
+                },
+                Target::Document(target_id, anchor) => document_exists(state, document.docuser_id, *target_id, anchor.as_deref()).await?,
+                Target::Page(link_id) => page_exists(state, link_id).await?,
+                Target::Wiki(title) => {
+                    let count = entity::docorg::Entity::find()
+                        .filter(entity::docorg::Column::DocuserId.eq(document.docuser_id))
+                        .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+                        .filter(entity::docorg::Column::Title.eq(title.as_str()))
+                        .count(&state.db_conn)
+                        .await?;
+                    match count {
+                        0 => Err(format!("no document titled \"{}\"", title)),
+                        _ => Ok(()),
+                    }
+                },
+            };
+            match res {
+                Ok(()) => results.push((link, LinkStatus::OK, None)),
+                Err(detail) => results.push((link, LinkStatus::BROKEN, Some(detail))),
+            }
+        }
+
+        let checked_at = chrono::Utc::now().naive_utc();
+        let rows = results.into_iter()
+            .map(|(link, status, detail)| entity::link_check::ActiveModel {
+                docorg_id: Set(document.id),
+                kind: Set(link.target.kind().to_string()),
+                url: Set(link.url),
+                line: Set(link.line as i32),
+                status: Set(status as i32),
+                detail: Set(detail),
+                checked_at: Set(checked_at),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        state.db_conn.transaction::<_, (), GlobalError>(|txn| {
+            Box::pin(async move {
+                entity::link_check::Entity::delete_many()
+                    .filter(entity::link_check::Column::DocorgId.eq(doc_id))
+                    .exec(txn)
+                    .await?;
+                if !rows.is_empty() {
+                    entity::link_check::Entity::insert_many(rows).exec(txn).await?;
+                }
+                Ok(())
+            })
+        }).await?;
+        Ok(())
+    }
+
+    // the first chunk of the stream is enough to know the object is there
+    async fn file_exists(state: &AppState, object_id: &str) -> Result<(), String> {
+        let file_proxy_addr = state.file_proxy_addr.lock().await.clone();
+        let mut client = DownloadClient::connect(file_proxy_addr).await.map_err(|e| e.to_string())?;
+        let res = client.download_stream(Request::new(DownloadRequest { object_id: object_id.to_string() })).await;
+        let head = match res {
+            Ok(res) => res.into_inner().message().await,
+            Err(status) => Err(status),
+        };
+        match head {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err("missing in the file service".to_string()),
+            Err(status) if status.code() == Code::NotFound => Err("missing in the file service".to_string()),
+            Err(status) => Err(status.message().to_string()),
+        }
+    }
+
+    async fn document_exists(state: &AppState, docuser_id: i32, doc_id: i32, anchor: Option<&str>) -> Result<Result<(), String>, GlobalError> {
+        let target = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&state.db_conn)
+            .await?;
+        let target = match target {
+            Some(target) => target,
+            None => return Ok(Err("document does not exist".to_string())),
+        };
+        let anchor = match anchor {
+            Some(anchor) => anchor,
+            None => return Ok(Ok(())),
+        };
+        let found = outline::from_json(target.outline.as_ref(), &target.raw)
+            .iter()
+            .any(|heading| heading.anchor == anchor);
+        match found {
+            true => Ok(Ok(())),
+            false => Ok(Err(format!("no heading #{} in document {}", anchor, doc_id))),
+        }
+    }
+
+    async fn page_exists(state: &AppState, link_id: &str) -> Result<Result<(), String>, GlobalError> {
+        let link = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::LinkId.eq(link_id))
+            .one(&state.db_conn)
+            .await?;
+        let now = chrono::Utc::now().naive_utc();
+        let res = match link {
+            None => Err("share link does not exist".to_string()),
+            Some(link) if link.revoked => Err("share link revoked".to_string()),
+            Some(link) if link.expires_at.is_some_and(|expires_at| expires_at <= now) => Err("share link expired".to_string()),
+            Some(link) if link.max_views.is_some_and(|max_views| link.views >= max_views) => Err("share link used up".to_string()),
+            Some(_) => Ok(()),
+        };
+        Ok(res)
+    }
+}
+
+pub mod webhooks {
+    use std::time::Duration;
+
+    use sea_orm::{entity::*, query::*, sea_query::Expr, ConnectionTrait, DbErr};
+
+    use crate::{AppState, entity, routes::{error::GlobalError, webhook::object::WebhookDeliveryStatus}, modules::{webhook, events::{self, Envelope}}};
+
+    // events of docuvault-file and docuvault-convert: the instance that claims one in redis first queues its deliveries, the others skip it
+    const CLAIM_TTL: usize = 60 * 60;
+    const POLL_INTERVAL: Duration = Duration::from_secs(5);
+    const BATCH: u64 = 20;
+    // a claimed delivery is left to its instance for this long before another one tries it again
+    const LEASE_SECONDS: i64 = 5 * 60;
+
+    fn claim_key(envelope_id: &str) -> String {
+        format!("webhook:claim:{}", envelope_id)
+    }
+
+    // the deliveries of the event to the user's webhooks subscribed to it, in the caller's transaction
+    pub async fn enqueue<C: ConnectionTrait>(conn: &C, docuser_id: i32, envelope: &Envelope) -> Result<(), DbErr> {
+        let name = envelope.event.name();
+        if !webhook::EVENTS.contains(&name) {
+            return Ok(());
+        }
+        let scope_ids = match &envelope.scope_ids {
+            Some(scope_ids) => scope_ids.clone(),
+            None => events::scopes_of(conn, &envelope.event).await?,
+        };
+        let hooks = entity::webhook::Entity::find()
+            .filter(entity::webhook::Column::DocuserId.eq(docuser_id))
+            .filter(entity::webhook::Column::Active.eq(true))
+            .filter(
+                Condition::any()
+                    .add(entity::webhook::Column::ScopeId.is_null())
+                    .add(entity::webhook::Column::ScopeId.is_in(scope_ids))
+            )
+            .all(conn)
+            .await?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let data = serde_json::to_value(&envelope.event).unwrap_or_default();
+        let rows = hooks.into_iter()
+            .filter(|hook| webhook::subscribed(&hook.events, name))
+            .map(|hook| entity::webhook_delivery::ActiveModel {
+                webhook_id: Set(hook.id),
+                event: Set(name.to_string()),
+                payload: Set(webhook::payload(&envelope.id, name, now, data.clone())),
+                status: Set(WebhookDeliveryStatus::PENDING as i32),
+                attempts: Set(0),
+                next_attempt_at: Set(now),
+                created_at: Set(now),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        if !rows.is_empty() {
+            entity::webhook_delivery::Entity::insert_many(rows).exec(conn).await?;
+        }
+        Ok(())
+    }
+
+    // events that were not queued by their publisher, see events::Envelope
+    pub fn enqueue_claimed(state: AppState, docuser_id: i32, envelope: Envelope){
+        if !webhook::EVENTS.contains(&envelope.event.name()) {
+            return;
+        }
+        tokio::spawn(async move {
+            if let Err(e) = claim_and_enqueue(&state, docuser_id, envelope).await {
+                dbg!(e);
+            }
+        });
+    }
+
+    async fn claim_and_enqueue(state: &AppState, docuser_id: i32, envelope: Envelope) -> Result<(), GlobalError> {
+        let mut con = state.redis_conn.get().await?;
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(claim_key(&envelope.id))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(CLAIM_TTL)
+            .query_async(&mut *con)
+            .await?;
+        drop(con);
+        if claimed.is_none() {
+            return Ok(());
+        }
+        enqueue(&state.db_conn, docuser_id, &envelope).await?;
+        Ok(())
+    }
+
+    /*
+     * send the deliveries that are due, for as long as the server runs.
+     * rows are the queue, so nothing is lost across restarts and every instance can run a worker.
+     */
+    pub fn worker(state: AppState){
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = deliver_due(&state).await {
+                    dbg!(e);
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn deliver_due(state: &AppState) -> Result<(), GlobalError> {
+        let now = chrono::Utc::now().naive_utc();
+        let due = entity::webhook_delivery::Entity::find()
+            .filter(entity::webhook_delivery::Column::Status.is_in([WebhookDeliveryStatus::PENDING as i32, WebhookDeliveryStatus::RETRYING as i32]))
+            .filter(entity::webhook_delivery::Column::NextAttemptAt.lte(now))
+            .order_by_asc(entity::webhook_delivery::Column::NextAttemptAt)
+            .limit(BATCH)
+            .all(&state.db_conn)
+            .await?;
+        for delivery in due {
+            // moving the next attempt past the lease claims the row, it fails when another worker moved it first
+            let claimed = entity::webhook_delivery::Entity::update_many()
+                .col_expr(entity::webhook_delivery::Column::NextAttemptAt, Expr::value(now + chrono::Duration::seconds(LEASE_SECONDS)))
+                .filter(entity::webhook_delivery::Column::Id.eq(delivery.id))
+                .filter(entity::webhook_delivery::Column::Status.eq(delivery.status))
+                .filter(entity::webhook_delivery::Column::NextAttemptAt.eq(delivery.next_attempt_at))
+                .exec(&state.db_conn)
+                .await?
+                .rows_affected;
+            if claimed == 0 {
+                continue;
+            }
+            attempt(state, delivery).await?;
+        }
+        Ok(())
+    }
+
+    // one attempt of a delivery claimed by the caller, the updated row is returned
+    pub async fn attempt(state: &AppState, delivery: entity::webhook_delivery::Model) -> Result<entity::webhook_delivery::Model, GlobalError> {
+        let hook = entity::webhook::Entity::find_by_id(delivery.webhook_id)
+            .one(&state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+        let res = match hook.active {
+            true => webhook::send(&hook.url, &hook.secret, delivery.id, &delivery.event, &delivery.payload.to_string()).await,
+            false => Err((None, "webhook is not active".to_string())),
+        };
+
+        let now = chrono::Utc::now().naive_utc();
+        let attempts = delivery.attempts + 1;
+        let mut delivery: entity::webhook_delivery::ActiveModel = delivery.into();
+        delivery.attempts = Set(attempts);
+        match res {
+            Ok(status) => {
+                delivery.status = Set(WebhookDeliveryStatus::DELIVERED as i32);
+                delivery.response_status = Set(Some(status as i32));
+                delivery.error = Set(None);
+                delivery.delivered_at = Set(Some(now));
+            },
+            Err((status, error)) => {
+                let status_after = match attempts >= webhook::MAX_ATTEMPTS || !hook.active {
+                    true => WebhookDeliveryStatus::DEAD,
+                    false => WebhookDeliveryStatus::RETRYING,
+                };
+                delivery.status = Set(status_after as i32);
+                delivery.response_status = Set(status.map(|status| status as i32));
+                delivery.error = Set(Some(error));
+                delivery.next_attempt_at = Set(now + webhook::backoff(attempts));
+            },
+        }
+        Ok(delivery.update(&state.db_conn).await?)
+    }
+}
+
+pub mod schedule {
+    use std::{env, time::Duration};
+
+    use chrono::{NaiveDateTime, SubsecRound};
+    use sea_orm::{entity::*, query::*, sea_query::Expr, UpdateMany};
+    use tonic::Request;
+
+    use crate::{AppState, entity::{self, publish_schedule::{self, Column}}, routes::{error::GlobalError, schedule::object::PublishScheduleStatus, share::{service::ShareService, object::ShareCreatePayload}}, modules::{audit::Actor, markdown::front_matter, events::{self, ServerEvent}, grpc::convert::{convert_client::ConvertClient, ConvertRequest}}};
+
+    const POLL_INTERVAL: Duration = Duration::from_secs(15);
+    const BATCH: u64 = 20;
+    // the conversion is requested this long before publish_at, so the link is ready on time
+    const CONVERT_LEAD_SECONDS: i64 = 10 * 60;
+    // a schedule still waiting for its conversion this long after publish_at fails
+    const CONVERT_TIMEOUT_SECONDS: i64 = 30 * 60;
+    // a claimed schedule is left to its instance for this long before another one tries it again
+    const LEASE_SECONDS: i64 = 2 * 60;
+
+    /*
+     * publish and unpublish the schedules that are due, for as long as the server runs.
+     * the rows are the timers, so nothing is lost across restarts, and every instance can run a worker:
+     * a row is claimed with a conditional update before anything is done with it.
+     */
+    pub fn worker(state: AppState){
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = request_conversions(&state).await {
+                    dbg!(e);
+                }
+                if let Err(e) = publish_due(&state).await {
+                    dbg!(e);
+                }
+                if let Err(e) = unpublish_due(&state).await {
+                    dbg!(e);
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    /*
+     * the conditional updates claiming a schedule. each applies only while the row is as the caller
+     * saw it or left it, no row affected means another instance, or a cancel, came first.
+     */
+    fn claim_conversion(schedule_id: i32) -> UpdateMany<publish_schedule::Entity> {
+        publish_schedule::Entity::update_many()
+            .col_expr(Column::ConvertRequested, Expr::value(true))
+            .filter(Column::Id.eq(schedule_id))
+            .filter(Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(Column::ConvertRequested.eq(false))
+    }
+
+    // kept to the microsecond like the column, the claim is compared with it afterwards
+    fn lease(now: NaiveDateTime) -> NaiveDateTime {
+        (now + chrono::Duration::seconds(LEASE_SECONDS)).trunc_subsecs(6)
+    }
+
+    fn claim_publish(schedule: &publish_schedule::Model, link_id: &str, lease_until: NaiveDateTime) -> UpdateMany<publish_schedule::Entity> {
+        publish_schedule::Entity::update_many()
+            .col_expr(Column::LeaseUntil, Expr::value(Some(lease_until)))
+            .col_expr(Column::LinkId, Expr::value(Some(link_id.to_string())))
+            .filter(Column::Id.eq(schedule.id))
+            .filter(Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(match schedule.lease_until {
+                Some(lease_until) => Column::LeaseUntil.eq(lease_until),
+                None => Column::LeaseUntil.is_null(),
+            })
+    }
+
+    // a schedule claimed until lease_until, as long as the claim holds
+    fn claimed(schedule_id: i32, lease_until: NaiveDateTime) -> UpdateMany<publish_schedule::Entity> {
+        publish_schedule::Entity::update_many()
+            .col_expr(Column::LeaseUntil, Expr::value(Option::<NaiveDateTime>::None))
+            .filter(Column::Id.eq(schedule_id))
+            .filter(Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(Column::LeaseUntil.eq(lease_until))
+    }
+
+    fn published(schedule_id: i32, lease_until: NaiveDateTime) -> UpdateMany<publish_schedule::Entity> {
+        claimed(schedule_id, lease_until)
+            .col_expr(Column::Status, Expr::value(PublishScheduleStatus::PUBLISHED as i32))
+    }
+
+    fn failed(schedule_id: i32, lease_until: NaiveDateTime, error: &str) -> UpdateMany<publish_schedule::Entity> {
+        claimed(schedule_id, lease_until)
+            .col_expr(Column::Status, Expr::value(PublishScheduleStatus::FAILED as i32))
+            .col_expr(Column::Error, Expr::value(Some(error.to_string())))
+    }
+
+    fn claim_unpublish(schedule_id: i32) -> UpdateMany<publish_schedule::Entity> {
+        publish_schedule::Entity::update_many()
+            .col_expr(Column::Status, Expr::value(PublishScheduleStatus::UNPUBLISHED as i32))
+            .filter(Column::Id.eq(schedule_id))
+            .filter(Column::Status.eq(PublishScheduleStatus::PUBLISHED as i32))
+    }
+
+    // conversions of the schedules publishing soon, requested once unless the request itself failed
+    async fn request_conversions(state: &AppState) -> Result<(), GlobalError> {
+        let lead = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(CONVERT_LEAD_SECONDS);
+        let due = publish_schedule::Entity::find()
+            .filter(Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(Column::ConvertRequested.eq(false))
+            .filter(Column::PublishAt.lte(lead))
+            .order_by_asc(Column::PublishAt)
+            .limit(BATCH)
+            .all(&state.db_conn)
+            .await?;
+        for schedule in due {
+            let claimed = claim_conversion(schedule.id).exec(&state.db_conn).await?.rows_affected;
+            if claimed == 0 {
+                continue;
+            }
+            if let Err(e) = request_conversion(state, &schedule).await {
+                // asked again on the next run
+                publish_schedule::Entity::update_many()
+                    .col_expr(Column::ConvertRequested, Expr::value(false))
+                    .filter(Column::Id.eq(schedule.id))
+                    .exec(&state.db_conn)
+                    .await?;
+                dbg!(e);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * a finished conversion is published as it is, like /document/convert does: the convert service writes
+     * a new result into a row of its own, so replacing it would take the content from the links already
+     * published for the document and type until the new one is done. a conversion still running is left to finish,
+     * a failed one is removed for the convert service to store the new result.
+     */
+    async fn request_conversion(state: &AppState, schedule: &publish_schedule::Model) -> Result<(), GlobalError> {
+        let docorg = match entity::docorg::Entity::find_by_id(schedule.docorg_id).one(&state.db_conn).await? {
+            Some(docorg) => docorg,
+            None => return Ok(()),
+        };
+        let convert = entity::convert::Entity::find_by_id((schedule.docorg_id, schedule.c_type))
+            .one(&state.db_conn)
+            .await?;
+        let failed = match convert {
+            Some(convert) if convert.status != 2 || schedule.c_type == 0 => return Ok(()),
+            failed => failed,
+        };
+        let convert_addr = env::var("CONVERT_ADDR").map_err(|_| GlobalError::InternalServerError)?;
+        let mut convert_client = ConvertClient::connect(convert_addr).await?;
+        if let Some(failed) = failed {
+            entity::convert::Entity::delete_many()
+                .filter(entity::convert::Column::DocorgId.eq(failed.docorg_id))
+                .filter(entity::convert::Column::CType.eq(failed.c_type))
+                .filter(entity::convert::Column::Status.eq(2))
+                .exec(&state.db_conn)
+                .await?;
+        }
+        convert_client.convert(Request::new(ConvertRequest{
+            title: docorg.title,
+            doc_id: docorg.id,
+            docuser_id: schedule.docuser_id,
+            data: front_matter::strip(&docorg.raw).to_owned(),
+            c_type: schedule.c_type,
+        })).await?;
+        Actor::background(schedule.docuser_id)
+            .entry("document.convert")
+            .targets([docorg.id])
+            .scopes([schedule.scope_id])
+            .summary(serde_json::json!({ "c_type": schedule.c_type, "schedule_id": schedule.id }))
+            .record(&state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn publish_due(state: &AppState) -> Result<(), GlobalError> {
+        let now = chrono::Utc::now().naive_utc();
+        let due = publish_schedule::Entity::find()
+            .filter(Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(Column::PublishAt.lte(now))
+            .filter(Condition::any()
+                .add(Column::LeaseUntil.is_null())
+                .add(Column::LeaseUntil.lt(now)))
+            .order_by_asc(Column::PublishAt)
+            .limit(BATCH)
+            .all(&state.db_conn)
+            .await?;
+        for schedule in due {
+            // the link id is picked with the first claim, so a retry finds the link an earlier attempt created
+            let link_id = schedule.link_id.clone().unwrap_or_else(ShareService::new_link_id);
+            let lease_until = lease(now);
+            let claimed = claim_publish(&schedule, &link_id, lease_until).exec(&state.db_conn).await?.rows_affected;
+            if claimed == 0 {
+                continue;
+            }
+            publish(state, schedule, link_id, lease_until).await?;
+        }
+        Ok(())
+    }
+
+    // what a claimed schedule without its link goes on with, from the status of its conversion
+    #[derive(Debug, PartialEq)]
+    enum Step {
+        Publish,
+        // no conversion to wait for: it was never requested, or the request was lost
+        Convert,
+        Wait,
+        Fail(&'static str),
+    }
+
+    fn step(schedule: &publish_schedule::Model, convert_status: Option<i32>, now: NaiveDateTime) -> Step {
+        if schedule.unpublish_at.is_some_and(|unpublish_at| unpublish_at <= now) {
+            return Step::Fail("unpublish_at passed before the document was published");
+        }
+        match convert_status {
+            Some(1) => Step::Publish,
+            Some(2) => Step::Fail("conversion failed"),
+            _ if schedule.publish_at + chrono::Duration::seconds(CONVERT_TIMEOUT_SECONDS) <= now => Step::Fail("conversion did not finish in time"),
+            None => Step::Convert,
+            Some(_) => Step::Wait,
+        }
+    }
+
+    /*
+     * a schedule claimed by the caller until lease_until. the link is created in the transaction marking
+     * the schedule published, which applies only while the claim holds: a schedule cancelled, or claimed
+     * again by another instance once the lease ran out, gets no link from this one.
+     */
+    async fn publish(state: &AppState, schedule: publish_schedule::Model, link_id: String, lease_until: NaiveDateTime) -> Result<(), GlobalError> {
+        let now = chrono::Utc::now().naive_utc();
+        let link_exists = |link_id: String| async move {
+            entity::share_link::Entity::find()
+                .filter(entity::share_link::Column::LinkId.eq(link_id))
+                .one(&state.db_conn)
+                .await
+                .map(|link| link.is_some())
+        };
+        // created by an earlier attempt, the schedule is published already
+        let exists = link_exists(link_id.clone()).await?;
+        if !exists {
+            let convert = entity::convert::Entity::find_by_id((schedule.docorg_id, schedule.c_type))
+                .one(&state.db_conn)
+                .await?;
+            match step(&schedule, convert.map(|convert| convert.status), now) {
+                Step::Publish => {},
+                Step::Fail(error) => {
+                    failed(schedule.id, lease_until, error).exec(&state.db_conn).await?;
+                    return Ok(());
+                },
+                // e.g. the instance that claimed the request stopped before making it, released for the next run
+                Step::Convert => {
+                    publish_schedule::Entity::update_many()
+                        .col_expr(Column::ConvertRequested, Expr::value(true))
+                        .filter(Column::Id.eq(schedule.id))
+                        .exec(&state.db_conn)
+                        .await?;
+                    if let Err(e) = request_conversion(state, &schedule).await {
+                        dbg!(e);
+                    }
+                    claimed(schedule.id, lease_until).exec(&state.db_conn).await?;
+                    return Ok(());
+                },
+                // still converting, released for the next run
+                Step::Wait => {
+                    claimed(schedule.id, lease_until).exec(&state.db_conn).await?;
+                    return Ok(());
+                },
+            }
+        }
+
+        let txn = state.db_conn.begin().await?;
+        if published(schedule.id, lease_until).exec(&txn).await?.rows_affected == 0 {
+            return Ok(());
+        }
+        if exists {
+            txn.commit().await?;
+            return Ok(());
+        }
+        let payload = ShareCreatePayload {
+            doc_id: schedule.docorg_id,
+            scope_ids: vec![schedule.scope_id],
+            c_type: schedule.c_type,
+            expires_in: None,
+            max_views: None,
+            password: None,
+            allowed_domains: Vec::new(),
+        };
+        let res = ShareService::new(state.clone())
+            .insert_link(&txn, schedule.docuser_id, payload, &link_id, schedule.unpublish_at, &Actor::background(schedule.docuser_id))
+            .await;
+        match res {
+            Ok(recorded) => {
+                txn.commit().await?;
+                events::send(state, recorded).await;
+            },
+            Err(e) => {
+                txn.rollback().await?;
+                // a link with the schedule's id is its own, whoever created it
+                match link_exists(link_id).await? {
+                    true => published(schedule.id, lease_until).exec(&state.db_conn).await?,
+                    // e.g. the document was deleted or left the scope since
+                    false => failed(schedule.id, lease_until, &format!("{:?}", e)).exec(&state.db_conn).await?,
+                };
+            },
+        }
+        Ok(())
+    }
+
+    // the link already expires at unpublish_at, it is revoked as well so the schedule shows up in the audit log and events
+    async fn unpublish_due(state: &AppState) -> Result<(), GlobalError> {
+        let now = chrono::Utc::now().naive_utc();
+        let due = publish_schedule::Entity::find()
+            .filter(Column::Status.eq(PublishScheduleStatus::PUBLISHED as i32))
+            .filter(Column::UnpublishAt.lte(now))
+            .order_by_asc(Column::UnpublishAt)
+            .limit(BATCH)
+            .all(&state.db_conn)
+            .await?;
+        for schedule in due {
+            let link_id = match schedule.link_id {
+                Some(link_id) => link_id,
+                None => continue,
+            };
+            // the status flip claims the row, in the same transaction as the revocation
+            let txn = state.db_conn.begin().await?;
+            let claimed = claim_unpublish(schedule.id).exec(&txn).await?.rows_affected;
+            if claimed == 0 {
+                continue;
+            }
+            entity::share_link::Entity::update_many()
+                .col_expr(entity::share_link::Column::Revoked, Expr::value(true))
+                .filter(entity::share_link::Column::LinkId.eq(link_id.as_str()))
+                .exec(&txn)
+                .await?;
+            Actor::background(schedule.docuser_id)
+                .entry("document.unpublish")
+                .targets([schedule.docorg_id])
+                .scopes([schedule.scope_id])
+                .summary(serde_json::json!({ "link_id": link_id, "schedule_id": schedule.id }))
+                .record(&txn)
+                .await?;
+            let recorded = events::record(&txn, schedule.docuser_id, ServerEvent::DocumentUnpublished { doc_id: schedule.docorg_id, link_id }).await?;
+            txn.commit().await?;
+            events::send(state, recorded).await;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn schedule_test() {
+        use sea_orm::DbBackend;
+
+        let sql = |update: UpdateMany<publish_schedule::Entity>| update.build(DbBackend::Postgres).to_string();
+        let at = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+        let schedule = publish_schedule::Model {
+            id: 7,
+            docorg_id: 3,
+            docuser_id: 2,
+            scope_id: 5,
+            c_type: 4,
+            publish_at: at("2023-08-22 10:00:00"),
+            unpublish_at: Some(at("2023-08-23 10:00:00")),
+            status: PublishScheduleStatus::SCHEDULED as i32,
+            link_id: None,
+            convert_requested: true,
+            lease_until: None,
+            error: None,
+            created_at: at("2023-08-21 10:00:00"),
+        };
+
+        // the lease compares equal once stored, the rendered statements below print it in seconds
+        let lease_until = lease(at("2023-08-22 10:00:00.123456789"));
+        assert_eq!(lease_until, at("2023-08-22 10:02:00.123456"));
+
+        // claimed once: a first claim needs no lease, a retry the expired one it read
+        assert_eq!(
+            sql(claim_publish(&schedule, "l1", lease_until)),
+            r#"UPDATE "publish_schedule" SET "lease_until" = '2023-08-22 10:02:00', "link_id" = 'l1' WHERE "publish_schedule"."id" = 7 AND "publish_schedule"."status" = 0 AND "publish_schedule"."lease_until" IS NULL"#,
+        );
+        let expired = publish_schedule::Model { lease_until: Some(at("2023-08-22 09:58:00.5")), ..schedule.clone() };
+        assert!(sql(claim_publish(&expired, "l1", lease_until)).ends_with(r#""publish_schedule"."lease_until" = '2023-08-22 09:58:00'"#));
+
+        // cancel vs publish: published and failed only apply to a schedule still SCHEDULED under this lease
+        let claim = r#"WHERE "publish_schedule"."id" = 7 AND "publish_schedule"."status" = 0 AND "publish_schedule"."lease_until" = '2023-08-22 10:02:00'"#;
+        let published = sql(published(7, lease_until));
+        assert!(published.starts_with(r#"UPDATE "publish_schedule" SET "lease_until" = NULL, "status" = 1 "#));
+        assert!(published.ends_with(claim));
+        let failed = sql(failed(7, lease_until, "conversion failed"));
+        assert!(failed.contains(r#""status" = 3, "error" = 'conversion failed'"#));
+        assert!(failed.ends_with(claim));
+        assert!(sql(claimed(7, lease_until)).ends_with(claim));
+
+        // the conversion is claimed once per schedule, unpublishing once per published one
+        assert!(sql(claim_conversion(7)).ends_with(r#""publish_schedule"."status" = 0 AND "publish_schedule"."convert_requested" = FALSE"#));
+        assert_eq!(
+            sql(claim_unpublish(7)),
+            r#"UPDATE "publish_schedule" SET "status" = 2 WHERE "publish_schedule"."id" = 7 AND "publish_schedule"."status" = 1"#,
+        );
+
+        // retry after a crash: no conversion row means the request was lost and is made again
+        let now = at("2023-08-22 10:05:00");
+        assert_eq!(step(&schedule, None, now), Step::Convert);
+        assert_eq!(step(&schedule, Some(0), now), Step::Wait);
+        assert_eq!(step(&schedule, Some(1), now), Step::Publish);
+        assert_eq!(step(&schedule, Some(2), now), Step::Fail("conversion failed"));
+        assert_eq!(step(&schedule, None, at("2023-08-22 10:30:00")), Step::Fail("conversion did not finish in time"));
+        assert_eq!(step(&schedule, Some(1), at("2023-08-23 10:00:00")), Step::Fail("unpublish_at passed before the document was published"));
+    }
+}
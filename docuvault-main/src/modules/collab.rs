@@ -0,0 +1,652 @@
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, sync::Arc, time::Duration};
+
+use once_cell::sync::Lazy;
+use rand::{distributions::Alphanumeric, Rng};
+use redis::{AsyncCommands, Script};
+use chrono::SubsecRound;
+use sea_orm::{entity::*, query::*};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex};
+
+use crate::{AppState, entity, modules::{audit::{self, Actor}, crdt::{Id, Op, Rga}, background::conversion::convert_to_html, events::{self, ServerEvent}}, routes::{error::GlobalError, document::{error::DocumentError, service::DocumentService}, comment::service::CommentService, collab::error::CollabError, lint::error::LintError}, modules::markdown::lint::Diagnostic};
+
+/*
+ * collaborative editing sessions, one per document being edited.
+ *
+ * a session holds the document as an RGA(see crdt) and relays operations, presence and cursors
+ * between the connected peers. redis keeps the state of the last snapshot(collab:<doc_id>:state)
+ * and the operations applied since(collab:<doc_id>:log), so a session opened again after a restart
+ * continues with the same character ids. snapshots are written back to docorg.raw every
+ * SNAPSHOT_INTERVAL and when the last peer leaves.
+ *
+ * a snapshot is saved like /document/update(lint, attachments) and only over the version the session
+ * started from: a document saved outside the session since ends it, its peers get the unsaved text back.
+ * snapshots update the document in place, no revision of the previous text is kept(there is no revision
+ * store yet), the audit log records every snapshot with its editors.
+ *
+ * sessions live in the instance that started them, which claims the document in redis
+ * (collab:<doc_id>:owner) for as long as the session runs. peers of a document edited on
+ * another instance are turned away until that session ends.
+ */
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+// sessions nobody comes back to are dropped from redis after a day
+const KEY_TTL: usize = 24 * 60 * 60;
+// the claim is renewed on every snapshot, it outlives a few missed ones
+const OWNER_TTL: usize = 3 * SNAPSHOT_INTERVAL.as_secs() as usize;
+const CHANNEL_CAPACITY: usize = 256;
+
+// takes the claim on the document for the token, or renews the one it has
+static CLAIM: Lazy<Script> = Lazy::new(|| Script::new(r#"
+    local owner = redis.call("GET", KEYS[1])
+    if owner == false or owner == ARGV[1] then
+        redis.call("SET", KEYS[1], ARGV[1], "EX", ARGV[2])
+        return 1
+    end
+    return 0
+"#));
+// deletes the keys(the claim first) as long as the token still holds the claim
+static RELEASE: Lazy<Script> = Lazy::new(|| Script::new(r#"
+    if redis.call("GET", KEYS[1]) == ARGV[1] then
+        return redis.call("DEL", unpack(KEYS))
+    end
+    return 0
+"#));
+
+fn state_key(doc_id: i32) -> String {
+    format!("collab:{}:state", doc_id)
+}
+fn log_key(doc_id: i32) -> String {
+    format!("collab:{}:log", doc_id)
+}
+fn site_key(doc_id: i32) -> String {
+    format!("collab:{}:site", doc_id)
+}
+fn owner_key(doc_id: i32) -> String {
+    format!("collab:{}:owner", doc_id)
+}
+
+// caret and selection end, as the id of the character left of them(None: start of the document)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Selection {
+    pub anchor: Option<Id>,
+    pub head: Option<Id>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Peer {
+    pub site: u32,
+    pub user_id: i32,
+    pub nickname: String,
+    pub selection: Option<Selection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ClientMessage {
+    Ops { ops: Vec<Op> },
+    Cursor { selection: Option<Selection> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerMessage {
+    // first message of a connection: the site to create ids with, the document and who is there
+    Init { site: u32, state: Rga, peers: Vec<Peer> },
+    Ops { site: u32, ops: Vec<Op> },
+    Join { peer: Peer },
+    Leave { site: u32 },
+    Cursor { site: u32, selection: Option<Selection> },
+    Saved { updated_at: chrono::NaiveDateTime },
+    // the snapshot has lint errors, it is saved once they are fixed
+    Rejected { diagnostics: Vec<Diagnostic> },
+    // the document was saved outside the session, the text of the session is not saved
+    Conflict { raw: String },
+    // the session went on on another instance or ended on a conflict, reconnect
+    Closed,
+}
+
+// from: the peer it originates from, not sent back to it(0 for the server)
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub from: u32,
+    pub message: ServerMessage,
+}
+
+#[derive(Debug)]
+pub struct SessionState {
+    pub rga: Rga,
+    pub peers: BTreeMap<u32, Peer>,
+    // operations applied since the last snapshot
+    pub dirty: bool,
+    pub closed: bool,
+    // the claim went to another instance or the document was saved outside, the text here is not saved anymore
+    pub lost: bool,
+    // updated_at of the document as the session last read or saved it
+    pub saved_at: chrono::NaiveDateTime,
+}
+impl SessionState {
+    pub fn new(rga: Rga, dirty: bool, saved_at: chrono::NaiveDateTime) -> Self {
+        Self {
+            rga,
+            peers: BTreeMap::new(),
+            dirty,
+            closed: false,
+            lost: false,
+            saved_at,
+        }
+    }
+
+    pub fn add(&mut self, peer: Peer) -> Result<(), CollabError> {
+        if self.closed {
+            return Err(CollabError::SessionClosed);
+        }
+        self.peers.insert(peer.site, peer);
+        Ok(())
+    }
+
+    // true when the peer was the last one, the session is closed then
+    pub fn remove(&mut self, site: u32) -> bool {
+        self.peers.remove(&site);
+        if self.peers.is_empty() {
+            self.closed = true;
+        }
+        self.closed
+    }
+
+    /*
+     * the operations of the peer that changed the text.
+     * characters are inserted with the site of the peer, an insert under another site is refused.
+     */
+    pub fn apply(&mut self, site: u32, ops: Vec<Op>) -> Result<Vec<Op>, CollabError> {
+        if self.closed || !self.peers.contains_key(&site) {
+            return Err(CollabError::SessionClosed);
+        }
+        if ops.iter().any(|op| matches!(op, Op::Insert { id, .. } if id.site != site)) {
+            return Err(CollabError::InvalidOperation);
+        }
+        let ops = ops.into_iter().filter(|op| self.rga.apply(op)).collect::<Vec<_>>();
+        self.dirty |= !ops.is_empty();
+        Ok(ops)
+    }
+
+    pub fn select(&mut self, site: u32, selection: Option<Selection>) {
+        if let Some(peer) = self.peers.get_mut(&site) {
+            peer.selection = selection;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Session {
+    pub doc_id: i32,
+    // holds the claim on the document
+    token: String,
+    pub state: Mutex<SessionState>,
+    pub tx: broadcast::Sender<Event>,
+}
+
+/*
+ * one slot per document being edited on this instance. a session starts, ends and is saved
+ * with its slot locked, the hub itself is only locked to find the slot.
+ */
+type Slot = Arc<Mutex<Option<Arc<Session>>>>;
+
+#[derive(Debug, Default)]
+pub struct Hub {
+    slots: Mutex<HashMap<i32, Slot>>,
+}
+impl Hub {
+    async fn slot(&self, doc_id: i32) -> Slot {
+        self.slots.lock().await.entry(doc_id).or_default().clone()
+    }
+    // whether the slot is still the one of the document, it may have been dropped meanwhile
+    async fn holds(&self, doc_id: i32, slot: &Slot) -> bool {
+        self.slots.lock().await.get(&doc_id).is_some_and(|held| Arc::ptr_eq(held, slot))
+    }
+    // drops the slot of the document, called with the slot locked and empty
+    async fn drop_slot(&self, doc_id: i32, slot: &Slot) {
+        let mut slots = self.slots.lock().await;
+        if slots.get(&doc_id).is_some_and(|held| Arc::ptr_eq(held, slot)) {
+            slots.remove(&doc_id);
+        }
+    }
+}
+
+pub struct Joined {
+    pub session: Arc<Session>,
+    pub site: u32,
+    pub rx: broadcast::Receiver<Event>,
+    pub init: ServerMessage,
+}
+
+/*
+ * join the running session of the document, or a new one picking up from redis or the saved raw.
+ * done with the slot of the document locked, a session can not end between being found and being joined.
+ */
+pub async fn open(state: &AppState, document: &entity::docorg::Model, user_id: i32, nickname: String) -> Result<Joined, GlobalError> {
+    let hub = &state.modules.collab;
+    loop {
+        let slot = hub.slot(document.id).await;
+        let mut current = slot.lock().await;
+        if !hub.holds(document.id, &slot).await {
+            continue;
+        }
+        if let Some(session) = current.as_ref() {
+            let (site, rx, init) = join(state, session, user_id, nickname).await?;
+            return Ok(Joined { session: session.clone(), site, rx, init });
+        }
+
+        let res = match start(state, document).await {
+            Ok(session) => match join(state, &session, user_id, nickname).await {
+                Ok((site, rx, init)) => {
+                    *current = Some(session.clone());
+                    snapshots(state.clone(), session.clone());
+                    return Ok(Joined { session, site, rx, init });
+                },
+                Err(e) => {
+                    if let Err(e) = release(state, session.doc_id, &session.token, false).await {
+                        dbg!(e);
+                    }
+                    Err(e)
+                },
+            },
+            Err(e) => Err(e),
+        };
+        hub.drop_slot(document.id, &slot).await;
+        return res;
+    }
+}
+
+async fn start(state: &AppState, document: &entity::docorg::Model) -> Result<Arc<Session>, GlobalError> {
+    let token = new_token();
+    if !claim(state, document.id, &token).await? {
+        return Err(CollabError::EditedElsewhere.into());
+    }
+    let (rga, dirty) = match load(state, document).await {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            if let Err(e) = release(state, document.id, &token, false).await {
+                dbg!(e);
+            }
+            return Err(e);
+        },
+    };
+    let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+    Ok(Arc::new(Session {
+        doc_id: document.id,
+        token,
+        state: Mutex::new(SessionState::new(rga, dirty, document.updated_at)),
+        tx,
+    }))
+}
+
+fn new_token() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(24).map(char::from).collect()
+}
+
+async fn claim(state: &AppState, doc_id: i32, token: &str) -> Result<bool, GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let claimed: i32 = CLAIM.key(owner_key(doc_id)).arg(token).arg(OWNER_TTL).invoke_async(&mut *con).await?;
+    Ok(claimed == 1)
+}
+
+// gives up the claim on the document, with the keys of the session when it ended
+async fn release(state: &AppState, doc_id: i32, token: &str, ended: bool) -> Result<(), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let mut script = RELEASE.key(owner_key(doc_id));
+    if ended {
+        script.key(state_key(doc_id)).key(log_key(doc_id)).key(site_key(doc_id));
+    }
+    script.arg(token).invoke_async::<_, i32>(&mut *con).await?;
+    Ok(())
+}
+
+// the stored state only holds while nothing saved the document outside the session since
+async fn load(state: &AppState, document: &entity::docorg::Model) -> Result<(Rga, bool), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let stored: Option<String> = con.get(state_key(document.id)).await?;
+    let stored = stored.and_then(|stored| serde_json::from_str::<Rga>(&stored).ok());
+    if let Some(mut rga) = stored.filter(|rga| rga.text() == document.raw) {
+        let log: Vec<String> = con.lrange(log_key(document.id), 0, -1).await?;
+        for op in log.iter().filter_map(|op| serde_json::from_str::<Op>(op).ok()) {
+            rga.apply(&op);
+        }
+        return Ok((rga, !log.is_empty()));
+    }
+
+    let rga = Rga::from_text(&document.raw);
+    redis::pipe()
+        .atomic()
+        .set_ex(state_key(document.id), serde_json::to_string(&rga).unwrap_or_default(), KEY_TTL)
+        .del(log_key(document.id))
+        .query_async::<_, ()>(&mut *con)
+        .await?;
+    Ok((rga, false))
+}
+
+// a new peer gets a site of its own, the current state and the events from then on
+async fn join(state: &AppState, session: &Session, user_id: i32, nickname: String) -> Result<(u32, broadcast::Receiver<Event>, ServerMessage), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let site: u32 = con.incr(site_key(session.doc_id), 1).await?;
+    let _: () = con.expire(site_key(session.doc_id), KEY_TTL).await?;
+
+    let mut inner = session.state.lock().await;
+    let peer = Peer {
+        site,
+        user_id,
+        nickname,
+        selection: None,
+    };
+    inner.add(peer.clone())?;
+    // subscribed under the lock, nothing applied after the state below is missed
+    let rx = session.tx.subscribe();
+    let init = ServerMessage::Init {
+        site,
+        state: inner.rga.clone(),
+        peers: inner.peers.values().cloned().collect(),
+    };
+    let _ = session.tx.send(Event { from: site, message: ServerMessage::Join { peer } });
+    Ok((site, rx, init))
+}
+
+pub async fn receive(state: &AppState, session: &Session, site: u32, message: ClientMessage) -> Result<(), GlobalError> {
+    let mut inner = session.state.lock().await;
+    match message {
+        ClientMessage::Ops { ops } => {
+            let ops = inner.apply(site, ops)?;
+            if ops.is_empty() {
+                return Ok(());
+            }
+            let log = ops.iter().map(|op| serde_json::to_string(op).unwrap_or_default()).collect::<Vec<_>>();
+            let mut con = state.redis_conn.get().await?;
+            redis::pipe()
+                .atomic()
+                .rpush(log_key(session.doc_id), log)
+                .expire(log_key(session.doc_id), KEY_TTL)
+                .query_async::<_, ()>(&mut *con)
+                .await?;
+            let _ = session.tx.send(Event { from: site, message: ServerMessage::Ops { site, ops } });
+        },
+        ClientMessage::Cursor { selection } => {
+            inner.select(site, selection.clone());
+            let _ = session.tx.send(Event { from: site, message: ServerMessage::Cursor { site, selection } });
+        },
+    }
+    Ok(())
+}
+
+// the last peer leaving saves the document and ends the session, the slot stays locked until then
+pub async fn leave(state: &AppState, session: &Arc<Session>, site: u32) -> Result<(), GlobalError> {
+    let hub = &state.modules.collab;
+    let slot = hub.slot(session.doc_id).await;
+    let mut current = slot.lock().await;
+    {
+        let mut inner = session.state.lock().await;
+        let last = inner.remove(site);
+        let _ = session.tx.send(Event { from: site, message: ServerMessage::Leave { site } });
+        if !last {
+            return Ok(());
+        }
+    }
+    let res = match persist(state, session).await {
+        Ok(()) => release(state, session.doc_id, &session.token, true).await,
+        // the stored state and log stay for the next session
+        Err(e) => {
+            if let Err(e) = release(state, session.doc_id, &session.token, false).await {
+                dbg!(e);
+            }
+            Err(e)
+        },
+    };
+    if current.as_ref().is_some_and(|current| Arc::ptr_eq(current, session)) {
+        *current = None;
+    }
+    if current.is_none() {
+        hub.drop_slot(session.doc_id, &slot).await;
+    }
+    res
+}
+
+/*
+ * saves the session every SNAPSHOT_INTERVAL and renews its claim.
+ * a session whose claim went to another instance is closed without saving, its peers reconnect.
+ */
+fn snapshots(state: AppState, session: Arc<Session>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if session.state.lock().await.closed {
+                break;
+            }
+            match claim(&state, session.doc_id, &session.token).await {
+                Ok(true) => {},
+                Ok(false) => {
+                    lose(&state, &session).await;
+                    break;
+                },
+                Err(e) => {
+                    dbg!(e);
+                    continue;
+                },
+            }
+            match persist(&state, &session).await {
+                Ok(()) => {},
+                Err(GlobalError::Collab(CollabError::Conflict)) => {
+                    lose(&state, &session).await;
+                    if let Err(e) = release(&state, session.doc_id, &session.token, true).await {
+                        dbg!(e);
+                    }
+                    break;
+                },
+                Err(e) => {
+                    dbg!(e);
+                },
+            }
+        }
+    });
+}
+
+async fn lose(state: &AppState, session: &Arc<Session>) {
+    let hub = &state.modules.collab;
+    let slot = hub.slot(session.doc_id).await;
+    let mut current = slot.lock().await;
+    {
+        let mut inner = session.state.lock().await;
+        inner.closed = true;
+        inner.lost = true;
+    }
+    let _ = session.tx.send(Event { from: 0, message: ServerMessage::Closed });
+    if current.as_ref().is_some_and(|current| Arc::ptr_eq(current, session)) {
+        *current = None;
+    }
+    if current.is_none() {
+        hub.drop_slot(session.doc_id, &slot).await;
+    }
+}
+
+/*
+ * write the text back to the document and start a new log from it.
+ * the session stays locked meanwhile, no operation falls between the snapshot and the log reset.
+ * a rejected snapshot keeps the log, the text is saved by a later snapshot or the next session.
+ */
+async fn persist(state: &AppState, session: &Session) -> Result<(), GlobalError> {
+    let mut inner = session.state.lock().await;
+    if !inner.dirty || inner.lost {
+        return Ok(());
+    }
+    let raw = inner.rga.text();
+    let editors = inner.peers.values().map(|peer| peer.user_id).collect::<BTreeSet<_>>();
+    let updated_at = match save(state, session.doc_id, &raw, editors, inner.saved_at).await {
+        Ok(updated_at) => updated_at,
+        Err(GlobalError::Lint(LintError::Rejected(diagnostics))) => {
+            let _ = session.tx.send(Event { from: 0, message: ServerMessage::Rejected { diagnostics: diagnostics.clone() } });
+            return Err(LintError::Rejected(diagnostics).into());
+        },
+        Err(GlobalError::Collab(CollabError::Conflict)) => {
+            inner.lost = true;
+            let _ = session.tx.send(Event { from: 0, message: ServerMessage::Conflict { raw } });
+            return Err(CollabError::Conflict.into());
+        },
+        Err(e) => return Err(e),
+    };
+    inner.saved_at = updated_at;
+
+    let mut con = state.redis_conn.get().await?;
+    redis::pipe()
+        .atomic()
+        .set_ex(state_key(session.doc_id), serde_json::to_string(&inner.rga).unwrap_or_default(), KEY_TTL)
+        .del(log_key(session.doc_id))
+        .query_async::<_, ()>(&mut *con)
+        .await?;
+    inner.dirty = false;
+    let _ = session.tx.send(Event { from: 0, message: ServerMessage::Saved { updated_at } });
+    Ok(())
+}
+
+/*
+ * saved like an update from the editor, minus scopes, tags and sequence which stay as they are.
+ * a front matter that does not parse(e.g. half typed) keeps the previous title and meta.
+ * the lint rules of the owner and the scopes apply, attachments are synced with the links of the text.
+ * the document is locked and only written when it is still at saved_at, Err(Conflict) otherwise.
+ * the audit entry is the owner's, with the users connected at the time.
+ */
+async fn save(state: &AppState, doc_id: i32, raw: &str, editors: BTreeSet<i32>, saved_at: chrono::NaiveDateTime) -> Result<chrono::NaiveDateTime, GlobalError> {
+    let document = entity::docorg::Entity::find_by_id(doc_id)
+        .one(&state.db_conn)
+        .await?
+        .ok_or(DocumentError::DocumentNotExist)?;
+    if document.updated_at != saved_at {
+        return Err(CollabError::Conflict.into());
+    }
+    let service = DocumentService::new(state.clone());
+    let resolved = service
+        .apply_front_matter(raw, &mut Vec::new(), &mut Vec::new(), &mut None)
+        .ok();
+    let docuser_id = document.docuser_id;
+    let scope_ids = audit::document_scopes(&state.db_conn, &[doc_id]).await?;
+    service.lint(docuser_id, raw, &scope_ids).await?;
+    // stored in microseconds, the next snapshot compares with it
+    let updated_at = chrono::Utc::now().naive_utc().trunc_subsecs(6);
+
+    let raw = raw.to_string();
+    let recorded = state.db_conn.transaction::<_, events::Recorded, GlobalError>(|txn| {
+        let raw = raw.clone();
+        let service = service.clone();
+        Box::pin(async move {
+            let document = entity::docorg::Entity::find_by_id(doc_id)
+                .lock_exclusive()
+                .one(txn)
+                .await?
+                .ok_or(DocumentError::DocumentNotExist)?;
+            if document.updated_at != saved_at {
+                return Err(CollabError::Conflict.into());
+            }
+            let mut document: entity::docorg::ActiveModel = document.into();
+            document.raw = Set(raw.clone());
+            if let Some(resolved) = resolved {
+                document.title = Set(resolved.title);
+                document.meta = Set(resolved.meta);
+            }
+            DocumentService::set_derived(&mut document, &raw);
+            document.updated_at = Set(updated_at);
+            document.update(txn).await?;
+            CommentService::refresh_anchors(txn, doc_id, &raw).await?;
+            service.sync_files(doc_id, &raw).await?;
+            Actor::background(docuser_id)
+                .entry("document.update")
+                .targets([doc_id])
+                .scopes(audit::document_scopes(txn, &[doc_id]).await?)
+                .summary(serde_json::json!({ "collab": true, "editors": editors, "length": raw.len() }))
+                .record(txn)
+                .await?;
+            Ok(events::record(txn, docuser_id, ServerEvent::DocumentUpdated { doc_id }).await?)
+        })
+    }).await?;
+
+    convert_to_html(state.clone(), (doc_id, 0), raw);
+    events::send(state, recorded).await;
+    Ok(updated_at)
+}
+
+/*
+ * sessions cut off by a restart are saved at startup, peers reconnecting continue from the stored state.
+ * sessions another instance still runs(it holds the claim) are left alone.
+ */
+pub fn recover(state: AppState) {
+    tokio::spawn(async move {
+        let keys: Vec<String> = match state.redis_conn.get().await {
+            Ok(mut con) => con.keys("collab:*:state").await.unwrap_or_default(),
+            Err(_) => return,
+        };
+        for doc_id in keys.iter().filter_map(|key| key.split(':').nth(1)?.parse::<i32>().ok()) {
+            let document = match entity::docorg::Entity::find_by_id(doc_id).one(&state.db_conn).await {
+                Ok(Some(document)) => document,
+                _ => continue,
+            };
+            let token = new_token();
+            match claim(&state, doc_id, &token).await {
+                Ok(true) => {},
+                Ok(false) => continue,
+                Err(e) => {
+                    dbg!(e);
+                    continue;
+                },
+            }
+            let res = match load(&state, &document).await {
+                Ok((rga, dirty)) => {
+                    let session = Session {
+                        doc_id,
+                        token,
+                        state: Mutex::new(SessionState::new(rga, dirty, document.updated_at)),
+                        tx: broadcast::channel(1).0,
+                    };
+                    let res = persist(&state, &session).await;
+                    let released = release(&state, doc_id, &session.token, false).await;
+                    res.and(released)
+                },
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                dbg!(e);
+            }
+        }
+    });
+}
+
+#[test]
+fn collab_test() {
+    let peer = |site: u32| Peer {
+        site,
+        user_id: 7,
+        nickname: "peer".to_string(),
+        selection: None,
+    };
+    let mut inner = SessionState::new(Rga::from_text("ab"), false, chrono::NaiveDateTime::default());
+    inner.add(peer(1)).unwrap();
+    inner.add(peer(2)).unwrap();
+
+    // inserts carry the site of the peer sending them, deletes may remove anyone's characters
+    let mut replica = inner.rga.clone();
+    let insert = replica.insert(1, 2, 'c');
+    let delete = replica.delete(0).unwrap();
+    assert!(matches!(inner.apply(2, vec![insert.clone()]), Err(CollabError::InvalidOperation)));
+    assert!(!inner.dirty);
+    assert_eq!(inner.apply(1, vec![insert.clone(), delete.clone()]).unwrap(), vec![insert.clone(), delete]);
+    assert_eq!((inner.rga.text().as_str(), inner.dirty), ("bc", true));
+    // applied already, nothing to relay
+    assert!(inner.apply(1, vec![insert.clone()]).unwrap().is_empty());
+    // a peer not in the session
+    assert!(matches!(inner.apply(3, Vec::new()), Err(CollabError::SessionClosed)));
+
+    inner.select(2, Some(Selection { anchor: None, head: None }));
+    assert!(inner.peers[&2].selection.is_some());
+
+    assert!(!inner.remove(1));
+    assert!(!inner.closed);
+    assert!(inner.remove(2));
+    assert!(inner.closed);
+    assert!(matches!(inner.add(peer(4)), Err(CollabError::SessionClosed)));
+    assert!(matches!(inner.apply(2, vec![insert]), Err(CollabError::SessionClosed)));
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * replicated growable array(RGA) of characters, the text of a collaborative editing session.
+ *
+ * every character carries a unique id(clock, site) and is inserted after the id of its left neighbour.
+ * deleted characters stay as tombstones, so operations referring to them still find their place.
+ * applying the same operations in any causal order gives every replica the same text.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Id {
+    pub clock: u64,
+    pub site: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Op {
+    // after: None inserts at the beginning
+    Insert { id: Id, after: Option<Id>, ch: char },
+    Delete { id: Id },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Element {
+    pub id: Id,
+    pub ch: char,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rga {
+    // highest clock seen, new local ids go past it
+    pub clock: u64,
+    pub elements: Vec<Element>,
+}
+
+// site of the characters a session starts from
+pub const BASE_SITE: u32 = 0;
+
+impl Rga {
+    pub fn from_text(text: &str) -> Self {
+        let elements = text.chars()
+            .enumerate()
+            .map(|(idx, ch)| Element {
+                id: Id { clock: idx as u64 + 1, site: BASE_SITE },
+                ch,
+                deleted: false,
+            })
+            .collect::<Vec<_>>();
+        Self {
+            clock: elements.len() as u64,
+            elements,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.elements.iter().filter(|element| !element.deleted).map(|element| element.ch).collect()
+    }
+
+    fn index_of(&self, id: Id) -> Option<usize> {
+        self.elements.iter().position(|element| element.id == id)
+    }
+
+    // false when the op changes nothing: already applied, or it refers to a character not seen yet
+    pub fn apply(&mut self, op: &Op) -> bool {
+        match *op {
+            Op::Insert { id, after, ch } => {
+                if self.index_of(id).is_some() {
+                    return false;
+                }
+                let mut idx = match after {
+                    Some(after) => match self.index_of(after) {
+                        Some(idx) => idx + 1,
+                        None => return false,
+                    },
+                    None => 0,
+                };
+                // concurrent inserts at the same place are ordered by id, newest first
+                while idx < self.elements.len() && self.elements[idx].id > id {
+                    idx += 1;
+                }
+                self.elements.insert(idx, Element { id, ch, deleted: false });
+                self.clock = self.clock.max(id.clock);
+                true
+            },
+            Op::Delete { id } => match self.index_of(id) {
+                Some(idx) if !self.elements[idx].deleted => {
+                    self.elements[idx].deleted = true;
+                    true
+                },
+                _ => false,
+            },
+        }
+    }
+
+    // id of the visible character at index(in chars)
+    fn visible(&self, index: usize) -> Option<Id> {
+        self.elements.iter().filter(|element| !element.deleted).nth(index).map(|element| element.id)
+    }
+
+    // op inserting ch at the visible index, applied locally
+    pub fn insert(&mut self, site: u32, index: usize, ch: char) -> Op {
+        let after = match index {
+            0 => None,
+            index => self.visible(index - 1),
+        };
+        let op = Op::Insert {
+            id: Id { clock: self.clock + 1, site },
+            after,
+            ch,
+        };
+        self.apply(&op);
+        op
+    }
+
+    // op deleting the visible character at index, applied locally
+    pub fn delete(&mut self, index: usize) -> Option<Op> {
+        let op = Op::Delete { id: self.visible(index)? };
+        self.apply(&op);
+        Some(op)
+    }
+}
+
+#[test]
+fn crdt_test() {
+    let mut a = Rga::from_text("ab");
+    let mut b = a.clone();
+
+    // both type at the same place concurrently
+    let ops_a = vec![a.insert(1, 1, 'x'), a.insert(1, 2, 'y')];
+    let ops_b = vec![b.insert(2, 1, 'z'), b.delete(0).unwrap()];
+    for op in &ops_b {
+        a.apply(op);
+    }
+    for op in &ops_a {
+        b.apply(op);
+    }
+    assert_eq!(a.text(), b.text());
+    assert_eq!(a.text(), "zxyb");
+
+    // applying twice changes nothing
+    assert!(!a.apply(&ops_a[0]));
+    assert!(!a.apply(&ops_b[1]));
+
+    let json = serde_json::to_string(&a).unwrap();
+    assert_eq!(serde_json::from_str::<Rga>(&json).unwrap(), a);
+}
@@ -0,0 +1,53 @@
+use std::{fmt::Display, str::FromStr};
+
+/*
+ * keyset pagination cursor: the sort key and id of the last row of a page.
+ *
+ * handed out hex encoded so clients treat it as opaque, the next page continues
+ * strictly after (key, id) in the order the listing uses.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor<K> {
+    pub key: K,
+    pub id: i32,
+}
+impl<K: Display + FromStr> Cursor<K> {
+    pub fn new(key: K, id: i32) -> Self {
+        Self { key, id }
+    }
+    pub fn encode(&self) -> String {
+        format!("{}.{}", self.key, self.id)
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+    pub fn decode(cursor: &str) -> Option<Self> {
+        if cursor.len() % 2 != 0 {
+            return None;
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|idx| cursor.get(idx..idx + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        // the id never contains a dot, the key might
+        let (key, id) = raw.rsplit_once('.')?;
+        Some(Self {
+            key: key.parse().ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+#[test]
+fn cursor_test() {
+    let cursor = Cursor::new(1_690_000_000_123_456i64, 42);
+    assert_eq!(Cursor::<i64>::decode(&cursor.encode()), Some(cursor));
+
+    let cursor = Cursor::new(String::from("v1.2 release"), 7);
+    assert_eq!(Cursor::<String>::decode(&cursor.encode()), Some(cursor));
+
+    assert_eq!(Cursor::<i64>::decode("zz"), None);
+    assert_eq!(Cursor::<i64>::decode("616263"), None);
+    assert_eq!(Cursor::<i64>::decode(&Cursor::new(String::from("abc"), 1).encode()), None);
+}
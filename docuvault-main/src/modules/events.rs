@@ -0,0 +1,267 @@
+use std::{env, time::Duration};
+
+use futures::StreamExt;
+use rand::{distributions::Alphanumeric, Rng};
+use redis::AsyncCommands;
+use sea_orm::{entity::*, query::*, ConnectionTrait, DbErr};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{AppState, entity, modules::background::webhooks};
+
+/*
+ * server events per user, streamed to the clients over /event/stream(SSE).
+ *
+ * events are published on redis(events:<docuser_id>) and every instance listens on events:*,
+ * handing them to the streams it serves itself. whichever instance, or docuvault-file and
+ * docuvault-convert, caused the event, the client gets it from the instance it is connected to.
+ * an event goes to the user it was published for and to the owners and members of the scopes it concerns.
+ * webhook deliveries are queued with the change, see record and background::webhooks.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    // c_type as in convert
+    ConversionFinished { doc_id: i32, c_type: i32 },
+    ConversionFailed { doc_id: i32, c_type: i32 },
+    UploadFinished { object_id: String },
+    UploadFailed { object_id: String },
+    DocumentCreated { doc_id: i32 },
+    DocumentUpdated { doc_id: i32 },
+    DocumentDeleted { doc_id: i32 },
+    DocumentPublished { doc_id: i32, link_id: String },
+    DocumentUnpublished { doc_id: i32, link_id: String },
+    SequenceReordered { seq_id: i32 },
+}
+impl ServerEvent {
+    // the SSE event name, same as the type in the data
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConversionFinished { .. } => "conversion_finished",
+            Self::ConversionFailed { .. } => "conversion_failed",
+            Self::UploadFinished { .. } => "upload_finished",
+            Self::UploadFailed { .. } => "upload_failed",
+            Self::DocumentCreated { .. } => "document_created",
+            Self::DocumentUpdated { .. } => "document_updated",
+            Self::DocumentDeleted { .. } => "document_deleted",
+            Self::DocumentPublished { .. } => "document_published",
+            Self::DocumentUnpublished { .. } => "document_unpublished",
+            Self::SequenceReordered { .. } => "sequence_reordered",
+        }
+    }
+}
+
+/*
+ * what goes over redis: the event with an id unique to it and the scopes it concerns.
+ * scope_ids is left out by publishers that do not know them, they are looked up on arrival.
+ * queued tells the webhook deliveries were queued by the publisher, events of docuvault-file
+ * and docuvault-convert have theirs queued on arrival.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub id: String,
+    pub scope_ids: Option<Vec<i32>>,
+    #[serde(default)]
+    pub queued: bool,
+    #[serde(flatten)]
+    pub event: ServerEvent,
+}
+
+const CHANNEL_CAPACITY: usize = 1024;
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+fn channel(docuser_id: i32) -> String {
+    format!("events:{}", docuser_id)
+}
+fn docuser_of(channel: &str) -> Option<i32> {
+    channel.strip_prefix("events:")?.parse().ok()
+}
+
+// events received by this instance, tagged with the user they are for
+#[derive(Debug)]
+pub struct EventHub {
+    tx: broadcast::Sender<(i32, ServerEvent)>,
+}
+impl EventHub {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx }
+    }
+    pub fn subscribe(&self) -> broadcast::Receiver<(i32, ServerEvent)> {
+        self.tx.subscribe()
+    }
+}
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// scopes of the document or sequence the event is about
+pub async fn scopes_of<C: ConnectionTrait>(conn: &C, event: &ServerEvent) -> Result<Vec<i32>, DbErr> {
+    let scopes = match event {
+        ServerEvent::ConversionFinished { doc_id, .. }
+        | ServerEvent::ConversionFailed { doc_id, .. }
+        | ServerEvent::DocumentCreated { doc_id }
+        | ServerEvent::DocumentUpdated { doc_id }
+        | ServerEvent::DocumentDeleted { doc_id }
+        | ServerEvent::DocumentPublished { doc_id, .. }
+        | ServerEvent::DocumentUnpublished { doc_id, .. } => entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::DocorgId.eq(*doc_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|docorg_scope| docorg_scope.scope_id)
+            .collect(),
+        ServerEvent::SequenceReordered { seq_id } => entity::scope_sequence::Entity::find()
+            .filter(entity::scope_sequence::Column::SequenceId.eq(*seq_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|scope_sequence| scope_sequence.scope_id)
+            .collect(),
+        ServerEvent::UploadFinished { .. } | ServerEvent::UploadFailed { .. } => Vec::new(),
+    };
+    Ok(scopes)
+}
+
+// the user the event was published for, then the owners and members of the scopes
+pub async fn recipients<C: ConnectionTrait>(conn: &C, docuser_id: i32, scope_ids: &[i32]) -> Result<Vec<i32>, DbErr> {
+    let mut recipients = vec![docuser_id];
+    if scope_ids.is_empty() {
+        return Ok(recipients);
+    }
+    let owners = entity::scope::Entity::find()
+        .filter(entity::scope::Column::Id.is_in(scope_ids.to_vec()))
+        .all(conn)
+        .await?;
+    let members = entity::scope_member::Entity::find()
+        .filter(entity::scope_member::Column::ScopeId.is_in(scope_ids.to_vec()))
+        .all(conn)
+        .await?;
+    for user_id in owners.into_iter().map(|scope| scope.docuser_id).chain(members.into_iter().map(|member| member.docuser_id)) {
+        if !recipients.contains(&user_id) {
+            recipients.push(user_id);
+        }
+    }
+    Ok(recipients)
+}
+
+// an event recorded with the change causing it, sent to the streams once that change committed
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    docuser_id: i32,
+    envelope: Envelope,
+}
+
+/*
+ * the webhook deliveries of the event are queued in the transaction of the change(the outbox),
+ * so they exist exactly when the change does, whatever happens to the instance after the commit.
+ */
+pub async fn record<C: ConnectionTrait>(conn: &C, docuser_id: i32, event: ServerEvent) -> Result<Recorded, DbErr> {
+    let scope_ids = scopes_of(conn, &event).await?;
+    record_with_scopes(conn, docuser_id, event, scope_ids).await
+}
+
+// for events whose scopes are gone once they happen, e.g. a deleted document
+pub async fn record_with_scopes<C: ConnectionTrait>(conn: &C, docuser_id: i32, event: ServerEvent, scope_ids: Vec<i32>) -> Result<Recorded, DbErr> {
+    let envelope = Envelope {
+        id: rand::thread_rng().sample_iter(&Alphanumeric).take(24).map(char::from).collect(),
+        scope_ids: Some(scope_ids),
+        queued: true,
+        event,
+    };
+    webhooks::enqueue(conn, docuser_id, &envelope).await?;
+    Ok(Recorded { docuser_id, envelope })
+}
+
+// events are a notification, a failed send never fails what caused it
+pub async fn send(state: &AppState, recorded: Recorded) {
+    let res = async {
+        let mut con = state.redis_conn.get().await.map_err(|e| e.to_string())?;
+        let data = serde_json::to_string(&recorded.envelope).map_err(|e| e.to_string())?;
+        con.publish::<_, _, ()>(channel(recorded.docuser_id), data).await.map_err(|e| e.to_string())
+    }.await;
+    if let Err(e) = res {
+        dbg!(e);
+    }
+}
+
+// for background jobs that only know the document, nothing is recorded once the document is gone
+pub async fn record_for_document<C: ConnectionTrait>(conn: &C, doc_id: i32, event: ServerEvent) -> Result<Option<Recorded>, DbErr> {
+    match entity::docorg::Entity::find_by_id(doc_id).one(conn).await? {
+        Some(document) => Ok(Some(record(conn, document.docuser_id, event).await?)),
+        None => Ok(None),
+    }
+}
+
+/*
+ * forward redis events to the hub for as long as the server runs.
+ * pub/sub needs a connection of its own, not one from the pool.
+ */
+pub fn listen(state: AppState) {
+    tokio::spawn(async move {
+        let redis_url = env::var("REDIS_URL").expect("redis url is not set");
+        loop {
+            if let Err(e) = forward(&state, &redis_url).await {
+                dbg!(e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn forward(state: &AppState, redis_url: &str) -> Result<(), redis::RedisError> {
+    let client = redis::Client::open(redis_url)?;
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    pubsub.psubscribe("events:*").await?;
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let docuser_id = match docuser_of(message.get_channel_name()) {
+            Some(docuser_id) => docuser_id,
+            None => continue,
+        };
+        let payload: String = message.get_payload()?;
+        if let Ok(envelope) = serde_json::from_str::<Envelope>(&payload) {
+            // the owner still gets the event when the others can not be looked up
+            let res = async {
+                let scope_ids = match &envelope.scope_ids {
+                    Some(scope_ids) => scope_ids.clone(),
+                    None => scopes_of(&state.db_conn, &envelope.event).await?,
+                };
+                recipients(&state.db_conn, docuser_id, &scope_ids).await
+            }.await;
+            let recipients = res.unwrap_or_else(|e| {
+                dbg!(e);
+                vec![docuser_id]
+            });
+            for recipient in recipients {
+                let _ = state.modules.events.tx.send((recipient, envelope.event.clone()));
+            }
+            if !envelope.queued {
+                webhooks::enqueue_claimed(state.clone(), docuser_id, envelope);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn events_test() {
+    let envelope = Envelope {
+        id: "x1".to_string(),
+        scope_ids: Some(vec![2]),
+        queued: true,
+        event: ServerEvent::ConversionFinished { doc_id: 3, c_type: 4 },
+    };
+    let data = serde_json::to_string(&envelope).unwrap();
+    assert_eq!(data, r#"{"id":"x1","scope_ids":[2],"queued":true,"type":"conversion_finished","doc_id":3,"c_type":4}"#);
+    assert_eq!(serde_json::from_str::<Envelope>(&data).unwrap(), envelope);
+    assert_eq!(envelope.event.name(), "conversion_finished");
+    // published by docuvault-file, without scopes, its webhook deliveries queued on arrival
+    let envelope = serde_json::from_str::<Envelope>(r#"{"id":"upload-ab12","type":"upload_failed","object_id":"ab12"}"#).unwrap();
+    assert_eq!(envelope.event, ServerEvent::UploadFailed { object_id: "ab12".to_string() });
+    assert!(!envelope.queued);
+    assert_eq!(docuser_of("events:12"), Some(12));
+    assert_eq!(docuser_of("collab:12:log"), None);
+}
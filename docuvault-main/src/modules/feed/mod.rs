@@ -0,0 +1,159 @@
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+
+/*
+ * Atom 1.0 and RSS 2.0 documents of a list of entries.
+ * timestamps are stored without a zone and treated as UTC.
+ */
+#[derive(Debug)]
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    // url of the feed itself
+    pub url: String,
+    pub updated: NaiveDateTime,
+    pub entries: Vec<Entry>,
+}
+#[derive(Debug)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    // published page of the document, if there is one
+    pub url: Option<String>,
+    pub html: String,
+    pub tags: Vec<String>,
+    pub published: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn rfc3339(time: &NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn rfc2822(time: &NaiveDateTime) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S +0000").to_string()
+}
+
+pub fn atom(feed: &Feed) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("<id>{}</id>\n", escape(&feed.id)));
+    out.push_str(&format!("<title>{}</title>\n", escape(&feed.title)));
+    out.push_str(&format!("<link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>\n", escape(&feed.url)));
+    out.push_str(&format!("<updated>{}</updated>\n", rfc3339(&feed.updated)));
+    for entry in &feed.entries {
+        out.push_str("<entry>\n");
+        out.push_str(&format!("<id>{}</id>\n", escape(&entry.id)));
+        out.push_str(&format!("<title>{}</title>\n", escape(&entry.title)));
+        if let Some(url) = &entry.url {
+            out.push_str(&format!("<link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", escape(url)));
+        }
+        out.push_str(&format!("<published>{}</published>\n", rfc3339(&entry.published)));
+        out.push_str(&format!("<updated>{}</updated>\n", rfc3339(&entry.updated)));
+        out.push_str(&format!("<author><name>{}</name></author>\n", escape(&feed.title)));
+        for tag in &entry.tags {
+            out.push_str(&format!("<category term=\"{}\"/>\n", escape(tag)));
+        }
+        out.push_str(&format!("<content type=\"html\">{}</content>\n", escape(&entry.html)));
+        out.push_str("</entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+pub fn rss(feed: &Feed) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(&feed.title)));
+    out.push_str(&format!("<link>{}</link>\n", escape(&feed.url)));
+    out.push_str(&format!("<description>{}</description>\n", escape(&feed.title)));
+    out.push_str(&format!("<atom:link rel=\"self\" type=\"application/rss+xml\" href=\"{}\"/>\n", escape(&feed.url)));
+    out.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", rfc2822(&feed.updated)));
+    for entry in &feed.entries {
+        out.push_str("<item>\n");
+        out.push_str(&format!("<guid isPermaLink=\"false\">{}</guid>\n", escape(&entry.id)));
+        out.push_str(&format!("<title>{}</title>\n", escape(&entry.title)));
+        if let Some(url) = &entry.url {
+            out.push_str(&format!("<link>{}</link>\n", escape(url)));
+        }
+        out.push_str(&format!("<pubDate>{}</pubDate>\n", rfc2822(&entry.published)));
+        for tag in &entry.tags {
+            out.push_str(&format!("<category>{}</category>\n", escape(tag)));
+        }
+        out.push_str(&format!("<description>{}</description>\n", escape(&entry.html)));
+        out.push_str("</item>\n");
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+/*
+ * conditional GET
+ */
+pub fn etag(body: &[u8]) -> String {
+    let hash = Sha256::digest(body);
+    format!("\"{}\"", hash[..16].iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+pub fn http_date(time: &NaiveDateTime) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+// If-None-Match takes precedence over If-Modified-Since(RFC 9110 13.2.2)
+pub fn is_not_modified(if_none_match: Option<&str>, if_modified_since: Option<&str>, etag: &str, last_modified: &NaiveDateTime) -> bool {
+    if let Some(if_none_match) = if_none_match {
+        return if_none_match.split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+    if let Some(since) = if_modified_since.and_then(|since| NaiveDateTime::parse_from_str(since.trim(), "%a, %d %b %Y %H:%M:%S GMT").ok()) {
+        // http dates have no sub-second part
+        return last_modified.timestamp() <= since.timestamp();
+    }
+    false
+}
+
+#[test]
+fn feed_test() {
+    let time = chrono::NaiveDate::from_ymd_opt(2023, 8, 1).unwrap().and_hms_opt(9, 30, 0).unwrap();
+    let feed = Feed {
+        id: "urn:docuvault:scope:1".to_string(),
+        title: "Notes & more".to_string(),
+        url: "http://localhost:8000/feed/scope/1/atom".to_string(),
+        updated: time,
+        entries: vec![Entry {
+            id: "urn:docuvault:document:2".to_string(),
+            title: "<Intro>".to_string(),
+            url: None,
+            html: "<p>a &amp; b</p>".to_string(),
+            tags: vec!["ops".to_string()],
+            published: time,
+            updated: time,
+        }],
+    };
+    let atom = atom(&feed);
+    assert!(atom.contains("<title>Notes &amp; more</title>"));
+    assert!(atom.contains("<content type=\"html\">&lt;p&gt;a &amp;amp; b&lt;/p&gt;</content>"));
+    assert!(atom.contains("<updated>2023-08-01T09:30:00Z</updated>"));
+    assert!(rss(&feed).contains("<pubDate>Tue, 01 Aug 2023 09:30:00 +0000</pubDate>"));
+
+    let tag = etag(atom.as_bytes());
+    assert!(is_not_modified(Some(&format!("W/{}, \"other\"", tag)), None, &tag, &time));
+    assert!(!is_not_modified(Some("\"other\""), Some(&http_date(&time)), &tag, &time));
+    assert!(is_not_modified(None, Some("Tue, 01 Aug 2023 09:30:00 GMT"), &tag, &time));
+    assert!(!is_not_modified(None, Some("Tue, 01 Aug 2023 09:29:59 GMT"), &tag, &time));
+    assert!(!is_not_modified(None, None, &tag, &time));
+}
@@ -0,0 +1,157 @@
+use sea_orm::{entity::*, query::*, sea_query::{Expr, Query, SelectStatement}};
+use serde::{Deserialize, Serialize};
+
+use crate::entity;
+
+// relative ranges reach back 100 years at most, longer ones are refused(see valid) and capped when a stored filter runs
+pub const MAX_WITHIN_DAYS: i64 = 100 * 366;
+
+/*
+ * document filters shared by /resource/list and saved queries.
+ *
+ * saved queries keep the filter as jsonb and evaluate it again on every run,
+ * so the relative ranges(e.g. updated in the last 30 days) move along with time.
+ */
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocFilter {
+    pub scope_ids: Vec<i32>,
+    // kept for older clients, merged into tag_ids
+    #[serde(default, skip_serializing)]
+    pub tag_id: Option<i32>,
+    #[serde(default)]
+    pub tag_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    // jsonb containment on front matter custom keys, e.g. {"owner": "infra"}
+    pub meta: Option<serde_json::Value>,
+    // case insensitive match on title or content
+    pub text: Option<String>,
+    pub created_from: Option<chrono::NaiveDateTime>,
+    pub created_to: Option<chrono::NaiveDateTime>,
+    pub updated_from: Option<chrono::NaiveDateTime>,
+    pub updated_to: Option<chrono::NaiveDateTime>,
+    pub created_within_days: Option<i64>,
+    pub updated_within_days: Option<i64>,
+}
+// documents carrying any of the tags, or all of them
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+impl DocFilter {
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(value.clone()).ok()
+    }
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+    // relative ranges within 0..=MAX_WITHIN_DAYS
+    pub fn valid(&self) -> bool {
+        [self.created_within_days, self.updated_within_days].into_iter().flatten().all(|days| (0..=MAX_WITHIN_DAYS).contains(&days))
+    }
+    pub fn tag_ids(&self) -> Vec<i32> {
+        let mut tag_ids = self.tag_ids.clone();
+        tag_ids.extend(self.tag_id);
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+        tag_ids
+    }
+
+    // documents of the user matching the filter
+    pub fn select(&self, docuser_id: i32) -> Select<entity::docorg::Entity> {
+        let now = chrono::Utc::now().naive_utc();
+        let tag_ids = self.tag_ids();
+        let select = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Id.in_subquery(in_scopes(&self.scope_ids)))
+            .apply_if(self.meta.clone(), |query, meta| {
+                query.filter(Expr::cust_with_values(r#""docorg"."meta" @> $1::jsonb"#, [meta.to_string()]))
+            })
+            .apply_if(self.text.as_deref().map(like_pattern), |query, pattern| {
+                query.filter(Expr::cust_with_values(r#"("docorg"."title" ILIKE $1 OR "docorg"."raw" ILIKE $2)"#, [pattern.clone(), pattern]))
+            })
+            .apply_if(self.created_from, |query, from| query.filter(entity::docorg::Column::CreatedAt.gte(from)))
+            .apply_if(self.created_to, |query, to| query.filter(entity::docorg::Column::CreatedAt.lte(to)))
+            .apply_if(self.updated_from, |query, from| query.filter(entity::docorg::Column::UpdatedAt.gte(from)))
+            .apply_if(self.updated_to, |query, to| query.filter(entity::docorg::Column::UpdatedAt.lte(to)))
+            .apply_if(self.created_within_days, |query, days| {
+                query.filter(entity::docorg::Column::CreatedAt.gte(now - chrono::Duration::days(days.clamp(0, MAX_WITHIN_DAYS))))
+            })
+            .apply_if(self.updated_within_days, |query, days| {
+                query.filter(entity::docorg::Column::UpdatedAt.gte(now - chrono::Duration::days(days.clamp(0, MAX_WITHIN_DAYS))))
+            });
+        match self.tag_match {
+            TagMatch::Any if !tag_ids.is_empty() => select.filter(entity::docorg::Column::Id.in_subquery(with_tags(&tag_ids))),
+            TagMatch::All => tag_ids.iter().fold(select, |select, &tag_id| {
+                select.filter(entity::docorg::Column::Id.in_subquery(with_tags(&[tag_id])))
+            }),
+            _ => select,
+        }
+    }
+}
+
+pub fn in_scopes(scope_ids: &[i32]) -> SelectStatement {
+    Query::select()
+        .column(entity::docorg_scope::Column::DocorgId)
+        .from(entity::docorg_scope::Entity)
+        .and_where(entity::docorg_scope::Column::ScopeId.is_in(scope_ids.iter().copied()))
+        .to_owned()
+}
+fn with_tags(tag_ids: &[i32]) -> SelectStatement {
+    Query::select()
+        .column(entity::docorg_tag::Column::DocorgId)
+        .from(entity::docorg_tag::Entity)
+        .and_where(entity::docorg_tag::Column::TagId.is_in(tag_ids.iter().copied()))
+        .to_owned()
+}
+// the text is matched literally, LIKE wildcards in it are escaped
+fn like_pattern(text: &str) -> String {
+    let escaped = text.trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+#[test]
+fn filter_test() {
+    use sea_orm::DbBackend;
+
+    let filter = DocFilter::from_json(&serde_json::json!({
+        "scope_ids": [3],
+        "tag_id": 5,
+        "tag_ids": [7, 5],
+        "tag_match": "all",
+        "text": "50%_off",
+        "updated_within_days": 30,
+    })).unwrap();
+    assert_eq!(filter.tag_ids(), vec![5, 7]);
+    assert_eq!(like_pattern(" 50%_off\\ "), "%50\\%\\_off\\\\%");
+    // the legacy tag_id is stored merged into tag_ids
+    let stored = DocFilter::from_json(&DocFilter { tag_ids: filter.tag_ids(), ..filter.clone() }.to_json()).unwrap();
+    assert_eq!((stored.tag_id, stored.tag_ids()), (None, vec![5, 7]));
+    assert!(DocFilter::from_json(&serde_json::json!({ "tag_ids": [1] })).is_none());
+
+    let sql = filter.select(9).build(DbBackend::Postgres).to_string();
+    assert!(sql.contains(r#""docorg"."docuser_id" = 9"#));
+    assert!(sql.contains(r#""docorg_scope"."scope_id" IN (3)"#));
+    assert!(sql.contains(r#""docorg_tag"."tag_id" IN (5)"#) && sql.contains(r#""docorg_tag"."tag_id" IN (7)"#));
+    assert!(sql.contains(r#""docorg"."title" ILIKE E'%50\\%\\_off%'"#));
+    assert!(sql.contains(r#""docorg"."updated_at" >="#));
+
+    // out of range relative ranges are refused, and capped when they were stored before
+    assert!(filter.valid());
+    let huge = DocFilter { created_within_days: Some(1_000_000_000_000), updated_within_days: Some(i64::MIN), ..filter.clone() };
+    assert!(!huge.valid());
+    assert!(!DocFilter { created_within_days: Some(-1), ..filter.clone() }.valid());
+    assert!(huge.select(9).build(DbBackend::Postgres).to_string().contains(r#""docorg"."created_at" >="#));
+
+    let any = DocFilter { tag_match: TagMatch::Any, ..filter.clone() }.select(9).build(DbBackend::Postgres).to_string();
+    assert!(any.contains(r#""docorg_tag"."tag_id" IN (5, 7)"#));
+    let untagged = DocFilter { scope_ids: vec![3], ..Default::default() }.select(9).build(DbBackend::Postgres).to_string();
+    assert!(!untagged.contains("docorg_tag") && !untagged.contains("ILIKE"));
+}
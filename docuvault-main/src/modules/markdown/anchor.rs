@@ -0,0 +1,93 @@
+use comrak::{Arena, parse_document, ComrakOptions, nodes::NodeValue};
+
+use super::{collect_text, front_matter};
+
+/*
+ * text anchor of a comment thread
+ * heading path (e.g. ["Runbook", "Failover"]) plus a quoted snippet.
+ * matching ignores case and whitespace differences so that small edits around
+ * the snippet(re-wrapping, trailing spaces) keep the anchor alive.
+ */
+#[derive(Debug)]
+pub struct Section {
+    pub path: Vec<String>,
+    pub text: String,
+}
+
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+pub fn sections(raw: &str) -> Vec<Section> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, front_matter::strip(raw), &ComrakOptions::default());
+
+    let mut stack: Vec<(u8, String)> = Vec::new();
+    let mut sections = vec![Section { path: Vec::new(), text: String::new() }];
+    for node in root.children() {
+        let mut text = Vec::new();
+        collect_text(node, &mut text);
+        let text = String::from_utf8_lossy(&text).to_string();
+
+        let heading = match node.data.borrow().value {
+            NodeValue::Heading(ref head) => Some(head.level),
+            _ => None,
+        };
+        match heading {
+            Some(level) => {
+                while stack.last().is_some_and(|(l, _)| *l >= level) {
+                    stack.pop();
+                }
+                stack.push((level, text.trim().to_string()));
+                sections.push(Section {
+                    path: stack.iter().map(|(_, t)| t.clone()).collect(),
+                    text: String::new(),
+                });
+            }
+            None => {
+                let section = sections.last_mut().unwrap();
+                section.text.push_str(&text);
+                section.text.push('\n');
+            }
+        }
+    }
+    sections
+}
+
+/*
+ * the quote is looked up in the sections of the heading path only while the path exists,
+ * anywhere in the document once it is gone(heading renamed or removed).
+ */
+pub fn locate(raw: &str, path: &[String], quote: &str) -> bool {
+    let quote = normalize(quote);
+    if quote.is_empty() {
+        return true;
+    }
+    let path = path.iter().map(|p| normalize(p)).collect::<Vec<_>>();
+    let sections = sections(raw);
+
+    let in_path = sections.iter()
+        .filter(|section| section.path.iter().map(|p| normalize(p)).collect::<Vec<_>>() == path)
+        .collect::<Vec<_>>();
+    match in_path.is_empty() {
+        true => sections.iter().any(|section| normalize(&section.text).contains(&quote)),
+        false => in_path.iter().any(|section| normalize(&section.text).contains(&quote)),
+    }
+}
+
+#[test]
+fn anchor_test() {
+    let raw = "---\ntitle: Runbook\n---\nintro\n# Runbook\n\nSome  setup\ntext.\n\n## Failover\n\nPromote the *replica*.\n# Appendix\n\nlinks\n";
+    let paths = sections(raw).into_iter().map(|section| section.path).collect::<Vec<_>>();
+    assert_eq!(paths, vec![vec![], vec!["Runbook".to_string()], vec!["Runbook".to_string(), "Failover".to_string()], vec!["Appendix".to_string()]]);
+    assert_eq!(normalize("  Some\n  SETUP text "), "some setup text");
+
+    let path = vec!["Runbook".to_string(), "Failover".to_string()];
+    assert!(locate(raw, &path, "promote the\nreplica"));
+    // renamed heading, the quote is found elsewhere
+    assert!(locate(raw, &["Missing".to_string()], "some setup text."));
+    assert!(!locate(raw, &path, "demote the replica"));
+    // the heading still exists, the quote moved under another one
+    assert!(!locate(raw, &path, "some setup text."));
+    assert!(locate(raw, &path, "  "));
+}
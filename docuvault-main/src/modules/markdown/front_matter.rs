@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
+
+/*
+ * optional yaml block at the very top of a document
+ *
+ * ---
+ * title: Postgres failover runbook
+ * tags: [postgres, incident]
+ * scopes: [1, 2]
+ * sequence: 3
+ * created_at: 2023-07-01 09:00
+ * owner: infra
+ * ---
+ */
+#[derive(Debug, Default, Deserialize)]
+pub struct FrontMatter {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<i32>,
+    #[serde(default)]
+    pub sequence: Option<i32>,
+    #[serde(default)]
+    created_at: Option<String>,
+    // every key that is not listed above is kept as custom metadata
+    #[serde(flatten)]
+    pub custom: BTreeMap<String, serde_json::Value>,
+}
+impl FrontMatter {
+    pub fn created_at(&self) -> Option<chrono::NaiveDateTime> {
+        let value = self.created_at.as_ref()?.trim();
+        for fmt in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+            if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(value, fmt) {
+                return Some(datetime);
+            }
+        }
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    }
+    pub fn custom_json(&self) -> Option<serde_json::Value> {
+        if self.custom.is_empty() {
+            return None;
+        }
+        Some(serde_json::Value::Object(self.custom.clone().into_iter().collect()))
+    }
+}
+
+// tags can be written either as a yaml list or as a comma separated string
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error> where D: Deserializer<'de> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => value.split(',').map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect(),
+        OneOrMany::Many(values) => values,
+    })
+}
+
+/*
+ * split raw markdown into its front matter and the remaining body.
+ * a document without front matter is returned as is.
+ */
+pub fn split(raw: &str) -> (Option<&str>, &str) {
+    let rest = match raw.strip_prefix("---\n").or_else(|| raw.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (None, raw),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, raw)
+}
+
+pub fn strip(raw: &str) -> &str {
+    split(raw).1
+}
+
+pub fn parse(raw: &str) -> Result<(Option<FrontMatter>, &str), serde_yaml::Error> {
+    match split(raw) {
+        (Some(yaml), body) if yaml.trim().is_empty() => Ok((Some(FrontMatter::default()), body)),
+        (Some(yaml), body) => Ok((Some(serde_yaml::from_str(yaml)?), body)),
+        (None, body) => Ok((None, body)),
+    }
+}
+
+// set(and remove) keys of the front matter, adding a front matter block when the document has none
+pub fn set(raw: &str, fields: serde_yaml::Mapping, remove: &[&str]) -> Result<String, serde_yaml::Error> {
+    let (yaml, body) = split(raw);
+    let mut mapping = match yaml {
+        Some(yaml) if !yaml.trim().is_empty() => serde_yaml::from_str::<serde_yaml::Mapping>(yaml)?,
+        _ => serde_yaml::Mapping::new(),
+    };
+    for key in remove {
+        mapping.remove(*key);
+    }
+    for (key, value) in fields {
+        mapping.insert(key, value);
+    }
+    Ok(format!("---\n{}---\n{}", serde_yaml::to_string(&mapping)?, body))
+}
+
+#[test]
+fn front_matter_test() {
+    let raw = "---\ntitle: runbook\ntags: postgres, incident\nscopes: [1]\ncreated_at: 2023-07-01\nowner: infra\n---\n# heading\n";
+    let (front_matter, body) = parse(raw).unwrap();
+    let front_matter = front_matter.unwrap();
+    assert_eq!(body, "# heading\n");
+    assert_eq!(front_matter.title.as_deref(), Some("runbook"));
+    assert_eq!(front_matter.tags, vec!["postgres", "incident"]);
+    assert_eq!(front_matter.scopes, vec![1]);
+    assert!(front_matter.created_at().is_some());
+    assert_eq!(front_matter.custom_json(), Some(serde_json::json!({"owner": "infra"})));
+
+    let raw = "# no front matter\n---\n";
+    assert_eq!(parse(raw).unwrap().1, raw);
+
+    let mut fields = serde_yaml::Mapping::new();
+    fields.insert("title".into(), "runbook".into());
+    let raw = set("---\nowner: infra\nscopes: [1]\n---\n# heading\n", fields, &["scopes"]).unwrap();
+    assert_eq!(raw, "---\nowner: infra\ntitle: runbook\n---\n# heading\n");
+}
@@ -0,0 +1,167 @@
+use comrak::{Arena, parse_document, nodes::{AstNode, NodeValue}};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{front_matter, render::Profile};
+
+/*
+ * references of a document for the link checker.
+ * links and images come from the comrak AST, wiki links([[Title]] or [[Title|label]]) from its text.
+ * line: 1-based line in the raw markdown(front matter included)
+ */
+static FILE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|/)file/([^/?#\s]+)").unwrap());
+static PAGE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|/)document/page/([^/?#\s]+)/?$").unwrap());
+static DOCUMENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|/)document/(\d+)/?$").unwrap());
+static WIKI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[\[([^\[\]|#]+)(?:#[^\[\]|]*)?(?:\|[^\[\]]*)?\]\]").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    // file/<object_id>
+    File(String),
+    // document/<doc_id>, optionally #<heading anchor>
+    Document(i32, Option<String>),
+    // document/page/<link_id>
+    Page(String),
+    Wiki(String),
+    // #<heading anchor> of the document itself
+    Anchor(String),
+    External(String),
+    // mailto:, relative paths and the like are not checked
+    Other,
+}
+impl Target {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::File(_) => "file",
+            Self::Document(..) => "document",
+            Self::Page(_) => "page",
+            Self::Wiki(_) => "wiki",
+            Self::Anchor(_) => "anchor",
+            Self::External(_) => "external",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub url: String,
+    pub target: Target,
+    pub line: usize,
+}
+
+pub fn classify(url: &str) -> Target {
+    let url = url.trim();
+    if let Some(anchor) = url.strip_prefix('#') {
+        return match anchor.is_empty() {
+            true => Target::Other,
+            false => Target::Anchor(anchor.to_string()),
+        };
+    }
+    if url.contains("://") || url.starts_with("//") {
+        let lower = url.to_lowercase();
+        return match lower.starts_with("http://") || lower.starts_with("https://") {
+            true => Target::External(url.to_string()),
+            false => Target::Other,
+        };
+    }
+    let (path, fragment) = match url.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment).filter(|fragment| !fragment.is_empty())),
+        None => (url, None),
+    };
+    let path = path.split('?').next().unwrap_or_default();
+    if let Some(cap) = FILE_REGEX.captures(path) {
+        return Target::File(cap[1].to_string());
+    }
+    if let Some(cap) = PAGE_REGEX.captures(path) {
+        return Target::Page(cap[1].to_string());
+    }
+    if let Some(doc_id) = DOCUMENT_REGEX.captures(path).and_then(|cap| cap[1].parse::<i32>().ok()) {
+        return Target::Document(doc_id, fragment.map(|fragment| fragment.to_string()));
+    }
+    Target::Other
+}
+
+pub fn wiki_links(text: &str) -> Vec<(String, String)> {
+    WIKI_REGEX.captures_iter(text)
+        .map(|cap| (cap[0].to_string(), cap[1].trim().to_string()))
+        .filter(|(_, title)| !title.is_empty())
+        .collect()
+}
+
+pub fn links(raw: &str) -> Vec<Link> {
+    let body = front_matter::strip(raw);
+    let base = raw[..raw.len() - body.len()].matches('\n').count();
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &Profile { gfm: true, ..Default::default() }.options());
+    let mut links = Vec::new();
+    // consecutive text nodes, wiki links can be split over several of them
+    let mut text = String::new();
+    let mut text_line = 0;
+    for node in root.descendants() {
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Link(link) | NodeValue::Image(link) => {
+                let url = String::from_utf8_lossy(&link.url).to_string();
+                links.push(Link {
+                    target: classify(&url),
+                    url,
+                    line: base + line_of(node),
+                });
+            },
+            NodeValue::Text(literal) => {
+                let continues = node.previous_sibling()
+                    .map(|sibling| matches!(sibling.data.borrow().value, NodeValue::Text(_)))
+                    .unwrap_or(false);
+                if !continues {
+                    flush(&mut text, text_line, &mut links);
+                    text_line = base + line_of(node);
+                }
+                text.push_str(&String::from_utf8_lossy(&literal));
+            },
+            _ => {},
+        }
+    }
+    flush(&mut text, text_line, &mut links);
+    links.sort_by_key(|link| link.line);
+    links
+}
+
+fn flush(text: &mut String, line: usize, links: &mut Vec<Link>) {
+    for (url, title) in wiki_links(text) {
+        links.push(Link { url, target: Target::Wiki(title), line });
+    }
+    text.clear();
+}
+
+// inline nodes take the line of the block they are in
+pub fn line_of<'a>(node: &'a AstNode<'a>) -> usize {
+    node.ancestors()
+        .map(|node| node.data.borrow().start_line as usize)
+        .find(|&line| line > 0)
+        .unwrap_or(1)
+}
+
+#[test]
+fn links_test() {
+    assert_eq!(classify("file/abc123"), Target::File("abc123".to_string()));
+    assert_eq!(classify("http://localhost:8000/file/abc123?download=1"), Target::External("http://localhost:8000/file/abc123?download=1".to_string()));
+    assert_eq!(classify("/profile/abc"), Target::Other);
+    assert_eq!(classify("/document/12#setup"), Target::Document(12, Some("setup".to_string())));
+    assert_eq!(classify("document/page/xyz"), Target::Page("xyz".to_string()));
+    assert_eq!(classify("#intro"), Target::Anchor("intro".to_string()));
+    assert_eq!(classify("mailto:someone@example.com"), Target::Other);
+    assert_eq!(wiki_links("see [[Runbook|the runbook]] and [[ Postgres#vacuum ]]"), vec![
+        ("[[Runbook|the runbook]]".to_string(), "Runbook".to_string()),
+        ("[[ Postgres#vacuum ]]".to_string(), "Postgres".to_string()),
+    ]);
+
+    let raw = "---\ntitle: x\n---\n# Title\n\nsee [file](file/abc) and [[Other Doc]]\n\n![img](https://example.com/a.png)\n";
+    let links = links(raw);
+    assert_eq!(links.len(), 3);
+    assert_eq!(links[0].target, Target::File("abc".to_string()));
+    assert_eq!(links[0].line, 6);
+    assert_eq!(links[1].target, Target::Wiki("Other Doc".to_string()));
+    assert_eq!(links[2].line, 8);
+}
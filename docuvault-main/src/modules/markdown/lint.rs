@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use comrak::{Arena, parse_document, nodes::{AstNode, NodeValue}};
+use serde::{Deserialize, Serialize};
+
+use super::{front_matter, links::{self, Target}, render::Profile};
+
+/*
+ * lint of a document as it is saved.
+ * every rule has a severity: warnings are returned along with the saved document, errors reject the save.
+ * line: 1-based line in the raw markdown(front matter included)
+ */
+pub const RULES: [&str; 7] = [
+    "single-h1",
+    "heading-increment",
+    "empty-link",
+    "unresolved-file",
+    "trailing-whitespace",
+    "line-length",
+    "front-matter",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+/*
+ * stored as docuser.lint_config and scope.lint_config(jsonb).
+ * rules not listed keep their default severity: line-length is off, front-matter(the keys of required_keys) is an error,
+ * everything else a warning.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub rules: BTreeMap<String, Severity>,
+    pub max_line_length: usize,
+    pub required_keys: Vec<String>,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+            max_line_length: 120,
+            required_keys: Vec::new(),
+        }
+    }
+}
+impl Config {
+    pub fn from_json(value: Option<&serde_json::Value>) -> Option<Self> {
+        serde_json::from_value(value?.clone()).ok()
+    }
+    pub fn is_valid(&self) -> bool {
+        self.rules.keys().all(|rule| RULES.contains(&rule.as_str())) && self.max_line_length > 0
+    }
+    pub fn severity(&self, rule: &str) -> Severity {
+        match self.rules.get(rule) {
+            Some(severity) => *severity,
+            None => match rule {
+                "line-length" => Severity::Off,
+                "front-matter" => Severity::Error,
+                _ => Severity::Warning,
+            },
+        }
+    }
+
+    /*
+     * the config of the first scope of the document that has one, otherwise the owner's.
+     * keys required by any of its scopes are required.
+     */
+    pub fn resolve(user: Option<Config>, scopes: Vec<Config>) -> Config {
+        let required_keys = scopes.iter()
+            .chain(user.iter())
+            .flat_map(|config| config.required_keys.iter().cloned())
+            .fold(Vec::new(), |mut keys, key| {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+                keys
+            });
+        let config = scopes.into_iter().next().or(user).unwrap_or_default();
+        Config { required_keys, ..config }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub line: usize,
+    pub message: String,
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+// object ids of the file/ links, to be looked up before linting
+pub fn file_refs(raw: &str) -> Vec<String> {
+    links::links(raw).into_iter()
+        .filter_map(|link| match link.target {
+            Target::File(object_id) => Some(object_id),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn lint<F>(raw: &str, config: &Config, file_exists: F) -> Vec<Diagnostic> where F: Fn(&str) -> bool {
+    let mut diagnostics = Vec::new();
+    let mut push = |rule: &'static str, line: usize, message: String| {
+        let severity = config.severity(rule);
+        if severity != Severity::Off {
+            diagnostics.push(Diagnostic { rule, severity, line, message });
+        }
+    };
+
+    let (yaml, body) = front_matter::split(raw);
+    let base = raw[..raw.len() - body.len()].matches('\n').count();
+
+    if !config.required_keys.is_empty() {
+        let mapping = yaml
+            .and_then(|yaml| serde_yaml::from_str::<serde_yaml::Mapping>(yaml).ok())
+            .unwrap_or_default();
+        for key in &config.required_keys {
+            let present = match mapping.get(key.as_str()) {
+                None | Some(serde_yaml::Value::Null) => false,
+                Some(serde_yaml::Value::String(value)) => !value.trim().is_empty(),
+                Some(_) => true,
+            };
+            if !present {
+                push("front-matter", 1, format!("front matter key \"{}\" is required", key));
+            }
+        }
+    }
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &Profile { gfm: true, ..Default::default() }.options());
+    let mut h1_seen = false;
+    let mut last_level = None;
+    for node in root.descendants() {
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Heading(heading) => {
+                let line = base + links::line_of(node);
+                let level = heading.level as usize;
+                if level == 1 {
+                    if h1_seen {
+                        push("single-h1", line, "more than one h1 heading".to_string());
+                    }
+                    h1_seen = true;
+                }
+                if let Some(last_level) = last_level {
+                    if level > last_level + 1 {
+                        push("heading-increment", line, format!("h{} follows h{}, skipping a level", level, last_level));
+                    }
+                }
+                last_level = Some(level);
+            },
+            NodeValue::Link(link) => {
+                let line = base + links::line_of(node);
+                if link.url.iter().all(u8::is_ascii_whitespace) {
+                    push("empty-link", line, "link without a target".to_string());
+                } else if !has_content(node) {
+                    push("empty-link", line, "link without text".to_string());
+                }
+            },
+            NodeValue::Image(link) if link.url.iter().all(u8::is_ascii_whitespace) => {
+                push("empty-link", base + links::line_of(node), "image without a source".to_string());
+            },
+            _ => {},
+        }
+    }
+
+    for link in links::links(raw) {
+        if let Target::File(object_id) = &link.target {
+            if !file_exists(object_id) {
+                push("unresolved-file", link.line, format!("file/{} is not an uploaded file", object_id));
+            }
+        }
+    }
+
+    for (idx, line) in raw.lines().enumerate() {
+        let trimmed = line.trim_end();
+        let trailing = &line[trimmed.len()..];
+        // two spaces after text are a hard line break
+        if !trailing.is_empty() && (trailing != "  " || trimmed.is_empty()) {
+            push("trailing-whitespace", idx + 1, "trailing whitespace".to_string());
+        }
+        // a line without spaces(e.g. a long url) can not be wrapped
+        let length = line.chars().count();
+        if length > config.max_line_length && trimmed.contains(char::is_whitespace) {
+            push("line-length", idx + 1, format!("line is {} characters long, at most {}", length, config.max_line_length));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    diagnostics
+}
+
+fn has_content<'a>(node: &'a AstNode<'a>) -> bool {
+    node.descendants().skip(1).any(|child| match &child.data.borrow().value {
+        NodeValue::Text(text) => !text.iter().all(u8::is_ascii_whitespace),
+        NodeValue::Code(_) | NodeValue::Image(_) | NodeValue::HtmlInline(_) => true,
+        _ => false,
+    })
+}
+
+#[test]
+fn lint_test() {
+    let raw = "---\ntitle: x\n---\n# One\n\n### Three \n\n# Again\n\n[](file/abc) [text]() ![img](file/known)\n";
+    let config = Config {
+        required_keys: vec!["owner".to_string()],
+        ..Default::default()
+    };
+    let diagnostics = lint(raw, &config, |object_id| object_id == "known");
+    let found = diagnostics.iter().map(|diagnostic| (diagnostic.rule, diagnostic.line)).collect::<Vec<_>>();
+    assert_eq!(found, vec![
+        ("front-matter", 1),
+        ("heading-increment", 6),
+        ("trailing-whitespace", 6),
+        ("single-h1", 8),
+        ("empty-link", 10),
+        ("empty-link", 10),
+        ("unresolved-file", 10),
+    ]);
+    assert!(has_errors(&diagnostics));
+    assert_eq!(file_refs(raw), vec!["abc".to_string(), "known".to_string()]);
+
+    // hard line breaks are kept, long lines only checked when turned on
+    let config = Config { rules: BTreeMap::from([("line-length".to_string(), Severity::Error)]), max_line_length: 10, ..Default::default() };
+    let diagnostics = lint("# Title\n\nfirst line  \nsecond line is long\nhttps://example.com/a/long/url\n", &config, |_| true);
+    assert_eq!(diagnostics.iter().map(|diagnostic| (diagnostic.rule, diagnostic.line)).collect::<Vec<_>>(), vec![("line-length", 3), ("line-length", 4)]);
+
+    let scope = Config { required_keys: vec!["owner".to_string()], ..Default::default() };
+    let user = Config { required_keys: vec!["team".to_string()], max_line_length: 80, ..Default::default() };
+    let resolved = Config::resolve(Some(user.clone()), vec![scope]);
+    assert_eq!(resolved.required_keys, vec!["owner".to_string(), "team".to_string()]);
+    assert_eq!(resolved.max_line_length, 120);
+    assert_eq!(Config::resolve(Some(user), vec![]).max_line_length, 80);
+    assert!(!Config { rules: BTreeMap::from([("unknown".to_string(), Severity::Error)]), ..Default::default() }.is_valid());
+}
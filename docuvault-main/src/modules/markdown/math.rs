@@ -0,0 +1,229 @@
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/*
+ * `$…$`(inline) and `$$…$$`(display) math.
+ * formulas are cut out of the markdown before it is parsed, so that emphasis and escapes
+ * do not touch the TeX, and put back as MathML into the rendered html.
+ * code spans and fenced code blocks are left alone, `\$` is a literal dollar sign.
+ */
+const OPEN: char = '\u{E000}';
+const CLOSE: char = '\u{E001}';
+
+/*
+ * latex2mathml copies text(\text{…}, unknown commands, operators) into the MathML as is,
+ * so its output is sanitized: MathML elements with plain attributes are kept, anything else is escaped.
+ */
+const TAGS: &[&str] = &[
+    "math", "semantics", "mrow", "mi", "mn", "mo", "mtext", "mspace", "msub", "msup", "msubsup",
+    "mfrac", "msqrt", "mroot", "mstyle", "mtable", "mtr", "mtd", "mover", "munder", "munderover",
+    "mmultiscripts", "mprescripts", "none",
+];
+const ATTRIBUTES: &[&str] = &[
+    "xmlns", "display", "mathvariant", "linethickness", "accent", "stretchy", "form",
+    "maxsize", "minsize", "width", "displaystyle", "columnalign", "class",
+];
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^<(/?)([a-z]+)((?:\s+[a-z]+="[^"<>&]*")*)\s*(/?)>"#).unwrap()
+});
+static ATTRIBUTE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"([a-z]+)=""#).unwrap()
+});
+static ENTITY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^&(?:#x[0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);"#).unwrap()
+});
+
+#[derive(Debug, PartialEq)]
+pub struct Formula {
+    pub tex: String,
+    pub display: bool,
+}
+
+fn placeholder(formulas: &mut Vec<Formula>, tex: &str, display: bool) -> String {
+    formulas.push(Formula { tex: tex.trim().to_string(), display });
+    format!("{}{}{}", OPEN, formulas.len() - 1, CLOSE)
+}
+
+fn fence(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    ["```", "~~~"].into_iter().find(|fence| line.starts_with(fence))
+}
+
+pub fn extract(raw: &str) -> (String, Vec<Formula>) {
+    let mut out = String::with_capacity(raw.len());
+    let mut formulas = Vec::new();
+    let mut code_fence: Option<&str> = None;
+    let mut block: Option<String> = None;
+    for line in raw.split_inclusive('\n') {
+        if let Some(tex) = block.as_mut() {
+            match line.find("$$") {
+                Some(end) => {
+                    tex.push_str(&line[..end]);
+                    let tex = block.take().unwrap();
+                    out.push_str(&placeholder(&mut formulas, &tex, true));
+                    out.push_str(&line[end + 2..]);
+                },
+                None => tex.push_str(line),
+            }
+            continue;
+        }
+        if let Some(open) = code_fence {
+            if fence(line) == Some(open) {
+                code_fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if let Some(open) = fence(line) {
+            code_fence = Some(open);
+            out.push_str(line);
+            continue;
+        }
+        // a display formula spanning lines
+        let trimmed = line.trim();
+        if trimmed.starts_with("$$") && !trimmed[2..].contains("$$") {
+            block = Some(trimmed[2..].to_string() + "\n");
+            continue;
+        }
+        out.push_str(&inline(line, &mut formulas));
+    }
+    // an unclosed block is not math
+    if let Some(tex) = block {
+        out.push_str("$$");
+        out.push_str(&tex);
+    }
+    (out, formulas)
+}
+
+fn inline(line: &str, formulas: &mut Vec<Formula>) -> String {
+    let chars = line.char_indices().collect::<Vec<_>>();
+    let mut out = String::with_capacity(line.len());
+    let mut idx = 0;
+    while idx < chars.len() {
+        let (pos, c) = chars[idx];
+        match c {
+            '\\' if chars.get(idx + 1).map(|(_, c)| *c) == Some('$') => {
+                out.push_str("\\$");
+                idx += 2;
+            },
+            // code span, up to the closing run of the same length
+            '`' => {
+                let run = chars[idx..].iter().take_while(|(_, c)| *c == '`').count();
+                let ticks = "`".repeat(run);
+                let rest = pos + run;
+                let end = line[rest..].find(&ticks).map(|end| rest + end + run).unwrap_or(rest);
+                out.push_str(&line[pos..end]);
+                idx = chars.iter().position(|(p, _)| *p >= end).unwrap_or(chars.len());
+            },
+            '$' => {
+                let display = chars.get(idx + 1).map(|(_, c)| *c) == Some('$');
+                let delim = if display { "$$" } else { "$" };
+                let start = pos + delim.len();
+                let end = line[start..].find(delim).map(|end| start + end);
+                let valid = end.is_some_and(|end| {
+                    let tex = &line[start..end];
+                    let next = line[end + delim.len()..].chars().next();
+                    !tex.trim().is_empty()
+                        && (display || (!tex.starts_with(char::is_whitespace) && !tex.ends_with(char::is_whitespace)))
+                        && !tex.ends_with('\\')
+                        && !next.is_some_and(|c| c.is_ascii_digit())
+                });
+                match (valid, end) {
+                    (true, Some(end)) => {
+                        out.push_str(&placeholder(formulas, &line[start..end], display));
+                        let after = end + delim.len();
+                        idx = chars.iter().position(|(p, _)| *p >= after).unwrap_or(chars.len());
+                    },
+                    _ => {
+                        out.push_str(delim);
+                        idx += delim.len();
+                    },
+                }
+            },
+            c => {
+                out.push(c);
+                idx += 1;
+            },
+        }
+    }
+    out
+}
+
+// formulas TeX can not be read from stay visible as code
+pub fn restore(html: &str, formulas: &[Formula]) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find(OPEN) {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + OPEN.len_utf8()..];
+        let formula = after.find(CLOSE)
+            .and_then(|close| Some((close, formulas.get(after[..close].parse::<usize>().ok()?)?)));
+        match formula {
+            Some((close, formula)) => {
+                let style = if formula.display { DisplayStyle::Block } else { DisplayStyle::Inline };
+                match latex_to_mathml(&formula.tex, style) {
+                    Ok(mathml) => out.push_str(&sanitize(&mathml)),
+                    Err(_) => out.push_str(&format!("<code class=\"math-error\">{}</code>", escape(&formula.tex))),
+                }
+                rest = &after[close + CLOSE.len_utf8()..];
+            },
+            None => {
+                rest = after;
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn sanitize(mathml: &str) -> String {
+    let mut out = String::with_capacity(mathml.len());
+    let mut rest = mathml;
+    while let Some(c) = rest.chars().next() {
+        let kept = match c {
+            '<' => TAG_REGEX.captures(rest)
+                .filter(|cap| TAGS.contains(&&cap[2]))
+                .filter(|cap| ATTRIBUTE_REGEX.captures_iter(&cap[3]).all(|attr| ATTRIBUTES.contains(&&attr[1])))
+                .map(|cap| cap[0].len()),
+            '&' => ENTITY_REGEX.find(rest).map(|entity| entity.end()),
+            _ => None,
+        };
+        match (kept, c) {
+            (Some(len), _) => {
+                out.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            },
+            (None, '<') => out.push_str("&lt;"),
+            (None, '>') => out.push_str("&gt;"),
+            (None, '&') => out.push_str("&amp;"),
+            (None, '"') => out.push_str("&quot;"),
+            (None, c) => out.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[test]
+fn math_extract_test() {
+    let raw = "Mass $E = mc^2$ costs $5 and $10.\n\n`$x$` and \\$y$\n\n```\n$z$\n```\n\n$$\n\\frac{a}{b}\n$$\n";
+    let (out, formulas) = extract(raw);
+    assert_eq!(formulas, vec![
+        Formula { tex: "E = mc^2".to_string(), display: false },
+        Formula { tex: "\\frac{a}{b}".to_string(), display: true },
+    ]);
+    assert_eq!(out, "Mass \u{E000}0\u{E001} costs $5 and $10.\n\n`$x$` and \\$y$\n\n```\n$z$\n```\n\n\u{E000}1\u{E001}\n");
+    let html = restore("<p>a \u{E000}0\u{E001}</p>", &formulas);
+    assert!(html.starts_with("<p>a <math") && html.ends_with("</math></p>"));
+    // text copied into the MathML can not open elements
+    let (_, formulas) = extract("$\\text{<img src=x onerror=alert(1)>} < b$");
+    let html = restore("\u{E000}0\u{E001}", &formulas);
+    assert!(html.starts_with("<math") && !html.contains("<img") && html.contains("&lt;"));
+    assert_eq!(sanitize("<mi onclick=\"x\">a</mi><mo>&#x2061;</mo>&x"), "&lt;mi onclick=&quot;x&quot;&gt;a</mi><mo>&#x2061;</mo>&amp;x");
+}
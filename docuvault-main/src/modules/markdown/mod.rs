@@ -1,8 +1,18 @@
-use comrak::{Arena, parse_document, ComrakOptions, nodes::{NodeValue, AstNode, NodeCode}};
+use comrak::{Arena, parse_document, nodes::{NodeValue, AstNode, NodeCode}};
+
+pub mod front_matter;
+pub mod anchor;
+pub mod math;
+pub mod render;
+pub mod outline;
+pub mod stats;
+pub mod links;
+pub mod lint;
 
 pub fn get_title(document: &str) -> String {
     let arena = Arena::new();
-    let root = parse_document(&arena, document, &ComrakOptions::default()); 
+    // extensions change what a heading's text is(e.g. ~~strikethrough~~), not where headings are
+    let root = parse_document(&arena, document, &render::Profile { gfm: true, ..Default::default() }.options()); 
     for node in root.children() {
         let header = match node.data.clone().into_inner().value {
             NodeValue::Heading(head) => head,
@@ -0,0 +1,113 @@
+use comrak::{Arena, parse_document, Anchorizer, nodes::NodeValue};
+use serde::{Deserialize, Serialize};
+
+use super::{collect_text, front_matter, render::Profile};
+
+/*
+ * heading outline of a document
+ * - anchor: the id comrak gives the heading when heading anchors are on
+ * - offset: byte offset of the heading line in the raw markdown(front matter included), utf-8
+ */
+pub const TOC_MARKER: &str = "[TOC]";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: u32,
+    pub text: String,
+    pub anchor: String,
+    pub offset: usize,
+}
+
+pub fn outline(raw: &str) -> Vec<Heading> {
+    let body = front_matter::strip(raw);
+    let base = raw.len() - body.len();
+    // byte offset of every line start of the body
+    let mut lines = vec![0];
+    lines.extend(body.match_indices('\n').map(|(idx, _)| idx + 1));
+    let count = body.len();
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &Profile { gfm: true, ..Default::default() }.options());
+    let mut anchorizer = Anchorizer::new();
+    let mut headings = Vec::new();
+    for node in root.descendants() {
+        let level = match node.data.borrow().value {
+            NodeValue::Heading(ref head) => head.level as u32,
+            _ => continue,
+        };
+        let mut text = Vec::new();
+        collect_text(node, &mut text);
+        let text = String::from_utf8_lossy(&text).to_string();
+        let line = node.data.borrow().start_line as usize;
+        headings.push(Heading {
+            level,
+            anchor: anchorizer.anchorize(text.clone()),
+            text: text.trim().to_string(),
+            offset: base + lines.get(line.saturating_sub(1)).copied().unwrap_or(count),
+        });
+    }
+    headings
+}
+
+// documents saved before outlines were stored get theirs on read
+pub fn from_json(value: Option<&serde_json::Value>, raw: &str) -> Vec<Heading> {
+    value.and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_else(|| outline(raw))
+}
+
+pub fn to_json(headings: &[Heading]) -> serde_json::Value {
+    serde_json::to_value(headings).unwrap_or_default()
+}
+
+pub fn has_toc(raw: &str) -> bool {
+    raw.lines().any(|line| line.trim() == TOC_MARKER)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// nested lists, a heading deeper than the previous one opens a list under it
+pub fn toc(headings: &[Heading]) -> String {
+    let mut out = String::from("<nav class=\"toc\">\n");
+    let mut stack: Vec<u32> = Vec::new();
+    for heading in headings {
+        while stack.last().is_some_and(|level| *level > heading.level) {
+            stack.pop();
+            out.push_str("</li>\n</ul>\n");
+        }
+        match stack.last() {
+            Some(level) if *level == heading.level => out.push_str("</li>\n"),
+            _ => {
+                stack.push(heading.level);
+                out.push_str("<ul>\n");
+            },
+        }
+        out.push_str(&format!("<li><a href=\"#{}\">{}</a>", escape(&heading.anchor), escape(&heading.text)));
+    }
+    for _ in stack {
+        out.push_str("</li>\n</ul>\n");
+    }
+    out.push_str("</nav>\n");
+    out
+}
+
+#[test]
+fn outline_test() {
+    let raw = "---\ntitle: Runbook\n---\n# Runbook\n\nintro\n\n## Failover\n\nSetup\n-----\n\n## Failover\n### *Check* `db`\n# Appendix\n";
+    let headings = outline(raw);
+    let anchors = headings.iter().map(|heading| (heading.level, heading.anchor.as_str())).collect::<Vec<_>>();
+    assert_eq!(anchors, vec![(1, "runbook"), (2, "failover"), (2, "setup"), (2, "failover-1"), (3, "check-db"), (1, "appendix")]);
+    assert_eq!(&raw[headings[1].offset..headings[1].offset + 11], "## Failover");
+    assert_eq!(&raw[headings[2].offset..headings[2].offset + 5], "Setup");
+    assert_eq!(headings[4].text, "Check db");
+    // offsets are in bytes, past multi-byte characters as well
+    let raw = "# Über\n\nnaïve café\n\n## Zürich\n";
+    let offset = outline(raw)[1].offset;
+    assert_eq!(&raw[offset..], "## Zürich\n");
+
+    let html = toc(&headings);
+    assert!(html.starts_with("<nav class=\"toc\">\n<ul>\n<li><a href=\"#runbook\">Runbook</a><ul>\n<li><a href=\"#failover\">"));
+    assert!(html.contains("<a href=\"#check-db\">Check db</a></li>\n</ul>\n</li>\n</ul>\n</li>\n<li><a href=\"#appendix\">"));
+    assert!(has_toc("# A\n\n[TOC]\n"));
+}
@@ -0,0 +1,89 @@
+use comrak::{ComrakOptions, ComrakPlugins, markdown_to_html_with_plugins, adapters::SyntaxHighlighterAdapter, plugins::syntect::SyntectAdapter};
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::{front_matter, math, outline};
+
+/*
+ * rendering profile of markdown documents, set per user or per scope(the scope's one wins).
+ * - gfm: tables, strikethrough, task lists, autolinks and footnotes
+ * - highlight: syntect theme for fenced code blocks
+ * - math: `$…$` and `$$…$$` as MathML
+ * - anchors: slug ids on headings(GitHub style, repeated headings get -1, -2..)
+ *   a `[TOC]` line turns them on for the document, see outline.
+ * documents without a profile render as plain CommonMark.
+ */
+pub const HIGHLIGHT_THEMES: &[&str] = &[
+    "InspiredGitHub",
+    "Solarized (dark)",
+    "Solarized (light)",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+    "base16-ocean.dark",
+    "base16-ocean.light",
+];
+
+// an adapter loads the syntax and theme sets, they are loaded once for every theme
+static ADAPTERS: Lazy<HashMap<&'static str, SyntectAdapter>> = Lazy::new(|| {
+    HIGHLIGHT_THEMES.iter().map(|&theme| (theme, SyntectAdapter::new(theme))).collect()
+});
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub gfm: bool,
+    pub highlight: Option<String>,
+    pub math: bool,
+    pub anchors: bool,
+}
+impl Profile {
+    pub fn from_json(value: Option<&serde_json::Value>) -> Option<Self> {
+        value.and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+    pub fn is_valid(&self) -> bool {
+        self.highlight.as_deref().map_or(true, |theme| HIGHLIGHT_THEMES.contains(&theme))
+    }
+    pub fn options(&self) -> ComrakOptions {
+        let mut options = ComrakOptions::default();
+        if self.gfm {
+            options.extension.strikethrough = true;
+            options.extension.table = true;
+            options.extension.autolink = true;
+            options.extension.tasklist = true;
+            options.extension.footnotes = true;
+        }
+        if self.anchors {
+            options.extension.header_ids = Some(String::new());
+        }
+        options
+    }
+}
+
+// a `[TOC]` paragraph becomes the table of contents, headings get anchors for it
+pub fn render(raw: &str, profile: &Profile) -> String {
+    let raw = front_matter::strip(raw);
+    let toc = outline::has_toc(raw);
+    let mut options = profile.options();
+    if toc {
+        options.extension.header_ids.get_or_insert_with(String::new);
+    }
+    let mut plugins = ComrakPlugins::default();
+    plugins.render.codefence_syntax_highlighter = profile.highlight.as_deref()
+        .and_then(|theme| ADAPTERS.get(theme))
+        .map(|adapter| adapter as &dyn SyntaxHighlighterAdapter);
+
+    let (raw, formulas) = match profile.math {
+        true => math::extract(raw),
+        false => (raw.to_string(), Vec::new()),
+    };
+    let mut html = markdown_to_html_with_plugins(&raw, &options, &plugins);
+    if toc {
+        html = html.replace(&format!("<p>{}</p>", outline::TOC_MARKER), &outline::toc(&outline::outline(&raw)));
+    }
+    match profile.math {
+        true => math::restore(&html, &formulas),
+        false => html,
+    }
+}
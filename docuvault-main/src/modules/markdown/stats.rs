@@ -0,0 +1,107 @@
+use comrak::{Arena, parse_document, nodes::{AstNode, NodeValue}};
+
+use super::{front_matter, render::Profile};
+
+/*
+ * list view statistics of a document
+ * - word_count: words of the text, code blocks, images and raw html excluded
+ * - reading_time: minutes at WORDS_PER_MINUTE, at least 1 for a non-empty document
+ * - excerpt: plain text of the first paragraph
+ * - cover: the first attached image(file/<object_id>)
+ */
+const WORDS_PER_MINUTE: i32 = 200;
+const EXCERPT_LEN: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub word_count: i32,
+    pub reading_time: i32,
+    pub excerpt: Option<String>,
+    pub cover: Option<String>,
+}
+
+// text of the node, blocks separated by spaces
+fn plain_text<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    let block = match node.data.borrow().value {
+        NodeValue::Text(ref literal) => {
+            out.push_str(&String::from_utf8_lossy(literal));
+            return;
+        },
+        NodeValue::Code(ref code) => {
+            out.push_str(&String::from_utf8_lossy(&code.literal));
+            return;
+        },
+        NodeValue::LineBreak | NodeValue::SoftBreak => {
+            out.push(' ');
+            return;
+        },
+        NodeValue::CodeBlock(_) | NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_) | NodeValue::Image(_) => return,
+        ref value => value.block(),
+    };
+    for child in node.children() {
+        plain_text(child, out);
+    }
+    if block {
+        out.push(' ');
+    }
+}
+
+fn cut(text: &str) -> String {
+    if text.chars().count() <= EXCERPT_LEN {
+        return text.to_string();
+    }
+    let end = text.char_indices().nth(EXCERPT_LEN).map(|(idx, _)| idx).unwrap_or(text.len());
+    let end = text[..end].rfind(' ').unwrap_or(end);
+    format!("{}…", text[..end].trim_end())
+}
+
+pub fn stats(raw: &str) -> Stats {
+    let arena = Arena::new();
+    let root = parse_document(&arena, front_matter::strip(raw), &Profile { gfm: true, ..Default::default() }.options());
+
+    let mut text = String::new();
+    plain_text(root, &mut text);
+    let word_count = text.split_whitespace().count() as i32;
+
+    let mut excerpt = None;
+    let mut cover = None;
+    for node in root.descendants() {
+        match node.data.borrow().value {
+            NodeValue::Paragraph if excerpt.is_none() => {
+                let mut text = String::new();
+                plain_text(node, &mut text);
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !text.is_empty() {
+                    excerpt = Some(cut(&text));
+                }
+            },
+            NodeValue::Image(ref link) if cover.is_none() => {
+                let url = String::from_utf8_lossy(&link.url);
+                if let Some(idx) = url.rfind("file/") {
+                    if !url.contains("://") {
+                        cover = Some(url[idx..].to_string());
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    Stats {
+        word_count,
+        reading_time: (word_count + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE,
+        excerpt,
+        cover,
+    }
+}
+
+#[test]
+fn stats_test() {
+    let raw = "---\ntitle: t\n---\n# Runbook\n\n![diagram](https://example.com/a.png)\n\nRestart the *primary* `db` node\nfirst.\n\n```\nignored code words\n```\n\n![](../file/abc123)\n";
+    let res = stats(raw);
+    assert_eq!(res.word_count, 7);
+    assert_eq!(res.reading_time, 1);
+    assert_eq!(res.excerpt.as_deref(), Some("Restart the primary db node first."));
+    assert_eq!(res.cover.as_deref(), Some("file/abc123"));
+    assert_eq!(stats("").reading_time, 0);
+    assert!(cut(&"word ".repeat(100)).ends_with("word…"));
+}
@@ -2,7 +2,7 @@ use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
 use sea_orm::DatabaseConnection;
 
-use self::{tag::TagSetModule, sequence::SequenceModule};
+use self::{tag::TagSetModule, sequence::SequenceModule, probe::HttpProbe, collab::Hub, events::EventHub};
 
 pub mod redis;
 pub mod markdown;
@@ -10,17 +10,42 @@ pub mod background;
 pub mod grpc;
 pub mod tag;
 pub mod sequence;
+pub mod template;
+pub mod archive;
+pub mod site;
+pub mod mail;
+pub mod negotiate;
+pub mod theme;
+pub mod feed;
+pub mod cursor;
+pub mod filter;
+pub mod probe;
+pub mod crdt;
+pub mod collab;
+pub mod events;
+pub mod audit;
+pub mod webhook;
+pub mod role;
 
 #[derive(Debug)]
 pub struct Modules {
     pub tag: TagSetModule,
     pub sequence: SequenceModule,
+    // external urls of the link checker go through this
+    pub probe: Box<dyn HttpProbe>,
+    // collaborative editing sessions in progress
+    pub collab: Hub,
+    // server events received from redis, see events
+    pub events: EventHub,
 }
 impl Modules {
-    pub async fn new(db_conn: DatabaseConnection, redis_conn: Pool<RedisConnectionManager>) -> Self {
+    pub async fn new(db_conn: DatabaseConnection, redis_conn: Pool<RedisConnectionManager>, probe: Box<dyn HttpProbe>) -> Self {
         Self {
             tag: TagSetModule::new(db_conn.clone(), redis_conn.clone()).await,
             sequence: SequenceModule::new(db_conn, redis_conn).await,
+            probe,
+            collab: Hub::default(),
+            events: EventHub::new(),
         }
     }
 }
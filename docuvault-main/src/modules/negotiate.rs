@@ -0,0 +1,69 @@
+/*
+ * content negotiation between the representations a resource offers,
+ * given as (mime type, extension) pairs in order of preference.
+ *
+ * - ?format=<extension> takes precedence over the Accept header
+ * - the most specific matching media range of the Accept header gives the quality of an offer,
+ *   the offer with the highest quality wins(ties go to the earlier offer)
+ * - without either, the first offer is taken
+ */
+pub fn negotiate(offers: &[(&str, &str)], accept: Option<&str>, format: Option<&str>) -> Option<usize> {
+    if let Some(format) = format {
+        let format = format.trim().trim_start_matches('.');
+        return offers.iter().position(|(_, ext)| ext.eq_ignore_ascii_case(format));
+    }
+    let accept = match accept {
+        Some(accept) if !accept.trim().is_empty() => accept,
+        _ => return (!offers.is_empty()).then_some(0),
+    };
+
+    let ranges = accept.split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let media = params.next()?.trim().to_lowercase();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((media, quality))
+        })
+        .collect::<Vec<_>>();
+
+    let mut best: Option<(usize, f32)> = None;
+    for (idx, (mime, _)) in offers.iter().enumerate() {
+        let mime = mime.split(';').next().unwrap_or_default().trim().to_lowercase();
+        let main_type = mime.split('/').next().unwrap_or_default();
+        let quality = ranges.iter()
+            .filter_map(|(media, quality)| {
+                let specificity = if *media == mime {
+                    3
+                } else if media.strip_suffix("/*") == Some(main_type) {
+                    2
+                } else if media == "*/*" {
+                    1
+                } else {
+                    return None;
+                };
+                Some((specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality);
+        match quality {
+            Some(quality) if quality > 0.0 && best.map_or(true, |(_, best)| quality > best) => best = Some((idx, quality)),
+            _ => {}
+        }
+    }
+    best.map(|(idx, _)| idx)
+}
+
+#[test]
+fn negotiate_test() {
+    let offers = [("application/json", "json"), ("text/html; charset=utf-8", "html")];
+    assert_eq!(negotiate(&offers, None, None), Some(0));
+    assert_eq!(negotiate(&offers, Some("application/json, text/plain, */*"), None), Some(0));
+    assert_eq!(negotiate(&offers, Some("text/html,application/xhtml+xml,*/*;q=0.8"), None), Some(1));
+    assert_eq!(negotiate(&offers, Some("application/pdf"), None), None);
+    assert_eq!(negotiate(&offers, Some("text/*, application/json;q=0"), None), Some(1));
+    assert_eq!(negotiate(&offers, Some("application/pdf"), Some("html")), Some(1));
+    assert_eq!(negotiate(&[("application/pdf", "pdf")], None, Some("epub")), None);
+}
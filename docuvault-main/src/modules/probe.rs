@@ -0,0 +1,144 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+
+use super::webhook::{self, PinError};
+
+/*
+ * HTTP client the link checker probes external URLs with.
+ * ReqwestProbe goes out to the network, StubProbe answers from a table(tests, local runs without network).
+ */
+#[async_trait]
+pub trait HttpProbe: std::fmt::Debug + Send + Sync {
+    // status code of the url, Err with the reason when no response came back or the url was not requested
+    async fn head(&self, url: &str) -> Result<u16, ProbeError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeError {
+    // the url points at an internal address(loopback, private network, metadata service), it is not requested
+    Refused(String),
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct ReqwestProbe {
+    // hosts are resolved and only requested at public addresses(see webhook::pin_public)
+    public_only: bool,
+}
+impl ReqwestProbe {
+    pub fn new() -> Self {
+        Self { public_only: true }
+    }
+
+    async fn client(&self, url: &str) -> Result<reqwest::Client, ProbeError> {
+        let builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            // a redirect answers the probe(see check), it is not followed to wherever it points
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent("docuvault-linkcheck");
+        let builder = match self.public_only {
+            true => webhook::pin_public(builder, url).await.map_err(|e| match e {
+                PinError::NotPublic(reason) => ProbeError::Refused(reason),
+                PinError::Failed(reason) => ProbeError::Failed(reason),
+            })?,
+            false => builder,
+        };
+        builder.build().map_err(|e| ProbeError::Failed(e.to_string()))
+    }
+}
+impl Default for ReqwestProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[async_trait]
+impl HttpProbe for ReqwestProbe {
+    async fn head(&self, url: &str) -> Result<u16, ProbeError> {
+        let client = self.client(url).await?;
+        let failed = |e: reqwest::Error| ProbeError::Failed(e.to_string());
+        let res = client.head(url).send().await.map_err(failed)?;
+        // some servers do not implement HEAD
+        let status = match res.status().as_u16() {
+            405 | 501 => client.get(url).send().await.map_err(failed)?.status().as_u16(),
+            status => status,
+        };
+        Ok(status)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StubProbe {
+    pub responses: HashMap<String, Result<u16, String>>,
+}
+#[async_trait]
+impl HttpProbe for StubProbe {
+    async fn head(&self, url: &str) -> Result<u16, ProbeError> {
+        self.responses.get(url).cloned().unwrap_or_else(|| Err("no route to host".to_string())).map_err(ProbeError::Failed)
+    }
+}
+
+// the probe of the link checker, LINK_PROBE=stub for local runs without network
+pub fn from_env() -> Box<dyn HttpProbe> {
+    match std::env::var("LINK_PROBE").as_deref() {
+        Ok("stub") => Box::new(StubProbe::default()),
+        _ => Box::new(ReqwestProbe::new()),
+    }
+}
+
+// Ok when the url answers with a success or redirect status, otherwise Err with the reason
+pub async fn check(probe: &dyn HttpProbe, url: &str) -> Result<(), ProbeError> {
+    match probe.head(url).await? {
+        status if (200..400).contains(&status) => Ok(()),
+        status => Err(ProbeError::Failed(format!("HTTP {}", status))),
+    }
+}
+
+#[test]
+fn probe_test() {
+    let probe = StubProbe {
+        responses: HashMap::from([
+            ("https://example.com/".to_string(), Ok(200)),
+            ("https://example.com/moved".to_string(), Ok(301)),
+            ("https://example.com/gone".to_string(), Ok(404)),
+        ]),
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        assert_eq!(check(&probe, "https://example.com/").await, Ok(()));
+        assert_eq!(check(&probe, "https://example.com/moved").await, Ok(()));
+        assert_eq!(check(&probe, "https://example.com/gone").await, Err(ProbeError::Failed("HTTP 404".to_string())));
+        assert_eq!(check(&probe, "https://unknown.example/").await, Err(ProbeError::Failed("no route to host".to_string())));
+    });
+
+    // a redirect is the answer, the location is not requested
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let target = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let location = format!("http://{}/internal", target.local_addr().unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/moved", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let response = format!("HTTP/1.1 302 Found\r\nlocation: {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", location);
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        assert_eq!(ReqwestProbe { public_only: false }.head(&url).await, Ok(302));
+        assert!(tokio::time::timeout(Duration::from_millis(100), target.accept()).await.is_err());
+    });
+
+    // internal addresses are refused before any request is made
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        for url in [format!("http://127.0.0.1:{}/", port), format!("http://localhost:{}/", port), "http://10.0.0.1/".to_string(), "http://169.254.169.254/latest/meta-data/".to_string(), "http://[::1]/".to_string()] {
+            assert!(matches!(check(&ReqwestProbe::new(), &url).await, Err(ProbeError::Refused(_))), "{}", url);
+        }
+        assert!(tokio::time::timeout(Duration::from_millis(100), listener.accept()).await.is_err());
+    });
+}
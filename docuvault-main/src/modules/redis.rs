@@ -58,3 +58,36 @@ pub async fn redis_does_docuser_have_scope(state: AppState, scope_id: &[i32], do
 
 }
 
+
+/*
+ * brute force limiter shared by login and share link unlocking.
+ * failed attempts are counted per key(attempt:<key>) in a fixed window,
+ * the key is locked until the window ends once MAX_ATTEMPTS is reached.
+ */
+const MAX_ATTEMPTS: usize = 5;
+const ATTEMPT_WINDOW: usize = 15 * 60;
+
+pub async fn redis_check_attempts(state: AppState, key: &str) -> Result<(), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let attempts: Option<usize> = con.get(format!("attempt:{}", key)).await?;
+    if attempts.unwrap_or(0) >= MAX_ATTEMPTS {
+        return Err(GlobalError::TooManyAttempts);
+    }
+    Ok(())
+}
+
+pub async fn redis_add_attempt(state: AppState, key: &str) -> Result<(), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let key = format!("attempt:{}", key);
+    let attempts: usize = con.incr(&key, 1).await?;
+    if attempts == 1 {
+        let _: () = con.expire(&key, ATTEMPT_WINDOW).await?;
+    }
+    Ok(())
+}
+
+pub async fn redis_reset_attempts(state: AppState, key: &str) -> Result<(), GlobalError> {
+    let mut con = state.redis_conn.get().await?;
+    let _: () = con.del(format!("attempt:{}", key)).await?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use sea_orm::{entity::*, query::*, ConnectionTrait, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::entity;
+
+/*
+ * roles of the users a scope is shared with(scope_member.role).
+ * a role includes the ones below it. the owner of a scope has every role in it,
+ * the owner of a document every role on it, whatever its scopes.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    // reads the documents of the scope
+    Viewer = 0,
+    // comments on them as well
+    Commenter = 1,
+    // edits them as well, e.g. in collaborative sessions
+    Editor = 2,
+}
+impl Role {
+    pub fn from_i32(value: i32) -> Option<Role> {
+        match value {
+            0 => Some(Role::Viewer),
+            1 => Some(Role::Commenter),
+            2 => Some(Role::Editor),
+            _ => None,
+        }
+    }
+}
+
+// the scopes among scope_ids in which the user has at least the role
+pub async fn scopes_with_role<C: ConnectionTrait>(conn: &C, docuser_id: i32, scope_ids: &[i32], role: Role) -> Result<Vec<i32>, DbErr> {
+    if scope_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut granted = entity::scope::Entity::find()
+        .filter(entity::scope::Column::Id.is_in(scope_ids.to_vec()))
+        .filter(entity::scope::Column::DocuserId.eq(docuser_id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|scope| scope.id)
+        .collect::<Vec<_>>();
+    let shared = entity::scope_member::Entity::find()
+        .filter(entity::scope_member::Column::ScopeId.is_in(scope_ids.to_vec()))
+        .filter(entity::scope_member::Column::DocuserId.eq(docuser_id))
+        .filter(entity::scope_member::Column::Role.gte(role as i32))
+        .all(conn)
+        .await?;
+    granted.extend(shared.into_iter().map(|member| member.scope_id));
+    granted.sort_unstable();
+    granted.dedup();
+    Ok(granted)
+}
+
+// every scope shared with the user with at least the role, not the ones the user owns
+pub async fn shared_scopes<C: ConnectionTrait>(conn: &C, docuser_id: i32, role: Role) -> Result<Vec<i32>, DbErr> {
+    let shared = entity::scope_member::Entity::find()
+        .filter(entity::scope_member::Column::DocuserId.eq(docuser_id))
+        .filter(entity::scope_member::Column::Role.gte(role as i32))
+        .order_by_asc(entity::scope_member::Column::ScopeId)
+        .all(conn)
+        .await?;
+    Ok(shared.into_iter().map(|member| member.scope_id).collect())
+}
+
+// whether the user has at least the role on the document, through its owner or any of its scopes
+pub async fn document_has_role<C: ConnectionTrait>(conn: &C, docuser_id: i32, document: &entity::docorg::Model, role: Role) -> Result<bool, DbErr> {
+    if document.docuser_id == docuser_id {
+        return Ok(true);
+    }
+    let scope_ids = entity::docorg_scope::Entity::find()
+        .filter(entity::docorg_scope::Column::DocorgId.eq(document.id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|docorg_scope| docorg_scope.scope_id)
+        .collect::<Vec<_>>();
+    Ok(!scopes_with_role(conn, docuser_id, &scope_ids, role).await?.is_empty())
+}
+
+#[test]
+fn role_test() {
+    assert!(Role::Editor > Role::Commenter && Role::Commenter > Role::Viewer);
+    assert_eq!(Role::from_i32(Role::Commenter as i32), Some(Role::Commenter));
+    assert_eq!(Role::from_i32(3), None);
+    assert_eq!(serde_json::from_str::<Role>("\"editor\"").unwrap(), Role::Editor);
+}
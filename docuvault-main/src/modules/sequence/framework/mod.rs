@@ -3,7 +3,7 @@ use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect, JoinType, RelationTr
 use sea_orm::{entity::*, query::*};
 use serde::Serialize;
 
-use crate::{routes::error::GlobalError, entity};
+use crate::{routes::error::GlobalError, entity, modules::audit::Entry};
 
 use super::domain::entity::sequence::SequenceObj;
 use super::{application::port::output::SequenceRepositoryPort, domain::entity::{sequence::Sequence, doc_seq_order::DocSeqOrder}, error::SequenceError};
@@ -85,7 +85,7 @@ impl SequenceRepositoryPort for SequencePersistentAdapter {
 
         Ok(())
     }
-    async fn create_seq(&self, seq: SequenceObj) -> Result<(), GlobalError> {
+    async fn create_seq(&self, seq: SequenceObj, entry: Entry) -> Result<(), GlobalError> {
         &self.conn.transaction::<_, (), DbErr>(|txn|{
             Box::pin(async move {
                 let model = entity::sequence::ActiveModel {
@@ -103,6 +103,7 @@ impl SequenceRepositoryPort for SequencePersistentAdapter {
                 }).collect::<Vec<_>>();
 
                 entity::scope_sequence::Entity::insert_many(records).exec(txn).await?;
+                entry.targets([res.last_insert_id]).record(txn).await?;
 
                 Ok(())
             })
@@ -110,10 +111,13 @@ impl SequenceRepositoryPort for SequencePersistentAdapter {
         Ok(())
         
     }
-    async fn delete_seq(&self, seq_id: i32) -> Result<(), GlobalError> {
+    async fn delete_seq(&self, seq_id: i32, entry: Entry) -> Result<(), GlobalError> {
+        let txn = self.conn.begin().await?;
         entity::sequence::Entity::delete_by_id(seq_id)
-            .exec(&self.conn)
+            .exec(&txn)
             .await?;
+        entry.record(&txn).await?;
+        txn.commit().await?;
         Ok(())
     }
     
@@ -134,7 +138,7 @@ impl SequenceRepositoryPort for SequencePersistentAdapter {
         let docseqorder = res.into_iter().map(|rec| { DocSeqOrder::new(rec.docorg_id, rec.sequence_id, rec.order) }).collect::<Vec<DocSeqOrder>>();
         return Ok(docseqorder);
     }
-    async fn save_docseqord(&self, seq_id: i32, docseqord: Vec<DocSeqOrder>) -> Result<(), GlobalError>{
+    async fn save_docseqord(&self, seq_id: i32, docseqord: Vec<DocSeqOrder>, entry: Entry) -> Result<(), GlobalError>{
         &self.conn.transaction::<_, (), DbErr>(|txn|{
             Box::pin(async move {
                 entity::docorg_sequence::Entity::delete_many()
@@ -150,7 +154,11 @@ impl SequenceRepositoryPort for SequencePersistentAdapter {
                     }
                 }).collect::<Vec<_>>();
 
-                entity::docorg_sequence::Entity::insert_many(records).exec(txn).await?;
+                // the last document taken out leaves nothing to insert
+                if !records.is_empty() {
+                    entity::docorg_sequence::Entity::insert_many(records).exec(txn).await?;
+                }
+                entry.record(txn).await?;
 
                 Ok(())
             })
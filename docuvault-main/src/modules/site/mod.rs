@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use super::archive;
+
+/*
+ * static html site of a scope
+ *
+ * - index.html
+ * - docs/<doc_id>-<slug>.html
+ * - tags/index.html, tags/<slug>.html
+ * - files/<object_id>/<name>(copied in by the caller)
+ * - search.json, search.js, style.css
+ *
+ * every sequence becomes a chapter navigation with prev/next links.
+ */
+const LAYOUTS: [(&str, &str); 5] = [
+    ("base.html", include_str!("templates/base.html")),
+    ("index.html", include_str!("templates/index.html")),
+    ("page.html", include_str!("templates/page.html")),
+    ("tags.html", include_str!("templates/tags.html")),
+    ("tag.html", include_str!("templates/tag.html")),
+];
+const STYLE: &str = include_str!("templates/style.css");
+const SEARCH: &str = include_str!("templates/search.js");
+
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\b(href|src)="([^"]*)""#).unwrap());
+static DOCUMENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|/)document/(\d+)/?$").unwrap());
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+#[derive(Debug)]
+pub struct SiteDocument {
+    pub id: i32,
+    pub title: String,
+    pub html: String,
+    pub tags: Vec<String>,
+    pub excerpt: Option<String>,
+    pub reading_time: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug)]
+pub struct SiteSequence {
+    pub title: String,
+    pub doc_ids: Vec<i32>,
+}
+#[derive(Debug, Default)]
+pub struct Site {
+    pub title: String,
+    pub documents: Vec<SiteDocument>,
+    pub sequences: Vec<SiteSequence>,
+    // object id -> file name, stored at files/<object_id>/<name>
+    pub files: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Item {
+    title: String,
+    url: String,
+    current: bool,
+}
+#[derive(Debug, Serialize)]
+struct Chapter {
+    title: String,
+    items: Vec<Item>,
+    prev: Option<Item>,
+    next: Option<Item>,
+}
+#[derive(Debug, Serialize)]
+struct TagItem {
+    name: String,
+    url: String,
+    count: usize,
+}
+#[derive(Debug, Serialize)]
+struct SearchItem<'a> {
+    title: &'a str,
+    url: &'a str,
+    tags: &'a [String],
+    excerpt: Option<&'a str>,
+    reading_time: Option<i32>,
+    text: String,
+}
+
+pub fn page_path(document: &SiteDocument) -> String {
+    format!("docs/{}-{}.html", document.id, archive::slug(&document.title))
+}
+
+fn tag_path(tag: &str) -> String {
+    format!("tags/{}.html", archive::slug(tag))
+}
+
+// links to attachments and other documents of the site become relative links
+fn rewrite_links(html: &str, root: &str, site: &Site, pages: &HashMap<i32, String>) -> String {
+    LINK_REGEX.replace_all(html, |cap: &Captures| {
+        let target = &cap[2];
+        if let Some(idx) = target.rfind("file/") {
+            let object_id = &target[idx + 5..];
+            if let Some(name) = site.files.get(object_id) {
+                return format!("{}=\"{}{}\"", &cap[1], root, archive::percent_encode(&format!("files/{}/{}", object_id, name)));
+            }
+        }
+        if let Some(doc) = DOCUMENT_REGEX.captures(target) {
+            if let Some(page) = doc[1].parse::<i32>().ok().and_then(|doc_id| pages.get(&doc_id)) {
+                return format!("{}=\"{}{}\"", &cap[1], root, page);
+            }
+        }
+        cap[0].to_string()
+    }).to_string()
+}
+
+pub fn text(html: &str) -> String {
+    TAG_REGEX.replace_all(html, " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn render(site: &Site) -> Result<BTreeMap<String, Vec<u8>>, tera::Error> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(LAYOUTS.to_vec())?;
+
+    let pages = site.documents.iter().map(|document| (document.id, page_path(document))).collect::<HashMap<_, _>>();
+    let titles = site.documents.iter().map(|document| (document.id, document.title.as_str())).collect::<HashMap<_, _>>();
+    let item = |doc_id: i32, current: i32| Item {
+        title: titles[&doc_id].to_string(),
+        url: pages[&doc_id].clone(),
+        current: doc_id == current,
+    };
+    // documents of other scopes can be part of a sequence, they are skipped
+    let sequences = site.sequences.iter()
+        .map(|sequence| (sequence.title.as_str(), sequence.doc_ids.iter().copied().filter(|doc_id| pages.contains_key(doc_id)).collect::<Vec<_>>()))
+        .filter(|(_, doc_ids)| !doc_ids.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut tags: BTreeMap<&str, Vec<&SiteDocument>> = BTreeMap::new();
+    for document in &site.documents {
+        for tag in &document.tags {
+            tags.entry(tag.as_str()).or_default().push(document);
+        }
+    }
+
+    let mut output = BTreeMap::new();
+
+    /*
+     * pages
+     */
+    for document in &site.documents {
+        let chapters = sequences.iter()
+            .filter_map(|(title, doc_ids)| {
+                let idx = doc_ids.iter().position(|doc_id| *doc_id == document.id)?;
+                Some(Chapter {
+                    title: title.to_string(),
+                    items: doc_ids.iter().map(|doc_id| item(*doc_id, document.id)).collect(),
+                    prev: idx.checked_sub(1).map(|idx| item(doc_ids[idx], document.id)),
+                    next: doc_ids.get(idx + 1).map(|doc_id| item(*doc_id, document.id)),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut context = Context::new();
+        context.insert("root", "../");
+        context.insert("site_title", &site.title);
+        context.insert("title", &document.title);
+        context.insert("html", &rewrite_links(&document.html, "../", site, &pages));
+        context.insert("tags", &document.tags.iter().map(|tag| TagItem { name: tag.clone(), url: tag_path(tag), count: tags[tag.as_str()].len() }).collect::<Vec<_>>());
+        context.insert("chapters", &chapters);
+        context.insert("created_at", &document.created_at.format("%Y-%m-%d").to_string());
+        context.insert("updated_at", &document.updated_at.format("%Y-%m-%d").to_string());
+        output.insert(pages[&document.id].clone(), tera.render("page.html", &context)?.into_bytes());
+    }
+
+    /*
+     * index, documents not part of any sequence are listed after the chapters
+     */
+    let mut context = Context::new();
+    context.insert("root", "");
+    context.insert("site_title", &site.title);
+    context.insert("chapters", &sequences.iter().map(|(title, doc_ids)| Chapter {
+        title: title.to_string(),
+        items: doc_ids.iter().map(|doc_id| item(*doc_id, 0)).collect(),
+        prev: None,
+        next: None,
+    }).collect::<Vec<_>>());
+    context.insert("documents", &site.documents.iter()
+        .filter(|document| !sequences.iter().any(|(_, doc_ids)| doc_ids.contains(&document.id)))
+        .map(|document| item(document.id, 0))
+        .collect::<Vec<_>>());
+    output.insert("index.html".to_string(), tera.render("index.html", &context)?.into_bytes());
+
+    /*
+     * tag pages
+     */
+    let mut context = Context::new();
+    context.insert("root", "../");
+    context.insert("site_title", &site.title);
+    context.insert("tags", &tags.iter().map(|(tag, documents)| TagItem { name: tag.to_string(), url: tag_path(tag), count: documents.len() }).collect::<Vec<_>>());
+    output.insert("tags/index.html".to_string(), tera.render("tags.html", &context)?.into_bytes());
+    for (tag, documents) in &tags {
+        context.insert("tag", tag);
+        context.insert("documents", &documents.iter().map(|document| item(document.id, 0)).collect::<Vec<_>>());
+        output.insert(tag_path(tag), tera.render("tag.html", &context)?.into_bytes());
+    }
+
+    /*
+     * search index
+     */
+    let index = site.documents.iter().map(|document| SearchItem {
+        title: &document.title,
+        url: &pages[&document.id],
+        tags: &document.tags,
+        excerpt: document.excerpt.as_deref(),
+        reading_time: document.reading_time,
+        text: text(&document.html),
+    }).collect::<Vec<_>>();
+    output.insert("search.json".to_string(), serde_json::to_vec(&index).map_err(|e| tera::Error::msg(e.to_string()))?);
+    output.insert("search.js".to_string(), SEARCH.as_bytes().to_vec());
+    output.insert("style.css".to_string(), STYLE.as_bytes().to_vec());
+    Ok(output)
+}
+
+#[test]
+fn site_test() {
+    let now = chrono::NaiveDate::from_ymd_opt(2023, 8, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    let document = |id: i32, title: &str, html: &str| SiteDocument {
+        id,
+        title: title.to_string(),
+        html: html.to_string(),
+        tags: vec!["ops".to_string()],
+        excerpt: None,
+        reading_time: None,
+        created_at: now,
+        updated_at: now,
+    };
+    let site = Site {
+        title: "Handbook".to_string(),
+        documents: vec![
+            document(1, "Intro", "<p><a href=\"/document/2\">next</a> <img src=\"file/abc\"></p>"),
+            document(2, "Setup", "<p>setup</p>"),
+            document(3, "Misc", "<p>misc</p>"),
+        ],
+        sequences: vec![SiteSequence { title: "Guide".to_string(), doc_ids: vec![1, 2, 9] }],
+        files: HashMap::from([("abc".to_string(), "a b.png".to_string())]),
+    };
+    let output = render(&site).unwrap();
+    let intro = String::from_utf8(output["docs/1-intro.html"].clone()).unwrap();
+    assert!(intro.contains("href=\"../docs/2-setup.html\">next"));
+    assert!(intro.contains("src=\"../files/abc/a%20b.png\""));
+    assert!(intro.contains("class=\"next\" href=\"../docs/2-setup.html\""));
+    assert!(!intro.contains("class=\"prev\""));
+    assert!(output.contains_key("tags/ops.html"));
+    let index = String::from_utf8(output["index.html"].clone()).unwrap();
+    assert!(index.contains("docs/3-misc.html"));
+}
@@ -0,0 +1,36 @@
+(function () {
+    var input = document.getElementById("search");
+    var results = document.getElementById("search-results");
+    var index = null;
+    input.addEventListener("input", function () {
+        var query = input.value.trim().toLowerCase();
+        if (index === null) {
+            fetch(input.dataset.index).then(function (res) { return res.json(); }).then(function (data) {
+                index = data;
+                input.dispatchEvent(new Event("input"));
+            });
+            return;
+        }
+        results.innerHTML = "";
+        if (query.length < 2) {
+            return;
+        }
+        index.filter(function (item) {
+            return item.title.toLowerCase().indexOf(query) >= 0
+                || item.tags.join(" ").indexOf(query) >= 0
+                || item.text.toLowerCase().indexOf(query) >= 0;
+        }).slice(0, 10).forEach(function (item) {
+            var li = document.createElement("li");
+            var a = document.createElement("a");
+            a.href = input.dataset.root + item.url;
+            a.textContent = item.title;
+            li.appendChild(a);
+            if (item.excerpt) {
+                var p = document.createElement("p");
+                p.textContent = item.excerpt + (item.reading_time ? " · " + item.reading_time + " min" : "");
+                li.appendChild(p);
+            }
+            results.appendChild(li);
+        });
+    });
+})();
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+/*
+ * document templates are markdown with a few placeholders
+ * - {{date}}               : current date(YYYY-MM-DD)
+ * - {{datetime}}           : current date and time(YYYY-MM-DD HH:MM)
+ * - {{user.nickname}}      : nickname of the instantiating user(also user.id, user.email)
+ * - {{prompt:Service name}}: value asked to the user when instantiating
+ *
+ * the placeholders are substituted as plain text, anything else(including `{{`, `{%` in code samples) is kept verbatim
+ */
+static PROMPT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*prompt:\s*([^{}]+?)\s*\}\}").unwrap()
+});
+static PLACEHOLDER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*(?:(date|datetime|user\.id|user\.nickname|user\.email)|prompt:\s*([^{}]+?))\s*\}\}").unwrap()
+});
+
+#[derive(Debug, Serialize)]
+pub struct TemplateUser {
+    pub id: i32,
+    pub nickname: String,
+    pub email: String,
+}
+
+// prompt labels in order of first appearance
+pub fn prompts(raw: &str) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for cap in PROMPT_REGEX.captures_iter(raw) {
+        let label = cap[1].to_string();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
+}
+
+pub fn render(raw: &str, user: &TemplateUser, answers: &HashMap<String, String>) -> String {
+    let now = chrono::Utc::now();
+    PLACEHOLDER_REGEX.replace_all(raw, |cap: &Captures| {
+        if let Some(label) = cap.get(2) {
+            return answers.get(label.as_str()).cloned().unwrap_or_default();
+        }
+        match &cap[1] {
+            "date" => now.format("%Y-%m-%d").to_string(),
+            "datetime" => now.format("%Y-%m-%d %H:%M").to_string(),
+            "user.id" => user.id.to_string(),
+            "user.nickname" => user.nickname.clone(),
+            _ => user.email.clone(),
+        }
+    }).to_string()
+}
+
+#[test]
+fn template_render_test() {
+    let raw = "# {{prompt:Service name}} runbook\nowner: {{user.nickname}}\n{{ prompt: Service name }} on {{date}}\n{{prompt:Pager}}";
+    assert_eq!(prompts(raw), vec!["Service name", "Pager"]);
+    let user = TemplateUser { id: 1, nickname: "kim".to_string(), email: "kim@kim.com".to_string() };
+    let answers = HashMap::from([("Service name".to_string(), "billing".to_string())]);
+    let res = render(raw, &user, &answers);
+    assert!(res.starts_with("# billing runbook\nowner: kim\nbilling on "));
+    assert!(res.ends_with("\n"));
+
+    // everything but the placeholders is kept verbatim
+    let raw = "```\n{{ get_env(name=\"ACCESS_JWT_SECRET\") }} {% if x %}{{ user.missing }}\n```\n{{user.email}}";
+    assert_eq!(render(raw, &user, &answers), "```\n{{ get_env(name=\"ACCESS_JWT_SECRET\") }} {% if x %}{{ user.missing }}\n```\nkim@kim.com");
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use super::site;
+
+/*
+ * themed page of a published document
+ *
+ * a theme is a tera template(page.html) and a stylesheet, inlined as the `css` variable.
+ * the template can include "meta.html" for the title, description, OpenGraph and Twitter tags,
+ * and gets the document as `page`(see Page).
+ */
+pub const LAYOUT: &str = include_str!("templates/page.html");
+pub const STYLE: &str = include_str!("templates/style.css");
+const META: &str = include_str!("templates/meta.html");
+
+const EXCERPT_LEN: usize = 200;
+
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\b(href|src)="([^"]*)""#).unwrap());
+static IMG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<img\b[^>]*?\bsrc="([^"]*)""#).unwrap());
+static DOCUMENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|/)document/(\d+)/?$").unwrap());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageItem {
+    pub title: String,
+    // documents without a published page are listed without a link
+    pub url: Option<String>,
+    pub current: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct PageChapter {
+    pub title: String,
+    pub items: Vec<PageItem>,
+    pub prev: Option<PageItem>,
+    pub next: Option<PageItem>,
+}
+#[derive(Debug, Serialize)]
+pub struct Page {
+    pub title: String,
+    pub scope: String,
+    pub url: String,
+    pub html: String,
+    pub excerpt: String,
+    pub image: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub chapters: Vec<PageChapter>,
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+// plain text of the html cut at a word boundary
+pub fn excerpt(html: &str) -> String {
+    let text = unescape(&site::text(html));
+    if text.chars().count() <= EXCERPT_LEN {
+        return text;
+    }
+    let cut = text.char_indices().nth(EXCERPT_LEN).map(|(idx, _)| idx).unwrap_or(text.len());
+    let cut = text[..cut].rfind(' ').unwrap_or(cut);
+    format!("{}…", text[..cut].trim_end())
+}
+
+pub fn first_image(html: &str) -> Option<String> {
+    IMG_REGEX.captures(html).map(|cap| cap[1].to_string())
+}
+
+pub fn absolute(base: &str, url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//") || url.starts_with("data:") {
+        return url.to_string();
+    }
+    format!("{}/{}", base.trim_end_matches('/'), url.trim_start_matches('/'))
+}
+
+/*
+ * attachments(file/<object_id>) become absolute links to the file route,
+ * links to other documents point at their published page when there is one
+ */
+pub fn rewrite_links(html: &str, base: &str, pages: &HashMap<i32, String>) -> String {
+    LINK_REGEX.replace_all(html, |cap: &Captures| {
+        let target = &cap[2];
+        if let Some(idx) = target.rfind("file/") {
+            if !target.contains("://") {
+                return format!("{}=\"{}\"", &cap[1], absolute(base, &target[idx..]));
+            }
+        }
+        if let Some(doc) = DOCUMENT_REGEX.captures(target) {
+            if let Some(page) = doc[1].parse::<i32>().ok().and_then(|doc_id| pages.get(&doc_id)) {
+                return format!("{}=\"{}\"", &cap[1], page);
+            }
+        }
+        cap[0].to_string()
+    }).to_string()
+}
+
+/*
+ * tera for user templates(themes, document templates)
+ *
+ * the built-in get_env would hand out the process environment(jwt secrets, database url),
+ * it is replaced by a function that always fails
+ */
+pub fn sandboxed() -> Tera {
+    let mut tera = Tera::default();
+    tera.register_function("get_env", |_: &HashMap<String, tera::Value>| -> tera::Result<tera::Value> {
+        Err(tera::Error::msg("get_env is not available in templates"))
+    });
+    tera
+}
+
+pub fn render(layout: &str, css: &str, page: &Page) -> Result<String, tera::Error> {
+    let mut tera = sandboxed();
+    tera.add_raw_templates(vec![("meta.html", META), ("page.html", layout)])?;
+    let mut context = Context::new();
+    context.insert("page", page);
+    context.insert("css", css);
+    tera.render("page.html", &context)
+}
+
+// user templates are checked against a sample page when they are saved
+pub fn validate(layout: &str, css: &str) -> Result<(), tera::Error> {
+    let item = |title: &str, current: bool| PageItem {
+        title: title.to_string(),
+        url: Some("https://example.com/document/page/sample".to_string()),
+        current,
+    };
+    let page = Page {
+        title: "Sample".to_string(),
+        scope: "Scope".to_string(),
+        url: "https://example.com/document/page/sample".to_string(),
+        html: "<p>sample</p>".to_string(),
+        excerpt: "sample".to_string(),
+        image: Some("https://example.com/file/sample".to_string()),
+        tags: vec!["sample".to_string()],
+        created_at: "2023-08-01".to_string(),
+        updated_at: "2023-08-01".to_string(),
+        chapters: vec![PageChapter {
+            title: "Sequence".to_string(),
+            items: vec![item("Sample", true), item("Next", false)],
+            prev: None,
+            next: Some(item("Next", false)),
+        }],
+    };
+    render(layout, css, &page).map(|_| ())
+}
+
+#[test]
+fn theme_render_test() {
+    let html = "<p>Fish &amp; chips <img src=\"file/abc\"> <a href=\"/document/2\">next</a> <a href=\"/document/3\">x</a></p>";
+    let pages = HashMap::from([(2, "http://localhost:8000/document/page/xyz".to_string())]);
+    let html = rewrite_links(html, "http://localhost:8000/", &pages);
+    assert!(html.contains("src=\"http://localhost:8000/file/abc\""));
+    assert!(html.contains("href=\"http://localhost:8000/document/page/xyz\""));
+    assert!(html.contains("href=\"/document/3\""));
+    assert_eq!(first_image(&html).as_deref(), Some("http://localhost:8000/file/abc"));
+    assert_eq!(excerpt(&html), "Fish & chips next x");
+    assert!(excerpt(&"word ".repeat(100)).ends_with("word…"));
+
+    let page = Page {
+        title: "A \"quoted\" title".to_string(),
+        scope: "Handbook".to_string(),
+        url: "http://localhost:8000/document/page/abc".to_string(),
+        excerpt: excerpt(&html),
+        image: first_image(&html),
+        html,
+        tags: vec!["ops".to_string()],
+        created_at: "2023-08-01".to_string(),
+        updated_at: "2023-08-01".to_string(),
+        chapters: vec![],
+    };
+    let out = render(LAYOUT, STYLE, &page).unwrap();
+    assert!(out.contains("<meta property=\"og:title\" content=\"A &quot;quoted&quot; title\">"));
+    assert!(out.contains("<meta property=\"og:image\" content=\"http://localhost:8000/file/abc\">"));
+    assert!(out.contains("summary_large_image"));
+    assert!(validate("{{ page.missing.value }}", "").is_err());
+    assert!(validate(LAYOUT, STYLE).is_ok());
+    assert!(validate(r#"{{ get_env(name="ACCESS_JWT_SECRET") }}"#, "").is_err());
+    assert!(validate(r#"{{ get_env(name="MISSING", default="x") }}"#, "").is_err());
+}
@@ -0,0 +1,260 @@
+use std::{net::{IpAddr, SocketAddr}, time::Duration};
+
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::Sha256;
+
+/*
+ * outgoing webhooks: which events can be subscribed to, how a delivery is signed and sent,
+ * and when a failed one is tried again. the queue itself is background::webhooks.
+ *
+ * the body is signed with HMAC-SHA256 under the secret of the subscription,
+ * receivers compare X-Docuvault-Signature(sha256=<hex>) with their own signature of the raw body.
+ *
+ * webhooks only reach public addresses: the url is checked when saved, and its host resolved
+ * again and checked on every delivery, the request going to the addresses that were checked.
+ */
+pub const EVENTS: [&str; 8] = [
+    "document_created",
+    "document_updated",
+    "document_deleted",
+    "document_published",
+    "document_unpublished",
+    "conversion_finished",
+    "conversion_failed",
+    "sequence_reordered",
+];
+// sent by the "send test event" action only
+pub const TEST_EVENT: &str = "ping";
+
+// attempts before a delivery goes to the dead letters
+pub const MAX_ATTEMPTS: i32 = 8;
+const BASE_DELAY_SECONDS: i64 = 30;
+const MAX_DELAY_SECONDS: i64 = 6 * 60 * 60;
+
+fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent("docuvault-webhook")
+}
+
+pub fn new_secret() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(40).map(char::from).collect()
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(body);
+    let signature = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    format!("sha256={}", signature)
+}
+
+// wait before the next attempt once `attempts` have failed: 30s, 1m, 2m, ... up to 6h
+pub fn backoff(attempts: i32) -> chrono::Duration {
+    let exp = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    chrono::Duration::seconds(BASE_DELAY_SECONDS.saturating_mul(2_i64.pow(exp)).min(MAX_DELAY_SECONDS))
+}
+
+// events of a subscription, none listed means all of them
+pub fn subscribed(events: &serde_json::Value, name: &str) -> bool {
+    match events.as_array() {
+        Some(events) => events.is_empty() || events.iter().any(|event| event.as_str() == Some(name)),
+        None => true,
+    }
+}
+
+// the body receivers get, id stays the same across retries of the event
+pub fn payload(id: &str, event: &str, created_at: chrono::NaiveDateTime, data: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "event": event,
+        "created_at": created_at,
+        "data": data,
+    })
+}
+
+// not loopback, private, link-local, shared(CGNAT), unspecified, broadcast or documentation ranges
+pub fn public_addr(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || octets[0] == 0
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+        },
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => public_addr(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                // unique local fc00::/7 and link-local fe80::/10
+                !(ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80)
+            },
+        },
+    }
+}
+
+// the literal address of the url's host, None for a domain
+fn host_addr(url: &reqwest::Url) -> Option<IpAddr> {
+    url.host_str()?.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+// names are checked again once resolved, see send
+pub fn valid_url(url: &str) -> bool {
+    let url = match reqwest::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    let host = match url.host_str() {
+        Some(host) => host,
+        None => return false,
+    };
+    let public = match host_addr(&url) {
+        Some(ip) => public_addr(ip),
+        None => host != "localhost" && !host.ends_with(".localhost"),
+    };
+    matches!(url.scheme(), "http" | "https") && public
+}
+
+// a client sending to the host of the url only at the addresses it resolves to now, all of them public
+async fn pinned_client(url: &str) -> Result<reqwest::Client, String> {
+    pin_public(client_builder(), url).await.map_err(|e| e.to_string())?.build().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PinError {
+    // the url or an address its host resolves to is not public
+    NotPublic(String),
+    // the url could not be parsed or resolved
+    Failed(String),
+}
+impl std::fmt::Display for PinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPublic(reason) | Self::Failed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+// the builder with the host of the url pinned to the addresses it resolves to now, all of them public
+pub async fn pin_public(builder: reqwest::ClientBuilder, url: &str) -> Result<reqwest::ClientBuilder, PinError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| PinError::Failed(e.to_string()))?;
+    if !valid_url(url) {
+        return Err(PinError::NotPublic("url is not allowed".to_string()));
+    }
+    let builder = match (host_addr(&parsed), parsed.host_str()) {
+        (None, Some(domain)) => {
+            let port = parsed.port_or_known_default().unwrap_or(80);
+            let addrs = tokio::net::lookup_host((domain, port)).await.map_err(|e| PinError::Failed(e.to_string()))?.collect::<Vec<SocketAddr>>();
+            if addrs.is_empty() || !addrs.iter().all(|addr| public_addr(addr.ip())) {
+                return Err(PinError::NotPublic(format!("{} does not resolve to a public address", domain)));
+            }
+            builder.resolve_to_addrs(domain, &addrs)
+        },
+        _ => builder,
+    };
+    Ok(builder)
+}
+
+// Ok with the status of a 2xx answer, otherwise Err with the status(if any answer came) and the reason
+pub async fn send(url: &str, secret: &str, delivery_id: i32, event: &str, body: &str) -> Result<u16, (Option<u16>, String)> {
+    let client = pinned_client(url).await.map_err(|e| (None, e))?;
+    send_with(&client, url, secret, delivery_id, event, body).await
+}
+
+pub async fn send_with(client: &reqwest::Client, url: &str, secret: &str, delivery_id: i32, event: &str, body: &str) -> Result<u16, (Option<u16>, String)> {
+    let res = client.post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Docuvault-Event", event)
+        .header("X-Docuvault-Delivery", delivery_id.to_string())
+        .header("X-Docuvault-Signature", sign(secret, body.as_bytes()))
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+    let status = res.status().as_u16();
+    match status {
+        200..=299 => Ok(status),
+        _ => Err((Some(status), format!("HTTP {}", status))),
+    }
+}
+
+#[test]
+fn webhook_test() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // RFC 4231 test case 2
+    assert_eq!(sign("Jefe", b"what do ya want for nothing?"), "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    assert_eq!(backoff(1).num_seconds(), 30);
+    assert_eq!(backoff(3).num_seconds(), 120);
+    assert_eq!(backoff(MAX_ATTEMPTS * 4).num_seconds(), MAX_DELAY_SECONDS);
+    assert!(valid_url("https://hooks.example.com/hook"));
+    assert!(valid_url("http://93.184.216.34:8080/hook"));
+    assert!(!valid_url("http://127.0.0.1:8080/hook"));
+    assert!(!valid_url("http://localhost/hook"));
+    assert!(!valid_url("http://10.0.0.5/hook"));
+    assert!(!valid_url("http://192.168.1.1/hook"));
+    assert!(!valid_url("http://169.254.169.254/latest/meta-data"));
+    assert!(!valid_url("http://[::1]/hook"));
+    assert!(!valid_url("http://[fd00::1]/hook"));
+    assert!(!valid_url("http://[::ffff:10.0.0.1]/hook"));
+    assert!(!valid_url("ftp://example.com/"));
+    assert!(subscribed(&serde_json::json!([]), "document_created"));
+    assert!(subscribed(&serde_json::json!(["document_created"]), "document_created"));
+    assert!(!subscribed(&serde_json::json!(["document_deleted"]), "document_created"));
+
+    // a local stub answering the first request with the given status
+    async fn stub(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head.lines()
+                        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|value| value.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            let response = format!("HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (url, handle)
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        let client = reqwest::Client::new();
+        let body = r#"{"event":"ping"}"#;
+
+        // refused before anything is sent, whatever the host resolves to
+        let (url, _handle) = stub(204).await;
+        assert!(matches!(send(&url, "secret", 6, "ping", body).await, Err((None, _))));
+
+        let (url, handle) = stub(204).await;
+        assert_eq!(send_with(&client, &url, "secret", 7, "ping", body).await, Ok(204));
+        let request = handle.await.unwrap().to_lowercase();
+        assert!(request.contains("x-docuvault-event: ping"));
+        assert!(request.contains("x-docuvault-delivery: 7"));
+        assert!(request.contains(&format!("x-docuvault-signature: {}", sign("secret", body.as_bytes()))));
+        assert!(request.ends_with(body));
+
+        let (url, handle) = stub(500).await;
+        assert_eq!(send_with(&client, &url, "secret", 8, "ping", body).await, Err((Some(500), "HTTP 500".to_string())));
+        handle.await.unwrap();
+    });
+}
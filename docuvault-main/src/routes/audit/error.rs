@@ -0,0 +1,21 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum AuditError {
+    InvalidFormat,
+}
+impl IntoResponse for AuditError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::InvalidFormat => (StatusCode::BAD_REQUEST, "format must be csv or jsonl."),
+        };
+        res.into_response()
+    }
+}
+impl From<AuditError> for GlobalError {
+    fn from(value: AuditError) -> Self {
+        Self::Audit(value)
+    }
+}
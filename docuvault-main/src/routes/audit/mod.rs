@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, body::StreamBody, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::AuditService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * reading the audit log, see modules::audit for what is recorded.
+ * entries are never changed or removed, only listed and exported.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<AuditService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(AuditService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/list", post(list))
+        .route("/export", post(export))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// newest first
+async fn list(State(state): State<ServiceState<AuditService>>, claims: Claims, Json(payload): Json<AuditListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.list(claims.user_id, payload).await?))
+}
+// everything matching the filters, newest first, streamed as it is read
+async fn export(State(state): State<ServiceState<AuditService>>, claims: Claims, Json(payload): Json<AuditExportPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let format = AuditService::format(&payload.format)?;
+    let (content_type, extension) = match format {
+        AuditExportFormat::Csv => ("text/csv; charset=utf-8", "csv"),
+        AuditExportFormat::Jsonl => ("application/x-ndjson", "jsonl"),
+    };
+    let body = StreamBody::new(state.service.export(claims.user_id, payload.filter, format));
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"audit.{}\"", extension)),
+        ],
+        body,
+    ))
+}
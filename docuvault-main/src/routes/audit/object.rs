@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+// filters shared by list and export, all of them optional
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    pub actor_id: Option<i32>,
+    // exact, or every action of a target type when it ends with a dot, e.g. "document."
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    // a document id, a link id, ...
+    pub target_id: Option<serde_json::Value>,
+    pub scope_id: Option<i32>,
+    pub since: Option<chrono::NaiveDateTime>,
+    pub until: Option<chrono::NaiveDateTime>,
+}
+
+// list
+#[derive(Debug, Deserialize)]
+pub struct AuditListPayload {
+    #[serde(flatten)]
+    pub filter: AuditFilter,
+    // id of the last entry of the previous page
+    pub before: Option<i32>,
+    pub limit: Option<u64>,
+}
+#[derive(Debug, Serialize)]
+pub struct AuditItem {
+    pub id: i32,
+    pub actor_id: Option<i32>,
+    pub ip: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_ids: serde_json::Value,
+    pub scope_ids: serde_json::Value,
+    pub summary: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::audit_log::Model> for AuditItem {
+    fn from(value: crate::entity::audit_log::Model) -> Self {
+        Self {
+            id: value.id,
+            actor_id: value.actor_id,
+            ip: value.ip,
+            action: value.action,
+            target_type: value.target_type,
+            target_ids: value.target_ids,
+            scope_ids: value.scope_ids,
+            summary: value.summary,
+            created_at: value.created_at,
+        }
+    }
+}
+
+// export
+#[derive(Debug, Deserialize)]
+pub struct AuditExportPayload {
+    #[serde(flatten)]
+    pub filter: AuditFilter,
+    // csv or jsonl
+    pub format: String,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditExportFormat {
+    Csv,
+    Jsonl,
+}
@@ -0,0 +1,129 @@
+use futures::Stream;
+use sea_orm::{entity::*, query::*, sea_query::Expr, DbErr};
+
+use crate::{AppState, entity, modules::audit, routes::error::GlobalError};
+
+use super::{object::*, error::AuditError};
+
+const LIST_LIMIT: u64 = 50;
+const MAX_LIST_LIMIT: u64 = 200;
+// entries read at once while exporting
+const EXPORT_PAGE: u64 = 500;
+
+#[derive(Clone, Debug)]
+pub struct AuditService {
+    state: AppState,
+}
+impl AuditService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    pub fn format(format: &str) -> Result<AuditExportFormat, AuditError> {
+        match format {
+            "csv" => Ok(AuditExportFormat::Csv),
+            "jsonl" => Ok(AuditExportFormat::Jsonl),
+            _ => Err(AuditError::InvalidFormat),
+        }
+    }
+
+    // admins read every entry, everyone else their own and the ones of the scopes they own
+    fn condition(docuser_id: i32, filter: &AuditFilter) -> Condition {
+        let visible = match audit::is_admin(docuser_id) {
+            true => Condition::all(),
+            false => Condition::any()
+                .add(entity::audit_log::Column::ActorId.eq(docuser_id))
+                .add(Expr::cust_with_values(
+                    r#"EXISTS (SELECT 1 FROM jsonb_array_elements_text("audit_log"."scope_ids") AS "s"("id") JOIN "scope" ON "scope"."id" = "s"."id"::int WHERE "scope"."docuser_id" = $1)"#,
+                    [docuser_id],
+                )),
+        };
+        Condition::all()
+            .add(visible)
+            .add_option(filter.actor_id.map(|actor_id| entity::audit_log::Column::ActorId.eq(actor_id)))
+            .add_option(filter.action.as_ref().map(|action| match action.ends_with('.') {
+                true => entity::audit_log::Column::Action.starts_with(action),
+                false => entity::audit_log::Column::Action.eq(action.as_str()),
+            }))
+            .add_option(filter.target_type.as_ref().map(|target_type| entity::audit_log::Column::TargetType.eq(target_type.as_str())))
+            .add_option(filter.target_id.as_ref().map(|target_id| {
+                Expr::cust_with_values(r#""audit_log"."target_ids" @> $1::jsonb"#, [serde_json::json!([target_id]).to_string()])
+            }))
+            .add_option(filter.scope_id.map(|scope_id| {
+                Expr::cust_with_values(r#""audit_log"."scope_ids" @> $1::jsonb"#, [serde_json::json!([scope_id]).to_string()])
+            }))
+            .add_option(filter.since.map(|since| entity::audit_log::Column::CreatedAt.gte(since)))
+            .add_option(filter.until.map(|until| entity::audit_log::Column::CreatedAt.lt(until)))
+    }
+
+    // newest first
+    async fn page(&self, docuser_id: i32, filter: &AuditFilter, before: Option<i32>, limit: u64) -> Result<Vec<entity::audit_log::Model>, DbErr> {
+        entity::audit_log::Entity::find()
+            .filter(Self::condition(docuser_id, filter))
+            .apply_if(before, |query, before| query.filter(entity::audit_log::Column::Id.lt(before)))
+            .order_by_desc(entity::audit_log::Column::Id)
+            .limit(limit)
+            .all(&self.state.db_conn)
+            .await
+    }
+
+    pub async fn list(&self, docuser_id: i32, payload: AuditListPayload) -> Result<Vec<AuditItem>, GlobalError> {
+        let limit = payload.limit.unwrap_or(LIST_LIMIT).min(MAX_LIST_LIMIT);
+        let entries = self.page(docuser_id, &payload.filter, payload.before, limit).await?;
+        Ok(entries.into_iter().map(|entry| entry.into()).collect())
+    }
+
+    fn line(format: AuditExportFormat, entry: entity::audit_log::Model) -> String {
+        match format {
+            AuditExportFormat::Csv => audit::csv_line(&[
+                entry.id.to_string(),
+                entry.created_at.to_string(),
+                entry.actor_id.map(|actor_id| actor_id.to_string()).unwrap_or_default(),
+                entry.ip.unwrap_or_default(),
+                entry.action,
+                entry.target_type,
+                entry.target_ids.to_string(),
+                entry.scope_ids.to_string(),
+                entry.summary.to_string(),
+            ]),
+            AuditExportFormat::Jsonl => {
+                let mut line = serde_json::to_string(&AuditItem::from(entry)).unwrap_or_default();
+                line.push('\n');
+                line
+            },
+        }
+    }
+
+    /*
+     * the whole log matching the filter, read a page at a time as the response is sent
+     * so large exports are never held in memory.
+     */
+    pub fn export(&self, docuser_id: i32, filter: AuditFilter, format: AuditExportFormat) -> impl Stream<Item = Result<String, DbErr>> + Send + 'static {
+        let header = match format {
+            AuditExportFormat::Csv => audit::csv_line(&audit::CSV_HEADER),
+            AuditExportFormat::Jsonl => String::new(),
+        };
+        let service = self.clone();
+        // Some(cursor) while there may be more, None once the last page was read
+        let pages = futures::stream::try_unfold(Some(None), move |cursor: Option<Option<i32>>| {
+            let service = service.clone();
+            let filter = filter.clone();
+            async move {
+                let before = match cursor {
+                    Some(before) => before,
+                    None => return Ok(None),
+                };
+                let entries = service.page(docuser_id, &filter, before, EXPORT_PAGE).await?;
+                let next = match entries.len() as u64 == EXPORT_PAGE {
+                    true => entries.last().map(|entry| Some(entry.id)),
+                    false => None,
+                };
+                let chunk = entries.into_iter().map(|entry| Self::line(format, entry)).collect::<String>();
+                Ok(Some((chunk, next)))
+            }
+        });
+        futures::StreamExt::chain(futures::stream::once(futures::future::ready(Ok(header))), pages)
+    }
+}
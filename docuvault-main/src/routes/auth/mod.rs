@@ -11,6 +11,8 @@ use tower_http::cors::{CorsLayer, Any};
 
 use crate::{db::schema::redis::{TokenPair, RedisSchemaHeader, Refresh, BlackList}, common::object::ServiceState};
 use crate::AppState;
+use crate::modules::redis::{redis_check_attempts, redis_add_attempt, redis_reset_attempts};
+use crate::modules::audit::Actor;
 use crate::entity;
 use crate::middleware::guard::Authenticate;
 
@@ -27,7 +29,7 @@ use self::module::password::verify_password;
 
 use super::error::GlobalError;
 
-mod module;
+pub mod module;
 
 
 pub fn create_router(shared_state: AppState) -> Router {
@@ -66,7 +68,12 @@ async fn index(State(state): State<ServiceState<AuthService>>) -> impl IntoRespo
     Html("welcome to auth index")
 }
 
-async fn register(State(state): State<ServiceState<AuthService>>, Json(payload): Json<RegisterPayload>) -> Result<impl IntoResponse, GlobalError> {
+/*
+ * sessions live in redis, logging in and out is recorded in the audit log after the fact:
+ * a failed entry is logged, the session is valid already and still handed out.
+ * registering is recorded in the transaction creating the user.
+ */
+async fn register(State(state): State<ServiceState<AuthService>>, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<RegisterPayload>) -> Result<impl IntoResponse, GlobalError> {
     if payload.email.is_empty() || payload.password.is_empty() || payload.nickname.is_empty() {
         return Err(AuthError::MissingCredential.into());
     }
@@ -82,12 +89,20 @@ async fn register(State(state): State<ServiceState<AuthService>>, Json(payload):
     
     let password_hash = module::password::create_hash(&payload.password[..].as_bytes()).map_err(|err| AuthError::from(err))?;
     let new_user = entity::docuser::ActiveModel {
-        email: Set(payload.email), 
-        nickname: Set(payload.nickname),
+        email: Set(payload.email.clone()), 
+        nickname: Set(payload.nickname.clone()),
         hash: Set(password_hash),
         ..Default::default()
     };
-    let insert_result = entity::docuser::Entity::insert(new_user).exec(&state.global_state.db_conn).await?;
+    let txn = state.global_state.db_conn.begin().await?;
+    let insert_result = entity::docuser::Entity::insert(new_user).exec(&txn).await?;
+    Actor::new(insert_result.last_insert_id, addr)
+        .entry("user.register")
+        .targets([insert_result.last_insert_id])
+        .summary(json!({ "email": payload.email, "nickname": payload.nickname }))
+        .record(&txn)
+        .await?;
+    txn.commit().await?;
     Ok(())
 
 }
@@ -100,26 +115,52 @@ async fn issue(State(state): State<ServiceState<AuthService>>, ConnectInfo(addr)
         return Err(AuthError::InvalidCredential.into());  
     }
     
+    let attempt_key = format!("login:{}:{}", addr.ip(), payload.email);
+    redis_check_attempts(state.global_state.clone(), &attempt_key).await?;
+
     let qr  = match state.service.find_user(&payload.email).await? {
         Some(qr) => qr,
-        None => return Err(AuthError::InvalidCredential.into()),
+        None => {
+            redis_add_attempt(state.global_state.clone(), &attempt_key).await?;
+            return Err(AuthError::InvalidCredential.into());
+        },
     };
     
-    verify_password(&qr.hash, &payload.password[..].as_bytes()).map_err(|err| AuthError::from(err))?;
+    if let Err(err) = verify_password(&qr.hash, &payload.password[..].as_bytes()) {
+        redis_add_attempt(state.global_state.clone(), &attempt_key).await?;
+        return Err(AuthError::from(err).into());
+    }
+    redis_reset_attempts(state.global_state.clone(), &attempt_key).await?;
 
     let access_token = state.service.issue_access_token(qr.id).await?;
     let refresh_token = state.service.issue_refresh_token(qr.id).await?;
     state.service.set_tokenpair(&access_token, &refresh_token).await?;
     state.service.set_refresh(&refresh_token, addr.to_string()).await?;
+    let res = Actor::new(qr.id, addr)
+        .entry("user.login")
+        .targets([qr.id])
+        .record(&state.global_state.db_conn)
+        .await;
+    if let Err(e) = res {
+        dbg!(e);
+    }
     
     Ok(Json(IssueResponse{
         access_token,
         refresh_token,
     }))
 }
-async fn disconnect(State(state): State<ServiceState<AuthService>>, TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+async fn disconnect(State(state): State<ServiceState<AuthService>>, ConnectInfo(addr): ConnectInfo<SocketAddr>, TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
     state.service.set_redis_blacklist(bearer.token(), claims.exp as usize);
     state.service.disable_auth(bearer.token());
+    let res = Actor::new(claims.user_id, addr)
+        .entry("user.logout")
+        .targets([claims.user_id])
+        .record(&state.global_state.db_conn)
+        .await;
+    if let Err(e) = res {
+        dbg!(e);
+    }
     Ok(()) 
 }
 
@@ -154,6 +195,14 @@ async fn refresh(State(state): State<ServiceState<AuthService>>, ConnectInfo(add
 
     // needs refactoring / same as length of access_token
     state.service.set_tokenpair(&access_token, bearer.token()).await?;
+    let res = Actor::new(token_data.claims.user_id, addr)
+        .entry("user.refresh")
+        .targets([token_data.claims.user_id])
+        .record(&state.global_state.db_conn)
+        .await;
+    if let Err(e) = res {
+        dbg!(e);
+    }
 
     Ok(Json(RefreshResponse{
         access_token,
@@ -88,7 +88,13 @@ impl<S> FromRequestParts<S> for Claims
             .await
             .map_err(|_| AuthError::TokenMissing)?;
 
-        let token_data = decode::<Claims>(bearer.token(), &ACCESS_KEYS.decoding, &Validation::default())
+        Claims::verify(bearer.token(), &DatabaseConnection::from_ref(state), Pool::<RedisConnectionManager>::from_ref(state)).await
+    }
+}
+impl Claims {
+    // websocket upgrades from the browser can not set an Authorization header, they hand the token over separately
+    pub async fn verify(token: &str, db_conn: &DatabaseConnection, redis_conn: Pool<RedisConnectionManager>) -> Result<Claims, AuthError> {
+        let token_data = decode::<Claims>(token, &ACCESS_KEYS.decoding, &Validation::default())
             .map_err(|err| {
                 if err.into_kind() == ErrorKind::ExpiredSignature {
                     AuthError::TokenExpired
@@ -99,9 +105,9 @@ impl<S> FromRequestParts<S> for Claims
             })?;
 
         let header = RedisSchemaHeader {
-            key: bearer.token().to_string(),
+            key: token.to_string(),
             expire_at: None,
-            con: Pool::<RedisConnectionManager>::from_ref(state),
+            con: redis_conn,
         };
         let mut schema = BlackList::new(header);
         schema.get_status().await;
@@ -112,7 +118,7 @@ impl<S> FromRequestParts<S> for Claims
 
         let qr = entity::docuser::Entity::find()
             .filter(entity::docuser::Column::Id.eq(token_data.claims.user_id.clone()))
-            .one(&DatabaseConnection::from_ref(state))
+            .one(db_conn)
             .await?;
         if qr.is_none() {
             return Err(AuthError::InvalidCredential);
@@ -0,0 +1,31 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum CollabError {
+    DocumentNotExist,
+    MissingToken,
+    EditedElsewhere,
+    SessionClosed,
+    InvalidOperation,
+    Conflict,
+}
+impl IntoResponse for CollabError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::DocumentNotExist => (StatusCode::BAD_REQUEST, "document does not exist."),
+            Self::MissingToken => (StatusCode::UNAUTHORIZED, "access token is missing."),
+            Self::EditedElsewhere => (StatusCode::CONFLICT, "document is being edited on another server, try again later."),
+            Self::SessionClosed => (StatusCode::CONFLICT, "session has ended, reconnect."),
+            Self::InvalidOperation => (StatusCode::BAD_REQUEST, "operation does not belong to the peer."),
+            Self::Conflict => (StatusCode::CONFLICT, "document was saved outside the session."),
+        };
+        res.into_response()
+    }
+}
+impl From<CollabError> for GlobalError {
+    fn from(value: CollabError) -> Self {
+        Self::Collab(value)
+    }
+}
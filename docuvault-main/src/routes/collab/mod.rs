@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get, extract::{State, Path, ws::WebSocketUpgrade}, http::{header, HeaderMap}, response::Response};
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::CollabService;
+use error::CollabError;
+
+use super::error::GlobalError;
+
+/*
+ * collaborative editing over a websocket, messages are the json of modules::collab::{ClientMessage, ServerMessage}.
+ * the connection starts with an init message carrying the document state, then relays
+ * operations, presence and cursors of the other peers.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<CollabService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(CollabService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/:doc_id", get(connect))
+        .with_state(service_state)
+}
+async fn connect(State(state): State<ServiceState<CollabService>>, Path(doc_id): Path<i32>, headers: HeaderMap, ws: WebSocketUpgrade) -> Result<Response, GlobalError> {
+    let token = headers.get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(protocol_token)
+        .ok_or(CollabError::MissingToken)?;
+    let (user_id, document, nickname) = state.service.authorize(token, doc_id).await?;
+    Ok(ws.protocols([TOKEN_PROTOCOL]).on_upgrade(move |socket| async move {
+        if let Err(e) = state.service.run(socket, user_id, document, nickname).await {
+            dbg!(e);
+        }
+    }))
+}
@@ -0,0 +1,13 @@
+/*
+ * browsers can not set headers on a websocket upgrade, the access token comes as a subprotocol
+ * instead of in the url(which ends up in the request logs): new WebSocket(url, ["access_token", token]).
+ * the upgrade answers with the access_token protocol.
+ */
+pub const TOKEN_PROTOCOL: &str = "access_token";
+
+// the token following TOKEN_PROTOCOL in Sec-WebSocket-Protocol
+pub fn protocol_token(protocols: &str) -> Option<&str> {
+    let mut protocols = protocols.split(',').map(str::trim);
+    protocols.find(|protocol| *protocol == TOKEN_PROTOCOL)?;
+    protocols.next().filter(|token| !token.is_empty())
+}
@@ -0,0 +1,81 @@
+use axum::extract::ws::{Message, WebSocket};
+use futures::{SinkExt, StreamExt};
+use sea_orm::{entity::*, query::*};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{AppState, entity, modules::{collab::{self, ClientMessage, ServerMessage, Event, Joined}, role::{self, Role}}, routes::{error::GlobalError, auth::object::Claims, document::object::DocumentStatus}};
+
+use super::error::CollabError;
+
+#[derive(Clone, Debug)]
+pub struct CollabService {
+    state: AppState,
+}
+impl CollabService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    // documents are edited together by their author and the editors of their scopes
+    pub async fn authorize(&self, token: &str, doc_id: i32) -> Result<(i32, entity::docorg::Model, String), GlobalError> {
+        let claims = Claims::verify(token, &self.state.db_conn, self.state.redis_conn.clone()).await?;
+        let document = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(CollabError::DocumentNotExist)?;
+        if !role::document_has_role(&self.state.db_conn, claims.user_id, &document, Role::Editor).await? {
+            return Err(CollabError::DocumentNotExist.into());
+        }
+        let docuser = entity::docuser::Entity::find_by_id(claims.user_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(GlobalError::NoPermission)?;
+        Ok((claims.user_id, document, docuser.nickname))
+    }
+
+    pub async fn run(&self, socket: WebSocket, user_id: i32, document: entity::docorg::Model, nickname: String) -> Result<(), GlobalError> {
+        let Joined { session, site, mut rx, init } = collab::open(&self.state, &document, user_id, nickname).await?;
+        let (mut sender, mut receiver) = socket.split();
+
+        let mut outgoing = Some(init);
+        let res = loop {
+            if let Some(message) = outgoing.take() {
+                let text = serde_json::to_string(&message).unwrap_or_default();
+                if sender.send(Message::Text(text)).await.is_err() {
+                    break Ok(());
+                }
+            }
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) if event.from == site => {},
+                    Ok(Event { message: ServerMessage::Closed, .. }) => {
+                        let text = serde_json::to_string(&ServerMessage::Closed).unwrap_or_default();
+                        let _ = sender.send(Message::Text(text)).await;
+                        break Ok(());
+                    },
+                    Ok(event) => outgoing = Some(event.message),
+                    // fell behind the session, the client reconnects and starts over from a fresh init
+                    Err(RecvError::Lagged(_)) | Err(RecvError::Closed) => break Ok(()),
+                },
+                message = receiver.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let message = match serde_json::from_str::<ClientMessage>(&text) {
+                            Ok(message) => message,
+                            Err(_) => continue,
+                        };
+                        if let Err(e) = collab::receive(&self.state, &session, site, message).await {
+                            break Err(e);
+                        }
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break Ok(()),
+                    Some(Ok(_)) => {},
+                },
+            }
+        };
+        collab::leave(&self.state, &session, site).await?;
+        res
+    }
+}
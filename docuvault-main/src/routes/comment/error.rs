@@ -0,0 +1,29 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum CommentError {
+    ThreadNotExist,
+    CommentNotExist,
+    EmptyBody,
+    AnchorNotFound,
+    NotAuthor,
+}
+impl IntoResponse for CommentError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::ThreadNotExist => (StatusCode::BAD_REQUEST, "target thread not exists."),
+            Self::CommentNotExist => (StatusCode::BAD_REQUEST, "target comment not exists."),
+            Self::EmptyBody => (StatusCode::BAD_REQUEST, "comment body must not be empty."),
+            Self::AnchorNotFound => (StatusCode::BAD_REQUEST, "quoted text does not exist in the document."),
+            Self::NotAuthor => (StatusCode::FORBIDDEN, "only the author can modify this comment."),
+        };
+        res.into_response()
+    }
+}
+impl From<CommentError> for GlobalError {
+    fn from(value: CommentError) -> Self {
+        Self::Comment(value)
+    }
+}
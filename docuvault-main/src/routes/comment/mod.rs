@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::CommentService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<CommentService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(CommentService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/thread/create", post(thread::create))
+        .route("/thread/list", post(thread::list))
+        .route("/thread/resolve", post(thread::resolve))
+        .route("/thread/reopen", post(thread::reopen))
+        .route("/reply", post(reply))
+        .route("/edit", post(edit))
+        .route("/delete", post(delete))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+mod thread {
+    use super::*;
+
+    pub async fn create(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<ThreadCreatePayload>) -> Result<impl IntoResponse, GlobalError> {
+        let thread_id = state.service.create_thread(claims.user_id, payload).await?;
+        Ok(Json(ThreadCreateResponse { thread_id }))
+    }
+    pub async fn list(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<ThreadListPayload>) -> Result<impl IntoResponse, GlobalError> {
+        let res = state.service.list_threads(claims.user_id, payload.doc_id).await?;
+        Ok(Json(res))
+    }
+    pub async fn resolve(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<ThreadStatusPayload>) -> Result<impl IntoResponse, GlobalError> {
+        state.service.set_status(claims.user_id, payload.thread_id, ThreadStatus::RESOLVED).await?;
+        Ok(())
+    }
+    pub async fn reopen(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<ThreadStatusPayload>) -> Result<impl IntoResponse, GlobalError> {
+        state.service.set_status(claims.user_id, payload.thread_id, ThreadStatus::OPEN).await?;
+        Ok(())
+    }
+}
+async fn reply(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<ReplyPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let comment_id = state.service.reply(claims.user_id, payload).await?;
+    Ok(Json(ReplyResponse { comment_id }))
+}
+async fn edit(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<EditPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.edit(claims.user_id, payload).await?;
+    Ok(())
+}
+async fn delete(State(state): State<ServiceState<CommentService>>, claims: Claims, Json(payload): Json<DeletePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.comment_id).await?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+pub enum ThreadStatus {
+    OPEN = 0,
+    RESOLVED = 1,
+}
+
+// thread/create
+#[derive(Debug, Deserialize)]
+pub struct ThreadCreatePayload {
+    pub doc_id: i32,
+    // omit both to comment on the whole document
+    pub heading_path: Option<Vec<String>>,
+    pub quote: Option<String>,
+    pub body: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ThreadCreateResponse {
+    pub thread_id: i32,
+}
+
+// thread/list
+#[derive(Debug, Deserialize)]
+pub struct ThreadListPayload {
+    pub doc_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct CommentItem {
+    pub id: i32,
+    pub docuser_id: i32,
+    pub body: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Serialize)]
+pub struct ThreadItem {
+    pub id: i32,
+    pub docuser_id: i32,
+    pub heading_path: Option<Vec<String>>,
+    pub quote: Option<String>,
+    pub status: i32,
+    pub outdated: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub comments: Vec<CommentItem>,
+}
+
+// thread/resolve, thread/reopen
+#[derive(Debug, Deserialize)]
+pub struct ThreadStatusPayload {
+    pub thread_id: i32,
+}
+
+// reply
+#[derive(Debug, Deserialize)]
+pub struct ReplyPayload {
+    pub thread_id: i32,
+    pub body: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ReplyResponse {
+    pub comment_id: i32,
+}
+
+// edit
+#[derive(Debug, Deserialize)]
+pub struct EditPayload {
+    pub comment_id: i32,
+    pub body: String,
+}
+
+// delete
+#[derive(Debug, Deserialize)]
+pub struct DeletePayload {
+    pub comment_id: i32,
+}
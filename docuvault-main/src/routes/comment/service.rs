@@ -0,0 +1,216 @@
+use sea_orm::{entity::*, query::*, ConnectionTrait, DbErr};
+
+use crate::{AppState, entity, modules::{role::{self, Role}, markdown::anchor}, routes::{error::GlobalError, document::{error::DocumentError, object::DocumentStatus}}};
+
+use super::{object::*, error::CommentError};
+
+#[derive(Clone, Debug)]
+pub struct CommentService {
+    state: AppState,
+}
+impl CommentService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state,
+        }
+    }
+
+    /*
+     * the author of the document takes part in its review, as do the users with the role
+     * in one of the scopes the document is connected to(viewers read the threads, commenters write them).
+     */
+    pub async fn check_access(&self, docuser_id: i32, doc_id: i32, role: Role) -> Result<entity::docorg::Model, GlobalError> {
+        let document = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&self.state.db_conn)
+            .await?;
+        let document = match document {
+            Some(document) => document,
+            None => return Err(DocumentError::DocumentNotExist.into()),
+        };
+        if !role::document_has_role(&self.state.db_conn, docuser_id, &document, role).await? {
+            return Err(GlobalError::NoPermission);
+        }
+        Ok(document)
+    }
+
+    async fn get_thread(&self, thread_id: i32) -> Result<entity::comment_thread::Model, GlobalError> {
+        match entity::comment_thread::Entity::find_by_id(thread_id).one(&self.state.db_conn).await? {
+            Some(thread) => Ok(thread),
+            None => Err(CommentError::ThreadNotExist.into()),
+        }
+    }
+
+    async fn get_own_comment(&self, docuser_id: i32, comment_id: i32) -> Result<entity::comment::Model, GlobalError> {
+        let comment = match entity::comment::Entity::find_by_id(comment_id).one(&self.state.db_conn).await? {
+            Some(comment) => comment,
+            None => return Err(CommentError::CommentNotExist.into()),
+        };
+        if comment.docuser_id != docuser_id {
+            return Err(CommentError::NotAuthor.into());
+        }
+        Ok(comment)
+    }
+
+    pub async fn create_thread(&self, docuser_id: i32, payload: ThreadCreatePayload) -> Result<i32, GlobalError> {
+        if payload.body.trim().is_empty() {
+            return Err(CommentError::EmptyBody.into());
+        }
+        let document = self.check_access(docuser_id, payload.doc_id, Role::Commenter).await?;
+
+        let quote = payload.quote.filter(|quote| !quote.trim().is_empty());
+        if let Some(quote) = &quote {
+            let path = payload.heading_path.clone().unwrap_or_default();
+            if !anchor::locate(&document.raw, &path[..], quote) {
+                return Err(CommentError::AnchorNotFound.into());
+            }
+        }
+
+        let thread_id = self.state.db_conn.transaction::<_, i32, GlobalError>(|txn| {
+            Box::pin(async move {
+                let thread = entity::comment_thread::ActiveModel {
+                    docorg_id: Set(payload.doc_id),
+                    docuser_id: Set(docuser_id),
+                    heading_path: Set(payload.heading_path.map(|path| serde_json::json!(path))),
+                    quote: Set(quote),
+                    status: Set(ThreadStatus::OPEN as i32),
+                    outdated: Set(false),
+                    ..Default::default()
+                };
+                let thread_id = entity::comment_thread::Entity::insert(thread).exec(txn).await?.last_insert_id;
+
+                let comment = entity::comment::ActiveModel {
+                    thread_id: Set(thread_id),
+                    docuser_id: Set(docuser_id),
+                    body: Set(payload.body),
+                    ..Default::default()
+                };
+                entity::comment::Entity::insert(comment).exec(txn).await?;
+                Ok(thread_id)
+            })
+        }).await?;
+        Ok(thread_id)
+    }
+
+    pub async fn list_threads(&self, docuser_id: i32, doc_id: i32) -> Result<Vec<ThreadItem>, GlobalError> {
+        self.check_access(docuser_id, doc_id, Role::Viewer).await?;
+
+        let res = entity::comment_thread::Entity::find()
+            .filter(entity::comment_thread::Column::DocorgId.eq(doc_id))
+            .order_by_asc(entity::comment_thread::Column::CreatedAt)
+            .find_with_related(entity::comment::Entity)
+            .all(&self.state.db_conn)
+            .await?;
+
+        Ok(res.into_iter().map(|(thread, mut comments)| {
+            comments.sort_by_key(|comment| (comment.created_at, comment.id));
+            ThreadItem {
+                id: thread.id,
+                docuser_id: thread.docuser_id,
+                heading_path: thread.heading_path.and_then(|path| serde_json::from_value(path).ok()),
+                quote: thread.quote,
+                status: thread.status,
+                outdated: thread.outdated,
+                created_at: thread.created_at,
+                updated_at: thread.updated_at,
+                comments: comments.into_iter().map(|comment| CommentItem {
+                    id: comment.id,
+                    docuser_id: comment.docuser_id,
+                    body: comment.body,
+                    created_at: comment.created_at,
+                    updated_at: comment.updated_at,
+                }).collect(),
+            }
+        }).collect())
+    }
+
+    pub async fn reply(&self, docuser_id: i32, payload: ReplyPayload) -> Result<i32, GlobalError> {
+        if payload.body.trim().is_empty() {
+            return Err(CommentError::EmptyBody.into());
+        }
+        let thread = self.get_thread(payload.thread_id).await?;
+        self.check_access(docuser_id, thread.docorg_id, Role::Commenter).await?;
+
+        let comment = entity::comment::ActiveModel {
+            thread_id: Set(thread.id),
+            docuser_id: Set(docuser_id),
+            body: Set(payload.body),
+            ..Default::default()
+        };
+        let res = entity::comment::Entity::insert(comment).exec(&self.state.db_conn).await?;
+
+        let mut thread: entity::comment_thread::ActiveModel = thread.into();
+        thread.updated_at = Set(chrono::Utc::now().naive_utc());
+        thread.update(&self.state.db_conn).await?;
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn set_status(&self, docuser_id: i32, thread_id: i32, status: ThreadStatus) -> Result<(), GlobalError> {
+        let thread = self.get_thread(thread_id).await?;
+        self.check_access(docuser_id, thread.docorg_id, Role::Commenter).await?;
+
+        let mut thread: entity::comment_thread::ActiveModel = thread.into();
+        thread.status = Set(status as i32);
+        thread.updated_at = Set(chrono::Utc::now().naive_utc());
+        thread.update(&self.state.db_conn).await?;
+        Ok(())
+    }
+
+    pub async fn edit(&self, docuser_id: i32, payload: EditPayload) -> Result<(), GlobalError> {
+        if payload.body.trim().is_empty() {
+            return Err(CommentError::EmptyBody.into());
+        }
+        let comment = self.get_own_comment(docuser_id, payload.comment_id).await?;
+
+        let mut comment: entity::comment::ActiveModel = comment.into();
+        comment.body = Set(payload.body);
+        comment.updated_at = Set(chrono::Utc::now().naive_utc());
+        comment.update(&self.state.db_conn).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, docuser_id: i32, comment_id: i32) -> Result<(), GlobalError> {
+        let comment = self.get_own_comment(docuser_id, comment_id).await?;
+
+        self.state.db_conn.transaction::<_, (), GlobalError>(|txn| {
+            Box::pin(async move {
+                entity::comment::Entity::delete_by_id(comment.id).exec(txn).await?;
+
+                // a thread without any comment is removed with its last comment
+                let left = entity::comment::Entity::find()
+                    .filter(entity::comment::Column::ThreadId.eq(comment.thread_id))
+                    .count(txn)
+                    .await?;
+                if left == 0 {
+                    entity::comment_thread::Entity::delete_by_id(comment.thread_id).exec(txn).await?;
+                }
+                Ok(())
+            })
+        }).await?;
+        Ok(())
+    }
+
+    /*
+     * called whenever the raw of a document changes.
+     * threads keep their anchor while the quoted text still exists, otherwise they are
+     * marked outdated(and become current again if the text comes back).
+     */
+    pub async fn refresh_anchors<C: ConnectionTrait>(conn: &C, doc_id: i32, raw: &str) -> Result<(), DbErr> {
+        let threads = entity::comment_thread::Entity::find()
+            .filter(entity::comment_thread::Column::DocorgId.eq(doc_id))
+            .filter(entity::comment_thread::Column::Quote.is_not_null())
+            .all(conn)
+            .await?;
+
+        for thread in threads {
+            let path: Vec<String> = thread.heading_path.clone().and_then(|path| serde_json::from_value(path).ok()).unwrap_or_default();
+            let outdated = !anchor::locate(raw, &path[..], thread.quote.as_deref().unwrap_or(""));
+            if outdated != thread.outdated {
+                let mut thread: entity::comment_thread::ActiveModel = thread.into();
+                thread.outdated = Set(outdated);
+                thread.update(conn).await?;
+            }
+        }
+        Ok(())
+    }
+}
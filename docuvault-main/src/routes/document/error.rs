@@ -6,9 +6,6 @@ use crate::routes::error::GlobalError;
 pub enum DocumentError {
     ScopeNotExist,
     DocumentNotExist,
-    PublishTokenMissing,
-    PublishTokenExpired,
-    InvalidPublishToken,
     JwtCreationError,
     PrivateDocument,
     DocumentNotConverted, 
@@ -16,15 +13,22 @@ pub enum DocumentError {
     ConvertFailed,
     NoMatchingConvertType,
     ConvertExists,
+    InvalidFrontMatter,
+    ArchiveMissing,
+    InvalidArchive,
+    ArchiveTooLarge,
+    InvalidImportOptions,
+    NotUtf8Document,
+    FileMissing,
+    UnsupportedImportFormat,
+    ImportFailed,
+    FormatNotAcceptable,
 }
 impl IntoResponse for DocumentError {
     fn into_response(self) -> axum::response::Response {
         let res = match self {
             Self::ScopeNotExist => (StatusCode::BAD_REQUEST, "specified scope does not exists."),
             Self::DocumentNotExist => (StatusCode::BAD_REQUEST, "target document not exists."),
-            Self::PublishTokenMissing => (StatusCode::UNAUTHORIZED, "publish token is missing."),
-            Self::PublishTokenExpired => (StatusCode::UNAUTHORIZED, "publish token is expired."),
-            Self::InvalidPublishToken => (StatusCode::UNAUTHORIZED, "invalid publish token."),
             Self::JwtCreationError => (StatusCode::INTERNAL_SERVER_ERROR, "jwt creation failed."),
             Self::PrivateDocument => (StatusCode::BAD_REQUEST, "private document"),
             Self::DocumentNotConverted => (StatusCode::NO_CONTENT, "target type is not converted"),
@@ -32,6 +36,16 @@ impl IntoResponse for DocumentError {
             Self::ConvertFailed => (StatusCode::BAD_REQUEST, "target content type conversion failed"),
             Self::NoMatchingConvertType => (StatusCode::BAD_REQUEST, "target content type does not supported"),
             Self::ConvertExists => (StatusCode::BAD_REQUEST, "target convert exists"),
+            Self::InvalidFrontMatter => (StatusCode::BAD_REQUEST, "front matter is not a valid yaml mapping"),
+            Self::ArchiveMissing => (StatusCode::BAD_REQUEST, "archive field is missing"),
+            Self::InvalidArchive => (StatusCode::BAD_REQUEST, "archive is not a valid zip file"),
+            Self::ArchiveTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "archive or one of its files is too large once uncompressed"),
+            Self::InvalidImportOptions => (StatusCode::BAD_REQUEST, "invalid import options"),
+            Self::NotUtf8Document => (StatusCode::BAD_REQUEST, "markdown file is not utf-8 encoded"),
+            Self::FileMissing => (StatusCode::BAD_REQUEST, "file field is missing"),
+            Self::UnsupportedImportFormat => (StatusCode::BAD_REQUEST, "file must be docx, html, odt, rst or latex"),
+            Self::ImportFailed => (StatusCode::BAD_REQUEST, "file could not be converted to markdown"),
+            Self::FormatNotAcceptable => (StatusCode::NOT_ACCEPTABLE, "requested format is not served by this link"),
         };
         res.into_response()
     }
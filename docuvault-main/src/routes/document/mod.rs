@@ -1,18 +1,20 @@
 use std::borrow::BorrowMut;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env;
+use std::net::SocketAddr;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 
-use axum::extract::Path;
-use axum::http::{Method, header, HeaderValue};
-use axum::response::Html;
+use axum::body::StreamBody;
+use axum::extract::{Path, Multipart, DefaultBodyLimit, Query, ConnectInfo};
+use axum::http::{Method, header, HeaderValue, HeaderMap};
+use axum::response::{Html, Response};
 use axum::routing::{get, options};
 use axum::{Router, extract::State, Json, response::IntoResponse, routing::post, middleware::from_extractor_with_state};
 use comrak::ComrakOptions;
-use jsonwebtoken::{encode, Header};
+use futures::StreamExt;
 use redis::AsyncCommands;
 use regex::Regex;
 use sea_orm::{entity::*, query::*, FromQueryResult};
@@ -20,7 +22,7 @@ use serde::Serialize;
 use tonic::Request;
 use tower_http::cors::{CorsLayer, Any};
 use crate::common::object::ServiceState;
-use crate::modules::background::conversion::{self, convert_to_html, extension};
+use crate::modules::background::conversion::{self, convert_to_html, extension, mime};
 use crate::modules::background::sanitize::sanitize;
 use crate::modules::grpc::convert::ConvertRequest;
 use crate::modules::grpc::convert::convert_client::ConvertClient;
@@ -28,7 +30,11 @@ use crate::modules::grpc::delete::DeleteRequest;
 use crate::modules::grpc::delete::delete_client::DeleteClient;
 use crate::modules::grpc::upload::UploadRequest;
 use crate::modules::grpc::upload::upload_client::UploadClient;
-use crate::modules::markdown::get_title;
+use crate::modules::markdown::{get_title, front_matter, outline};
+use crate::modules::archive;
+use crate::modules::audit::{self, Actor};
+use crate::modules::events::{self, ServerEvent};
+use crate::modules::negotiate::negotiate;
 use crate::modules::redis::redis_does_docuser_have_scope;
 use crate::modules::tag::application::port::input::TagSetUseCase;
 use crate::modules::tag::domain::entity::tag::Tag;
@@ -38,9 +44,9 @@ use crate::{AppState, entity};
 
 pub mod error;
 use error::*;
-mod object;
+pub mod object;
 use object::*;
-mod service;
+pub mod service;
 
 
 use self::service::DocumentService;
@@ -49,6 +55,8 @@ use super::error::GlobalError;
 use super::auth::object::Claims;
 use super::auth::object::Claims as Authenticate;
 use super::resource::error::ResourceError;
+use super::comment::service::CommentService;
+use super::share::object::{ShareCreatePayload, find_unlock_token};
 
 pub fn create_router(shared_state: AppState) -> Router {
     let service_state: ServiceState<DocumentService> = ServiceState {
@@ -60,12 +68,19 @@ pub fn create_router(shared_state: AppState) -> Router {
         .route("/pre_create", post(pre_create))
         .route("/pending_create", post(pending_create))
         .route("/create", post(create))
+        .route("/import", post(import).layer(DefaultBodyLimit::max(1024*1024*1024)))
+        .route("/import_file", post(import_file).layer(DefaultBodyLimit::max(1024*1024*1024)))
         .route("/convert", post(convert))
         .route("/get_update_resource/:doc_id", post(get_update_resource))
+        .route("/outline/:doc_id", post(get_outline))
         .route("/delete", post(delete))
+        .route("/bulk", post(bulk))
+        .route("/duplicate", post(duplicate))
         .route("/update", post(update))
         .route("/publish", post(publish))
         .route("/", post(get_document))
+        .route("/:publish_token", get(get_published))
+        .route("/page/:publish_token", get(get_page))
         .layer(
             CorsLayer::new()
                 .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
@@ -79,21 +94,31 @@ async fn test(State(state): State<ServiceState<DocumentService>>) -> Result<impl
     //let res = state.service.test_tag().await?;
     Ok(())
 }
-async fn pre_create(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<PendingCreatePayload>) -> Result<impl IntoResponse, GlobalError>{
-    let res = state.service.create_or_get_pending_document(claims.user_id, payload).await?; 
+async fn pre_create(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<PendingCreatePayload>) -> Result<impl IntoResponse, GlobalError>{
+    let res = state.service.create_or_get_pending_document(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?; 
     Ok(Json(res))
 }
-async fn pending_create(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<PendingCreatePayload>) -> Result<impl IntoResponse, GlobalError>{
-    let res = state.service.overwrite_pending_document(claims.user_id, payload).await?;
+async fn pending_create(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<PendingCreatePayload>) -> Result<impl IntoResponse, GlobalError>{
+    let res = state.service.overwrite_pending_document(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
     Ok(Json(res))
 }
-async fn create(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<CreatePayload>) -> Result<impl IntoResponse, GlobalError>{
+async fn create(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(mut payload): Json<CreatePayload>) -> Result<impl IntoResponse, GlobalError>{
+
+    /*
+     * merge front matter into payload
+     */
+    let resolved = state.service.apply_front_matter(&payload.raw, &mut payload.tags, &mut payload.scope_ids, &mut payload.seq_id)?;
 
     /*
      * check user has scope
      */
     state.service.check_user_has_scope(claims.user_id, &payload.scope_ids[..]).await?;
 
+    /*
+     * lint, errors reject the document
+     */
+    let warnings = state.service.lint(claims.user_id, &payload.raw, &payload.scope_ids[..]).await?;
+
     /*
      * insert new document(docorg)
      */
@@ -105,8 +130,17 @@ async fn create(State(state): State<ServiceState<DocumentService>>, claims: Clai
     let mut convertres = Arc::new(Mutex::new(None));
     let cloned_convertres = convertres.clone();
     let cloned_payload = payload.clone();
+    let entry = Actor::new(claims.user_id, addr)
+        .entry("document.create")
+        .scopes(payload.scope_ids.clone())
+        .summary(serde_json::json!({
+            "title": resolved.title,
+            "tags": payload.tags,
+            "seq_id": payload.seq_id,
+            "length": payload.raw.len(),
+        }));
     
-    state.global_state.db_conn.clone().transaction::<_, (), GlobalError>(|txn|{
+    let created = state.global_state.db_conn.clone().transaction::<_, events::Recorded, GlobalError>(|txn|{
         Box::pin(async move {
             
             let state = cloned_state;
@@ -116,7 +150,7 @@ async fn create(State(state): State<ServiceState<DocumentService>>, claims: Clai
              * create document
              */
 
-            let document_id = state.service.complete_pending_document(claims.user_id, &payload.raw).await?;
+            let document_id = state.service.complete_pending_document(claims.user_id, &payload.raw, &resolved).await?;
             *cloned_docres.lock().await = Some(document_id);
 
     
@@ -149,32 +183,7 @@ async fn create(State(state): State<ServiceState<DocumentService>>, claims: Clai
              * connect to tags
              */
 
-            if payload.tags.len()>0 {
-                let tags: TagSet = state.global_state.modules.tag.service.get(txn).await?;
-                let document_tags = payload.tags.into_iter().map(|tag|tag.trim().to_lowercase().to_string()).collect::<std::collections::BTreeSet<_>>();
-
-                let new_tags = document_tags.iter().filter(|tag| !tags.tags.contains(&Tag::new(tag.to_string()))).map(|tag| (0, tag.clone())).collect::<Vec<_>>();
-
-
-                for (_, tag) in &new_tags {
-                    state.global_state.modules.tag.service.add(txn, tag.clone()).await?;
-                }
-
-                let mut cond = Condition::any();
-                for tag in &document_tags {
-                    cond = cond.add(entity::tag::Column::Value.eq(tag.clone()));
-                }
-                let res = entity::tag::Entity::find().filter(cond).all(txn).await?;
-
-                let models = res.iter().map(|m|{
-                    entity::docorg_tag::ActiveModel {
-                        docorg_id: Set(document_id),
-                        tag_id: Set(m.id),
-                        ..Default::default()
-                    }
-                }).collect::<Vec<_>>();
-                let res = entity::docorg_tag::Entity::insert_many(models).exec(txn).await?;
-            }
+            state.service.attach_tags(txn, document_id, payload.tags).await?;
 
             /*
              * build sequence
@@ -190,36 +199,11 @@ async fn create(State(state): State<ServiceState<DocumentService>>, claims: Clai
                     return Err(ResourceError::SequenceNotExist.into());  
                 }
 
-                #[derive(FromQueryResult, Serialize, Debug)]
-                struct DocorgSeq  {
-                    last_order: i32,
-                };
-
-                let seq = entity::docorg_sequence::Entity::find()
-                    .filter(entity::docorg_sequence::Column::SequenceId.eq(seq_id))
-                    .select_only()
-                    .column_as(entity::docorg_sequence::Column::Order.max(), "last_order")
-                    .group_by(entity::docorg_sequence::Column::SequenceId)
-                    .into_model::<DocorgSeq>()
-                    .one(txn)
-                    .await?;
-
-                let seq = match seq {
-                    Some(seq) => seq,
-                    None => DocorgSeq {
-                        last_order: 0,
-                    }
-                };
-
-                let docseq = entity::docorg_sequence::ActiveModel {
-                    sequence_id: Set(seq_id),
-                    docorg_id: Set(document_id),
-                    order: Set(seq.last_order + 1),
-                };
-
-                docseq.insert(txn).await?;
+                state.service.append_to_sequence(txn, seq_id, document_id).await?;
             }
-            Ok(())
+
+            entry.targets([document_id]).record(txn).await?;
+            Ok(events::record(txn, claims.user_id, ServerEvent::DocumentCreated { doc_id: document_id }).await?)
         })
     }).await?; 
 
@@ -254,13 +238,66 @@ async fn create(State(state): State<ServiceState<DocumentService>>, claims: Clai
     if let Some(convert_id) = *convertres.lock().await {
         conversion::convert_to_html(state.global_state.clone(), convert_id, payload.raw.clone());
     }
+    events::send(&state.global_state, created).await;
 
     /* after fixing file in file server, clean up all temporary files
      * this is asynchronous task
      */
     sanitize(state.global_state.clone(), claims.user_id); 
 
-    Ok(())
+    let doc_id = *docres.lock().await;
+    Ok(Json(CreateResponse { doc_id, warnings }))
+}
+async fn import(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, mut multipart: Multipart) -> Result<impl IntoResponse, GlobalError>{
+    let mut archive_data = None;
+    let mut options = ImportOptions::default();
+    while let Some(field) = multipart.next_field().await.map_err(|_| DocumentError::InvalidArchive)? {
+        match field.name() {
+            Some("archive") => {
+                let name = field.file_name().unwrap_or("import.zip").to_string();
+                let data = field.bytes().await.map_err(|_| DocumentError::InvalidArchive)?;
+                archive_data = Some((name, data));
+            },
+            Some("options") => {
+                let data = field.bytes().await.map_err(|_| DocumentError::InvalidImportOptions)?;
+                options = serde_json::from_slice(&data).map_err(|_| DocumentError::InvalidImportOptions)?;
+            },
+            _ => {},
+        }
+    }
+    let (name, data) = archive_data.ok_or(DocumentError::ArchiveMissing)?;
+    let entries = archive::read_zip(&data).map_err(|e| match e {
+        archive::ReadError::TooLarge => DocumentError::ArchiveTooLarge,
+        archive::ReadError::Zip(_) => DocumentError::InvalidArchive,
+    })?;
+
+    state.service.check_user_has_scope(claims.user_id, &options.scope_ids[..]).await?;
+
+    let res = state.service.import_archive(claims.user_id, &name, entries, options, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(res))
+}
+// a document of another format(docx, html, odt, rst, latex) converted to markdown
+async fn import_file(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, mut multipart: Multipart) -> Result<impl IntoResponse, GlobalError>{
+    let mut file = None;
+    let mut options = ImportFileOptions::default();
+    while let Some(field) = multipart.next_field().await.map_err(|_| DocumentError::FileMissing)? {
+        match field.name() {
+            Some("file") => {
+                let name = field.file_name().unwrap_or_default().to_string();
+                let data = field.bytes().await.map_err(|_| DocumentError::FileMissing)?;
+                file = Some((name, data));
+            },
+            Some("options") => {
+                let data = field.bytes().await.map_err(|_| DocumentError::InvalidImportOptions)?;
+                options = serde_json::from_slice(&data).map_err(|_| DocumentError::InvalidImportOptions)?;
+            },
+            _ => {},
+        }
+    }
+    let (name, data) = file.ok_or(DocumentError::FileMissing)?;
+
+    let res = state.service.import_file(claims.user_id, &name, data.to_vec(), options, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(res))
 }
 async fn get_update_resource(State(state): State<ServiceState<DocumentService>>, claims: Claims, Path(doc_id): Path<i32>) -> Result<impl IntoResponse, GlobalError>{
     #[derive(FromQueryResult, Serialize, Debug)]
@@ -275,6 +312,7 @@ async fn get_update_resource(State(state): State<ServiceState<DocumentService>>,
         tag_id: Option<i32>,
         tag_value: Option<String>,
         seq_id: Option<i32>,
+        outline: Option<serde_json::Value>,
     }
     let res = entity::docorg::Entity::find()
         .filter(entity::docorg::Column::Id.eq(doc_id))
@@ -308,6 +346,7 @@ async fn get_update_resource(State(state): State<ServiceState<DocumentService>>,
         updated_at: chrono::NaiveDateTime,
         tags: BTreeSet<String>,
         seq_ids: BTreeSet<i32>,
+        outline: Vec<outline::Heading>,
     }
     let mut target: CompDocs = CompDocs {
         id: res[0].id,
@@ -318,6 +357,7 @@ async fn get_update_resource(State(state): State<ServiceState<DocumentService>>,
         updated_at: res[0].updated_at,
         tags: BTreeSet::new(),
         seq_ids: BTreeSet::new(), 
+        outline: outline::from_json(res[0].outline.as_ref(), &res[0].raw),
     };
     
     for docs in res {
@@ -332,13 +372,33 @@ async fn get_update_resource(State(state): State<ServiceState<DocumentService>>,
 
     Ok(Json(target))
 }
-async fn update(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<UpdatePayload>) -> Result<impl IntoResponse, GlobalError>{
+// headings of the document, for navigating long documents
+async fn get_outline(State(state): State<ServiceState<DocumentService>>, claims: Claims, Path(doc_id): Path<i32>) -> Result<impl IntoResponse, GlobalError>{
+    let res = state.service.outline(claims.user_id, doc_id).await?;
+    Ok(Json(res))
+}
+async fn update(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(mut payload): Json<UpdatePayload>) -> Result<impl IntoResponse, GlobalError>{
+
+    let resolved = state.service.apply_front_matter(&payload.raw, &mut payload.tags, &mut payload.scope_ids, &mut payload.seq_id)?;
 
     redis_does_docuser_have_scope(state.global_state.clone(), &payload.scope_ids[..], claims.user_id).await?;
 
-    state.global_state.db_conn.transaction::<_, (), GlobalError>(|txn|{
+    let warnings = state.service.lint(claims.user_id, &payload.raw, &payload.scope_ids[..]).await?;
+
+    let entry = Actor::new(claims.user_id, addr)
+        .entry("document.update")
+        .targets([payload.doc_id])
+        .scopes(payload.scope_ids.clone())
+        .summary(serde_json::json!({
+            "title": resolved.title,
+            "tags": payload.tags,
+            "seq_id": payload.seq_id,
+            "length": payload.raw.len(),
+        }));
+    let recorded = state.global_state.db_conn.transaction::<_, Vec<events::Recorded>, GlobalError>(|txn|{
         let state = state.clone();
         let payload = payload.clone();
+        let resolved = resolved.clone();
         Box::pin(async move {
             let document = entity::docorg::Entity::find()
                 .filter(
@@ -351,6 +411,9 @@ async fn update(State(state): State<ServiceState<DocumentService>>, claims: Clai
             if document.is_none() {
                 return Err(DocumentError::DocumentNotExist.into());
             }
+            // scopes before the update, owners of a scope the document leaves see the change too
+            entry.scopes(audit::document_scopes(txn, &[payload.doc_id]).await?).record(txn).await?;
+
             let mut document: entity::docorg::ActiveModel = document.unwrap().into();
             // delete all connected scopes and tags
             let res = entity::docorg_scope::Entity::delete_many()
@@ -364,10 +427,18 @@ async fn update(State(state): State<ServiceState<DocumentService>>, claims: Clai
 
             // update target document;
             document.raw = Set(payload.raw.clone()); 
-            document.title = Set(get_title(&payload.raw));
+            document.title = Set(resolved.title);
+            document.meta = Set(resolved.meta);
+            DocumentService::set_derived(&mut document, &payload.raw);
+            if let Some(created_at) = resolved.created_at.or(payload.created_at) {
+                document.created_at = Set(created_at);
+            }
             document.updated_at = Set(chrono::Utc::now().naive_utc());
             document.update(txn).await?;
 
+            // carry comment threads over to the new revision
+            CommentService::refresh_anchors(txn, payload.doc_id, &payload.raw).await?;
+
             let scope_ids: Vec<_> = payload.scope_ids.iter().map(|&value|{
                 entity::docorg_scope::ActiveModel {
                     docorg_id: Set(payload.doc_id),
@@ -470,63 +541,25 @@ async fn update(State(state): State<ServiceState<DocumentService>>, claims: Clai
              * update files
              */
 
-            let objs = entity::docfile::Entity::find()
-                .filter(entity::docfile::Column::DocorgId.eq(payload.doc_id))
-                .column_as(entity::docfile::Column::ObjectId, "object_id")
-                .into_model::<Obj>()
-                .all(&state.global_state.db_conn)
-                .await?;
-            
-            let re = Regex::new(r"file/((?:\[??[^\[\]]*?\)))").unwrap();
-            let mut reiter = re.find_iter(&payload.raw[..]);
-
-            let mut set = objs.into_iter().map(|obj|{obj.object_id}).collect::<HashSet<_>>();
-
-            let file_proxy_addr = env::var("FILE_PROXY_ADDR").expect("file proxy addr is not set.");
-            let mut upload_client = UploadClient::connect(file_proxy_addr.clone()).await.unwrap();
-            let mut delete_client = DeleteClient::connect(file_proxy_addr).await.unwrap();
-
-            for m in reiter {
-                let mut chars = m.as_str().chars();
-                for _ in 0..5 {
-                    chars.next();
-                }
-                chars.next_back();
-
-                if !set.contains(chars.as_str()) {
-                    upload_client.upload(tonic::Request::new(UploadRequest { object_id: chars.as_str().to_owned(), doc_id: payload.doc_id })).await?; 
-                }
-                set.remove(chars.as_str());
-            }
-
-            let set = set.into_iter().collect::<Vec<_>>();
-
-            if set.len() > 0 {
-                delete_client.delete(tonic::Request::new(DeleteRequest { object_ids: set.clone() })).await?;
-            }
-
-            for obj in set{
-                let res = entity::docfile::Entity::delete_many()
-                    .filter(entity::docfile::Column::ObjectId.eq(&obj))
-                    .exec(&state.global_state.db_conn)
-                    .await?;
+            state.service.sync_files(payload.doc_id, &payload.raw).await?;
 
-                let res = entity::convert::Entity::delete_many()
-                    .filter(entity::convert::Column::Data.eq(&obj))
-                    .exec(&state.global_state.db_conn)
-                    .await?;
+            let mut recorded = vec![events::record(txn, claims.user_id, ServerEvent::DocumentUpdated { doc_id: payload.doc_id }).await?];
+            if let Some(seq_id) = payload.seq_id {
+                recorded.push(events::record(txn, claims.user_id, ServerEvent::SequenceReordered { seq_id }).await?);
             }
-            
-            Ok(())
+            Ok(recorded)
         })
     }).await?; 
 
-    convert_to_html(state.global_state, (payload.doc_id, 0), payload.raw);
-    Ok(())
+    convert_to_html(state.global_state.clone(), (payload.doc_id, 0), payload.raw);
+    for recorded in recorded {
+        events::send(&state.global_state, recorded).await;
+    }
+    Ok(Json(UpdateResponse { warnings }))
 }
-async fn delete(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<DeletePayload>) -> Result<impl IntoResponse, GlobalError>{
+async fn delete(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<DeletePayload>) -> Result<impl IntoResponse, GlobalError>{
     let mut cond =  Condition::any();
-    for doc_id in payload.doc_ids {
+    for &doc_id in &payload.doc_ids {
         cond = cond.add(entity::docorg::Column::Id.eq(doc_id));
     }
     
@@ -550,69 +583,82 @@ async fn delete(State(state): State<ServiceState<DocumentService>>, claims: Clai
         object_ids: res.into_iter().filter(|o|o.object_id.is_some()).map(|o|o.object_id.unwrap()).collect::<Vec<_>>(),
     })).await?; */
 
-    let res = entity::docorg::Entity::delete_many()
-        .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
-        .filter(cond)
-        .exec(&state.global_state.db_conn)
-        .await?;
+    let txn = state.global_state.db_conn.begin().await?;
+    let deleted = state.service.delete_documents(&txn, claims.user_id, &payload.doc_ids, &Actor::new(claims.user_id, addr)).await?;
+    txn.commit().await?;
 
+    for recorded in deleted {
+        events::send(&state.global_state, recorded).await;
+    }
     Ok(())
 }
-async fn publish(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<PublishPayload>) -> Result<impl IntoResponse, GlobalError>{
-    let mut cond = Condition::any();
-    for scope_id in payload.scope_ids {
-        cond = cond.add(entity::docorg_scope::Column::ScopeId.eq(scope_id));
-    }
-
-    let res = entity::docorg_scope::Entity::find()
-        .filter(
-            Condition::all()
-                .add(entity::docorg_scope::Column::DocorgId.eq(payload.doc_id))
-            )
-        .filter(cond)
-        .join(JoinType::LeftJoin, entity::docorg_scope::Relation::Docorg.def())
-        .join(JoinType::LeftJoin, entity::docorg::Relation::Docuser.def())
-        .filter(entity::docuser::Column::Id.eq(claims.user_id))
-        .column_as(entity::docorg::Column::Id, "id")
-        .columns([entity::docorg::Column::Raw, entity::docorg::Column::DocuserId, entity::docorg::Column::Status])
-        .into_model::<DocorgWithScope>()
-        .one(&state.global_state.db_conn)
-        .await?;
-
-    if res.is_none() {
-        return Err(DocumentError::DocumentNotExist.into());
-    }
-    let res = res.unwrap();
-
-    let convertres = entity::convert::Entity::find_by_id((payload.doc_id, payload.c_type))
-        .one(&state.global_state.db_conn)
-        .await?;
-    if convertres.is_none() {
-        return Err(DocumentError::DocumentNotConverted.into());
-    }
-    let convertres = convertres.unwrap();
-    match convertres.status {
-        0 => return Err(DocumentError::ConvertPending.into()),
-        2 => return Err(DocumentError::ConvertFailed.into()),
-        _ => {}
-    }
-    let publish_claims = DocumentClaims {
-        iat: chrono::Utc::now().timestamp(),
-        exp: (chrono::Utc::now() + chrono::Duration::minutes(10)).timestamp(),
-        iss: "docuvault".to_owned(),
-        doc_id: res.id,
-        c_type: payload.c_type,
-        scope_id: res.scope_id,
-        token_typ: "publish".to_owned(),
-    };
-    let publish_token = encode(&Header::default(), &publish_claims, &PUBLISH_KEYS.encoding).map_err(|err| DocumentError::from(err))?;
-    
+// tags, scopes, sequence position and status of many documents at once
+async fn bulk(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<BulkPayload>) -> Result<impl IntoResponse, GlobalError>{
+    state.service.bulk(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
+    Ok(())
+}
+async fn duplicate(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<DuplicatePayload>) -> Result<impl IntoResponse, GlobalError>{
+    let doc_id = state.service.duplicate(claims.user_id, payload.doc_id, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(DuplicateResponse { doc_id }))
+}
+// kept for the dashboard, same as /share/create
+async fn publish(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ShareCreatePayload>) -> Result<impl IntoResponse, GlobalError>{
+    let publish_token = state.service.publish(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
     Ok(Json(PublishResponse{
         publish_token,
     }))
 }
-async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(payload): Json<GetDocumentPayload>) -> Result<impl IntoResponse, GlobalError> {
-    let claims = get_claims(payload)?;
+async fn get_document(State(state): State<ServiceState<DocumentService>>, headers: HeaderMap, Query(query): Query<GetDocumentQuery>, Json(payload): Json<GetDocumentPayload>) -> Result<Response, GlobalError> {
+    serve_published(state, &payload.publish_token, &headers, query.format.as_deref()).await
+}
+// for links opened in the browser(pdf, epub, ...)
+async fn get_published(State(state): State<ServiceState<DocumentService>>, Path(publish_token): Path<String>, headers: HeaderMap, Query(query): Query<GetDocumentQuery>) -> Result<Response, GlobalError> {
+    serve_published(state, &publish_token, &headers, query.format.as_deref()).await
+}
+// counts the view, protected links need the unlock cookie(see routes::share)
+async fn resolve_published(state: &ServiceState<DocumentService>, publish_token: &str, headers: &HeaderMap) -> Result<entity::share_link::Model, GlobalError> {
+    let referrer = headers.get(header::REFERER).and_then(|value| value.to_str().ok()).map(|value| value.to_owned());
+    let unlock_token = headers.get(header::COOKIE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|cookie| find_unlock_token(cookie, publish_token));
+    state.service.resolve_share_link(publish_token, referrer, unlock_token).await
+}
+// themed html page of an html link, for sharing on the web.
+// scripts are blocked, custom themes can not run code on this origin
+async fn get_page(State(state): State<ServiceState<DocumentService>>, Path(publish_token): Path<String>, headers: HeaderMap) -> Result<Response, GlobalError> {
+    let link = state.service.find_share_link(&publish_token).await?;
+    if link.c_type != 0 {
+        return Err(DocumentError::FormatNotAcceptable.into());
+    }
+    let link = resolve_published(&state, &publish_token, &headers).await?;
+    let page = state.service.render_page(&link).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CONTENT_SECURITY_POLICY, "default-src 'none'; img-src * data:; media-src *; style-src 'unsafe-inline'; font-src * data:"),
+        ],
+        page,
+    ).into_response())
+}
+/*
+ * a link serves the format it was created for, negotiated with ?format= or the Accept header
+ * before the view is counted.
+ * - html links(c_type 0): the document as json for the viewer, or the bare html
+ * - other links: the converted file, streamed from the file service
+ */
+async fn serve_published(state: ServiceState<DocumentService>, publish_token: &str, headers: &HeaderMap, format: Option<&str>) -> Result<Response, GlobalError> {
+    let link = state.service.find_share_link(publish_token).await?;
+    let offers = match link.c_type {
+        0 => vec![("application/json", "json"), (mime(0), extension(0))],
+        c_type => vec![(mime(c_type), extension(c_type))],
+    };
+    let accept = headers.get(header::ACCEPT).and_then(|value| value.to_str().ok());
+    let offer = negotiate(&offers, accept, format).ok_or(DocumentError::FormatNotAcceptable)?;
+
+    let link = resolve_published(&state, publish_token, headers).await?;
+    if link.c_type != 0 {
+        return serve_converted(state, link).await;
+    }
 
     #[derive(FromQueryResult, Serialize, Debug)]
     struct Docs {
@@ -625,9 +671,10 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
         updated_at: chrono::NaiveDateTime,
         tag_id: Option<i32>,
         tag_value: Option<String>,
+        outline: Option<serde_json::Value>,
     }
-    let res = entity::docorg::Entity::find_by_id(claims.doc_id)
-        //.filter(entity::docorg::Column::Id.eq(claims.doc_id))
+    let res = entity::docorg::Entity::find_by_id(link.docorg_id)
+        //.filter(entity::docorg::Column::Id.eq(link.docorg_id))
         .join_rev(JoinType::LeftJoin, entity::docorg_tag::Relation::Docorg.def())
         .join_rev(JoinType::LeftJoin, entity::tag::Entity::belongs_to(entity::docorg_tag::Entity)
                   .from(entity::tag::Column::Id)
@@ -646,14 +693,14 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
         return Err(DocumentError::DocumentNotExist.into());
     }
 
+    // available formats only, the files are served through links of that c_type
     #[derive(Serialize, Debug)]
     struct Convert {
         c_type: i32,
         extension: String,
-        object_id: String,
     }
     let convertres = entity::convert::Entity::find()
-        .filter(entity::convert::Column::DocorgId.eq(claims.doc_id))
+        .filter(entity::convert::Column::DocorgId.eq(link.docorg_id))
         .all(&state.global_state.db_conn)
         .await?;
     
@@ -661,7 +708,6 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
         Convert {
             c_type: m.c_type,
             extension: extension(m.c_type).to_owned(),
-            object_id: m.data.unwrap(),
         }
     }).collect::<Vec<_>>(); 
         
@@ -682,7 +728,8 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
         created_at: chrono::NaiveDateTime,
         updated_at: chrono::NaiveDateTime,
         tags: BTreeSet<Tag>,
-        convert: Vec<Convert>
+        convert: Vec<Convert>,
+        outline: Vec<outline::Heading>,
     }
     let mut ret = CompDocs{
         id: res[0].id,
@@ -694,6 +741,7 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
         updated_at: res[0].updated_at,
         tags: BTreeSet::new(),
         convert: converts,
+        outline: outline::from_json(res[0].outline.as_ref(), &res[0].raw),
     };
     for docs in res {
         match docs.tag_id {
@@ -711,10 +759,54 @@ async fn get_document(State(state): State<ServiceState<DocumentService>>, Json(p
     if ret.status != DocumentStatus::CREATED as i32 {
         return Err(DocumentError::PrivateDocument.into());
     }
-    Ok(Json(ret))
+    if offer == 1 {
+        return Ok(([(header::CONTENT_TYPE, mime(0))], ret.data).into_response());
+    }
+    Ok(Json(ret).into_response())
+}
+async fn serve_converted(state: ServiceState<DocumentService>, link: entity::share_link::Model) -> Result<Response, GlobalError> {
+    let docres = entity::docorg::Entity::find_by_id(link.docorg_id)
+        .one(&state.global_state.db_conn)
+        .await?
+        .ok_or(DocumentError::DocumentNotExist)?;
+    if docres.status != DocumentStatus::CREATED as i32 {
+        return Err(DocumentError::PrivateDocument.into());
+    }
+    let convertres = entity::convert::Entity::find_by_id((link.docorg_id, link.c_type))
+        .one(&state.global_state.db_conn)
+        .await?
+        .ok_or(DocumentError::DocumentNotConverted)?;
+    match convertres.status {
+        0 => return Err(DocumentError::ConvertPending.into()),
+        2 => return Err(DocumentError::ConvertFailed.into()),
+        _ => {}
+    }
+    let object_id = convertres.data.ok_or(DocumentError::DocumentNotConverted)?;
+    let (head, stream) = state.service.download_stream(object_id).await?;
+
+    let name = format!("{}.{}", archive::slug(&docres.title), extension(link.c_type));
+    let fallback = match name.is_ascii() {
+        true => name.clone(),
+        false => format!("document.{}", extension(link.c_type)),
+    };
+    // docx and epub are saved, the browser shows everything else
+    let disposition = match link.c_type {
+        3 | 5 => "attachment",
+        _ => "inline",
+    };
+    let body = StreamBody::new(futures::stream::once(futures::future::ready(Ok(head.data)))
+        .chain(stream.map(|chunk| chunk.map(|chunk| chunk.data))));
+    Ok((
+        [
+            (header::CONTENT_TYPE, mime(link.c_type).to_string()),
+            (header::CONTENT_LENGTH, head.size.to_string()),
+            (header::CONTENT_DISPOSITION, format!("{}; filename=\"{}\"; filename*=UTF-8''{}", disposition, fallback, archive::percent_encode(&name))),
+        ],
+        body,
+    ).into_response())
 }
 
-async fn convert(State(state): State<ServiceState<DocumentService>>, claims: Claims, Json(payload): Json<ConvertPayload>) -> Result<impl IntoResponse, GlobalError>{
+async fn convert(State(state): State<ServiceState<DocumentService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ConvertPayload>) -> Result<impl IntoResponse, GlobalError>{
     // only available for the document owner
     
     let docres = entity::docorg::Entity::find_by_id(payload.doc_id)
@@ -761,9 +853,17 @@ async fn convert(State(state): State<ServiceState<DocumentService>>, claims: Cla
                 title: docres.title,
                 doc_id: payload.doc_id,
                 docuser_id: claims.user_id,
-                data: docres.raw,
+                data: front_matter::strip(&docres.raw).to_owned(),
                 c_type: payload.c_type,
             })).await?;
+            // the conversion is stored by the convert service, the request is recorded once it took it
+            Actor::new(claims.user_id, addr)
+                .entry("document.convert")
+                .targets([payload.doc_id])
+                .scopes(audit::document_scopes(&state.global_state.db_conn, &[payload.doc_id]).await?)
+                .summary(serde_json::json!({ "c_type": payload.c_type }))
+                .record(&state.global_state.db_conn)
+                .await?;
             Ok(())
         },
         _ => {
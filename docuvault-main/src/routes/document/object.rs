@@ -8,11 +8,12 @@ use axum::{
 };
 use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
-use jsonwebtoken::{EncodingKey, DecodingKey, decode, Validation, errors::ErrorKind};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use sea_orm::{entity::*, query::*, FromQueryResult, DatabaseConnection};
 
+use crate::modules::markdown::lint::Diagnostic;
+
 use super::error::DocumentError;
 
 // pre_create
@@ -43,32 +44,76 @@ pub struct CreatePayload {
     pub scope_ids: Vec<i32>,
     pub seq_id: Option<i32>,
 }
-
-pub struct Keys {
-    pub encoding : EncodingKey,
-    pub decoding : DecodingKey,
+// lint warnings of the saved document, lint errors reject the save instead
+#[derive(Debug, Serialize)]
+pub struct CreateResponse {
+    pub doc_id: Option<i32>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+// import
+/*
+ * multipart form
+ * - archive: zip of markdown files and the files they reference
+ * - options: json encoded ImportOptions
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub scope_ids: Vec<i32>,
+    // use folder names as tags
+    #[serde(default)]
+    pub folder_tags: bool,
+    // build one sequence per folder, ordered by file name
+    #[serde(default)]
+    pub sequence: bool,
 }
-
-impl Keys {
-    pub fn new(secret: &[u8]) -> Self {
-        Keys {
-            encoding: EncodingKey::from_secret(secret),
-            decoding: DecodingKey::from_secret(secret),
-        }
-    }
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub path: String,
+    pub doc_id: Option<i32>,
+    pub files: usize,
+    pub error: Option<String>,
+}
+
+// import_file
+/*
+ * multipart form
+ * - file: docx, html, odt, rst or latex, told apart by the extension of its name
+ * - options: json encoded ImportFileOptions
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportFileOptions {
+    // the first h1 of the converted markdown otherwise
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub scope_ids: Vec<i32>,
+    pub seq_id: Option<i32>,
 }
-
-pub static PUBLISH_KEYS: Lazy<Keys> = Lazy::new(||{
-    tracing::debug!("initializing publish keys");
-    Keys::new(env::var("PUBLISH_JWT_SECRET").expect("PUBLISH_JWT_SECRET is not set").as_bytes())
-});
-
-#[derive(Debug, Deserialize)]
-pub struct PublishPayload {
+#[derive(Debug, Serialize)]
+pub struct ImportFileResponse {
     pub doc_id: i32,
-    pub scope_ids: Vec<i32>,
-    pub c_type: i32,
+    // embedded images stored along with the document
+    pub files: usize,
+}
+
+// front matter
+/*
+ * front matter and json fields are merged as follows
+ * - title, sequence, created_at: front matter takes precedence over the json field
+ *   (title falls back to the first h1 of the body)
+ * - tags, scopes: union of front matter and json fields
+ * - every other key: stored as docorg.meta(jsonb), replaced on every save
+ */
+#[derive(Debug, Clone)]
+pub struct ResolvedMeta {
+    pub title: String,
+    pub created_at: Option<chrono::NaiveDateTime>,
+    pub meta: Option<serde_json::Value>,
 }
+
 #[derive(Debug, Serialize)]
 pub struct PublishResponse {
     pub publish_token  : String,
@@ -84,33 +129,14 @@ pub struct DocorgWithScope {
 }
 #[derive(Debug, Deserialize)]
 pub struct GetDocumentPayload{
+    // share link id(see routes::share)
     pub publish_token: String,
 }
 
-pub fn get_claims(payload: GetDocumentPayload) -> Result<DocumentClaims, DocumentError>{
-    let token_data = decode::<DocumentClaims>(&payload.publish_token, &PUBLISH_KEYS.decoding, &Validation::default())
-        .map_err(|err| {
-            if err.into_kind() == ErrorKind::ExpiredSignature {
-                DocumentError::PublishTokenExpired
-            }
-            else {
-                DocumentError::InvalidPublishToken
-            }
-        })?;
-
-    Ok(token_data.claims)
-    
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DocumentClaims {
-    pub iat       : i64,
-    pub exp       : i64,
-    pub iss       : String,
-    pub token_typ : String,
-    pub c_type: i32,
-    pub doc_id: i32,
-    pub scope_id: i32,
+// ?format=<extension>, takes precedence over the Accept header
+#[derive(Debug, Default, Deserialize)]
+pub struct GetDocumentQuery {
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -128,11 +154,82 @@ pub struct UpdatePayload {
     pub seq_id: Option<i32>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct UpdateResponse {
+    pub warnings: Vec<Diagnostic>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DeletePayload {
     pub doc_ids: Vec<i32>,
 }
 
+// bulk
+/*
+ * every operation applies to all documents, in one transaction.
+ * the documents, scopes and the sequence must all belong to the user.
+ * - add_tags / remove_tags: tag values, normalized like on save
+ * - move_to_scopes: replaces the scopes of the documents
+ * - add_scopes: keeps the current scopes
+ * - sequence: inserted at position(0 based, end of the sequence when missing)
+ * - status: "deleted" deletes them like /document/delete, only created documents are edited
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkPayload {
+    pub doc_ids: Vec<i32>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    pub move_to_scopes: Option<Vec<i32>>,
+    #[serde(default)]
+    pub add_scopes: Vec<i32>,
+    pub sequence: Option<BulkSequence>,
+    pub status: Option<BulkStatus>,
+}
+impl BulkPayload {
+    // scopes the documents are moved or added to, None when they would be left without any
+    pub fn scope_ids(&self) -> Option<Vec<i32>> {
+        let mut scope_ids = self.add_scopes.clone();
+        if let Some(move_to) = &self.move_to_scopes {
+            if move_to.is_empty() {
+                return None;
+            }
+            scope_ids.extend(move_to);
+        }
+        Some(scope_ids)
+    }
+}
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkSequence {
+    pub seq_id: i32,
+    pub position: Option<usize>,
+}
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkStatus {
+    Created,
+    Deleted,
+}
+impl From<BulkStatus> for DocumentStatus {
+    fn from(value: BulkStatus) -> Self {
+        match value {
+            BulkStatus::Created => DocumentStatus::CREATED,
+            BulkStatus::Deleted => DocumentStatus::DELETED,
+        }
+    }
+}
+
+// duplicate
+#[derive(Debug, Deserialize)]
+pub struct DuplicatePayload {
+    pub doc_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct DuplicateResponse {
+    pub doc_id: i32,
+}
+
 #[derive(Debug, FromQueryResult)]
 pub struct Obj{
     pub object_id: String,
@@ -144,3 +241,13 @@ pub struct ConvertPayload {
     pub c_type: i32,
 }
 
+
+#[test]
+fn bulk_payload_test() {
+    let payload: BulkPayload = serde_json::from_str(r#"{"doc_ids":[1,2],"add_scopes":[3],"move_to_scopes":[4],"status":"deleted"}"#).unwrap();
+    assert_eq!(payload.scope_ids(), Some(vec![3, 4]));
+    assert!(matches!(payload.status, Some(BulkStatus::Deleted)));
+    let payload: BulkPayload = serde_json::from_str(r#"{"doc_ids":[1],"move_to_scopes":[]}"#).unwrap();
+    assert_eq!(payload.scope_ids(), None);
+    assert!(serde_json::from_str::<BulkPayload>(r#"{"doc_ids":[1],"status":"pending"}"#).is_err());
+}
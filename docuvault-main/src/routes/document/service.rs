@@ -1,25 +1,267 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
 use std::sync::{Arc, Mutex};
-use sea_orm::{entity::*, query::*, FromQueryResult};
-use crate::{AppState, modules::{redis::redis_does_docuser_have_scope, markdown::get_title, tag::{TagSetModule, application::port::input::TagSetUseCase, domain::entity::tag::Tag}}, routes::error::GlobalError, entity::{self, docorg::ActiveModel}};
+use regex::Regex;
+use sea_orm::{entity::*, query::*, sea_query::{Expr, Query}, FromQueryResult, DatabaseTransaction};
+use serde::Serialize;
+use tonic::{Request, Streaming, Code, transport::Channel};
+use crate::{AppState, modules::{redis::redis_does_docuser_have_scope, audit::{self, Actor}, markdown::{get_title, front_matter, outline, stats, lint::Diagnostic}, tag::{TagSetModule, application::port::input::TagSetUseCase, domain::entity::{tag::Tag, tag_set::TagSet}}, archive::{self, Manifest}, background::{conversion, site, watch}, events::{self, ServerEvent}, theme, grpc::{upload::{upload_client::UploadClient, PreUploadRequest, UploadRequest}, download::{download_client::DownloadClient, DownloadRequest, DownloadChunk}, delete::{delete_client::DeleteClient, DeleteRequest}, convert::{convert_client::ConvertClient, ImportRequest, import_request::IType}}}, routes::{error::GlobalError, resource::error::ResourceError, share::{service::ShareService, object::ShareCreatePayload}, lint::service::LintService}, entity::{self, docorg::ActiveModel}};
 
-use super::{object::{DocumentStatus, PendingCreatePayload, PendingCreateResponse, CreatePayload}, error::DocumentError};
+use super::{object::{Obj, DocumentStatus, PendingCreatePayload, PendingCreateResponse, CreatePayload, ResolvedMeta, ImportOptions, ImportReport, ImportFileOptions, ImportFileResponse, BulkPayload, BulkStatus}, error::DocumentError};
+
+// a file of an imported archive, pre-uploaded and then fixed to the first document created with it
+struct ArchiveUpload {
+    object_id: String,
+    fixed: bool,
+}
+
+// an archive being imported, shared by the documents created from it
+struct ArchiveImport<'a> {
+    upload_client: UploadClient<Channel>,
+    docuser_id: i32,
+    name: &'a str,
+    entries: &'a BTreeMap<String, Vec<u8>>,
+    options: &'a ImportOptions,
+    actor: &'a Actor,
+    // folder path -> sequence id, filled while importing when options.sequence is set
+    sequences: HashMap<String, i32>,
+    // archive path -> uploaded file, shared by every document linking to it
+    uploads: HashMap<String, ArchiveUpload>,
+}
 
 #[derive(Clone, Debug)]
 pub struct DocumentService{
     state: AppState,
+    share_service: ShareService,
+    lint_service: LintService,
 }
 impl DocumentService {
     pub fn new(shared_state: AppState) -> Self{
         Self {
             state: shared_state.clone(),
+            share_service: ShareService::new(shared_state.clone()),
+            lint_service: LintService::new(shared_state),
         }
     }
+
+    // warnings of the document about to be saved, Err when it has lint errors
+    pub async fn lint(&self, docuser_id: i32, raw: &str, scope_ids: &[i32]) -> Result<Vec<Diagnostic>, GlobalError> {
+        self.lint_service.enforce(docuser_id, raw, scope_ids).await
+    }
+
+    pub async fn publish(&self, docuser_id: i32, payload: ShareCreatePayload, actor: &Actor) -> Result<String, GlobalError> {
+        self.share_service.create(docuser_id, payload, actor).await
+    }
+
+    pub async fn find_share_link(&self, link_id: &str) -> Result<entity::share_link::Model, GlobalError> {
+        self.share_service.find_open(link_id).await
+    }
+
+    pub async fn resolve_share_link(&self, link_id: &str, referrer: Option<String>, unlock_token: Option<&str>) -> Result<entity::share_link::Model, GlobalError> {
+        self.share_service.resolve(link_id, referrer, unlock_token).await
+    }
     
+    // the first chunk carries name, ftype and size of the object
+    pub async fn download_stream(&self, object_id: String) -> Result<(DownloadChunk, Streaming<DownloadChunk>), GlobalError> {
+        let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+        let mut client = DownloadClient::connect(file_proxy_addr).await?;
+        let mut stream = client.download_stream(Request::new(DownloadRequest { object_id })).await?.into_inner();
+        let head = stream.message().await?.ok_or(DocumentError::DocumentNotConverted)?;
+        Ok((head, stream))
+    }
+
+    /*
+     * full html page of a document published through an html share link, with the theme of the link's scope.
+     * sequence entries link to the pages of other documents published in the same scope.
+     */
+    pub async fn render_page(&self, link: &entity::share_link::Model) -> Result<String, GlobalError> {
+        let base = env::var("PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+        let page_url = |link_id: &str| format!("{}/document/page/{}", base.trim_end_matches('/'), link_id);
+
+        let docres = entity::docorg::Entity::find_by_id(link.docorg_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotExist)?;
+        if docres.status != DocumentStatus::CREATED as i32 {
+            return Err(DocumentError::PrivateDocument.into());
+        }
+        let html = entity::convert::Entity::find_by_id((link.docorg_id, 0))
+            .filter(entity::convert::Column::Status.eq(1))
+            .one(&self.state.db_conn)
+            .await?
+            .and_then(|convert| convert.data)
+            .ok_or(DocumentError::DocumentNotConverted)?;
+        let tags = entity::tag::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::docorg_tag::Relation::Tag.def())
+            .filter(entity::docorg_tag::Column::DocorgId.eq(link.docorg_id))
+            .order_by_asc(entity::tag::Column::Value)
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|tag| tag.value)
+            .collect::<Vec<_>>();
+        let (scope, custom_theme) = entity::scope::Entity::find_by_id(link.scope_id)
+            .find_also_related(entity::theme::Entity)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::ScopeNotExist)?;
+
+        /*
+         * sequences of the document
+         */
+        let seq_ids = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::DocorgId.eq(link.docorg_id))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|doc_seq| doc_seq.sequence_id)
+            .collect::<Vec<_>>();
+        let sequences = entity::sequence::Entity::find()
+            .filter(entity::sequence::Column::Id.is_in(seq_ids.clone()))
+            .order_by_asc(entity::sequence::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        let doc_seqs = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::SequenceId.is_in(seq_ids))
+            .order_by_asc(entity::docorg_sequence::Column::Order)
+            .all(&self.state.db_conn)
+            .await?;
+        let doc_ids = doc_seqs.iter().map(|doc_seq| doc_seq.docorg_id).collect::<Vec<_>>();
+        let titles = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::Id.is_in(doc_ids.clone()))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|document| (document.id, document.title))
+            .collect::<HashMap<_, _>>();
+        // open html links of the same scope, the latest one per document
+        let pages = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::DocorgId.is_in(doc_ids))
+            .filter(entity::share_link::Column::ScopeId.eq(link.scope_id))
+            .filter(entity::share_link::Column::CType.eq(0))
+            .filter(entity::share_link::Column::Revoked.eq(false))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::ExpiresAt.is_null())
+                .add(entity::share_link::Column::ExpiresAt.gt(chrono::Utc::now().naive_utc())))
+            .order_by_asc(entity::share_link::Column::Id)
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|share_link| (share_link.docorg_id, page_url(&share_link.link_id)))
+            .collect::<HashMap<_, _>>();
+
+        let item = |doc_id: i32| theme::PageItem {
+            title: titles[&doc_id].clone(),
+            url: match doc_id == link.docorg_id {
+                true => Some(page_url(&link.link_id)),
+                false => pages.get(&doc_id).cloned(),
+            },
+            current: doc_id == link.docorg_id,
+        };
+        let chapters = sequences.iter()
+            .filter_map(|sequence| {
+                let doc_ids = doc_seqs.iter()
+                    .filter(|doc_seq| doc_seq.sequence_id == sequence.id && titles.contains_key(&doc_seq.docorg_id))
+                    .map(|doc_seq| doc_seq.docorg_id)
+                    .collect::<Vec<_>>();
+                let idx = doc_ids.iter().position(|doc_id| *doc_id == link.docorg_id)?;
+                Some(theme::PageChapter {
+                    title: sequence.title.clone(),
+                    items: doc_ids.iter().map(|doc_id| item(*doc_id)).collect(),
+                    prev: idx.checked_sub(1).map(|idx| item(doc_ids[idx])),
+                    next: doc_ids.get(idx + 1).map(|doc_id| item(*doc_id)),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let html = theme::rewrite_links(&html, &base, &pages);
+        let page = theme::Page {
+            title: docres.title,
+            scope: scope.name,
+            url: page_url(&link.link_id),
+            excerpt: theme::excerpt(&html),
+            image: theme::first_image(&html),
+            html,
+            tags,
+            created_at: docres.created_at.format("%Y-%m-%d").to_string(),
+            updated_at: docres.updated_at.format("%Y-%m-%d").to_string(),
+            chapters,
+        };
+        // a theme saved before the page variables changed can fail, the built-in theme is the fallback
+        let rendered = match custom_theme {
+            Some(custom) => theme::render(&custom.layout, &custom.css, &page).or_else(|e| {
+                dbg!(e);
+                theme::render(theme::LAYOUT, theme::STYLE, &page)
+            }),
+            None => theme::render(theme::LAYOUT, theme::STYLE, &page),
+        };
+        rendered.map_err(|e| {
+            dbg!(e);
+            GlobalError::InternalServerError
+        })
+    }
+
+    // outline and list statistics, derived from the markdown on every save
+    pub fn set_derived(document: &mut ActiveModel, raw: &str) {
+        let stats = stats::stats(raw);
+        document.outline = Set(Some(outline::to_json(&outline::outline(raw))));
+        document.word_count = Set(Some(stats.word_count));
+        document.reading_time = Set(Some(stats.reading_time));
+        document.excerpt = Set(stats.excerpt);
+        document.cover = Set(stats.cover);
+    }
+
+    pub async fn outline(&self, docuser_id: i32, doc_id: i32) -> Result<Vec<outline::Heading>, GlobalError> {
+        let document = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotExist)?;
+        Ok(outline::from_json(document.outline.as_ref(), &document.raw))
+    }
+
     pub async fn check_user_has_scope(&self, docuser_id: i32, scope_ids: &[i32]) -> Result<(), GlobalError>{
         redis_does_docuser_have_scope(self.state.clone(), scope_ids, docuser_id).await?;
         Ok(())
     }
 
+    pub fn apply_front_matter(&self, raw: &str, tags: &mut Vec<String>, scope_ids: &mut Vec<i32>, seq_id: &mut Option<i32>) -> Result<ResolvedMeta, GlobalError> {
+        let (front_matter, body) = front_matter::parse(raw).map_err(|_| DocumentError::InvalidFrontMatter)?;
+        let front_matter = match front_matter {
+            Some(front_matter) => front_matter,
+            None => return Ok(ResolvedMeta {
+                title: get_title(body),
+                created_at: None,
+                meta: None,
+            }),
+        };
+
+        for tag in &front_matter.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        for scope_id in &front_matter.scopes {
+            if !scope_ids.contains(scope_id) {
+                scope_ids.push(*scope_id);
+            }
+        }
+        if front_matter.sequence.is_some() {
+            *seq_id = front_matter.sequence;
+        }
+
+        Ok(ResolvedMeta {
+            title: match &front_matter.title {
+                Some(title) if !title.trim().is_empty() => title.trim().to_string(),
+                _ => get_title(body),
+            },
+            created_at: front_matter.created_at(),
+            meta: front_matter.custom_json(),
+        })
+    }
+
     pub async fn get_pending_document_records(&self, docuser_id: i32) -> Result<Vec<entity::docorg::Model>, GlobalError> {
         let document = entity::docorg::Entity::find()
             .filter(
@@ -32,7 +274,7 @@ impl DocumentService {
     }
 
 
-    pub async fn create_or_get_pending_document(&self, docuser_id: i32, payload: PendingCreatePayload) -> Result<PendingCreateResponse, GlobalError>{
+    pub async fn create_or_get_pending_document(&self, docuser_id: i32, payload: PendingCreatePayload, actor: &Actor) -> Result<PendingCreateResponse, GlobalError>{
         let document = self.get_pending_document_records(docuser_id).await?;
         let res: PendingCreateResponse;
         match document.len() {
@@ -44,7 +286,14 @@ impl DocumentService {
                     status: Set(DocumentStatus::PENDING as i32),
                     ..Default::default()
                 };
-                entity::docorg::Entity::insert(new_document).exec(&self.state.db_conn).await?;
+                let txn = self.state.db_conn.begin().await?;
+                let document_id = entity::docorg::Entity::insert(new_document).exec(&txn).await?.last_insert_id;
+                actor.entry("document.draft")
+                    .targets([document_id])
+                    .summary(serde_json::json!({ "length": payload.raw.len() }))
+                    .record(&txn)
+                    .await?;
+                txn.commit().await?;
                 res = PendingCreateResponse {
                     exists: false,
                     raw: payload.raw,
@@ -64,7 +313,7 @@ impl DocumentService {
                 
         Ok(res)
     }
-    pub async fn overwrite_pending_document(&self, docuser_id: i32, payload: PendingCreatePayload) -> Result<PendingCreateResponse, GlobalError>{
+    pub async fn overwrite_pending_document(&self, docuser_id: i32, payload: PendingCreatePayload, actor: &Actor) -> Result<PendingCreateResponse, GlobalError>{
         let document = self.get_pending_document_records(docuser_id).await?;
         let res: PendingCreateResponse;
         match document.len() {
@@ -74,7 +323,14 @@ impl DocumentService {
             1 => {
                 let mut updated_document: ActiveModel = document[0].clone().into();
                 updated_document.raw = Set(payload.raw.clone());  
-                updated_document.update(&self.state.db_conn).await?;
+                let txn = self.state.db_conn.begin().await?;
+                let updated_document = updated_document.update(&txn).await?;
+                actor.entry("document.draft")
+                    .targets([updated_document.id])
+                    .summary(serde_json::json!({ "length": payload.raw.len() }))
+                    .record(&txn)
+                    .await?;
+                txn.commit().await?;
                 res = PendingCreateResponse {
                     exists: true,
                     raw: payload.raw,
@@ -86,7 +342,7 @@ impl DocumentService {
         }
         Ok(res)
     }
-    pub async fn complete_pending_document(&self, docuser_id: i32, raw: &str) -> Result<i32, GlobalError>{
+    pub async fn complete_pending_document(&self, docuser_id: i32, raw: &str, resolved: &ResolvedMeta) -> Result<i32, GlobalError>{
         let document = self.get_pending_document_records(docuser_id).await?;
         let res: i32;
         match document.len() {
@@ -95,8 +351,13 @@ impl DocumentService {
             },
             1 => {
                 let mut updated_document: ActiveModel = document[0].clone().into();
-                updated_document.title = Set(get_title(raw));
+                updated_document.title = Set(resolved.title.clone());
                 updated_document.raw = Set(raw.to_string());  
+                updated_document.meta = Set(resolved.meta.clone());
+                Self::set_derived(&mut updated_document, raw);
+                if let Some(created_at) = resolved.created_at {
+                    updated_document.created_at = Set(created_at);
+                }
                 updated_document.status = Set(DocumentStatus::CREATED as i32);
                 let updated_document = updated_document.update(&self.state.db_conn).await?;
                 res = updated_document.id;
@@ -107,5 +368,861 @@ impl DocumentService {
         }
         Ok(res)
     }
+
+    pub async fn attach_tags(&self, txn: &DatabaseTransaction, document_id: i32, tags: Vec<String>) -> Result<(), GlobalError> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+        let tag_set: TagSet = self.state.modules.tag.service.get(txn).await?;
+        let document_tags = tags.into_iter().map(|tag|tag.trim().to_lowercase().to_string()).filter(|tag| !tag.is_empty()).collect::<BTreeSet<_>>();
+
+        for tag in document_tags.iter().filter(|tag| !tag_set.tags.contains(&Tag::new(tag.to_string()))) {
+            self.state.modules.tag.service.add(txn, tag.clone()).await?;
+        }
+
+        let mut cond = Condition::any();
+        for tag in &document_tags {
+            cond = cond.add(entity::tag::Column::Value.eq(tag.clone()));
+        }
+        let res = entity::tag::Entity::find().filter(cond).all(txn).await?;
+
+        let models = res.iter().map(|m|{
+            entity::docorg_tag::ActiveModel {
+                docorg_id: Set(document_id),
+                tag_id: Set(m.id),
+                ..Default::default()
+            }
+        }).collect::<Vec<_>>();
+        if !models.is_empty() {
+            entity::docorg_tag::Entity::insert_many(models).exec(txn).await?;
+        }
+        Ok(())
+    }
+
+    // append the document at the end of the sequence
+    pub async fn append_to_sequence(&self, txn: &DatabaseTransaction, seq_id: i32, document_id: i32) -> Result<(), GlobalError> {
+        #[derive(FromQueryResult, Serialize, Debug)]
+        struct DocorgSeq  {
+            last_order: i32,
+        }
+
+        let seq = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::SequenceId.eq(seq_id))
+            .select_only()
+            .column_as(entity::docorg_sequence::Column::Order.max(), "last_order")
+            .group_by(entity::docorg_sequence::Column::SequenceId)
+            .into_model::<DocorgSeq>()
+            .one(txn)
+            .await?;
+
+        let docseq = entity::docorg_sequence::ActiveModel {
+            sequence_id: Set(seq_id),
+            docorg_id: Set(document_id),
+            order: Set(seq.map_or(0, |seq| seq.last_order) + 1),
+        };
+        docseq.insert(txn).await?;
+        Ok(())
+    }
+
+    // the documents are taken out of the sequence, then inserted together at the position
+    pub async fn insert_into_sequence(&self, txn: &DatabaseTransaction, seq_id: i32, doc_ids: &[i32], position: Option<usize>) -> Result<(), GlobalError> {
+        let mut order = entity::docorg_sequence::Entity::find()
+            .filter(entity::docorg_sequence::Column::SequenceId.eq(seq_id))
+            .order_by_asc(entity::docorg_sequence::Column::Order)
+            .all(txn)
+            .await?
+            .into_iter()
+            .map(|docseq| docseq.docorg_id)
+            .filter(|doc_id| !doc_ids.contains(doc_id))
+            .collect::<Vec<_>>();
+        let position = position.unwrap_or(order.len()).min(order.len());
+        order.splice(position..position, doc_ids.iter().copied());
+
+        entity::docorg_sequence::Entity::delete_many()
+            .filter(entity::docorg_sequence::Column::SequenceId.eq(seq_id))
+            .exec(txn)
+            .await?;
+        let models = order.into_iter().enumerate().map(|(idx, doc_id)| {
+            entity::docorg_sequence::ActiveModel {
+                sequence_id: Set(seq_id),
+                docorg_id: Set(doc_id),
+                order: Set(idx as i32 + 1),
+            }
+        }).collect::<Vec<_>>();
+        entity::docorg_sequence::Entity::insert_many(models).exec(txn).await?;
+        Ok(())
+    }
+
+    pub async fn create_sequence(&self, txn: &DatabaseTransaction, docuser_id: i32, title: String, scope_ids: &[i32]) -> Result<i32, GlobalError> {
+        let new_sequence = entity::sequence::ActiveModel {
+            title: Set(title),
+            docuser_id: Set(docuser_id),
+            ..Default::default()
+        };
+        let seq_id = entity::sequence::Entity::insert(new_sequence).exec(txn).await?.last_insert_id;
+        let records = scope_ids.iter().map(|&scope_id| {
+            entity::scope_sequence::ActiveModel {
+                sequence_id: Set(seq_id),
+                scope_id: Set(scope_id),
+            }
+        }).collect::<Vec<_>>();
+        if !records.is_empty() {
+            entity::scope_sequence::Entity::insert_many(records).exec(txn).await?;
+        }
+        Ok(seq_id)
+    }
+
+    /*
+     * import every markdown file of an archive as a created document.
+     * a failing file does not abort the import, it is reported with its error instead.
+     */
+    pub async fn import_archive(&self, docuser_id: i32, name: &str, entries: BTreeMap<String, Vec<u8>>, options: ImportOptions, actor: &Actor) -> Result<Vec<ImportReport>, GlobalError> {
+        let file_proxy_addr = env::var("FILE_PROXY_ADDR").expect("file proxy addr is not set.");
+        let upload_client = UploadClient::connect(file_proxy_addr).await?;
+
+        // an exported archive brings its own sequences, folder sequences are not built then
+        let manifest = match entries.get(archive::MANIFEST) {
+            Some(data) => Some(serde_json::from_slice::<Manifest>(data).map_err(|_| DocumentError::InvalidArchive)?),
+            None => None,
+        };
+        let options = ImportOptions {
+            sequence: options.sequence && manifest.is_none(),
+            ..options
+        };
+
+        let mut import = ArchiveImport {
+            upload_client,
+            docuser_id,
+            name,
+            entries: &entries,
+            options: &options,
+            actor,
+            sequences: HashMap::new(),
+            uploads: HashMap::new(),
+        };
+        let mut report = Vec::new();
+        for path in entries.keys().filter(|path| archive::is_markdown(path)) {
+            let res = self.import_document(&mut import, path).await;
+            report.push(match res {
+                Ok((doc_id, files)) => ImportReport { path: path.clone(), doc_id: Some(doc_id), files, error: None },
+                Err(e) => ImportReport { path: path.clone(), doc_id: None, files: 0, error: Some(e.to_string()) },
+            });
+        }
+
+        if let Some(manifest) = manifest {
+            let doc_ids = report.iter()
+                .filter_map(|item| item.doc_id.map(|doc_id| (item.path.clone(), doc_id)))
+                .collect::<HashMap<_, _>>();
+            let service = self.clone();
+            let scope_ids = options.scope_ids.clone();
+            let actor = actor.clone();
+            self.state.db_conn.clone().transaction::<_, (), GlobalError>(|txn| {
+                Box::pin(async move {
+                    for sequence in manifest.sequences {
+                        let seq_id = service.create_sequence(txn, docuser_id, sequence.title.clone(), &scope_ids[..]).await?;
+                        actor.entry("sequence.create")
+                            .targets([seq_id])
+                            .scopes(scope_ids.clone())
+                            .summary(serde_json::json!({ "title": sequence.title, "import": true }))
+                            .record(txn)
+                            .await?;
+                        for path in &sequence.documents {
+                            if let Some(&doc_id) = doc_ids.get(path) {
+                                service.append_to_sequence(txn, seq_id, doc_id).await?;
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            }).await?;
+        }
+        Ok(report)
+    }
+
+    pub fn import_type(name: &str) -> Option<IType> {
+        let extension = name.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "docx" => Some(IType::Docx),
+            "html" | "htm" => Some(IType::Html),
+            "odt" => Some(IType::Odt),
+            "rst" => Some(IType::Rst),
+            "tex" | "latex" => Some(IType::Latex),
+            _ => None,
+        }
+    }
+
+    /*
+     * a docx, html, odt, rst or latex file as a created document, converted to markdown by docuvault-convert.
+     * the images embedded in it are uploaded there and fixed to the document here, in the transaction creating it.
+     * they belong to no one else, a rolled back import removes them.
+     */
+    pub async fn import_file(&self, docuser_id: i32, name: &str, data: Vec<u8>, options: ImportFileOptions, actor: &Actor) -> Result<ImportFileResponse, GlobalError> {
+        let i_type = Self::import_type(name).ok_or(DocumentError::UnsupportedImportFormat)?;
+        self.check_user_has_scope(docuser_id, &options.scope_ids[..]).await?;
+        if let Some(seq_id) = options.seq_id {
+            entity::sequence::Entity::find_by_id(seq_id)
+                .filter(entity::sequence::Column::DocuserId.eq(docuser_id))
+                .one(&self.state.db_conn)
+                .await?
+                .ok_or(ResourceError::SequenceNotExist)?;
+        }
+
+        let convert_addr = env::var("CONVERT_ADDR").expect("convert addr is not set.");
+        let mut convert_client = ConvertClient::connect(convert_addr).await?;
+        let res = convert_client.import(Request::new(ImportRequest {
+            name: name.to_string(),
+            docuser_id,
+            data,
+            i_type: i_type as i32,
+        })).await.map_err(|status| match status.code() {
+            Code::InvalidArgument => DocumentError::ImportFailed.into(),
+            _ => GlobalError::from(status),
+        })?.into_inner();
+        let raw = res.markdown;
+        let object_ids = res.object_ids;
+
+        let title = match options.title {
+            Some(title) if !title.trim().is_empty() => title.trim().to_string(),
+            _ => get_title(&raw),
+        };
+        let service = self.clone();
+        let cloned_raw = raw.clone();
+        let scope_ids = options.scope_ids;
+        let tags = options.tags;
+        let seq_id = options.seq_id;
+        let entry = actor.entry("document.import")
+            .scopes(scope_ids.clone())
+            .summary(serde_json::json!({
+                "title": title,
+                "file": name,
+                "tags": tags,
+                "files": object_ids.len(),
+                "length": raw.len(),
+            }));
+        let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+        let fix_ids = object_ids.clone();
+        let res = self.state.db_conn.clone().transaction::<_, (i32, events::Recorded), GlobalError>(|txn| {
+            Box::pin(async move {
+                let mut new_document = entity::docorg::ActiveModel {
+                    title: Set(title),
+                    raw: Set(cloned_raw.clone()),
+                    docuser_id: Set(docuser_id),
+                    status: Set(DocumentStatus::CREATED as i32),
+                    ..Default::default()
+                };
+                DocumentService::set_derived(&mut new_document, &cloned_raw);
+                let document_id = entity::docorg::Entity::insert(new_document).exec(txn).await?.last_insert_id;
+
+                let scopes = scope_ids.iter().map(|&scope_id| {
+                    entity::docorg_scope::ActiveModel {
+                        docorg_id: Set(document_id),
+                        scope_id: Set(scope_id),
+                        ..Default::default()
+                    }
+                }).collect::<Vec<_>>();
+                if !scopes.is_empty() {
+                    entity::docorg_scope::Entity::insert_many(scopes).exec(txn).await?;
+                }
+
+                service.attach_tags(txn, document_id, tags).await?;
+
+                let new_convert = entity::convert::ActiveModel {
+                    docorg_id: Set(document_id),
+                    c_type: Set(0),
+                    status: Set(0),
+                    ..Default::default()
+                };
+                entity::convert::Entity::insert(new_convert).exec(txn).await?;
+
+                if let Some(seq_id) = seq_id {
+                    service.append_to_sequence(txn, seq_id, document_id).await?;
+                }
+                entry.targets([document_id]).record(txn).await?;
+                let created = events::record(txn, docuser_id, ServerEvent::DocumentCreated { doc_id: document_id }).await?;
+
+                // fixed last, a failure rolls the document back
+                if !fix_ids.is_empty() {
+                    let mut upload_client = UploadClient::connect(file_proxy_addr).await?;
+                    for object_id in fix_ids {
+                        upload_client.upload(Request::new(UploadRequest {
+                            doc_id: document_id,
+                            object_id,
+                        })).await?;
+                    }
+                }
+                Ok((document_id, created))
+            })
+        }).await;
+
+        let (document_id, created) = match res {
+            Ok(res) => res,
+            Err(e) => {
+                self.remove_objects(object_ids).await;
+                return Err(e.into());
+            },
+        };
+        conversion::convert_to_html(self.state.clone(), (document_id, 0), raw);
+        events::send(&self.state, created).await;
+
+        Ok(ImportFileResponse {
+            doc_id: document_id,
+            files: object_ids.len(),
+        })
+    }
+
+    /*
+     * documents of the user removed for good, their scopes, tags and converts go with them.
+     * the scopes are read beforehand for the events and the audit log, the events are sent once committed.
+     */
+    pub async fn delete_documents(&self, txn: &DatabaseTransaction, docuser_id: i32, doc_ids: &[i32], actor: &Actor) -> Result<Vec<events::Recorded>, GlobalError> {
+        let documents = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Id.is_in(doc_ids.to_vec()))
+            .all(txn)
+            .await?;
+        let mut deleted = Vec::new();
+        for document in documents {
+            let event = ServerEvent::DocumentDeleted { doc_id: document.id };
+            let scope_ids = events::scopes_of(txn, &event).await?;
+            actor.entry("document.delete")
+                .targets([document.id])
+                .scopes(scope_ids.clone())
+                .summary(serde_json::json!({ "title": document.title, "status": document.status }))
+                .record(txn)
+                .await?;
+            deleted.push(events::record_with_scopes(txn, docuser_id, event, scope_ids).await?);
+        }
+        entity::docorg::Entity::delete_many()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Id.is_in(doc_ids.to_vec()))
+            .exec(txn)
+            .await?;
+        Ok(deleted)
+    }
+
+    pub async fn bulk(&self, docuser_id: i32, payload: BulkPayload, actor: &Actor) -> Result<(), GlobalError> {
+        let doc_ids = payload.doc_ids.iter().copied().collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>();
+        if doc_ids.is_empty() {
+            return Ok(());
+        }
+
+        /*
+         * every document, scope and the sequence must belong to the user.
+         * drafts and deleted documents are not edited(nor brought back) in bulk.
+         */
+        let owned = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::Id.is_in(doc_ids.clone()))
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .count(&self.state.db_conn)
+            .await?;
+        if owned as usize != doc_ids.len() {
+            return Err(DocumentError::DocumentNotExist.into());
+        }
+        let scope_ids = payload.scope_ids().ok_or(DocumentError::ScopeNotExist)?;
+        if !scope_ids.is_empty() {
+            self.check_user_has_scope(docuser_id, &scope_ids[..]).await?;
+        }
+        if let Some(sequence) = &payload.sequence {
+            entity::sequence::Entity::find_by_id(sequence.seq_id)
+                .filter(entity::sequence::Column::DocuserId.eq(docuser_id))
+                .one(&self.state.db_conn)
+                .await?
+                .ok_or(ResourceError::SequenceNotExist)?;
+        }
+
+        let service = self.clone();
+        let cloned_doc_ids = doc_ids.clone();
+        let cloned_actor = actor.clone();
+        let scopes_changed = !scope_ids.is_empty();
+        let seq_id = payload.sequence.as_ref().map(|sequence| sequence.seq_id);
+        let entry = actor.entry("document.bulk")
+            .targets(doc_ids.clone())
+            .scopes(scope_ids.clone())
+            .summary(serde_json::json!({
+                "add_tags": payload.add_tags,
+                "remove_tags": payload.remove_tags,
+                "move_to_scopes": payload.move_to_scopes,
+                "add_scopes": payload.add_scopes,
+                "seq_id": seq_id,
+                "status": payload.status.map(|status| DocumentStatus::from(status) as i32),
+            }));
+        let (deleted, recorded) = self.state.db_conn.clone().transaction::<_, (bool, Vec<events::Recorded>), GlobalError>(|txn| {
+            Box::pin(async move {
+                let doc_ids = cloned_doc_ids;
+                // scopes before the change, owners of the scopes the documents leave see it too
+                entry.scopes(audit::document_scopes(txn, &doc_ids).await?).record(txn).await?;
+
+                // tags to add are removed first, so that no document has a tag twice
+                let changed_tags = payload.add_tags.iter()
+                    .chain(payload.remove_tags.iter())
+                    .map(|tag| tag.trim().to_lowercase())
+                    .collect::<Vec<_>>();
+                if !changed_tags.is_empty() {
+                    entity::docorg_tag::Entity::delete_many()
+                        .filter(entity::docorg_tag::Column::DocorgId.is_in(doc_ids.clone()))
+                        .filter(entity::docorg_tag::Column::TagId.in_subquery(Query::select()
+                            .column(entity::tag::Column::Id)
+                            .from(entity::tag::Entity)
+                            .and_where(entity::tag::Column::Value.is_in(changed_tags))
+                            .to_owned()))
+                        .exec(txn)
+                        .await?;
+                }
+                if !payload.add_tags.is_empty() {
+                    for &doc_id in &doc_ids {
+                        service.attach_tags(txn, doc_id, payload.add_tags.clone()).await?;
+                    }
+                }
+
+                if payload.move_to_scopes.is_some() {
+                    entity::docorg_scope::Entity::delete_many()
+                        .filter(entity::docorg_scope::Column::DocorgId.is_in(doc_ids.clone()))
+                        .exec(txn)
+                        .await?;
+                }
+                let new_scopes = payload.move_to_scopes.unwrap_or_default().into_iter()
+                    .chain(payload.add_scopes)
+                    .collect::<BTreeSet<_>>();
+                if !new_scopes.is_empty() {
+                    entity::docorg_scope::Entity::delete_many()
+                        .filter(entity::docorg_scope::Column::DocorgId.is_in(doc_ids.clone()))
+                        .filter(entity::docorg_scope::Column::ScopeId.is_in(new_scopes.clone()))
+                        .exec(txn)
+                        .await?;
+                    let models = doc_ids.iter().flat_map(|&doc_id| new_scopes.iter().map(move |&scope_id| {
+                        entity::docorg_scope::ActiveModel {
+                            docorg_id: Set(doc_id),
+                            scope_id: Set(scope_id),
+                            ..Default::default()
+                        }
+                    })).collect::<Vec<_>>();
+                    entity::docorg_scope::Entity::insert_many(models).exec(txn).await?;
+                }
+
+                if let Some(sequence) = payload.sequence {
+                    service.insert_into_sequence(txn, sequence.seq_id, &doc_ids, sequence.position).await?;
+                }
+
+                // deleted the same way as /document/delete, the documents are created already otherwise
+                if let Some(BulkStatus::Deleted) = payload.status {
+                    return Ok((true, service.delete_documents(txn, docuser_id, &doc_ids, &cloned_actor).await?));
+                }
+                entity::docorg::Entity::update_many()
+                    .col_expr(entity::docorg::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+                    .filter(entity::docorg::Column::Id.is_in(doc_ids.clone()))
+                    .exec(txn)
+                    .await?;
+                let mut recorded = Vec::new();
+                for doc_id in doc_ids {
+                    recorded.push(events::record(txn, docuser_id, ServerEvent::DocumentUpdated { doc_id }).await?);
+                }
+                if let Some(seq_id) = seq_id {
+                    recorded.push(events::record(txn, docuser_id, ServerEvent::SequenceReordered { seq_id }).await?);
+                }
+                Ok((false, recorded))
+            })
+        }).await?;
+
+        for recorded in recorded {
+            events::send(&self.state, recorded).await;
+        }
+        if deleted {
+            return Ok(());
+        }
+
+        // tags and scopes can make documents match saved queries
+        for &doc_id in &doc_ids {
+            watch::document(self.state.clone(), doc_id);
+        }
+        // the scopes decide the rendering profile, rendering again also rebuilds the sites
+        if scopes_changed {
+            conversion::rerender(self.state.clone(), doc_ids);
+        } else {
+            for doc_id in doc_ids {
+                site::request_for_document(self.state.clone(), doc_id);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * copy of a saved document with its tags and scopes.
+     * attachments are uploaded again, the copy owns its files and either document can be deleted alone.
+     */
+    pub async fn duplicate(&self, docuser_id: i32, doc_id: i32, actor: &Actor) -> Result<i32, GlobalError> {
+        let document = entity::docorg::Entity::find_by_id(doc_id)
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotExist)?;
+        let scope_ids = entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::DocorgId.eq(doc_id))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|doc_scope| doc_scope.scope_id)
+            .collect::<Vec<_>>();
+        let tags = entity::tag::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::docorg_tag::Relation::Tag.def())
+            .filter(entity::docorg_tag::Column::DocorgId.eq(doc_id))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|tag| tag.value)
+            .collect::<Vec<_>>();
+        let docfiles = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::DocorgId.eq(doc_id))
+            .filter(entity::docfile::Column::IsFixed.eq(true))
+            .all(&self.state.db_conn)
+            .await?;
+
+        let service = self.clone();
+        let entry = actor.entry("document.duplicate")
+            .scopes(scope_ids.clone())
+            .summary(serde_json::json!({
+                "source": doc_id,
+                "title": document.title,
+                "files": docfiles.len(),
+            }));
+        // objects copied so far, removed again when the copy is rolled back
+        let copied = Arc::new(Mutex::new(Vec::new()));
+        let cloned_copied = copied.clone();
+        let res = self.state.db_conn.clone().transaction::<_, (i32, String, events::Recorded), GlobalError>(|txn| {
+            Box::pin(async move {
+                let mut new_document = entity::docorg::ActiveModel {
+                    title: Set(document.title),
+                    raw: Set(document.raw.clone()),
+                    docuser_id: Set(docuser_id),
+                    status: Set(DocumentStatus::CREATED as i32),
+                    meta: Set(document.meta),
+                    ..Default::default()
+                };
+                DocumentService::set_derived(&mut new_document, &document.raw);
+                let document_id = entity::docorg::Entity::insert(new_document).exec(txn).await?.last_insert_id;
+
+                let scopes = scope_ids.iter().map(|&scope_id| {
+                    entity::docorg_scope::ActiveModel {
+                        docorg_id: Set(document_id),
+                        scope_id: Set(scope_id),
+                        ..Default::default()
+                    }
+                }).collect::<Vec<_>>();
+                if !scopes.is_empty() {
+                    entity::docorg_scope::Entity::insert_many(scopes).exec(txn).await?;
+                }
+                service.attach_tags(txn, document_id, tags).await?;
+
+                let new_convert = entity::convert::ActiveModel {
+                    docorg_id: Set(document_id),
+                    c_type: Set(0),
+                    status: Set(0),
+                    ..Default::default()
+                };
+                entity::convert::Entity::insert(new_convert).exec(txn).await?;
+                entry.targets([document_id]).record(txn).await?;
+
+                /*
+                 * the files are copied one at a time and fixed to the copy,
+                 * the links are pointed to the copies once all of them exist
+                 */
+                let file_proxy_addr = service.state.file_proxy_addr.lock().await.clone();
+                let mut upload_client = UploadClient::connect(file_proxy_addr).await?;
+                // object id of the original -> object id of the copy
+                let mut object_ids = HashMap::new();
+                for docfile in &docfiles {
+                    let (head, mut stream) = service.download_stream(docfile.object_id.clone()).await?;
+                    let mut data = head.data;
+                    while let Some(chunk) = stream.message().await? {
+                        data.extend(chunk.data);
+                    }
+                    let object_id = upload_client.pre_upload(Request::new(PreUploadRequest {
+                        name: head.name,
+                        ftype: head.ftype,
+                        size: data.len() as u64,
+                        docuser_id,
+                        data,
+                        object_id: None,
+                    })).await?.into_inner().object_id;
+                    cloned_copied.lock().unwrap().push(object_id.clone());
+                    upload_client.upload(Request::new(UploadRequest {
+                        doc_id: document_id,
+                        object_id: object_id.clone(),
+                    })).await?;
+                    object_ids.insert(docfile.object_id.clone(), object_id);
+                }
+                let raw = archive::replace_objects(&document.raw, &object_ids);
+                if raw != document.raw {
+                    entity::docorg::Entity::update_many()
+                        .col_expr(entity::docorg::Column::Raw, Expr::value(raw.clone()))
+                        .filter(entity::docorg::Column::Id.eq(document_id))
+                        .exec(txn)
+                        .await?;
+                }
+                Ok((document_id, raw, events::record(txn, docuser_id, ServerEvent::DocumentCreated { doc_id: document_id }).await?))
+            })
+        }).await;
+
+        let (document_id, raw, created) = match res {
+            Ok(res) => res,
+            Err(e) => {
+                let object_ids = copied.lock().unwrap().clone();
+                self.remove_objects(object_ids).await;
+                return Err(e.into());
+            },
+        };
+        conversion::convert_to_html(self.state.clone(), (document_id, 0), raw);
+        events::send(&self.state, created).await;
+        Ok(document_id)
+    }
+
+    /*
+     * attachments follow the file/<object_id> links of the raw: uploads linked for the first time are fixed to the document,
+     * the ones not linked anymore are deleted with their conversions
+     */
+    pub async fn sync_files(&self, doc_id: i32, raw: &str) -> Result<(), GlobalError> {
+        let objs = entity::docfile::Entity::find()
+            .filter(entity::docfile::Column::DocorgId.eq(doc_id))
+            .column_as(entity::docfile::Column::ObjectId, "object_id")
+            .into_model::<Obj>()
+            .all(&self.state.db_conn)
+            .await?;
+
+        let re = Regex::new(r"file/((?:\[??[^\[\]]*?\)))").unwrap();
+        let mut set = objs.into_iter().map(|obj| obj.object_id).collect::<HashSet<_>>();
+
+        let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+        let mut upload_client = UploadClient::connect(file_proxy_addr.clone()).await?;
+        let mut delete_client = DeleteClient::connect(file_proxy_addr).await?;
+
+        for m in re.find_iter(raw) {
+            let mut chars = m.as_str().chars();
+            for _ in 0..5 {
+                chars.next();
+            }
+            chars.next_back();
+
+            if !set.contains(chars.as_str()) {
+                upload_client.upload(Request::new(UploadRequest { object_id: chars.as_str().to_owned(), doc_id })).await?;
+            }
+            set.remove(chars.as_str());
+        }
+
+        let set = set.into_iter().collect::<Vec<_>>();
+        if !set.is_empty() {
+            delete_client.delete(Request::new(DeleteRequest { object_ids: set.clone() })).await?;
+        }
+        for obj in set {
+            entity::docfile::Entity::delete_many()
+                .filter(entity::docfile::Column::ObjectId.eq(&obj))
+                .exec(&self.state.db_conn)
+                .await?;
+            entity::convert::Entity::delete_many()
+                .filter(entity::convert::Column::Data.eq(&obj))
+                .exec(&self.state.db_conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // files of a rolled back change, a failure is only logged, the change failed already
+    pub async fn remove_objects(&self, object_ids: Vec<String>) {
+        if object_ids.is_empty() {
+            return;
+        }
+        let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+        let removed = match DeleteClient::connect(file_proxy_addr).await {
+            Ok(mut delete_client) => delete_client.delete(Request::new(DeleteRequest { object_ids })).await.map(|_| ()).map_err(GlobalError::from),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = removed {
+            dbg!(e);
+        }
+    }
+
+    async fn import_document(&self, import: &mut ArchiveImport<'_>, path: &str) -> Result<(i32, usize), GlobalError> {
+        let (docuser_id, name, entries, options, actor) = (import.docuser_id, import.name, import.entries, import.options, import.actor);
+        let (upload_client, sequences, uploads) = (&mut import.upload_client, &mut import.sequences, &mut import.uploads);
+        let raw = String::from_utf8(entries[path].clone()).map_err(|_| DocumentError::NotUtf8Document)?;
+
+        let mut tags = if options.folder_tags { archive::folders(path) } else { Vec::new() };
+        let mut scope_ids = options.scope_ids.clone();
+        let mut seq_id = None;
+        let resolved = self.apply_front_matter(&raw, &mut tags, &mut scope_ids, &mut seq_id)?;
+        self.check_user_has_scope(docuser_id, &scope_ids[..]).await?;
+
+        if let Some(seq_id) = seq_id {
+            entity::sequence::Entity::find_by_id(seq_id)
+                .filter(entity::sequence::Column::DocuserId.eq(docuser_id))
+                .one(&self.state.db_conn)
+                .await?
+                .ok_or(ResourceError::SequenceNotExist)?;
+        }
+
+        /*
+         * upload referenced files and point the links to them
+         */
+        let base_dir = archive::parent(path);
+        let mut targets = BTreeSet::new();
+        archive::rewrite_links(&raw, |target| {
+            if archive::is_local(target) && !archive::is_markdown(target) {
+                if let Some(target) = archive::resolve(base_dir, target) {
+                    targets.insert(target);
+                }
+            }
+            None
+        });
+
+        /*
+         * a file is uploaded once per archive, the first document linking to it owns it.
+         * files not fixed yet(their document failed) are fixed to this one.
+         */
+        let mut object_ids = HashMap::new();
+        let mut unfixed = Vec::new();
+        for target in targets {
+            if let Some(upload) = uploads.get(&target) {
+                if !upload.fixed {
+                    unfixed.push(target.clone());
+                }
+                object_ids.insert(target, upload.object_id.clone());
+                continue;
+            }
+            let data = match entries.get(&target) {
+                Some(data) => data.clone(),
+                None => continue,
+            };
+            let res = upload_client.pre_upload(Request::new(PreUploadRequest {
+                name: archive::file_name(&target).to_string(),
+                ftype: archive::ftype(&target).to_string(),
+                size: data.len() as u64,
+                docuser_id,
+                data,
+                object_id: None,
+            })).await?;
+            let object_id = res.into_inner().object_id;
+            uploads.insert(target.clone(), ArchiveUpload { object_id: object_id.clone(), fixed: false });
+            unfixed.push(target.clone());
+            object_ids.insert(target, object_id);
+        }
+        let raw = archive::rewrite_links(&raw, |target| {
+            if !archive::is_local(target) {
+                return None;
+            }
+            archive::resolve(base_dir, target)
+                .and_then(|target| object_ids.get(&target))
+                .map(|object_id| format!("file/{}", object_id))
+        });
+
+        // one sequence per folder, created along with the first document of the folder
+        let folder = match (options.sequence, base_dir) {
+            (false, _) => None,
+            (true, "") => Some(name.trim_end_matches(".zip").to_string()),
+            (true, dir) => Some(dir.to_string()),
+        };
+        let folder_seq_id = folder.as_ref().and_then(|folder| sequences.get(folder).copied());
+
+        let service = self.clone();
+        let cloned_raw = raw.clone();
+        let cloned_folder = folder.clone();
+        let entry = actor.entry("document.import")
+            .scopes(scope_ids.clone())
+            .summary(serde_json::json!({
+                "title": resolved.title,
+                "path": path,
+                "archive": name,
+                "tags": tags,
+                "files": object_ids.len(),
+                "length": raw.len(),
+            }));
+        let sequence_entry = actor.entry("sequence.create").scopes(scope_ids.clone());
+        let mut fix_client = upload_client.clone();
+        let fix_ids = unfixed.iter().map(|target| object_ids[target].clone()).collect::<Vec<_>>();
+        let (document_id, seq_id, created) = self.state.db_conn.clone().transaction::<_, (i32, Option<i32>, events::Recorded), GlobalError>(|txn| {
+            Box::pin(async move {
+                let mut new_document = entity::docorg::ActiveModel {
+                    title: Set(resolved.title.clone()),
+                    raw: Set(cloned_raw.clone()),
+                    docuser_id: Set(docuser_id),
+                    status: Set(DocumentStatus::CREATED as i32),
+                    meta: Set(resolved.meta.clone()),
+                    ..Default::default()
+                };
+                if let Some(created_at) = resolved.created_at {
+                    new_document.created_at = Set(created_at);
+                }
+                DocumentService::set_derived(&mut new_document, &cloned_raw);
+                let document_id = entity::docorg::Entity::insert(new_document).exec(txn).await?.last_insert_id;
+
+                let scopes = scope_ids.iter().map(|&scope_id| {
+                    entity::docorg_scope::ActiveModel {
+                        docorg_id: Set(document_id),
+                        scope_id: Set(scope_id),
+                        ..Default::default()
+                    }
+                }).collect::<Vec<_>>();
+                if !scopes.is_empty() {
+                    entity::docorg_scope::Entity::insert_many(scopes).exec(txn).await?;
+                }
+
+                service.attach_tags(txn, document_id, tags).await?;
+
+                let new_convert = entity::convert::ActiveModel {
+                    docorg_id: Set(document_id),
+                    c_type: Set(0),
+                    status: Set(0),
+                    ..Default::default()
+                };
+                entity::convert::Entity::insert(new_convert).exec(txn).await?;
+
+                let seq_id = match (cloned_folder, folder_seq_id) {
+                    (Some(_), Some(folder_seq_id)) => Some(folder_seq_id),
+                    (Some(title), None) => {
+                        let seq_id = service.create_sequence(txn, docuser_id, title.clone(), &scope_ids[..]).await?;
+                        sequence_entry.targets([seq_id])
+                            .summary(serde_json::json!({ "title": title, "import": true }))
+                            .record(txn)
+                            .await?;
+                        Some(seq_id)
+                    },
+                    (None, _) => seq_id,
+                };
+                if let Some(seq_id) = seq_id {
+                    service.append_to_sequence(txn, seq_id, document_id).await?;
+                }
+                entry.targets([document_id]).record(txn).await?;
+
+                // fixed last, a failure rolls the document back and leaves the files to the next one
+                for object_id in fix_ids {
+                    fix_client.upload(Request::new(UploadRequest {
+                        doc_id: document_id,
+                        object_id,
+                    })).await?;
+                }
+                Ok((document_id, seq_id, events::record(txn, docuser_id, ServerEvent::DocumentCreated { doc_id: document_id }).await?))
+            })
+        }).await?;
+
+        if let (Some(folder), Some(seq_id)) = (folder, seq_id) {
+            sequences.insert(folder, seq_id);
+        }
+        for target in unfixed {
+            if let Some(upload) = uploads.get_mut(&target) {
+                upload.fixed = true;
+            }
+        }
+        conversion::convert_to_html(self.state.clone(), (document_id, 0), raw);
+        events::send(&self.state, created).await;
+
+        Ok((document_id, object_ids.len()))
+    }
 }
 
+#[test]
+fn import_type_test() {
+    assert_eq!(DocumentService::import_type("Report.DOCX"), Some(IType::Docx));
+    assert_eq!(DocumentService::import_type("page.htm"), Some(IType::Html));
+    assert_eq!(DocumentService::import_type("paper.v2.tex"), Some(IType::Latex));
+    assert_eq!(DocumentService::import_type("notes.md"), None);
+    assert_eq!(DocumentService::import_type("docx"), None);
+}
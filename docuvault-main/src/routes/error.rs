@@ -13,6 +13,21 @@ use super::auth::error::AuthError;
 use super::document::error::DocumentError;
 use super::file::error::FileError;
 use super::resource::error::ResourceError;
+use super::template::error::TemplateError;
+use super::comment::error::CommentError;
+use super::export::error::ExportError;
+use super::site::error::SiteError;
+use super::share::error::ShareError;
+use super::theme::error::ThemeError;
+use super::feed::error::FeedError;
+use super::render::error::RenderError;
+use super::query::error::QueryError;
+use super::link::error::LinkError;
+use super::collab::error::CollabError;
+use super::webhook::error::WebhookError;
+use super::audit::error::AuditError;
+use super::lint::error::LintError;
+use super::schedule::error::ScheduleError;
 
 #[derive(Debug)]
 pub enum GlobalError {
@@ -22,12 +37,28 @@ pub enum GlobalError {
     DbTrxError,
     RedisError,
     RedisConnectionPoolError,
+    TooManyAttempts,
     GrpcError(String),
     Auth(AuthError),
     Document(DocumentError),
     Resource(ResourceError),
     File(FileError),
     Sequence(SequenceError),
+    Template(TemplateError),
+    Comment(CommentError),
+    Export(ExportError),
+    Site(SiteError),
+    Share(ShareError),
+    Theme(ThemeError),
+    Feed(FeedError),
+    Render(RenderError),
+    Query(QueryError),
+    Link(LinkError),
+    Collab(CollabError),
+    Webhook(WebhookError),
+    Audit(AuditError),
+    Lint(LintError),
+    Schedule(ScheduleError),
 }
 impl Display for GlobalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,12 +75,28 @@ impl IntoResponse for GlobalError {
             Self::DbTrxError => (StatusCode::INTERNAL_SERVER_ERROR, "Db transaction error").into_response(),
             Self::RedisError => (StatusCode::INTERNAL_SERVER_ERROR, "Redis error").into_response(),
             Self::RedisConnectionPoolError => (StatusCode::INTERNAL_SERVER_ERROR, "Redis bb8 connection pool error").into_response(),
+            Self::TooManyAttempts => (StatusCode::TOO_MANY_REQUESTS, "too many failed attempts, try again later").into_response(),
             Self::GrpcError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response(),
             Self::Auth(error) => error.into_response(),
             Self::Document(error) => error.into_response(),
             Self::Resource(error) => error.into_response(),
             Self::File(error) => error.into_response(),
             Self::Sequence(error) => error.into_response(),
+            Self::Template(error) => error.into_response(),
+            Self::Comment(error) => error.into_response(),
+            Self::Export(error) => error.into_response(),
+            Self::Site(error) => error.into_response(),
+            Self::Share(error) => error.into_response(),
+            Self::Theme(error) => error.into_response(),
+            Self::Feed(error) => error.into_response(),
+            Self::Render(error) => error.into_response(),
+            Self::Query(error) => error.into_response(),
+            Self::Link(error) => error.into_response(),
+            Self::Collab(error) => error.into_response(),
+            Self::Webhook(error) => error.into_response(),
+            Self::Audit(error) => error.into_response(),
+            Self::Lint(error) => error.into_response(),
+            Self::Schedule(error) => error.into_response(),
         }
     }
 }
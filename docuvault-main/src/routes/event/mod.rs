@@ -0,0 +1,45 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{Router, routing::get, extract::{State, Query}, response::{IntoResponse, sse::{Event, KeepAlive, Sse}}, http::{Method, HeaderValue}};
+use futures::StreamExt;
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod object;
+use object::*;
+pub mod service;
+use service::EventService;
+
+use super::error::GlobalError;
+
+/*
+ * server events of the user as a Server-Sent Events stream, see modules::events::ServerEvent.
+ * a "lagged" event means some were missed, the client should reload what it shows.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<EventService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(EventService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/stream", get(stream))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::GET])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn stream(State(state): State<ServiceState<EventService>>, Query(query): Query<StreamQuery>) -> Result<impl IntoResponse, GlobalError> {
+    let docuser_id = state.service.authorize(&query.token).await?;
+    let events = state.service.stream(docuser_id).map(|event| {
+        let event = match event {
+            Some(event) => Event::default().event(event.name()).json_data(&event).unwrap_or_default(),
+            None => Event::default().event("lagged").data(""),
+        };
+        Ok::<_, Infallible>(event)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+// the access token, EventSource can not set headers
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    pub token: String,
+}
@@ -0,0 +1,36 @@
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{AppState, modules::events::ServerEvent, routes::{error::GlobalError, auth::object::Claims}};
+
+#[derive(Clone, Debug)]
+pub struct EventService {
+    state: AppState,
+}
+impl EventService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    pub async fn authorize(&self, token: &str) -> Result<i32, GlobalError> {
+        let claims = Claims::verify(token, &self.state.db_conn, self.state.redis_conn.clone()).await?;
+        Ok(claims.user_id)
+    }
+
+    // events of the user and of the scopes shared with the user from now on, None where some were dropped for falling behind
+    pub fn stream(&self, docuser_id: i32) -> impl Stream<Item = Option<ServerEvent>> {
+        let rx = self.state.modules.events.subscribe();
+        futures::stream::unfold(rx, move |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok((id, event)) if id == docuser_id => return Some((Some(event), rx)),
+                    Ok(_) => {},
+                    Err(RecvError::Lagged(_)) => return Some((None, rx)),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum ExportError {
+    JobNotExist,
+    JobInProgress,
+    ArchiveNotReady,
+}
+impl IntoResponse for ExportError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::JobNotExist => (StatusCode::BAD_REQUEST, "target export job not exists."),
+            Self::JobInProgress => (StatusCode::CONFLICT, "another export job is in progress."),
+            Self::ArchiveNotReady => (StatusCode::CONFLICT, "export job is not done."),
+        };
+        res.into_response()
+    }
+}
+impl From<ExportError> for GlobalError {
+    fn from(value: ExportError) -> Self {
+        Self::Export(value)
+    }
+}
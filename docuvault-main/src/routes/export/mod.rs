@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::{get, post}, extract::{State, Path}, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::ExportService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<ExportService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(ExportService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/create", post(create))
+        .route("/status", post(status))
+        .route("/list", post(list))
+        .route("/download/:job_id", get(download))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::GET, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// the archive is built in background, poll /status until it is done
+async fn create(State(state): State<ServiceState<ExportService>>, claims: Claims, Json(payload): Json<ExportCreatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let job_id = state.service.create(claims.user_id, payload).await?;
+    Ok(Json(ExportCreateResponse { job_id }))
+}
+async fn status(State(state): State<ServiceState<ExportService>>, claims: Claims, Json(payload): Json<ExportStatusPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.status(claims.user_id, payload.job_id).await?;
+    Ok(Json(res))
+}
+async fn list(State(state): State<ServiceState<ExportService>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.list(claims.user_id).await?;
+    Ok(Json(res))
+}
+// only the owner of the job can download the archive
+async fn download(State(state): State<ServiceState<ExportService>>, claims: Claims, Path(job_id): Path<i32>) -> Result<impl IntoResponse, GlobalError> {
+    let (name, data) = state.service.download(claims.user_id, job_id).await?;
+    Ok(([(header::CONTENT_TYPE, "application/zip".to_string()), (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", name))], data))
+}
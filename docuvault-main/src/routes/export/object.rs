@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+// export_job.status
+pub enum ExportStatus {
+    PENDING = 0,
+    RUNNING = 1,
+    DONE = 2,
+    FAILED = 3,
+}
+
+// create
+#[derive(Debug, Deserialize)]
+pub struct ExportCreatePayload {
+    // empty: export the whole account
+    #[serde(default)]
+    pub scope_ids: Vec<i32>,
+    // export the documents of a saved query instead, scope_ids are ignored
+    pub saved_query_id: Option<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct ExportCreateResponse {
+    pub job_id: i32,
+}
+
+// status
+#[derive(Debug, Deserialize)]
+pub struct ExportStatusPayload {
+    pub job_id: i32,
+}
+// the archive is downloaded through /export/download/:job_id once status is DONE
+#[derive(Debug, Serialize)]
+pub struct ExportJobItem {
+    pub id: i32,
+    pub scope_ids: serde_json::Value,
+    pub status: i32,
+    pub processed: i32,
+    pub total: i32,
+    pub saved_query_id: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::export_job::Model> for ExportJobItem {
+    fn from(value: crate::entity::export_job::Model) -> Self {
+        Self {
+            id: value.id,
+            scope_ids: value.scope_ids,
+            status: value.status,
+            processed: value.processed,
+            total: value.total,
+            saved_query_id: value.saved_query_id,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use sea_orm::{entity::*, query::*};
+use tonic::Request;
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, background::export, filter::DocFilter, grpc::download::{download_client::DownloadClient, DownloadRequest}}, routes::{error::GlobalError, query::service::QueryService}};
+
+use super::{object::*, error::ExportError};
+
+#[derive(Clone, Debug)]
+pub struct ExportService {
+    state: AppState,
+}
+impl ExportService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    // one running export per user, an archive of the whole account can be large
+    pub async fn create(&self, docuser_id: i32, payload: ExportCreatePayload) -> Result<i32, GlobalError> {
+        let scope_ids = match payload.saved_query_id {
+            Some(query_id) => {
+                let query = QueryService::new(self.state.clone()).accessible(docuser_id, query_id).await?;
+                DocFilter::from_json(&query.filter).ok_or(GlobalError::InternalServerError)?.scope_ids
+            },
+            None => payload.scope_ids,
+        };
+        redis_does_docuser_have_scope(self.state.clone(), &scope_ids[..], docuser_id).await?;
+
+        export::reclaim(&self.state.db_conn, docuser_id).await?;
+        let running = entity::export_job::Entity::find()
+            .filter(entity::export_job::Column::DocuserId.eq(docuser_id))
+            .filter(entity::export_job::Column::Status.is_in([ExportStatus::PENDING as i32, ExportStatus::RUNNING as i32]))
+            .count(&self.state.db_conn)
+            .await?;
+        if running > 0 {
+            return Err(ExportError::JobInProgress.into());
+        }
+
+        let new_job = entity::export_job::ActiveModel {
+            docuser_id: Set(docuser_id),
+            scope_ids: Set(serde_json::json!(scope_ids)),
+            saved_query_id: Set(payload.saved_query_id),
+            status: Set(ExportStatus::PENDING as i32),
+            ..Default::default()
+        };
+        let job_id = entity::export_job::Entity::insert(new_job).exec(&self.state.db_conn).await?.last_insert_id;
+
+        export::export(self.state.clone(), job_id);
+        Ok(job_id)
+    }
+
+    pub async fn status(&self, docuser_id: i32, job_id: i32) -> Result<ExportJobItem, GlobalError> {
+        let job = entity::export_job::Entity::find_by_id(job_id)
+            .filter(entity::export_job::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ExportError::JobNotExist)?;
+        Ok(job.into())
+    }
+
+    pub async fn list(&self, docuser_id: i32) -> Result<Vec<ExportJobItem>, GlobalError> {
+        let jobs = entity::export_job::Entity::find()
+            .filter(entity::export_job::Column::DocuserId.eq(docuser_id))
+            .order_by_desc(entity::export_job::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(jobs.into_iter().map(|job| job.into()).collect())
+    }
+
+    // archive of a finished job of the user, as (file name, data)
+    pub async fn download(&self, docuser_id: i32, job_id: i32) -> Result<(String, Vec<u8>), GlobalError> {
+        let job = entity::export_job::Entity::find_by_id(job_id)
+            .filter(entity::export_job::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ExportError::JobNotExist)?;
+        let object_id = match job.object_id {
+            Some(object_id) if job.status == ExportStatus::DONE as i32 => object_id,
+            _ => return Err(ExportError::ArchiveNotReady.into()),
+        };
+
+        let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+        let mut client = DownloadClient::connect(file_proxy_addr).await?;
+        let res = client.download(Request::new(DownloadRequest { object_id })).await?.into_inner();
+        Ok((format!("docuvault-export-{}.zip", job_id), res.data))
+    }
+}
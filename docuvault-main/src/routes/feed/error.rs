@@ -0,0 +1,25 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum FeedError {
+    FeedNotExist,
+    InvalidFormat,
+    InvalidOrder,
+}
+impl IntoResponse for FeedError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::FeedNotExist => (StatusCode::NOT_FOUND, "target feed not exists."),
+            Self::InvalidFormat => (StatusCode::BAD_REQUEST, "feed format must be atom or rss."),
+            Self::InvalidOrder => (StatusCode::BAD_REQUEST, "feed order must be created or updated."),
+        };
+        res.into_response()
+    }
+}
+impl From<FeedError> for GlobalError {
+    fn from(value: FeedError) -> Self {
+        Self::Feed(value)
+    }
+}
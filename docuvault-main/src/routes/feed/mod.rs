@@ -0,0 +1,90 @@
+use std::env;
+use std::sync::Arc;
+
+use axum::{Router, routing::get, extract::{State, Path, Query, OriginalUri}, http::{Method, header, HeaderMap, StatusCode}, response::{IntoResponse, Response}};
+use tower_http::cors::{CorsLayer, Any};
+
+use crate::{AppState, common::object::ServiceState, modules::feed::{self, Feed}};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::FeedService;
+
+use super::error::GlobalError;
+
+/*
+ * Atom and RSS feeds of public scopes, tags in a public scope, sequences of public scopes
+ * and saved queries made public(limited to the public scopes among theirs).
+ * feeds are read without a login, readers revalidate with ETag / Last-Modified.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<FeedService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(FeedService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/scope/:scope_id/:format", get(scope_feed))
+        .route("/scope/:scope_id/tag/:tag/:format", get(tag_feed))
+        .route("/sequence/:sequence_id/:format", get(sequence_feed))
+        .route("/query/:query_id/:format", get(query_feed))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods([Method::OPTIONS, Method::GET])
+                .allow_headers([header::IF_NONE_MATCH, header::IF_MODIFIED_SINCE])
+            )
+        .with_state(service_state)
+}
+async fn scope_feed(State(state): State<ServiceState<FeedService>>, Path((scope_id, format)): Path<(i32, String)>, Query(query): Query<FeedQuery>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Result<Response, GlobalError> {
+    let format = format.parse::<FeedFormat>()?;
+    let feed = state.service.scope_feed(scope_id, None, query.order()?, feed_url(&uri)).await?;
+    Ok(respond(feed, format, &headers))
+}
+async fn tag_feed(State(state): State<ServiceState<FeedService>>, Path((scope_id, tag, format)): Path<(i32, String, String)>, Query(query): Query<FeedQuery>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Result<Response, GlobalError> {
+    let format = format.parse::<FeedFormat>()?;
+    let feed = state.service.scope_feed(scope_id, Some(tag), query.order()?, feed_url(&uri)).await?;
+    Ok(respond(feed, format, &headers))
+}
+async fn sequence_feed(State(state): State<ServiceState<FeedService>>, Path((sequence_id, format)): Path<(i32, String)>, Query(query): Query<FeedQuery>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Result<Response, GlobalError> {
+    let format = format.parse::<FeedFormat>()?;
+    let feed = state.service.sequence_feed(sequence_id, query.order()?, feed_url(&uri)).await?;
+    Ok(respond(feed, format, &headers))
+}
+async fn query_feed(State(state): State<ServiceState<FeedService>>, Path((query_id, format)): Path<(i32, String)>, Query(query): Query<FeedQuery>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Result<Response, GlobalError> {
+    let format = format.parse::<FeedFormat>()?;
+    let feed = state.service.query_feed(query_id, query.order()?, feed_url(&uri)).await?;
+    Ok(respond(feed, format, &headers))
+}
+
+fn feed_url(uri: &axum::http::Uri) -> String {
+    let base = env::var("PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+    let path = uri.path_and_query().map(|path| path.as_str()).unwrap_or_else(|| uri.path());
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+fn respond(feed: Feed, format: FeedFormat, headers: &HeaderMap) -> Response {
+    let body = match format {
+        FeedFormat::Atom => feed::atom(&feed),
+        FeedFormat::Rss => feed::rss(&feed),
+    };
+    let etag = feed::etag(body.as_bytes());
+    let last_modified = feed::http_date(&feed.updated);
+    let if_none_match = headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok());
+    let if_modified_since = headers.get(header::IF_MODIFIED_SINCE).and_then(|value| value.to_str().ok());
+    if feed::is_not_modified(if_none_match, if_modified_since, &etag, &feed.updated) {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::LAST_MODIFIED, last_modified)],
+        ).into_response();
+    }
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::ETAG, etag),
+            (header::LAST_MODIFIED, last_modified),
+        ],
+        body,
+    ).into_response()
+}
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::error::FeedError;
+
+#[derive(Debug, Clone, Copy)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+}
+impl FeedFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Atom => "application/atom+xml; charset=utf-8",
+            Self::Rss => "application/rss+xml; charset=utf-8",
+        }
+    }
+}
+impl FromStr for FeedFormat {
+    type Err = FeedError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "atom" => Ok(Self::Atom),
+            "rss" => Ok(Self::Rss),
+            _ => Err(FeedError::InvalidFormat),
+        }
+    }
+}
+
+// entries are the latest documents by creation or by last update
+#[derive(Debug, Clone, Copy)]
+pub enum FeedOrder {
+    Created,
+    Updated,
+}
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    pub order: Option<String>,
+}
+impl FeedQuery {
+    pub fn order(&self) -> Result<FeedOrder, FeedError> {
+        match self.order.as_deref() {
+            None | Some("created") => Ok(FeedOrder::Created),
+            Some("updated") => Ok(FeedOrder::Updated),
+            Some(_) => Err(FeedError::InvalidOrder),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::env;
+
+use sea_orm::{entity::*, query::*, sea_query::Query};
+
+use crate::{AppState, entity, modules::{feed::{Feed, Entry}, filter::{self, DocFilter}, markdown::render, background::conversion, theme}, routes::{error::GlobalError, document::object::DocumentStatus}};
+
+use super::{object::FeedOrder, error::FeedError};
+
+const FEED_LEN: u64 = 50;
+
+#[derive(Clone, Debug)]
+pub struct FeedService {
+    state: AppState,
+}
+impl FeedService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    // documents of a public scope, optionally only the ones with the given tag
+    pub async fn scope_feed(&self, scope_id: i32, tag: Option<String>, order: FeedOrder, url: String) -> Result<Feed, GlobalError> {
+        let scope = entity::scope::Entity::find_by_id(scope_id)
+            .filter(entity::scope::Column::Public.eq(true))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(FeedError::FeedNotExist)?;
+
+        let mut select = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::Id.in_subquery(Query::select()
+                .column(entity::docorg_scope::Column::DocorgId)
+                .from(entity::docorg_scope::Entity)
+                .and_where(entity::docorg_scope::Column::ScopeId.eq(scope.id))
+                .to_owned()));
+        let (id, title) = match &tag {
+            Some(tag) => {
+                select = select.filter(entity::docorg::Column::Id.in_subquery(Query::select()
+                    .column(entity::docorg_tag::Column::DocorgId)
+                    .from(entity::docorg_tag::Entity)
+                    .and_where(entity::docorg_tag::Column::TagId.in_subquery(Query::select()
+                        .column(entity::tag::Column::Id)
+                        .from(entity::tag::Entity)
+                        .and_where(entity::tag::Column::Value.eq(tag.as_str()))
+                        .to_owned()))
+                    .to_owned()));
+                (format!("urn:docuvault:scope:{}:tag:{}", scope.id, tag), format!("{} #{}", scope.name, tag))
+            },
+            None => (format!("urn:docuvault:scope:{}", scope.id), scope.name.clone()),
+        };
+        let entries = self.entries(select, order, &[scope.id]).await?;
+        Ok(feed(id, title, url, entries))
+    }
+
+    // documents of a sequence, as far as they belong to a public scope the sequence is linked to
+    pub async fn sequence_feed(&self, sequence_id: i32, order: FeedOrder, url: String) -> Result<Feed, GlobalError> {
+        let sequence = entity::sequence::Entity::find_by_id(sequence_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(FeedError::FeedNotExist)?;
+        let scope_ids = entity::scope::Entity::find()
+            .join_rev(JoinType::InnerJoin, entity::scope_sequence::Relation::Scope.def())
+            .filter(entity::scope_sequence::Column::SequenceId.eq(sequence.id))
+            .filter(entity::scope::Column::Public.eq(true))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|scope| scope.id)
+            .collect::<Vec<_>>();
+        if scope_ids.is_empty() {
+            return Err(FeedError::FeedNotExist.into());
+        }
+
+        let select = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::Id.in_subquery(Query::select()
+                .column(entity::docorg_sequence::Column::DocorgId)
+                .from(entity::docorg_sequence::Entity)
+                .and_where(entity::docorg_sequence::Column::SequenceId.eq(sequence.id))
+                .to_owned()))
+            .filter(entity::docorg::Column::Id.in_subquery(Query::select()
+                .column(entity::docorg_scope::Column::DocorgId)
+                .from(entity::docorg_scope::Entity)
+                .and_where(entity::docorg_scope::Column::ScopeId.is_in(scope_ids.clone()))
+                .to_owned()));
+        let entries = self.entries(select, order, &scope_ids).await?;
+        Ok(feed(format!("urn:docuvault:sequence:{}", sequence.id), sequence.title, url, entries))
+    }
+
+    // documents of a public saved query, as far as they belong to a public scope of the query
+    pub async fn query_feed(&self, query_id: i32, order: FeedOrder, url: String) -> Result<Feed, GlobalError> {
+        let query = entity::saved_query::Entity::find_by_id(query_id)
+            .filter(entity::saved_query::Column::Public.eq(true))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(FeedError::FeedNotExist)?;
+        let filter = DocFilter::from_json(&query.filter).ok_or(FeedError::FeedNotExist)?;
+        let scope_ids = entity::scope::Entity::find()
+            .filter(entity::scope::Column::Id.is_in(filter.scope_ids.clone()))
+            .filter(entity::scope::Column::DocuserId.eq(query.docuser_id))
+            .filter(entity::scope::Column::Public.eq(true))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|scope| scope.id)
+            .collect::<Vec<_>>();
+        if scope_ids.is_empty() {
+            return Err(FeedError::FeedNotExist.into());
+        }
+
+        let select = filter.select(query.docuser_id)
+            .filter(entity::docorg::Column::Id.in_subquery(filter::in_scopes(&scope_ids)));
+        let entries = self.entries(select, order, &scope_ids).await?;
+        Ok(feed(format!("urn:docuvault:query:{}", query.id), query.name, url, entries))
+    }
+
+    /*
+     * the latest created documents with their html(converted on save, rendered here otherwise).
+     * links to other documents point at their published page in one of the scopes.
+     */
+    async fn entries(&self, select: Select<entity::docorg::Entity>, order: FeedOrder, scope_ids: &[i32]) -> Result<Vec<Entry>, GlobalError> {
+        let base = env::var("PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+        let page_url = |link_id: &str| format!("{}/document/page/{}", base.trim_end_matches('/'), link_id);
+
+        let column = match order {
+            FeedOrder::Created => entity::docorg::Column::CreatedAt,
+            FeedOrder::Updated => entity::docorg::Column::UpdatedAt,
+        };
+        let documents = select
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .order_by_desc(column)
+            .order_by_desc(entity::docorg::Column::Id)
+            .limit(FEED_LEN)
+            .all(&self.state.db_conn)
+            .await?;
+        let doc_ids = documents.iter().map(|document| document.id).collect::<Vec<_>>();
+
+        let converts = entity::convert::Entity::find()
+            .filter(entity::convert::Column::DocorgId.is_in(doc_ids.clone()))
+            .filter(entity::convert::Column::CType.eq(0))
+            .filter(entity::convert::Column::Status.eq(1))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .filter_map(|convert| convert.data.map(|data| (convert.docorg_id, data)))
+            .collect::<HashMap<_, _>>();
+        let doc_tags = entity::docorg_tag::Entity::find()
+            .filter(entity::docorg_tag::Column::DocorgId.is_in(doc_ids))
+            .find_also_related(entity::tag::Entity)
+            .all(&self.state.db_conn)
+            .await?;
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for (doc_tag, tag) in doc_tags {
+            if let Some(tag) = tag {
+                tags.entry(doc_tag.docorg_id).or_default().push(tag.value);
+            }
+        }
+        // open html links of the scopes, the latest one per document
+        let pages = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::ScopeId.is_in(scope_ids.to_vec()))
+            .filter(entity::share_link::Column::CType.eq(0))
+            .filter(entity::share_link::Column::Revoked.eq(false))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::ExpiresAt.is_null())
+                .add(entity::share_link::Column::ExpiresAt.gt(chrono::Utc::now().naive_utc())))
+            .order_by_asc(entity::share_link::Column::Id)
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|share_link| (share_link.docorg_id, page_url(&share_link.link_id)))
+            .collect::<HashMap<_, _>>();
+
+        // documents not converted yet are rendered the way their conversion will be
+        let mut entries = Vec::with_capacity(documents.len());
+        for document in documents {
+            let html = match converts.get(&document.id) {
+                Some(html) => html.clone(),
+                None => render::render(&document.raw, &conversion::profile_of(&self.state, document.id).await?),
+            };
+            entries.push(Entry {
+                id: format!("urn:docuvault:document:{}", document.id),
+                title: document.title,
+                url: pages.get(&document.id).cloned(),
+                html: theme::rewrite_links(&html, &base, &pages),
+                tags: tags.remove(&document.id).unwrap_or_default(),
+                published: document.created_at,
+                updated: document.updated_at,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+// an empty feed was last modified at the epoch
+fn feed(id: String, title: String, url: String, entries: Vec<Entry>) -> Feed {
+    let updated = entries.iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_default();
+    Feed { id, title, url, updated, entries }
+}
@@ -1,22 +1,23 @@
-use std::env;
+use std::{env, net::SocketAddr};
 
-use axum::{Router, extract::{State, Query, Multipart, DefaultBodyLimit, Path, BodyStream}, routing::{get, post}, response::IntoResponse, body::Bytes, headers::ContentType, http::{header, Method, HeaderValue}, Json,};
+use axum::{Router, extract::{State, Query, Multipart, DefaultBodyLimit, Path, BodyStream, ConnectInfo}, routing::{get, post}, response::IntoResponse, body::Bytes, headers::ContentType, http::{header, Method, HeaderValue}, Json,};
 use sha2::{Sha256, Digest};
 use chrono::Duration;
 use serde::{Deserialize, Serialize};
+use sea_orm::EntityTrait;
 use tonic::Request;
 use tower_http::cors::CorsLayer;
 use futures::StreamExt;
 use futures_util::stream;
 
-use crate::{AppState, modules::grpc::{voting::{voting_client::VotingClient, VotingRequest}, upload::{upload_client::UploadClient, PreUploadRequest, UploadRequest}, download::{download_client::DownloadClient, DownloadRequest}}, db::schema::redis::{File, RedisSchemaHeader}};
+use crate::{AppState, entity, modules::{audit::{self, Actor}, role::{self, Role}, grpc::{voting::{voting_client::VotingClient, VotingRequest}, upload::{upload_client::UploadClient, PreUploadRequest, UploadRequest}, download::{download_client::DownloadClient, DownloadRequest}}}, db::schema::redis::{File, RedisSchemaHeader}};
 
 pub mod error;
 use error::*;
 mod object;
 use object::*;
 
-use super::{error::GlobalError, auth::object::Claims};
+use super::{error::GlobalError, auth::object::Claims, document::error::DocumentError};
 
 pub fn create_router(shared_state: AppState) -> Router {
     Router::new()
@@ -35,11 +36,15 @@ pub fn create_router(shared_state: AppState) -> Router {
 }
 
 
+/*
+ * files are stored by the file service, the changes are recorded in the audit log once it answered
+ */
 //preupload
-async fn upload(State(state): State<AppState>, claims: Claims, mut multipart: Multipart) -> Result<impl IntoResponse, GlobalError> {
+async fn upload(State(state): State<AppState>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, mut multipart: Multipart) -> Result<impl IntoResponse, GlobalError> {
     let file_proxy_addr = env::var("FILE_PROXY_ADDR").expect("file proxy addr is not set.");
     let mut upload_client = UploadClient::connect(file_proxy_addr).await.unwrap();
     let mut object_ids = vec![];
+    let mut sizes = vec![];
     #[derive(Serialize)]
     struct Resource {
         name: String,
@@ -61,12 +66,24 @@ async fn upload(State(state): State<AppState>, claims: Claims, mut multipart: Mu
             data,
         });
         let response = upload_client.pre_upload(request).await.unwrap();
+        sizes.push(size);
         object_ids.push(Resource{
             name,
             ftype,
             object_id: response.into_inner().object_id,
         });
     }
+    if !object_ids.is_empty() {
+        Actor::new(claims.user_id, addr)
+            .entry("file.upload")
+            .targets(object_ids.iter().map(|resource| resource.object_id.clone()))
+            .summary(serde_json::json!({
+                "names": object_ids.iter().map(|resource| resource.name.clone()).collect::<Vec<_>>(),
+                "sizes": sizes,
+            }))
+            .record(&state.db_conn)
+            .await?;
+    }
     Ok(Json(object_ids))
 }
 #[derive(Deserialize)]
@@ -74,13 +91,30 @@ struct UploadfixPayload{
     doc_id: i32,
     object_id: String,
 }
-async fn uploadfix(State(state): State<AppState>, Json(payload): Json<UploadfixPayload>) -> Result<impl IntoResponse, GlobalError> {
+// attaching changes the document, so only its owner and the editors of its scopes can
+async fn uploadfix(State(state): State<AppState>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<UploadfixPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let document = entity::docorg::Entity::find_by_id(payload.doc_id)
+        .one(&state.db_conn)
+        .await?
+        .ok_or(DocumentError::DocumentNotExist)?;
+    if !role::document_has_role(&state.db_conn, claims.user_id, &document, Role::Editor).await? {
+        return Err(DocumentError::DocumentNotExist.into());
+    }
+
     let file_proxy_addr = env::var("FILE_PROXY_ADDR").expect("file proxy addr is not set.");
     let mut upload_client = UploadClient::connect(file_proxy_addr).await.unwrap();
     let res = upload_client.upload(UploadRequest {
         doc_id: payload.doc_id,
-        object_id: payload.object_id,
+        object_id: payload.object_id.clone(),
     }).await?;
+
+    Actor::new(claims.user_id, addr)
+        .entry("file.attach")
+        .targets([payload.object_id])
+        .scopes(audit::document_scopes(&state.db_conn, &[payload.doc_id]).await?)
+        .summary(serde_json::json!({ "doc_id": payload.doc_id }))
+        .record(&state.db_conn)
+        .await?;
     Ok(res.into_inner().msg)
 }
 
@@ -0,0 +1,23 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum LinkError {
+    TargetRequired,
+    DocumentNotExist,
+}
+impl IntoResponse for LinkError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::TargetRequired => (StatusCode::BAD_REQUEST, "either doc_id or scope_id is required."),
+            Self::DocumentNotExist => (StatusCode::BAD_REQUEST, "document does not exist."),
+        };
+        res.into_response()
+    }
+}
+impl From<LinkError> for GlobalError {
+    fn from(value: LinkError) -> Self {
+        Self::Link(value)
+    }
+}
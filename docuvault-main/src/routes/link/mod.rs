@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::LinkService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * link checker: attachments, links to documents and share pages, wiki links,
+ * heading anchors and(on request) external urls.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<LinkService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(LinkService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/check", post(check))
+        .route("/report", post(report))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// runs in the background, the results show up in /report
+async fn check(State(state): State<ServiceState<LinkService>>, claims: Claims, Json(payload): Json<LinkCheckPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let documents = state.service.check(claims.user_id, payload).await?;
+    Ok(Json(LinkCheckResponse { documents }))
+}
+async fn report(State(state): State<ServiceState<LinkService>>, claims: Claims, Json(payload): Json<LinkReportPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.report(claims.user_id, payload).await?))
+}
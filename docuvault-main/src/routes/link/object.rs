@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+// link_check.status
+pub enum LinkStatus {
+    OK = 0,
+    BROKEN = 1,
+    // external urls when the check ran without probing them, or pointing at internal addresses
+    SKIPPED = 2,
+}
+
+// check, either one document or every document of a scope
+#[derive(Debug, Deserialize)]
+pub struct LinkCheckPayload {
+    pub doc_id: Option<i32>,
+    pub scope_id: Option<i32>,
+    // probe external urls too(slow, goes out to the network)
+    #[serde(default)]
+    pub external: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct LinkCheckResponse {
+    // documents queued for the check
+    pub documents: usize,
+}
+
+// report
+#[derive(Debug, Deserialize)]
+pub struct LinkReportPayload {
+    pub doc_id: Option<i32>,
+    pub scope_id: Option<i32>,
+    #[serde(default)]
+    pub broken_only: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct LinkReportDocument {
+    pub doc_id: i32,
+    pub title: String,
+    pub links: Vec<LinkReportItem>,
+}
+#[derive(Debug, Serialize)]
+pub struct LinkReportItem {
+    pub url: String,
+    pub kind: String,
+    pub line: i32,
+    pub status: i32,
+    pub detail: Option<String>,
+    pub checked_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::link_check::Model> for LinkReportItem {
+    fn from(value: crate::entity::link_check::Model) -> Self {
+        Self {
+            url: value.url,
+            kind: value.kind,
+            line: value.line,
+            status: value.status,
+            detail: value.detail,
+            checked_at: value.checked_at,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use sea_orm::{entity::*, query::*};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, background::links, filter::in_scopes}, routes::{error::GlobalError, document::object::DocumentStatus}};
+
+use super::{object::*, error::LinkError};
+
+#[derive(Clone, Debug)]
+pub struct LinkService {
+    state: AppState,
+}
+impl LinkService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    // documents of the user the request is about, by id
+    async fn documents(&self, docuser_id: i32, doc_id: Option<i32>, scope_id: Option<i32>) -> Result<Vec<entity::docorg::Model>, GlobalError> {
+        let select = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32));
+        let select = match (doc_id, scope_id) {
+            (Some(doc_id), _) => select.filter(entity::docorg::Column::Id.eq(doc_id)),
+            (None, Some(scope_id)) => {
+                redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+                select.filter(entity::docorg::Column::Id.in_subquery(in_scopes(&[scope_id])))
+            },
+            (None, None) => return Err(LinkError::TargetRequired.into()),
+        };
+        let documents = select
+            .order_by_asc(entity::docorg::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        if doc_id.is_some() && documents.is_empty() {
+            return Err(LinkError::DocumentNotExist.into());
+        }
+        Ok(documents)
+    }
+
+    pub async fn check(&self, docuser_id: i32, payload: LinkCheckPayload) -> Result<usize, GlobalError> {
+        let doc_ids = self.documents(docuser_id, payload.doc_id, payload.scope_id).await?
+            .into_iter()
+            .map(|document| document.id)
+            .collect::<Vec<_>>();
+        let count = doc_ids.len();
+        links::check(self.state.clone(), doc_ids, payload.external);
+        Ok(count)
+    }
+
+    // documents never checked are left out
+    pub async fn report(&self, docuser_id: i32, payload: LinkReportPayload) -> Result<Vec<LinkReportDocument>, GlobalError> {
+        let documents = self.documents(docuser_id, payload.doc_id, payload.scope_id).await?;
+        let results = entity::link_check::Entity::find()
+            .filter(entity::link_check::Column::DocorgId.is_in(documents.iter().map(|document| document.id).collect::<Vec<_>>()))
+            .apply_if(payload.broken_only.then_some(LinkStatus::BROKEN as i32), |query, status| {
+                query.filter(entity::link_check::Column::Status.eq(status))
+            })
+            .order_by_asc(entity::link_check::Column::Line)
+            .order_by_asc(entity::link_check::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        let mut results_of: HashMap<i32, Vec<LinkReportItem>> = HashMap::new();
+        for result in results {
+            results_of.entry(result.docorg_id).or_default().push(result.into());
+        }
+
+        let report = documents.into_iter()
+            .filter_map(|document| results_of.remove(&document.id).map(|links| LinkReportDocument {
+                doc_id: document.id,
+                title: document.title,
+                links,
+            }))
+            .collect();
+        Ok(report)
+    }
+}
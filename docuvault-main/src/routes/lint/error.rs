@@ -0,0 +1,27 @@
+use axum::{response::IntoResponse, http::StatusCode, Json};
+
+use crate::{routes::error::GlobalError, modules::markdown::lint::Diagnostic};
+
+#[derive(Debug)]
+pub enum LintError {
+    InvalidConfig,
+    // a save with lint errors, all the diagnostics are returned so the editor can show them at once
+    Rejected(Vec<Diagnostic>),
+}
+impl IntoResponse for LintError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::InvalidConfig => (StatusCode::BAD_REQUEST, "unknown lint rule or max_line_length of 0."),
+            Self::Rejected(diagnostics) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+                "msg": "document has lint errors.",
+                "diagnostics": diagnostics,
+            }))).into_response(),
+        };
+        res.into_response()
+    }
+}
+impl From<LintError> for GlobalError {
+    fn from(value: LintError) -> Self {
+        Self::Lint(value)
+    }
+}
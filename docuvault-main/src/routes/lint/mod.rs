@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::markdown::lint::{self, RULES}};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::LintService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * lint rules of documents, run by /document/create and /document/update as they save.
+ * /check is the same lint without saving, for editors.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<LintService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(LintService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/check", post(check))
+        .route("/get", post(get))
+        .route("/user", post(user))
+        .route("/scope", post(scope))
+        .route("/rules", post(rules))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn check(State(state): State<ServiceState<LintService>>, claims: Claims, Json(payload): Json<LintCheckPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let diagnostics = state.service.check(claims.user_id, &payload.raw, payload.scope_ids).await?;
+    let rejected = lint::has_errors(&diagnostics);
+    Ok(Json(LintCheckResponse { diagnostics, rejected }))
+}
+// config of the user, or of the given scope
+async fn get(State(state): State<ServiceState<LintService>>, claims: Claims, Json(payload): Json<LintGetPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let config = state.service.get(claims.user_id, payload.scope_id).await?;
+    Ok(Json(LintConfigResponse { config }))
+}
+async fn user(State(state): State<ServiceState<LintService>>, claims: Claims, Json(payload): Json<LintUserPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.set_user(claims.user_id, payload.config).await?;
+    Ok(())
+}
+async fn scope(State(state): State<ServiceState<LintService>>, claims: Claims, Json(payload): Json<LintScopePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.set_scope(claims.user_id, payload.scope_id, payload.config).await?;
+    Ok(())
+}
+async fn rules(_claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(RULES))
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::modules::markdown::lint::{Config, Diagnostic};
+
+/*
+ * a config of null goes back to the default rules(or the user's config, for a scope)
+ */
+
+// check
+#[derive(Debug, Deserialize)]
+pub struct LintCheckPayload {
+    pub raw: String,
+    // scopes the document is saved to, along with the ones of its front matter
+    #[serde(default)]
+    pub scope_ids: Vec<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct LintCheckResponse {
+    pub diagnostics: Vec<Diagnostic>,
+    // whether saving it as is would be rejected
+    pub rejected: bool,
+}
+
+// get
+#[derive(Debug, Deserialize)]
+pub struct LintGetPayload {
+    pub scope_id: Option<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct LintConfigResponse {
+    pub config: Option<Config>,
+}
+
+// user
+#[derive(Debug, Deserialize)]
+pub struct LintUserPayload {
+    pub config: Option<Config>,
+}
+
+// scope
+#[derive(Debug, Deserialize)]
+pub struct LintScopePayload {
+    pub scope_id: i32,
+    pub config: Option<Config>,
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use sea_orm::{entity::*, query::*, sea_query::Expr};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, markdown::{front_matter, lint::{self, Config, Diagnostic}}}, routes::error::GlobalError};
+
+use super::error::LintError;
+
+#[derive(Clone, Debug)]
+pub struct LintService {
+    state: AppState,
+}
+impl LintService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    fn to_json(config: Option<Config>) -> Result<Option<serde_json::Value>, GlobalError> {
+        match config {
+            Some(config) if !config.is_valid() => Err(LintError::InvalidConfig.into()),
+            Some(config) => Ok(Some(serde_json::to_value(config).map_err(|_| LintError::InvalidConfig)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get(&self, docuser_id: i32, scope_id: Option<i32>) -> Result<Option<Config>, GlobalError> {
+        let config = match scope_id {
+            Some(scope_id) => {
+                redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+                entity::scope::Entity::find_by_id(scope_id)
+                    .one(&self.state.db_conn)
+                    .await?
+                    .ok_or(GlobalError::NoPermission)?
+                    .lint_config
+            },
+            None => entity::docuser::Entity::find_by_id(docuser_id)
+                .one(&self.state.db_conn)
+                .await?
+                .ok_or(GlobalError::NoPermission)?
+                .lint_config,
+        };
+        Ok(Config::from_json(config.as_ref()))
+    }
+
+    // documents already saved are left as they are, the rules apply from their next save
+    pub async fn set_user(&self, docuser_id: i32, config: Option<Config>) -> Result<(), GlobalError> {
+        let config = Self::to_json(config)?;
+        entity::docuser::Entity::update_many()
+            .col_expr(entity::docuser::Column::LintConfig, Expr::value(config))
+            .filter(entity::docuser::Column::Id.eq(docuser_id))
+            .exec(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_scope(&self, docuser_id: i32, scope_id: i32, config: Option<Config>) -> Result<(), GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+        let config = Self::to_json(config)?;
+        entity::scope::Entity::update_many()
+            .col_expr(entity::scope::Column::LintConfig, Expr::value(config))
+            .filter(entity::scope::Column::Id.eq(scope_id))
+            .exec(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn config_of(&self, docuser_id: i32, scope_ids: &[i32]) -> Result<Config, GlobalError> {
+        let scopes = match scope_ids.is_empty() {
+            true => Vec::new(),
+            false => entity::scope::Entity::find()
+                .filter(entity::scope::Column::Id.is_in(scope_ids.to_vec()))
+                .filter(entity::scope::Column::LintConfig.is_not_null())
+                .order_by_asc(entity::scope::Column::Id)
+                .all(&self.state.db_conn)
+                .await?
+                .into_iter()
+                .filter_map(|scope| Config::from_json(scope.lint_config.as_ref()))
+                .collect(),
+        };
+        let user = entity::docuser::Entity::find_by_id(docuser_id)
+            .one(&self.state.db_conn)
+            .await?
+            .and_then(|docuser| Config::from_json(docuser.lint_config.as_ref()));
+        Ok(Config::resolve(user, scopes))
+    }
+
+    /*
+     * the document as it would be saved to the given scopes(those of its front matter included).
+     * file/ links resolve to files the user uploaded.
+     */
+    pub async fn lint(&self, docuser_id: i32, raw: &str, scope_ids: &[i32]) -> Result<Vec<Diagnostic>, GlobalError> {
+        let config = self.config_of(docuser_id, scope_ids).await?;
+        let refs = lint::file_refs(raw);
+        let uploaded = match refs.is_empty() {
+            true => HashSet::new(),
+            false => entity::docfile::Entity::find()
+                .filter(entity::docfile::Column::DocuserId.eq(docuser_id))
+                .filter(entity::docfile::Column::ObjectId.is_in(refs))
+                .all(&self.state.db_conn)
+                .await?
+                .into_iter()
+                .map(|docfile| docfile.object_id)
+                .collect::<HashSet<_>>(),
+        };
+        Ok(lint::lint(raw, &config, |object_id| uploaded.contains(object_id)))
+    }
+
+    // run as a document is saved, the warnings are returned to the caller and errors reject the save
+    pub async fn enforce(&self, docuser_id: i32, raw: &str, scope_ids: &[i32]) -> Result<Vec<Diagnostic>, GlobalError> {
+        let diagnostics = self.lint(docuser_id, raw, scope_ids).await?;
+        if lint::has_errors(&diagnostics) {
+            return Err(LintError::Rejected(diagnostics).into());
+        }
+        Ok(diagnostics)
+    }
+
+    // dry run for editors
+    pub async fn check(&self, docuser_id: i32, raw: &str, mut scope_ids: Vec<i32>) -> Result<Vec<Diagnostic>, GlobalError> {
+        if let Ok((Some(front_matter), _)) = front_matter::parse(raw) {
+            for scope_id in front_matter.scopes {
+                if !scope_ids.contains(&scope_id) {
+                    scope_ids.push(scope_id);
+                }
+            }
+        }
+        redis_does_docuser_have_scope(self.state.clone(), &scope_ids[..], docuser_id).await?;
+        self.lint(docuser_id, raw, &scope_ids[..]).await
+    }
+}
@@ -10,6 +10,22 @@ pub mod auth;
 pub mod document;
 pub mod resource;
 pub mod file;
+pub mod template;
+pub mod comment;
+pub mod export;
+pub mod site;
+pub mod share;
+pub mod theme;
+pub mod feed;
+pub mod render;
+pub mod query;
+pub mod link;
+pub mod collab;
+pub mod event;
+pub mod webhook;
+pub mod audit;
+pub mod lint;
+pub mod schedule;
 
 pub fn create_router(shared_state: AppState) -> Router {
     Router::new()
@@ -19,6 +35,22 @@ pub fn create_router(shared_state: AppState) -> Router {
         .nest("/document", document::create_router(shared_state.clone()))
         .nest("/resource", resource::create_router(shared_state.clone()))
         .nest("/file", file::create_router(shared_state.clone()))
+        .nest("/template", template::create_router(shared_state.clone()))
+        .nest("/comment", comment::create_router(shared_state.clone()))
+        .nest("/export", export::create_router(shared_state.clone()))
+        .nest("/site", site::create_router(shared_state.clone()))
+        .nest("/share", share::create_router(shared_state.clone()))
+        .nest("/theme", theme::create_router(shared_state.clone()))
+        .nest("/feed", feed::create_router(shared_state.clone()))
+        .nest("/render", render::create_router(shared_state.clone()))
+        .nest("/query", query::create_router(shared_state.clone()))
+        .nest("/link", link::create_router(shared_state.clone()))
+        .nest("/collab", collab::create_router(shared_state.clone()))
+        .nest("/event", event::create_router(shared_state.clone()))
+        .nest("/webhook", webhook::create_router(shared_state.clone()))
+        .nest("/audit", audit::create_router(shared_state.clone()))
+        .nest("/lint", lint::create_router(shared_state.clone()))
+        .nest("/schedule", schedule::create_router(shared_state.clone()))
         .layer(TraceLayer::new_for_http())
 }
 
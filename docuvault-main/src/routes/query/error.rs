@@ -0,0 +1,25 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum QueryError {
+    QueryNotExist,
+    EmptyName,
+    EmptyScope,
+}
+impl IntoResponse for QueryError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::QueryNotExist => (StatusCode::BAD_REQUEST, "saved query does not exist."),
+            Self::EmptyName => (StatusCode::BAD_REQUEST, "saved query needs a name."),
+            Self::EmptyScope => (StatusCode::BAD_REQUEST, "saved query needs at least one scope."),
+        };
+        res.into_response()
+    }
+}
+impl From<QueryError> for GlobalError {
+    fn from(value: QueryError) -> Self {
+        Self::Query(value)
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::QueryService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * saved queries(smart collections).
+ * the same query is also a feed source(/feed/query/...) and an export source(/export/create).
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<QueryService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(QueryService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/new", post(new))
+        .route("/update", post(update))
+        .route("/delete", post(delete))
+        .route("/list", post(list))
+        .route("/run", post(run))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn new(State(state): State<ServiceState<QueryService>>, claims: Claims, Json(payload): Json<QueryNewPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let query_id = state.service.new_query(claims.user_id, payload).await?;
+    Ok(Json(QueryNewResponse { query_id }))
+}
+async fn update(State(state): State<ServiceState<QueryService>>, claims: Claims, Json(payload): Json<QueryUpdatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.update(claims.user_id, payload).await?;
+    Ok(())
+}
+async fn delete(State(state): State<ServiceState<QueryService>>, claims: Claims, Json(payload): Json<QueryDeletePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.query_id).await?;
+    Ok(())
+}
+// own queries and the ones shared with the user's scopes, pinned ones first
+async fn list(State(state): State<ServiceState<QueryService>>, claims: Claims, Json(payload): Json<QueryListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.list(claims.user_id, payload.pinned).await?))
+}
+// the query as a virtual folder, paginated like /resource/list
+async fn run(State(state): State<ServiceState<QueryService>>, claims: Claims, Json(payload): Json<QueryRunPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.run(claims.user_id, payload).await?))
+}
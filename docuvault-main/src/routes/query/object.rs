@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{modules::filter::DocFilter, routes::resource::object::{ListOrder, ListSort}};
+
+/*
+ * a saved query is a named DocFilter, private to its author or shared with a scope.
+ * running it lists the matching documents like /resource/list does.
+ */
+
+// new
+#[derive(Debug, Deserialize)]
+pub struct QueryNewPayload {
+    pub name: String,
+    // share with everyone who has the scope
+    pub scope_id: Option<i32>,
+    pub filter: DocFilter,
+    #[serde(default)]
+    pub pinned: bool,
+    // mail the author about documents that start matching
+    #[serde(default)]
+    pub notify: bool,
+    // serve the query as a feed without a login, see /feed/query
+    #[serde(default)]
+    pub public: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct QueryNewResponse {
+    pub query_id: i32,
+}
+
+// update, fields left out stay as they are
+#[derive(Debug, Deserialize)]
+pub struct QueryUpdatePayload {
+    pub query_id: i32,
+    pub name: Option<String>,
+    pub filter: Option<DocFilter>,
+    pub pinned: Option<bool>,
+    pub notify: Option<bool>,
+    pub public: Option<bool>,
+}
+
+// delete
+#[derive(Debug, Deserialize)]
+pub struct QueryDeletePayload {
+    pub query_id: i32,
+}
+
+// list
+#[derive(Debug, Deserialize)]
+pub struct QueryListPayload {
+    #[serde(default)]
+    pub pinned: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct QueryItem {
+    pub id: i32,
+    pub docuser_id: i32,
+    pub scope_id: Option<i32>,
+    pub name: String,
+    pub filter: serde_json::Value,
+    pub pinned: bool,
+    pub notify: bool,
+    pub public: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::saved_query::Model> for QueryItem {
+    fn from(value: crate::entity::saved_query::Model) -> Self {
+        Self {
+            id: value.id,
+            docuser_id: value.docuser_id,
+            scope_id: value.scope_id,
+            name: value.name,
+            filter: value.filter,
+            pinned: value.pinned,
+            notify: value.notify,
+            public: value.public,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+// run
+#[derive(Debug, Deserialize)]
+pub struct QueryRunPayload {
+    pub query_id: i32,
+    pub limit: Option<u64>,
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub sort: ListSort,
+    #[serde(default)]
+    pub order: ListOrder,
+}
@@ -0,0 +1,190 @@
+use sea_orm::{entity::*, query::*, sea_query::{OnConflict, Query}, FromQueryResult};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, filter::DocFilter, role::{self, Role}}, routes::{error::GlobalError, resource::{error::ResourceError, object::{Docs, ListPayload, Page}, service::ResourceService}}};
+
+use super::{object::*, error::QueryError};
+
+#[derive(Clone, Debug)]
+pub struct QueryService {
+    state: AppState,
+}
+impl QueryService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    async fn validate(&self, docuser_id: i32, name: &str, scope_id: Option<i32>, filter: &DocFilter) -> Result<(), GlobalError> {
+        if name.trim().is_empty() {
+            return Err(QueryError::EmptyName.into());
+        }
+        if filter.scope_ids.is_empty() {
+            return Err(QueryError::EmptyScope.into());
+        }
+        if !filter.valid() {
+            return Err(ResourceError::InvalidFilter.into());
+        }
+        redis_does_docuser_have_scope(self.state.clone(), &filter.scope_ids[..], docuser_id).await?;
+        if let Some(scope_id) = scope_id {
+            redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+        }
+        Ok(())
+    }
+
+    // readable by the author and, for a shared query, by whoever can view the scope
+    pub async fn accessible(&self, docuser_id: i32, query_id: i32) -> Result<entity::saved_query::Model, GlobalError> {
+        let query = entity::saved_query::Entity::find_by_id(query_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(QueryError::QueryNotExist)?;
+        if query.docuser_id == docuser_id {
+            return Ok(query);
+        }
+        match query.scope_id {
+            Some(scope_id) if !role::scopes_with_role(&self.state.db_conn, docuser_id, &[scope_id], Role::Viewer).await?.is_empty() => Ok(query),
+            _ => Err(QueryError::QueryNotExist.into()),
+        }
+    }
+    async fn owned(&self, docuser_id: i32, query_id: i32) -> Result<entity::saved_query::Model, GlobalError> {
+        let query = entity::saved_query::Entity::find_by_id(query_id)
+            .filter(entity::saved_query::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(QueryError::QueryNotExist)?;
+        Ok(query)
+    }
+
+    /*
+     * documents already matching when notifications are turned on are recorded as seen,
+     * only the ones matching afterwards are mailed about.
+     */
+    async fn mark_seen(&self, query: &entity::saved_query::Model) -> Result<(), GlobalError> {
+        #[derive(FromQueryResult)]
+        struct DocId {
+            id: i32,
+        }
+        let filter = DocFilter::from_json(&query.filter).ok_or(GlobalError::InternalServerError)?;
+        let doc_ids = filter.select(query.docuser_id)
+            .select_only()
+            .column(entity::docorg::Column::Id)
+            .into_model::<DocId>()
+            .all(&self.state.db_conn)
+            .await?;
+        if doc_ids.is_empty() {
+            return Ok(());
+        }
+        let hits = doc_ids.into_iter().map(|doc| entity::saved_query_hit::ActiveModel {
+            saved_query_id: Set(query.id),
+            docorg_id: Set(doc.id),
+            ..Default::default()
+        });
+        entity::saved_query_hit::Entity::insert_many(hits)
+            .on_conflict(OnConflict::columns([entity::saved_query_hit::Column::SavedQueryId, entity::saved_query_hit::Column::DocorgId])
+                .do_nothing()
+                .to_owned())
+            .exec_without_returning(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn new_query(&self, docuser_id: i32, payload: QueryNewPayload) -> Result<i32, GlobalError> {
+        self.validate(docuser_id, &payload.name, payload.scope_id, &payload.filter).await?;
+
+        let new_query = entity::saved_query::ActiveModel {
+            docuser_id: Set(docuser_id),
+            scope_id: Set(payload.scope_id),
+            name: Set(payload.name.trim().to_string()),
+            filter: Set(payload.filter.to_json()),
+            pinned: Set(payload.pinned),
+            notify: Set(payload.notify),
+            public: Set(payload.public),
+            ..Default::default()
+        };
+        let query = new_query.insert(&self.state.db_conn).await?;
+        if query.notify {
+            self.mark_seen(&query).await?;
+        }
+        Ok(query.id)
+    }
+
+    pub async fn update(&self, docuser_id: i32, payload: QueryUpdatePayload) -> Result<(), GlobalError> {
+        let query = self.owned(docuser_id, payload.query_id).await?;
+        let name = payload.name.unwrap_or_else(|| query.name.clone());
+        let filter = match payload.filter {
+            Some(filter) => filter,
+            None => DocFilter::from_json(&query.filter).unwrap_or_default(),
+        };
+        self.validate(docuser_id, &name, query.scope_id, &filter).await?;
+
+        let mut query: entity::saved_query::ActiveModel = query.into();
+        query.name = Set(name.trim().to_string());
+        query.filter = Set(filter.to_json());
+        if let Some(pinned) = payload.pinned {
+            query.pinned = Set(pinned);
+        }
+        if let Some(notify) = payload.notify {
+            query.notify = Set(notify);
+        }
+        if let Some(public) = payload.public {
+            query.public = Set(public);
+        }
+        query.updated_at = Set(chrono::Utc::now().naive_utc());
+        let query = query.update(&self.state.db_conn).await?;
+        // a changed filter should not report what it matched all along either
+        if query.notify {
+            self.mark_seen(&query).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&self, docuser_id: i32, query_id: i32) -> Result<(), GlobalError> {
+        let query = self.owned(docuser_id, query_id).await?;
+        entity::saved_query::Entity::delete_by_id(query.id)
+            .exec(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list(&self, docuser_id: i32, pinned: bool) -> Result<Vec<QueryItem>, GlobalError> {
+        let shared = role::shared_scopes(&self.state.db_conn, docuser_id, Role::Viewer).await?;
+        let queries = entity::saved_query::Entity::find()
+            .filter(Condition::any()
+                .add(entity::saved_query::Column::DocuserId.eq(docuser_id))
+                .add(entity::saved_query::Column::ScopeId.in_subquery(Query::select()
+                    .column(entity::scope::Column::Id)
+                    .from(entity::scope::Entity)
+                    .and_where(entity::scope::Column::DocuserId.eq(docuser_id))
+                    .to_owned()))
+                .add(entity::saved_query::Column::ScopeId.is_in(shared)))
+            .apply_if(pinned.then_some(true), |query, pinned| query.filter(entity::saved_query::Column::Pinned.eq(pinned)))
+            .order_by_desc(entity::saved_query::Column::Pinned)
+            .order_by_asc(entity::saved_query::Column::Name)
+            .order_by_asc(entity::saved_query::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(queries.into_iter().map(|query| query.into()).collect())
+    }
+
+    /*
+     * the filter is evaluated for its author, whose documents and scopes it names.
+     * the users it is shared with only see the scopes among them they can view.
+     */
+    pub async fn run(&self, docuser_id: i32, payload: QueryRunPayload) -> Result<Page<Docs>, GlobalError> {
+        let query = self.accessible(docuser_id, payload.query_id).await?;
+        let mut filter = DocFilter::from_json(&query.filter).ok_or(GlobalError::InternalServerError)?;
+        if query.docuser_id != docuser_id {
+            filter.scope_ids = role::scopes_with_role(&self.state.db_conn, docuser_id, &filter.scope_ids, Role::Viewer).await?;
+            if filter.scope_ids.is_empty() {
+                return Ok(Page { items: Vec::new(), next_cursor: None, total: 0 });
+            }
+        }
+        ResourceService::new(self.state.clone()).list(query.docuser_id, ListPayload {
+            filter,
+            limit: payload.limit,
+            cursor: payload.cursor,
+            sort: payload.sort,
+            order: payload.order,
+        }).await
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum RenderError {
+    InvalidProfile,
+}
+impl IntoResponse for RenderError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::InvalidProfile => (StatusCode::BAD_REQUEST, "unknown highlight theme."),
+        };
+        res.into_response()
+    }
+}
+impl From<RenderError> for GlobalError {
+    fn from(value: RenderError) -> Self {
+        Self::Render(value)
+    }
+}
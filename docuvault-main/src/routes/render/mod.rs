@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::markdown::render::HIGHLIGHT_THEMES};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::RenderService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<RenderService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(RenderService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/get", post(get))
+        .route("/user", post(user))
+        .route("/scope", post(scope))
+        .route("/themes", post(themes))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// profile of the user, or of the given scope
+async fn get(State(state): State<ServiceState<RenderService>>, claims: Claims, Json(payload): Json<RenderGetPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let profile = state.service.get(claims.user_id, payload.scope_id).await?;
+    Ok(Json(RenderProfileResponse { profile }))
+}
+// the affected documents are rendered again in the background
+async fn user(State(state): State<ServiceState<RenderService>>, claims: Claims, Json(payload): Json<RenderUserPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.set_user(claims.user_id, payload.profile).await?;
+    Ok(())
+}
+async fn scope(State(state): State<ServiceState<RenderService>>, claims: Claims, Json(payload): Json<RenderScopePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.set_scope(claims.user_id, payload.scope_id, payload.profile).await?;
+    Ok(())
+}
+async fn themes(_claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(HIGHLIGHT_THEMES))
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::modules::markdown::render::Profile;
+
+/*
+ * a profile of null goes back to plain CommonMark(or the user's profile, for a scope)
+ */
+
+// get
+#[derive(Debug, Deserialize)]
+pub struct RenderGetPayload {
+    pub scope_id: Option<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct RenderProfileResponse {
+    pub profile: Option<Profile>,
+}
+
+// user
+#[derive(Debug, Deserialize)]
+pub struct RenderUserPayload {
+    pub profile: Option<Profile>,
+}
+
+// scope
+#[derive(Debug, Deserialize)]
+pub struct RenderScopePayload {
+    pub scope_id: i32,
+    pub profile: Option<Profile>,
+}
@@ -0,0 +1,94 @@
+use sea_orm::{entity::*, query::*, sea_query::{Expr, Query}};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, background::conversion, markdown::render::Profile}, routes::{error::GlobalError, document::object::DocumentStatus}};
+
+use super::error::RenderError;
+
+#[derive(Clone, Debug)]
+pub struct RenderService {
+    state: AppState,
+}
+impl RenderService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    fn to_json(profile: Option<Profile>) -> Result<Option<serde_json::Value>, GlobalError> {
+        match profile {
+            Some(profile) if !profile.is_valid() => Err(RenderError::InvalidProfile.into()),
+            Some(profile) => Ok(Some(serde_json::to_value(profile).map_err(|_| RenderError::InvalidProfile)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get(&self, docuser_id: i32, scope_id: Option<i32>) -> Result<Option<Profile>, GlobalError> {
+        let profile = match scope_id {
+            Some(scope_id) => {
+                redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+                entity::scope::Entity::find_by_id(scope_id)
+                    .one(&self.state.db_conn)
+                    .await?
+                    .ok_or(GlobalError::NoPermission)?
+                    .render_profile
+            },
+            None => entity::docuser::Entity::find_by_id(docuser_id)
+                .one(&self.state.db_conn)
+                .await?
+                .ok_or(GlobalError::NoPermission)?
+                .render_profile,
+        };
+        Ok(Profile::from_json(profile.as_ref()))
+    }
+
+    // documents in a scope with its own profile keep rendering with that one
+    pub async fn set_user(&self, docuser_id: i32, profile: Option<Profile>) -> Result<(), GlobalError> {
+        let profile = Self::to_json(profile)?;
+        entity::docuser::Entity::update_many()
+            .col_expr(entity::docuser::Column::RenderProfile, Expr::value(profile))
+            .filter(entity::docuser::Column::Id.eq(docuser_id))
+            .exec(&self.state.db_conn)
+            .await?;
+
+        let doc_ids = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .filter(entity::docorg::Column::Id.not_in_subquery(Query::select()
+                .column(entity::docorg_scope::Column::DocorgId)
+                .from(entity::docorg_scope::Entity)
+                .and_where(entity::docorg_scope::Column::ScopeId.in_subquery(Query::select()
+                    .column(entity::scope::Column::Id)
+                    .from(entity::scope::Entity)
+                    .and_where(entity::scope::Column::RenderProfile.is_not_null())
+                    .to_owned()))
+                .to_owned()))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|document| document.id)
+            .collect::<Vec<_>>();
+        conversion::rerender(self.state.clone(), doc_ids);
+        Ok(())
+    }
+
+    pub async fn set_scope(&self, docuser_id: i32, scope_id: i32, profile: Option<Profile>) -> Result<(), GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+        let profile = Self::to_json(profile)?;
+        entity::scope::Entity::update_many()
+            .col_expr(entity::scope::Column::RenderProfile, Expr::value(profile))
+            .filter(entity::scope::Column::Id.eq(scope_id))
+            .exec(&self.state.db_conn)
+            .await?;
+
+        let doc_ids = entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::ScopeId.eq(scope_id))
+            .all(&self.state.db_conn)
+            .await?
+            .into_iter()
+            .map(|doc_scope| doc_scope.docorg_id)
+            .collect::<Vec<_>>();
+        conversion::rerender(self.state.clone(), doc_ids);
+        Ok(())
+    }
+}
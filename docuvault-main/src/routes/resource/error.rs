@@ -8,6 +8,9 @@ pub enum ResourceError {
     SequenceNotExist,
     SequenceNotSync,
     PermissionDenied,
+    InvalidCursor,
+    InvalidMember,
+    InvalidFilter,
 }
 
 impl IntoResponse for ResourceError {
@@ -17,6 +20,9 @@ impl IntoResponse for ResourceError {
             Self::SequenceNotExist => (StatusCode::BAD_REQUEST, "specified sequence id does not exist"), 
             Self::SequenceNotSync => (StatusCode::BAD_REQUEST, "update sequence not synchronized"), 
             Self::PermissionDenied => (StatusCode::BAD_REQUEST, "permission denied"), 
+            Self::InvalidCursor => (StatusCode::BAD_REQUEST, "invalid cursor"), 
+            Self::InvalidMember => (StatusCode::BAD_REQUEST, "member must be another existing user"), 
+            Self::InvalidFilter => (StatusCode::BAD_REQUEST, "relative ranges must be between 0 and 36600 days"), 
         };
         res.into_response()
     }
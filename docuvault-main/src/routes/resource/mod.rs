@@ -1,12 +1,12 @@
-use std::{sync::Arc, collections::{HashSet, BTreeSet}};
+use std::{net::SocketAddr, sync::Arc};
 
-use axum::{routing::post, Router, http::{Method, header, HeaderValue}, extract::State, Json, response::IntoResponse};
+use axum::{routing::post, Router, http::{Method, header, HeaderValue}, extract::{State, ConnectInfo}, Json, response::IntoResponse};
 use sea_orm::FromQueryResult;
 use serde::Serialize;
 use tower_http::cors::{CorsLayer, Any};
-use sea_orm::{entity::*, query::*};
+use sea_orm::{entity::*, query::*, sea_query::Expr};
 
-use crate::{AppState, entity, modules::redis::redis_does_docuser_have_scope, routes::document::error::DocumentError, common::object::ServiceState};
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, audit::Actor, role::Role}, routes::document::error::DocumentError, common::object::ServiceState};
 
 pub mod object;
 use object::*;
@@ -27,7 +27,14 @@ pub fn create_router(shared_state: AppState) -> Router {
     Router::new()
         .route("/list", post(list))
         .route("/tag", post(tag))
+        // keyset paginated, the responses are Page objects instead of arrays
+        .route("/v2/list", post(page::list))
+        .route("/v2/tag", post(page::tag))
+        .route("/v2/sequence/list", post(page::sequence_list))
         .route("/scope/all", post(scope::all))
+        .route("/scope/public", post(scope::public))
+        .route("/scope/member/set", post(scope::member_set))
+        .route("/scope/member/list", post(scope::member_list))
         .route("/sequence/all", post(sequence::all))
         .route("/sequence/list", post(sequence::list))
         .route("/sequence/new", post(sequence::new))
@@ -61,18 +68,107 @@ mod scope {
 
         let res = res.into_iter().map(|m|(m.id, m.name)).collect::<Vec<(_,_)>>();
 
+        let shared = entity::scope_member::Entity::find()
+            .filter(entity::scope_member::Column::DocuserId.eq(claims.user_id))
+            .find_also_related(entity::scope::Entity)
+            .all(&state.global_state.db_conn)
+            .await?
+            .into_iter()
+            .filter_map(|(member, scope)| Some((member.scope_id, scope?.name, Role::from_i32(member.role)?)))
+            .collect::<Vec<_>>();
+
         Ok(Json(ScopeAllResponse{
             scopes: res,
+            shared,
         }))
     }
+    // public scopes are readable through /feed without a share link
+    pub async fn public(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ScopePublicPayload>) -> Result<impl IntoResponse, GlobalError> {
+        redis_does_docuser_have_scope(state.global_state.clone(), &[payload.scope_id], claims.user_id).await?;
+        let txn = state.global_state.db_conn.begin().await?;
+        entity::scope::Entity::update_many()
+            .col_expr(entity::scope::Column::Public, Expr::value(payload.public))
+            .filter(entity::scope::Column::Id.eq(payload.scope_id))
+            .exec(&txn)
+            .await?;
+        Actor::new(claims.user_id, addr)
+            .entry("scope.public")
+            .targets([payload.scope_id])
+            .scopes([payload.scope_id])
+            .summary(serde_json::json!({ "public": payload.public }))
+            .record(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+    // only the owner of the scope shares it, see modules::role
+    pub async fn member_set(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ScopeMemberSetPayload>) -> Result<impl IntoResponse, GlobalError> {
+        redis_does_docuser_have_scope(state.global_state.clone(), &[payload.scope_id], claims.user_id).await?;
+        if payload.docuser_id == claims.user_id {
+            return Err(ResourceError::InvalidMember.into());
+        }
+        entity::docuser::Entity::find_by_id(payload.docuser_id)
+            .one(&state.global_state.db_conn)
+            .await?
+            .ok_or(ResourceError::InvalidMember)?;
+
+        let txn = state.global_state.db_conn.begin().await?;
+        entity::scope_member::Entity::delete_many()
+            .filter(entity::scope_member::Column::ScopeId.eq(payload.scope_id))
+            .filter(entity::scope_member::Column::DocuserId.eq(payload.docuser_id))
+            .exec(&txn)
+            .await?;
+        if let Some(role) = payload.role {
+            let new_member = entity::scope_member::ActiveModel {
+                scope_id: Set(payload.scope_id),
+                docuser_id: Set(payload.docuser_id),
+                role: Set(role as i32),
+                created_at: Set(chrono::Utc::now().naive_utc()),
+            };
+            entity::scope_member::Entity::insert(new_member).exec(&txn).await?;
+        }
+        Actor::new(claims.user_id, addr)
+            .entry("scope.member")
+            .targets([payload.docuser_id])
+            .scopes([payload.scope_id])
+            .summary(serde_json::json!({ "role": payload.role }))
+            .record(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+    pub async fn member_list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<ScopeMemberListPayload>) -> Result<impl IntoResponse, GlobalError> {
+        redis_does_docuser_have_scope(state.global_state.clone(), &[payload.scope_id], claims.user_id).await?;
+        let members = entity::scope_member::Entity::find()
+            .filter(entity::scope_member::Column::ScopeId.eq(payload.scope_id))
+            .order_by_asc(entity::scope_member::Column::CreatedAt)
+            .all(&state.global_state.db_conn)
+            .await?
+            .into_iter()
+            .filter_map(|member| Some(ScopeMember {
+                docuser_id: member.docuser_id,
+                role: Role::from_i32(member.role)?,
+                created_at: member.created_at,
+            }))
+            .collect::<Vec<_>>();
+        Ok(Json(members))
+    }
 }
 mod sequence {
     use std::collections::HashMap;
 
     use axum::extract::Path;
-    use crate::modules::sequence::{domain::entity::sequence::SequenceObj, application::port::input::SequenceUseCase};
+    use crate::modules::{audit::Entry, sequence::{domain::entity::sequence::{Sequence, SequenceObj}, application::port::input::SequenceUseCase}};
 
     use super::*;
+
+    // a document going in, out or moving within the sequence
+    fn sequence_entry(actor: &Actor, action: &str, seq: &Sequence, doc_id: i32) -> Entry {
+        actor.entry(action)
+            .targets([seq.id])
+            .scopes(seq.scope_ids.clone())
+            .summary(serde_json::json!({ "doc_id": doc_id }))
+    }
     pub async fn all(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SequenceAllPayload>) -> Result<impl IntoResponse, GlobalError> {
         // inquire should be based on scope ids
 
@@ -102,110 +198,41 @@ mod sequence {
 
     pub async fn list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SequenceListPayload>) -> Result<impl IntoResponse, GlobalError> {
         //inquire is based on scope ids
-
-        /*
-         * check user has scope ID
-         */
-        redis_does_docuser_have_scope(state.global_state.clone(), &payload.scope_ids[..], claims.user_id).await?;
-
-        /*
-         * return document list
-         */
-
-        let mut scope_id_cond = Condition::any();
-        for &scope_id in payload.scope_ids.iter() {
-            scope_id_cond = scope_id_cond.add(entity::scope_sequence::Column::ScopeId.eq(scope_id));
-        }
-
-        // check if the inquired sequence has scope ids that the user can access(which means the
-        // user has scope_ids). 
-        let res = entity::scope_sequence::Entity::find()
-            .filter(entity::scope_sequence::Column::SequenceId.eq(payload.seq_id))
-            .filter(scope_id_cond.clone())
-            .one(&state.global_state.db_conn)
-            .await?;
-        if res.is_none() {
-            return Err(ResourceError::SequenceNotExist.into());
-        }
-        
-        // can be modified to search without user_id, because it can be extended to many users to
-        // work on the same scope
-        let mut scope_id_cond = Condition::any();
-        for scope_id in payload.scope_ids {
-            scope_id_cond = scope_id_cond.add(entity::docorg_scope::Column::ScopeId.eq(scope_id));
-        }
-        let res = entity::docorg::Entity::find()
-            .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
-            .join_rev(JoinType::LeftJoin, entity::docorg_scope::Relation::Docorg.def())
-            .join_rev(JoinType::LeftJoin, entity::docorg_tag::Relation::Docorg.def())
-            .join_rev(JoinType::LeftJoin, entity::docorg_sequence::Relation::Docorg.def())
-            .filter(scope_id_cond)
-            .column_as(entity::docorg_scope::Column::ScopeId, "scope_id")
-            .column_as(entity::docorg_tag::Column::TagId, "tag_id")
-            .column_as(entity::docorg_sequence::Column::SequenceId, "seq_id")
-            .column_as(entity::docorg_sequence::Column::Order, "seq_order")
-            .filter(entity::docorg_sequence::Column::SequenceId.eq(payload.seq_id))
-            .order_by_asc(entity::docorg_sequence::Column::Order)
-            .into_model::<SeqDocs>()
-            .all(&state.global_state.db_conn)
-            .await?;
-
-        let mut list: Vec<SeqCompDocs> = Vec::new();
-        for docs in res {
-            match list.last_mut() {
-                Some(compdocs) if compdocs.id == docs.id => {
-                    compdocs.scope_ids.insert(docs.scope_id); 
-                    if let Some(tag_id) = docs.tag_id {
-                        compdocs.tag_ids.insert(tag_id);
-                    }
-                }
-                _ => {
-                    let mut compdocs = SeqCompDocs{
-                        id: docs.id,
-                        seq_id: docs.seq_id,
-                        seq_order: docs.seq_order,
-                        scope_ids: BTreeSet::new(),
-                        title: docs.title,
-                        created_at: docs.created_at,
-                        updated_at: docs.updated_at,
-                        tag_ids: BTreeSet::new(), 
-                    };
-                    compdocs.scope_ids.insert(docs.scope_id);
-                    if let Some(tag_id) = docs.tag_id {
-                        compdocs.tag_ids.insert(tag_id);
-                    }
-                    list.push(compdocs);
-
-                }
-            } 
-        } 
-
-        Ok(Json(list))
+        Ok(Json(state.service.sequence_list(claims.user_id, payload).await?.items))
     }       
-    pub async fn new(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SeqNewPayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn new(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<SeqNewPayload>) -> Result<impl IntoResponse, GlobalError> {
         /*
          * check user has scope ID
          */
         redis_does_docuser_have_scope(state.global_state.clone(), &payload.scope_ids[..], claims.user_id).await?;
 
+        let entry = Actor::new(claims.user_id, addr)
+            .entry("sequence.create")
+            .scopes(payload.scope_ids.clone())
+            .summary(serde_json::json!({ "title": payload.title }));
         let new_seq = SequenceObj::new(claims.user_id, payload.title, payload.scope_ids);
-        state.global_state.modules.sequence.service.create_seq(new_seq).await?;
+        state.global_state.modules.sequence.service.create_seq(new_seq, entry).await?;
 
         
         Ok(())
     }
 
-    pub async fn delete(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SeqDeletePayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn delete(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<SeqDeletePayload>) -> Result<impl IntoResponse, GlobalError> {
         let seq = state.global_state.modules.sequence.service.get_seq(payload.seq_id).await?;
         if(seq.uid != claims.user_id) {
             return Err(ResourceError::PermissionDenied.into());
         }  
-        state.global_state.modules.sequence.service.remove_seq(seq.id).await?;
+        let entry = Actor::new(claims.user_id, addr)
+            .entry("sequence.delete")
+            .targets([seq.id])
+            .scopes(seq.scope_ids.clone())
+            .summary(serde_json::json!({ "title": seq.title }));
+        state.global_state.modules.sequence.service.remove_seq(seq.id, entry).await?;
         
         Ok(())
     }
     
-    pub async fn doc_out(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SeqOutPayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn doc_out(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<SeqOutPayload>) -> Result<impl IntoResponse, GlobalError> {
         // doesn't matter the scopes thisi document is assigned.
         // only author of document is previleged to do this function
         let res = entity::docorg::Entity::find_by_id(payload.doc_id)
@@ -220,11 +247,12 @@ mod sequence {
         if(seq.uid != claims.user_id) {
             return Err(ResourceError::PermissionDenied.into());
         }  
-        state.global_state.modules.sequence.service.doc_dealloc(seq, payload.doc_id).await?;
+        let entry = sequence_entry(&Actor::new(claims.user_id, addr), "sequence.remove_document", &seq, payload.doc_id);
+        state.global_state.modules.sequence.service.doc_dealloc(seq, payload.doc_id, entry).await?;
         
         Ok(())
     }       
-    pub async fn doc_in(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SeqInPayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn doc_in(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<SeqInPayload>) -> Result<impl IntoResponse, GlobalError> {
         let res = entity::docorg::Entity::find_by_id(payload.doc_id)
             .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
             .one(&state.global_state.db_conn)
@@ -237,11 +265,12 @@ mod sequence {
         if(seq.uid != claims.user_id) {
             return Err(ResourceError::PermissionDenied.into());
         }  
-        state.global_state.modules.sequence.service.doc_alloc(seq, payload.doc_id).await?;
+        let entry = sequence_entry(&Actor::new(claims.user_id, addr), "sequence.add_document", &seq, payload.doc_id);
+        state.global_state.modules.sequence.service.doc_alloc(seq, payload.doc_id, entry).await?;
 
         Ok(())
     }       
-    pub async fn update(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(mut payload): Json<SeqUpdatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn update(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(mut payload): Json<SeqUpdatePayload>) -> Result<impl IntoResponse, GlobalError> {
         // check user has sequence
         let res = entity::sequence::Entity::find_by_id(payload.seq_id)
             .filter(entity::sequence::Column::DocuserId.eq(claims.user_id))
@@ -280,18 +309,32 @@ mod sequence {
 
         }
 
+        let scope_ids = entity::scope_sequence::Entity::find()
+            .filter(entity::scope_sequence::Column::SequenceId.eq(payload.seq_id))
+            .all(&state.global_state.db_conn)
+            .await?
+            .into_iter()
+            .map(|scope_sequence| scope_sequence.scope_id);
+        let mut order = order_map.into_iter().collect::<Vec<_>>();
+        order.sort_by_key(|&(_, order)| order);
+        let entry = Actor::new(claims.user_id, addr)
+            .entry("sequence.reorder")
+            .targets([payload.seq_id])
+            .scopes(scope_ids)
+            .summary(serde_json::json!({ "doc_ids": order.into_iter().map(|(doc_id, _)| doc_id).collect::<Vec<_>>() }));
         state.global_state.db_conn.clone().transaction::<_, (), GlobalError>(|txn|{
             Box::pin(async move {
                 for am in seq {
                     am.update(txn).await?;
                 }
+                entry.record(txn).await?;
                 Ok(())
             })
         }).await?;
 
         Ok(())
     }       
-    pub async fn doc_up(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(mut payload): Json<SeqUpPayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn doc_up(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(mut payload): Json<SeqUpPayload>) -> Result<impl IntoResponse, GlobalError> {
         let res = entity::docorg::Entity::find_by_id(payload.doc_id)
             .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
             .one(&state.global_state.db_conn)
@@ -304,12 +347,13 @@ mod sequence {
             return Err(ResourceError::PermissionDenied.into());
         }  
 
-        state.global_state.modules.sequence.service.doc_ord_up(seq, payload.doc_id).await?;
+        let entry = sequence_entry(&Actor::new(claims.user_id, addr), "sequence.move_up", &seq, payload.doc_id);
+        state.global_state.modules.sequence.service.doc_ord_up(seq, payload.doc_id, entry).await?;
 
         Ok(())
     
     }
-    pub async fn doc_down(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(mut payload): Json<SeqDownPayload>) -> Result<impl IntoResponse, GlobalError> {
+    pub async fn doc_down(State(state): State<ServiceState<ResourceService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(mut payload): Json<SeqDownPayload>) -> Result<impl IntoResponse, GlobalError> {
         let res = entity::docorg::Entity::find_by_id(payload.doc_id)
             .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
             .one(&state.global_state.db_conn)
@@ -322,150 +366,31 @@ mod sequence {
             return Err(ResourceError::PermissionDenied.into());
         }  
 
-        state.global_state.modules.sequence.service.doc_ord_down(seq, payload.doc_id).await?;
+        let entry = sequence_entry(&Actor::new(claims.user_id, addr), "sequence.move_down", &seq, payload.doc_id);
+        state.global_state.modules.sequence.service.doc_ord_down(seq, payload.doc_id, entry).await?;
         Ok(())
     
     }
         
 }
 async fn tag(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<TagPayload>) -> Result<impl IntoResponse, GlobalError> {
-    /*
-     * need for redis optimization
-     */
-
-    redis_does_docuser_have_scope(state.global_state.clone(), &payload.scope_ids[..], claims.user_id).await?;
-
-    let mut scope_id_cond = Condition::any();
-    for scope_id in payload.scope_ids {
-        scope_id_cond = scope_id_cond.add(entity::docorg_scope::Column::ScopeId.eq(scope_id));
-    }
-    
-    #[derive(FromQueryResult, Serialize, Debug)]
-    struct Docs {
-        id: i32,
-        scope_id: i32,
-        tag_id: Option<i32>,
-        tag_value: Option<String>,
-    }
-    let res = entity::docorg::Entity::find()
-        .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
-        .join_rev(JoinType::LeftJoin, entity::docorg_scope::Relation::Docorg.def())
-        .join_rev(JoinType::LeftJoin, entity::docorg_tag::Relation::Docorg.def())
-        .join_rev(JoinType::LeftJoin, entity::tag::Entity::belongs_to(entity::docorg_tag::Entity)
-                  .from(entity::tag::Column::Id)
-                  .to(entity::docorg_tag::Column::TagId)
-                  .into())
-        .filter(scope_id_cond)
-        .column_as(entity::docorg_scope::Column::ScopeId, "scope_id")
-        .column_as(entity::docorg_tag::Column::TagId, "tag_id")
-        .column_as(entity::tag::Column::Value, "tag_value")
-        .into_model::<Docs>()
-        .all(&state.global_state.db_conn)
-        .await?;
-    
-    #[derive(Serialize)]
-    struct Tag {
-        id: i32,
-        value: String,
-    }
-    let mut tag_set = BTreeSet::new(); 
-    let tag_set = res.into_iter().fold(tag_set, move |mut acc, m|{
-        match m.tag_id {
-            Some(id) => {acc.insert((id, m.tag_value.unwrap()));},
-            None => (),
-        }
-        acc
-    });
-    let tag_vec: Vec<Tag> = tag_set.into_iter().map(|(id, value)| Tag {
-        id,
-        value,
-    }).collect();
-
-    Ok(Json(tag_vec))
+    Ok(Json(state.service.tags(claims.user_id, payload).await?.items))
 }
 
-async fn list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<ListPayload>) -> Result<impl IntoResponse, GlobalError> {
-    
-    /*
-     * check user has scope ID
-     */
-    redis_does_docuser_have_scope(state.global_state.clone(), &payload.scope_ids[..], claims.user_id).await?;
-    
+async fn list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<UnitListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.list_units(claims.user_id, payload).await?))
+}
 
-    /*
-     * return document list
-     */
+mod page {
+    use super::*;
 
-    let unit_size = match payload.unit_size {
-        Some(value) => {
-            if value==0 {
-                return Err(ResourceError::UnitSizeZero.into())
-            }
-            value
-        },
-        None => 10000, 
-    };
-    let unit_number = match payload.unit_number {
-        Some(value) => value,
-        None => 0,
-    };
-    let mut scope_id_cond = Condition::any();
-    for scope_id in payload.scope_ids {
-        scope_id_cond = scope_id_cond.add(entity::docorg_scope::Column::ScopeId.eq(scope_id));
+    pub async fn list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<ListPayload>) -> Result<impl IntoResponse, GlobalError> {
+        Ok(Json(state.service.list(claims.user_id, payload).await?))
     }
-    let res = entity::docorg::Entity::find()
-        .filter(entity::docorg::Column::DocuserId.eq(claims.user_id))
-        .join_rev(JoinType::LeftJoin, entity::docorg_scope::Relation::Docorg.def())
-        .join_rev(JoinType::LeftJoin, entity::docorg_tag::Relation::Docorg.def())
-        .join_rev(JoinType::LeftJoin, entity::docorg_sequence::Relation::Docorg.def())
-        .filter(scope_id_cond)
-        .column_as(entity::docorg_scope::Column::ScopeId, "scope_id")
-        .column_as(entity::docorg_tag::Column::TagId, "tag_id")
-        .column_as(entity::docorg_sequence::Column::SequenceId, "seq_id")
-        .order_by_desc(entity::docorg::Column::CreatedAt)
-        .order_by_desc(entity::docorg::Column::Id)
-        .into_model::<Docs>()
-        .paginate(&state.global_state.db_conn, unit_size);
-        // .all(&state.db_conn)
-        // .await?;
-    let res = res.fetch_page(unit_number).await?; 
-    
-    let mut list: Vec<CompDocs> = Vec::new();
-    for docs in res {
-        match list.last_mut() {
-            Some(compdocs) if compdocs.id == docs.id => {
-                compdocs.scope_ids.insert(docs.scope_id); 
-                if let Some(tag_id) = docs.tag_id {
-                    compdocs.tag_ids.insert(tag_id);
-                }
-                if let Some(seq_id) = docs.seq_id {
-                    compdocs.seq_ids.insert(seq_id);
-                }
-            }
-            _ => {
-                let mut compdocs = CompDocs{
-                    id: docs.id,
-                    scope_ids: BTreeSet::new(),
-                    seq_ids: BTreeSet::new(),
-                    title: docs.title,
-                    created_at: docs.created_at,
-                    updated_at: docs.updated_at,
-                    tag_ids: BTreeSet::new(), 
-                };
-                compdocs.scope_ids.insert(docs.scope_id);
-                if let Some(tag_id) = docs.tag_id {
-                    compdocs.tag_ids.insert(tag_id);
-                }
-                if let Some(seq_id) = docs.seq_id {
-                    compdocs.seq_ids.insert(seq_id);
-                }
-                list.push(compdocs);
-
-            }
-        } 
-    } 
-    if let Some(tag_id) = payload.tag_id {
-        list = list.into_iter().filter(|compdocs| compdocs.tag_ids.contains(&tag_id)).collect::<Vec<CompDocs>>();
+    pub async fn tag(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<TagPayload>) -> Result<impl IntoResponse, GlobalError> {
+        Ok(Json(state.service.tags(claims.user_id, payload).await?))
+    }
+    pub async fn sequence_list(State(state): State<ServiceState<ResourceService>>, claims: Claims, Json(payload): Json<SequenceListPayload>) -> Result<impl IntoResponse, GlobalError> {
+        Ok(Json(state.service.sequence_list(claims.user_id, payload).await?))
     }
-    Ok(Json(list))
 }
@@ -1,67 +1,126 @@
-use std::collections::BTreeSet;
-
 use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
 
+use crate::modules::{filter::DocFilter, role::Role};
+
 #[derive(Debug, Serialize)]
 pub struct ScopeAllResponse {
-    pub scopes: Vec<(i32, String)>
+    pub scopes: Vec<(i32, String)>,
+    // scopes of other users shared with this one, with the role in each
+    pub shared: Vec<(i32, String, Role)>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ScopePublicPayload {
+    pub scope_id: i32,
+    pub public: bool,
+}
+#[derive(Debug, Deserialize)]
+pub struct ScopeMemberSetPayload {
+    pub scope_id: i32,
+    pub docuser_id: i32,
+    // null removes the member from the scope
+    pub role: Option<Role>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ScopeMemberListPayload {
+    pub scope_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct ScopeMember {
+    pub docuser_id: i32,
+    pub role: Role,
+    pub created_at: chrono::NaiveDateTime,
 }
 #[derive(Debug, Deserialize)]
 pub struct TagPayload {
-    pub scope_ids: Vec<i32>
+    pub scope_ids: Vec<i32>,
+    pub limit: Option<u64>,
+    pub cursor: Option<String>,
 }
-
 #[derive(FromQueryResult, Serialize, Debug)]
-pub struct Docs {
+pub struct Tags {
     pub id: i32,
-    pub scope_id: i32,
-    pub title: String,
-    pub seq_id: Option<i32>,
-    pub created_at: chrono::NaiveDateTime,
-    pub updated_at: chrono::NaiveDateTime,
-    pub tag_id: Option<i32>,
+    pub value: String,
+    // number of listed documents carrying the tag
+    pub count: i64,
 }
-#[derive(Serialize, Debug)]
-pub struct CompDocs {
+
+// one row per document, related ids are aggregated into sorted arrays
+#[derive(FromQueryResult, Serialize, Debug)]
+pub struct Docs {
     pub id: i32,
-    pub scope_ids: BTreeSet<i32>,
-    pub seq_ids: BTreeSet<i32>,
+    pub scope_ids: Vec<i32>,
+    pub seq_ids: Vec<i32>,
     pub title: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
-    pub tag_ids: BTreeSet<i32>,
+    pub tag_ids: Vec<i32>,
+    pub word_count: Option<i32>,
+    pub reading_time: Option<i32>,
+    pub excerpt: Option<String>,
+    pub cover: Option<String>,
 }
 #[derive(FromQueryResult, Serialize, Debug)]
 pub struct SeqDocs {
     pub id: i32,
-    pub scope_id: i32,
+    pub scope_ids: Vec<i32>,
     pub seq_id: i32,
     pub seq_order: i32,
     pub title: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
-    pub tag_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+    pub word_count: Option<i32>,
+    pub reading_time: Option<i32>,
+    pub excerpt: Option<String>,
+    pub cover: Option<String>,
 }
+
+// keyset paginated response, pass next_cursor back as cursor for the following page
 #[derive(Serialize, Debug)]
-pub struct SeqCompDocs {
-    pub id: i32,
-    pub scope_ids: BTreeSet<i32>,
-    pub seq_id: i32,
-    pub seq_order: i32,
-    pub title: String,
-    pub created_at: chrono::NaiveDateTime,
-    pub updated_at: chrono::NaiveDateTime,
-    pub tag_ids: BTreeSet<i32>,
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    // matching rows regardless of the cursor
+    pub total: u64,
 }
 
+// /resource/list, offset pagination kept for existing clients
 #[derive(Debug, Deserialize)]
-pub struct ListPayload{
+pub struct UnitListPayload{
     pub scope_ids: Vec<i32>,
     pub unit_size: Option<u64>,
     pub unit_number: Option<u64>,
     pub tag_id: Option<i32>,
 }
+// /resource/v2/list
+#[derive(Debug, Deserialize)]
+pub struct ListPayload{
+    #[serde(flatten)]
+    pub filter: DocFilter,
+    pub limit: Option<u64>,
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub sort: ListSort,
+    #[serde(default)]
+    pub order: ListOrder,
+}
+// by creation, by last update, or by length
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    #[default]
+    Created,
+    Updated,
+    Length,
+}
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListOrder {
+    #[default]
+    Desc,
+    Asc,
+}
 #[derive(Debug, Deserialize)]
 pub struct SequenceAllPayload{
     pub scope_ids: Vec<i32>,
@@ -71,6 +130,8 @@ pub struct SequenceAllPayload{
 pub struct SequenceListPayload{
     pub scope_ids: Vec<i32>,
     pub seq_id: i32, 
+    pub limit: Option<u64>,
+    pub cursor: Option<String>,
 }
 #[derive(Debug, Deserialize)]
 pub struct SeqNewPayload{
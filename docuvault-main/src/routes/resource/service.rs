@@ -1,4 +1,13 @@
-use crate::AppState;
+use std::{fmt::Display, str::FromStr};
+
+use sea_orm::{entity::*, query::*, sea_query::{Expr, Order, Query, SimpleExpr}, Value};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, cursor::Cursor, filter::{DocFilter, in_scopes}}, routes::error::GlobalError};
+
+use super::{object::*, error::ResourceError};
+
+// without a limit a listing fits one page, as it did with the old unit_size default
+const DEFAULT_LIMIT: u64 = 10000;
 
 #[derive(Clone, Debug)]
 pub struct ResourceService {
@@ -10,4 +19,246 @@ impl ResourceService {
             state: shared_state.clone(),
         }
     }
+
+    fn limit(limit: Option<u64>) -> Result<u64, GlobalError> {
+        match limit {
+            Some(0) => Err(ResourceError::UnitSizeZero.into()),
+            Some(limit) => Ok(limit),
+            None => Ok(DEFAULT_LIMIT),
+        }
+    }
+    fn cursor<K: Display + FromStr>(cursor: Option<&str>) -> Result<Option<Cursor<K>>, GlobalError> {
+        cursor
+            .map(|cursor| Cursor::decode(cursor).ok_or(GlobalError::from(ResourceError::InvalidCursor)))
+            .transpose()
+    }
+    // pages are fetched with one extra row to tell whether another page follows
+    fn next_cursor<T, K: Display + FromStr>(items: &mut Vec<T>, limit: u64, cursor_of: impl Fn(&T) -> Cursor<K>) -> Option<String> {
+        if items.len() as u64 <= limit {
+            return None;
+        }
+        items.truncate(limit as usize);
+        items.last().map(|item| cursor_of(item).encode())
+    }
+    // rows strictly past (key, id) in the given order
+    fn after(key: (SimpleExpr, Value), id: (SimpleExpr, i32), order: ListOrder) -> Condition {
+        let ((key, key_value), (id, id_value)) = (key, id);
+        let (key_past, id_past) = match order {
+            ListOrder::Desc => (Expr::expr(key.clone()).lt(key_value.clone()), Expr::expr(id).lt(id_value)),
+            ListOrder::Asc => (Expr::expr(key.clone()).gt(key_value.clone()), Expr::expr(id).gt(id_value)),
+        };
+        Condition::any()
+            .add(key_past)
+            .add(Condition::all().add(Expr::expr(key).eq(key_value)).add(id_past))
+    }
+
+    // related ids of the current docorg row as a sorted array
+    fn ids_of(table: &str, column: &str) -> SimpleExpr {
+        Expr::cust(&format!(
+            r#"ARRAY(SELECT "{table}"."{column}" FROM "{table}" WHERE "{table}"."docorg_id" = "docorg"."id" ORDER BY 1)"#
+        ))
+    }
+
+    fn sort_key(sort: ListSort) -> SimpleExpr {
+        match sort {
+            ListSort::Created => Expr::col((entity::docorg::Entity, entity::docorg::Column::CreatedAt)).into(),
+            ListSort::Updated => Expr::col((entity::docorg::Entity, entity::docorg::Column::UpdatedAt)).into(),
+            // documents not counted yet sort as empty ones
+            ListSort::Length => Expr::cust(r#"COALESCE("docorg"."word_count", 0)"#),
+        }
+    }
+    fn sort_value(sort: ListSort, docs: &Docs) -> i64 {
+        match sort {
+            ListSort::Created => docs.created_at.timestamp_micros(),
+            ListSort::Updated => docs.updated_at.timestamp_micros(),
+            ListSort::Length => docs.word_count.unwrap_or(0) as i64,
+        }
+    }
+    fn key_value(sort: ListSort, key: i64) -> Option<Value> {
+        match sort {
+            ListSort::Created | ListSort::Updated => chrono::NaiveDateTime::from_timestamp_opt(key.div_euclid(1_000_000), (key.rem_euclid(1_000_000) * 1_000) as u32).map(Value::from),
+            ListSort::Length => i32::try_from(key).ok().map(Value::from),
+        }
+    }
+
+    // the columns of Docs
+    fn docs(query: Select<entity::docorg::Entity>) -> Select<entity::docorg::Entity> {
+        query
+            .select_only()
+            .columns([
+                entity::docorg::Column::Id,
+                entity::docorg::Column::Title,
+                entity::docorg::Column::CreatedAt,
+                entity::docorg::Column::UpdatedAt,
+                entity::docorg::Column::WordCount,
+                entity::docorg::Column::ReadingTime,
+                entity::docorg::Column::Excerpt,
+                entity::docorg::Column::Cover,
+            ])
+            .column_as(Self::ids_of("docorg_scope", "scope_id"), "scope_ids")
+            .column_as(Self::ids_of("docorg_sequence", "sequence_id"), "seq_ids")
+            .column_as(Self::ids_of("docorg_tag", "tag_id"), "tag_ids")
+    }
+
+    // the unit_size/unit_number listing of /resource/list, newest first
+    pub async fn list_units(&self, docuser_id: i32, payload: UnitListPayload) -> Result<Vec<Docs>, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &payload.scope_ids[..], docuser_id).await?;
+
+        let unit_size = Self::limit(payload.unit_size)?;
+        let unit_number = payload.unit_number.unwrap_or(0);
+        let filter = DocFilter {
+            scope_ids: payload.scope_ids,
+            tag_id: payload.tag_id,
+            ..Default::default()
+        };
+        let items = Self::docs(filter.select(docuser_id))
+            .order_by_desc(entity::docorg::Column::CreatedAt)
+            .order_by_desc(entity::docorg::Column::Id)
+            .limit(unit_size)
+            .offset(unit_size.saturating_mul(unit_number))
+            .into_model::<Docs>()
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(items)
+    }
+
+    pub async fn list(&self, docuser_id: i32, payload: ListPayload) -> Result<Page<Docs>, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &payload.filter.scope_ids[..], docuser_id).await?;
+        if !payload.filter.valid() {
+            return Err(ResourceError::InvalidFilter.into());
+        }
+
+        let limit = Self::limit(payload.limit)?;
+        let cursor = Self::cursor::<i64>(payload.cursor.as_deref())?;
+        let query = payload.filter.select(docuser_id);
+        let total = query.clone().count(&self.state.db_conn).await?;
+
+        let key = Self::sort_key(payload.sort);
+        let query = match cursor {
+            Some(cursor) => {
+                let value = Self::key_value(payload.sort, cursor.key).ok_or(ResourceError::InvalidCursor)?;
+                query.filter(Self::after(
+                    (key.clone(), value),
+                    (Expr::col((entity::docorg::Entity, entity::docorg::Column::Id)).into(), cursor.id),
+                    payload.order,
+                ))
+            },
+            None => query,
+        };
+        let order = match payload.order {
+            ListOrder::Desc => Order::Desc,
+            ListOrder::Asc => Order::Asc,
+        };
+        let mut items = Self::docs(query)
+            .order_by(key, order.clone())
+            .order_by(entity::docorg::Column::Id, order)
+            .limit(limit.saturating_add(1))
+            .into_model::<Docs>()
+            .all(&self.state.db_conn)
+            .await?;
+
+        let next_cursor = Self::next_cursor(&mut items, limit, |docs| Cursor::new(Self::sort_value(payload.sort, docs), docs.id));
+        Ok(Page { items, next_cursor, total })
+    }
+
+    pub async fn sequence_list(&self, docuser_id: i32, payload: SequenceListPayload) -> Result<Page<SeqDocs>, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &payload.scope_ids[..], docuser_id).await?;
+
+        // the inquired sequence has to be in one of the scopes the user asked with
+        let res = entity::scope_sequence::Entity::find()
+            .filter(entity::scope_sequence::Column::SequenceId.eq(payload.seq_id))
+            .filter(entity::scope_sequence::Column::ScopeId.is_in(payload.scope_ids.iter().copied()))
+            .one(&self.state.db_conn)
+            .await?;
+        if res.is_none() {
+            return Err(ResourceError::SequenceNotExist.into());
+        }
+
+        let limit = Self::limit(payload.limit)?;
+        let cursor = Self::cursor::<i32>(payload.cursor.as_deref())?;
+
+        let query = entity::docorg::Entity::find()
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Id.in_subquery(in_scopes(&payload.scope_ids)))
+            .join_rev(JoinType::InnerJoin, entity::docorg_sequence::Relation::Docorg.def())
+            .filter(entity::docorg_sequence::Column::SequenceId.eq(payload.seq_id));
+        let total = query.clone().count(&self.state.db_conn).await?;
+
+        let query = match cursor {
+            Some(cursor) => query.filter(Self::after(
+                (Expr::col((entity::docorg_sequence::Entity, entity::docorg_sequence::Column::Order)).into(), Value::from(cursor.key)),
+                (Expr::col((entity::docorg::Entity, entity::docorg::Column::Id)).into(), cursor.id),
+                ListOrder::Asc,
+            )),
+            None => query,
+        };
+        let mut items = query
+            .select_only()
+            .columns([
+                entity::docorg::Column::Id,
+                entity::docorg::Column::Title,
+                entity::docorg::Column::CreatedAt,
+                entity::docorg::Column::UpdatedAt,
+                entity::docorg::Column::WordCount,
+                entity::docorg::Column::ReadingTime,
+                entity::docorg::Column::Excerpt,
+                entity::docorg::Column::Cover,
+            ])
+            .column_as(entity::docorg_sequence::Column::SequenceId, "seq_id")
+            .column_as(entity::docorg_sequence::Column::Order, "seq_order")
+            .column_as(Self::ids_of("docorg_scope", "scope_id"), "scope_ids")
+            .column_as(Self::ids_of("docorg_tag", "tag_id"), "tag_ids")
+            .order_by_asc(entity::docorg_sequence::Column::Order)
+            .order_by_asc(entity::docorg::Column::Id)
+            .limit(limit.saturating_add(1))
+            .into_model::<SeqDocs>()
+            .all(&self.state.db_conn)
+            .await?;
+
+        let next_cursor = Self::next_cursor(&mut items, limit, |docs| Cursor::new(docs.seq_order, docs.id));
+        Ok(Page { items, next_cursor, total })
+    }
+
+    // tags used by the user's documents in the scopes, alphabetically
+    pub async fn tags(&self, docuser_id: i32, payload: TagPayload) -> Result<Page<Tags>, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &payload.scope_ids[..], docuser_id).await?;
+
+        let limit = Self::limit(payload.limit)?;
+        let cursor = Self::cursor::<String>(payload.cursor.as_deref())?;
+
+        let docs = Query::select()
+            .column(entity::docorg::Column::Id)
+            .from(entity::docorg::Entity)
+            .and_where(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .and_where(entity::docorg::Column::Id.in_subquery(in_scopes(&payload.scope_ids)))
+            .to_owned();
+        let query = entity::tag::Entity::find()
+            .select_only()
+            .column(entity::tag::Column::Id)
+            .column(entity::tag::Column::Value)
+            .column_as(Expr::col((entity::docorg_tag::Entity, entity::docorg_tag::Column::DocorgId)).count(), "count")
+            .join_rev(JoinType::InnerJoin, entity::docorg_tag::Relation::Tag.def())
+            .filter(entity::docorg_tag::Column::DocorgId.in_subquery(docs))
+            .group_by(entity::tag::Column::Id);
+        let total = query.clone().count(&self.state.db_conn).await?;
+
+        let query = match cursor {
+            Some(cursor) => query.filter(Self::after(
+                (Expr::col((entity::tag::Entity, entity::tag::Column::Value)).into(), Value::from(cursor.key)),
+                (Expr::col((entity::tag::Entity, entity::tag::Column::Id)).into(), cursor.id),
+                ListOrder::Asc,
+            )),
+            None => query,
+        };
+        let mut items = query
+            .order_by_asc(entity::tag::Column::Value)
+            .order_by_asc(entity::tag::Column::Id)
+            .limit(limit.saturating_add(1))
+            .into_model::<Tags>()
+            .all(&self.state.db_conn)
+            .await?;
+
+        let next_cursor = Self::next_cursor(&mut items, limit, |tag| Cursor::new(tag.value.clone(), tag.id));
+        Ok(Page { items, next_cursor, total })
+    }
 }
@@ -0,0 +1,25 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum ScheduleError {
+    ScheduleNotExist,
+    InvalidSchedule,
+    NotPending,
+}
+impl IntoResponse for ScheduleError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::ScheduleNotExist => (StatusCode::BAD_REQUEST, "schedule not exists."),
+            Self::InvalidSchedule => (StatusCode::BAD_REQUEST, "unpublish_at must be in the future and after publish_at."),
+            Self::NotPending => (StatusCode::BAD_REQUEST, "schedule is already published, or being published."),
+        };
+        res.into_response()
+    }
+}
+impl From<ScheduleError> for GlobalError {
+    fn from(value: ScheduleError) -> Self {
+        Self::Schedule(value)
+    }
+}
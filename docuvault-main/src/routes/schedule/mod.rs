@@ -0,0 +1,51 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{Router, routing::post, extract::{State, ConnectInfo}, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::audit::Actor};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::ScheduleService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * publishing a document(a share link, see routes::share) at publish_at and revoking it at unpublish_at.
+ * the schedules are rows run by modules::background::schedule, they survive restarts
+ * and are published once however many instances run.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<ScheduleService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(ScheduleService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/set", post(set))
+        .route("/cancel", post(cancel))
+        .route("/list", post(list))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn set(State(state): State<ServiceState<ScheduleService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ScheduleSetPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let schedule_id = state.service.set(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(ScheduleSetResponse { schedule_id }))
+}
+async fn cancel(State(state): State<ServiceState<ScheduleService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ScheduleCancelPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.cancel(claims.user_id, payload.schedule_id, &Actor::new(claims.user_id, addr)).await?;
+    Ok(())
+}
+async fn list(State(state): State<ServiceState<ScheduleService>>, claims: Claims, Json(payload): Json<ScheduleListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let schedules = state.service.list(claims.user_id, payload.doc_id).await?;
+    Ok(Json(schedules))
+}
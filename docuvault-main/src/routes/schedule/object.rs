@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+// publish_schedule.status
+pub enum PublishScheduleStatus {
+    // waiting for publish_at, the conversion is requested ahead of it
+    SCHEDULED = 0,
+    // share link created, revoked at unpublish_at(if any)
+    PUBLISHED = 1,
+    UNPUBLISHED = 2,
+    // the conversion failed or the document left the scope, see error
+    FAILED = 3,
+    CANCELLED = 4,
+}
+
+// set
+#[derive(Debug, Deserialize)]
+pub struct ScheduleSetPayload {
+    pub doc_id: i32,
+    // the scope the share link is created for
+    pub scope_id: i32,
+    pub c_type: i32,
+    // utc, a time already passed publishes on the next run of the scheduler
+    pub publish_at: chrono::NaiveDateTime,
+    // utc, null to keep the link until it is revoked
+    #[serde(default)]
+    pub unpublish_at: Option<chrono::NaiveDateTime>,
+}
+#[derive(Debug, Serialize)]
+pub struct ScheduleSetResponse {
+    pub schedule_id: i32,
+}
+
+// cancel
+#[derive(Debug, Deserialize)]
+pub struct ScheduleCancelPayload {
+    pub schedule_id: i32,
+}
+
+// list
+#[derive(Debug, Deserialize)]
+pub struct ScheduleListPayload {
+    pub doc_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct ScheduleItem {
+    pub id: i32,
+    pub doc_id: i32,
+    pub scope_id: i32,
+    pub c_type: i32,
+    pub publish_at: chrono::NaiveDateTime,
+    pub unpublish_at: Option<chrono::NaiveDateTime>,
+    pub status: i32,
+    // share link id, once published
+    pub link_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::publish_schedule::Model> for ScheduleItem {
+    fn from(value: crate::entity::publish_schedule::Model) -> Self {
+        let published = value.status == PublishScheduleStatus::PUBLISHED as i32 || value.status == PublishScheduleStatus::UNPUBLISHED as i32;
+        Self {
+            id: value.id,
+            doc_id: value.docorg_id,
+            scope_id: value.scope_id,
+            c_type: value.c_type,
+            publish_at: value.publish_at,
+            unpublish_at: value.unpublish_at,
+            status: value.status,
+            // the id is picked before the link exists
+            link_id: value.link_id.filter(|_| published),
+            error: value.error,
+            created_at: value.created_at,
+        }
+    }
+}
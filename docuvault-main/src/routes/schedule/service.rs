@@ -0,0 +1,111 @@
+use sea_orm::{entity::*, query::*, sea_query::Expr};
+
+use crate::{AppState, entity, modules::audit::Actor, routes::{error::GlobalError, document::{error::DocumentError, object::DocumentStatus}}};
+
+use super::{object::*, error::ScheduleError};
+
+#[derive(Clone, Debug)]
+pub struct ScheduleService {
+    state: AppState,
+}
+impl ScheduleService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    // published by modules::background::schedule, the document must belong to the user and be in the scope
+    pub async fn set(&self, docuser_id: i32, payload: ScheduleSetPayload, actor: &Actor) -> Result<i32, GlobalError> {
+        if !(0..=6).contains(&payload.c_type) {
+            return Err(DocumentError::NoMatchingConvertType.into());
+        }
+        let now = chrono::Utc::now().naive_utc();
+        if payload.unpublish_at.is_some_and(|unpublish_at| unpublish_at <= payload.publish_at || unpublish_at <= now) {
+            return Err(ScheduleError::InvalidSchedule.into());
+        }
+        entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::DocorgId.eq(payload.doc_id))
+            .filter(entity::docorg_scope::Column::ScopeId.eq(payload.scope_id))
+            .join(JoinType::InnerJoin, entity::docorg_scope::Relation::Docorg.def())
+            .filter(entity::docorg::Column::DocuserId.eq(docuser_id))
+            .filter(entity::docorg::Column::Status.eq(DocumentStatus::CREATED as i32))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotExist)?;
+
+        let new_schedule = entity::publish_schedule::ActiveModel {
+            docorg_id: Set(payload.doc_id),
+            docuser_id: Set(docuser_id),
+            scope_id: Set(payload.scope_id),
+            c_type: Set(payload.c_type),
+            publish_at: Set(payload.publish_at),
+            unpublish_at: Set(payload.unpublish_at),
+            status: Set(PublishScheduleStatus::SCHEDULED as i32),
+            created_at: Set(now),
+            ..Default::default()
+        };
+        let txn = self.state.db_conn.begin().await?;
+        let schedule_id = entity::publish_schedule::Entity::insert(new_schedule).exec(&txn).await?.last_insert_id;
+        actor.entry("document.schedule")
+            .targets([payload.doc_id])
+            .scopes([payload.scope_id])
+            .summary(serde_json::json!({
+                "schedule_id": schedule_id,
+                "c_type": payload.c_type,
+                "publish_at": payload.publish_at,
+                "unpublish_at": payload.unpublish_at,
+            }))
+            .record(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(schedule_id)
+    }
+
+    /*
+     * only schedules not published yet. a schedule the scheduler is publishing(its lease is held)
+     * can not be cancelled, so a link is never created for a cancelled schedule.
+     * published links are revoked with /share/revoke.
+     */
+    pub async fn cancel(&self, docuser_id: i32, schedule_id: i32, actor: &Actor) -> Result<(), GlobalError> {
+        let schedule = entity::publish_schedule::Entity::find_by_id(schedule_id)
+            .filter(entity::publish_schedule::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ScheduleError::ScheduleNotExist)?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let txn = self.state.db_conn.begin().await?;
+        let res = entity::publish_schedule::Entity::update_many()
+            .col_expr(entity::publish_schedule::Column::Status, Expr::value(PublishScheduleStatus::CANCELLED as i32))
+            .filter(entity::publish_schedule::Column::Id.eq(schedule_id))
+            .filter(entity::publish_schedule::Column::Status.eq(PublishScheduleStatus::SCHEDULED as i32))
+            .filter(Condition::any()
+                .add(entity::publish_schedule::Column::LeaseUntil.is_null())
+                .add(entity::publish_schedule::Column::LeaseUntil.lt(now)))
+            .exec(&txn)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(ScheduleError::NotPending.into());
+        }
+        actor.entry("document.unschedule")
+            .targets([schedule.docorg_id])
+            .scopes([schedule.scope_id])
+            .summary(serde_json::json!({ "schedule_id": schedule_id }))
+            .record(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    // every schedule of the document, newest first
+    pub async fn list(&self, docuser_id: i32, doc_id: i32) -> Result<Vec<ScheduleItem>, GlobalError> {
+        let schedules = entity::publish_schedule::Entity::find()
+            .filter(entity::publish_schedule::Column::DocorgId.eq(doc_id))
+            .filter(entity::publish_schedule::Column::DocuserId.eq(docuser_id))
+            .order_by_desc(entity::publish_schedule::Column::PublishAt)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(schedules.into_iter().map(|schedule| schedule.into()).collect())
+    }
+}
@@ -0,0 +1,48 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum ShareError {
+    LinkNotExist,
+    LinkExpired,
+    LinkRevoked,
+    ViewLimitReached,
+    InvalidExpiry,
+    InvalidMaxViews,
+    InvalidProtection,
+    PasswordRequired,
+    EmailVerificationRequired,
+    InvalidPassword,
+    DomainNotAllowed,
+    InvalidCode,
+    InvalidEmail,
+    MailNotSent,
+}
+impl IntoResponse for ShareError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::LinkNotExist => (StatusCode::BAD_REQUEST, "share link not exists."),
+            Self::LinkExpired => (StatusCode::UNAUTHORIZED, "share link is expired."),
+            Self::LinkRevoked => (StatusCode::UNAUTHORIZED, "share link is revoked."),
+            Self::ViewLimitReached => (StatusCode::UNAUTHORIZED, "share link reached its view limit."),
+            Self::InvalidExpiry => (StatusCode::BAD_REQUEST, "expiry must be a positive number of minutes."),
+            Self::InvalidMaxViews => (StatusCode::BAD_REQUEST, "max views must be at least 1."),
+            Self::InvalidProtection => (StatusCode::BAD_REQUEST, "share link can be protected by either a password or email domains."),
+            // the front end prompts for the password or an email on these two
+            Self::PasswordRequired => (StatusCode::FORBIDDEN, "share link requires a password."),
+            Self::EmailVerificationRequired => (StatusCode::FORBIDDEN, "share link requires email verification."),
+            Self::InvalidPassword => (StatusCode::UNAUTHORIZED, "invalid password."),
+            Self::DomainNotAllowed => (StatusCode::FORBIDDEN, "email domain is not allowed."),
+            Self::InvalidCode => (StatusCode::UNAUTHORIZED, "invalid or expired code."),
+            Self::InvalidEmail => (StatusCode::BAD_REQUEST, "invalid email address."),
+            Self::MailNotSent => (StatusCode::SERVICE_UNAVAILABLE, "verification mail could not be sent."),
+        };
+        res.into_response()
+    }
+}
+impl From<ShareError> for GlobalError {
+    fn from(value: ShareError) -> Self {
+        Self::Share(value)
+    }
+}
@@ -0,0 +1,70 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{Router, routing::post, extract::{State, ConnectInfo}, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::audit::Actor};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::ShareService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+use super::document::object::PublishResponse;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<ShareService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(ShareService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/create", post(create))
+        .route("/list", post(list))
+        .route("/revoke", post(revoke))
+        .route("/access", post(access))
+        // readers of protected links, no login
+        .route("/unlock", post(unlock))
+        .route("/request_code", post(request_code))
+        .route("/verify_code", post(verify_code))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// the link id is passed to /document as publish_token
+async fn create(State(state): State<ServiceState<ShareService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ShareCreatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let publish_token = state.service.create(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(PublishResponse { publish_token }))
+}
+async fn list(State(state): State<ServiceState<ShareService>>, claims: Claims, Json(payload): Json<ShareListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.list(claims.user_id, payload.doc_id).await?;
+    Ok(Json(res))
+}
+async fn revoke(State(state): State<ServiceState<ShareService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<ShareLinkPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.revoke(claims.user_id, &payload.link_id, &Actor::new(claims.user_id, addr)).await?;
+    Ok(())
+}
+async fn access(State(state): State<ServiceState<ShareService>>, claims: Claims, Json(payload): Json<ShareLinkPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.access(claims.user_id, &payload.link_id).await?;
+    Ok(Json(res))
+}
+// on success the unlock cookie is set, /document accepts the link until it expires
+async fn unlock(State(state): State<ServiceState<ShareService>>, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<UnlockPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let token = state.service.unlock(&payload.link_id, &payload.password, &addr.ip().to_string()).await?;
+    Ok([(header::SET_COOKIE, unlock_cookie(&payload.link_id, &token))])
+}
+async fn request_code(State(state): State<ServiceState<ShareService>>, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<RequestCodePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.request_code(&payload.link_id, &payload.email, &addr.ip().to_string()).await?;
+    Ok(())
+}
+async fn verify_code(State(state): State<ServiceState<ShareService>>, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<VerifyCodePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let token = state.service.verify_code(&payload.link_id, &payload.email, &payload.code, &addr.ip().to_string()).await?;
+    Ok([(header::SET_COOKIE, unlock_cookie(&payload.link_id, &token))])
+}
@@ -0,0 +1,142 @@
+use std::env;
+
+use jsonwebtoken::{decode, Validation};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::routes::auth::object::Keys;
+
+fn default_expires_in() -> Option<i64> {
+    Some(10)
+}
+
+// create(also /document/publish)
+#[derive(Debug, Deserialize)]
+pub struct ShareCreatePayload {
+    pub doc_id: i32,
+    pub scope_ids: Vec<i32>,
+    pub c_type: i32,
+    // minutes, null for a link that never expires. ten minutes when omitted
+    #[serde(default = "default_expires_in")]
+    pub expires_in: Option<i64>,
+    // null for unlimited views
+    #[serde(default)]
+    pub max_views: Option<i32>,
+    // readers must enter the password, or verify an email of one of the domains.
+    // the two can not be combined
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+}
+
+// list
+#[derive(Debug, Deserialize)]
+pub struct ShareListPayload {
+    pub doc_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct ShareLinkItem {
+    pub link_id: String,
+    pub doc_id: i32,
+    pub scope_id: i32,
+    pub c_type: i32,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub max_views: Option<i32>,
+    pub views: i32,
+    pub password: bool,
+    pub allowed_domains: Vec<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::share_link::Model> for ShareLinkItem {
+    fn from(value: crate::entity::share_link::Model) -> Self {
+        Self {
+            link_id: value.link_id,
+            doc_id: value.docorg_id,
+            scope_id: value.scope_id,
+            c_type: value.c_type,
+            expires_at: value.expires_at,
+            max_views: value.max_views,
+            views: value.views,
+            password: value.password_hash.is_some(),
+            allowed_domains: allowed_domains(&value.allowed_domains),
+            created_at: value.created_at,
+        }
+    }
+}
+
+// revoke, access
+#[derive(Debug, Deserialize)]
+pub struct ShareLinkPayload {
+    pub link_id: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ShareAccessItem {
+    pub referrer: Option<String>,
+    pub accessed_at: chrono::NaiveDateTime,
+}
+
+// unlock, request_code, verify_code
+#[derive(Debug, Deserialize)]
+pub struct UnlockPayload {
+    pub link_id: String,
+    pub password: String,
+}
+#[derive(Debug, Deserialize)]
+pub struct RequestCodePayload {
+    pub link_id: String,
+    pub email: String,
+}
+#[derive(Debug, Deserialize)]
+pub struct VerifyCodePayload {
+    pub link_id: String,
+    pub email: String,
+    pub code: String,
+}
+
+pub fn allowed_domains(value: &Option<serde_json::Value>) -> Vec<String> {
+    value.clone()
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/*
+ * a reader who passed the password or email check gets a short lived cookie
+ * (share_<link_id>) holding a jwt bound to the link
+ */
+pub const UNLOCK_DUR_MINUTES: i64 = 30;
+pub const CODE_DUR_MINUTES: i64 = 10;
+
+pub static UNLOCK_KEYS: Lazy<Keys> = Lazy::new(||{
+    tracing::debug!("initializing share unlock keys");
+    Keys::new(env::var("PUBLISH_JWT_SECRET").expect("PUBLISH_JWT_SECRET is not set").as_bytes())
+});
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlockClaims {
+    pub link_id: String,
+    pub exp: i64,
+}
+
+pub fn unlock_cookie_name(link_id: &str) -> String {
+    format!("share_{}", link_id)
+}
+
+pub fn unlock_cookie(link_id: &str, token: &str) -> String {
+    format!("{}={}; Max-Age={}; Path=/; HttpOnly; SameSite=Lax", unlock_cookie_name(link_id), token, UNLOCK_DUR_MINUTES * 60)
+}
+
+// token of the link in a Cookie header value
+pub fn find_unlock_token<'a>(cookie: &'a str, link_id: &str) -> Option<&'a str> {
+    let name = unlock_cookie_name(link_id);
+    cookie.split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+pub fn is_unlocked(token: Option<&str>, link_id: &str) -> bool {
+    token
+        .and_then(|token| decode::<UnlockClaims>(token, &UNLOCK_KEYS.decoding, &Validation::default()).ok())
+        .is_some_and(|data| data.claims.link_id == link_id)
+}
@@ -0,0 +1,318 @@
+use jsonwebtoken::{encode, Header};
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::{entity::*, query::*, sea_query::Expr, DatabaseTransaction};
+
+use crate::{AppState, entity, routes::{error::GlobalError, document::{error::DocumentError, object::DocorgWithScope}}};
+use crate::db::schema::redis::{RedisSchemaHeader, ShareCode};
+use crate::modules::{mail::{self, MailError}, audit::Actor, events::{self, ServerEvent}, redis::{redis_check_attempts, redis_add_attempt, redis_reset_attempts}};
+use crate::routes::auth::{error::AuthError, module::password::{create_hash, verify_password}};
+
+use super::{object::*, error::ShareError};
+
+#[derive(Clone, Debug)]
+pub struct ShareService {
+    state: AppState,
+}
+impl ShareService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    pub fn new_link_id() -> String {
+        rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()
+    }
+
+    async fn get(&self, docuser_id: i32, link_id: &str) -> Result<entity::share_link::Model, GlobalError> {
+        let link = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::LinkId.eq(link_id))
+            .filter(entity::share_link::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ShareError::LinkNotExist)?;
+        Ok(link)
+    }
+
+    // link that is neither revoked nor expired, view limit is checked when the view is counted
+    pub async fn find_open(&self, link_id: &str) -> Result<entity::share_link::Model, GlobalError> {
+        let link = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::LinkId.eq(link_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ShareError::LinkNotExist)?;
+        if link.revoked {
+            return Err(ShareError::LinkRevoked.into());
+        }
+        if link.expires_at.is_some_and(|expires_at| expires_at <= chrono::Utc::now().naive_utc()) {
+            return Err(ShareError::LinkExpired.into());
+        }
+        Ok(link)
+    }
+
+    fn issue_unlock_token(link_id: &str) -> Result<String, GlobalError> {
+        let claims = UnlockClaims {
+            link_id: link_id.to_string(),
+            exp: (chrono::Utc::now() + chrono::Duration::minutes(UNLOCK_DUR_MINUTES)).timestamp(),
+        };
+        let token = encode(&Header::default(), &claims, &UNLOCK_KEYS.encoding).map_err(AuthError::from)?;
+        Ok(token)
+    }
+
+    // the document must belong to the user, be in one of the scopes and have the converted content
+    pub async fn create(&self, docuser_id: i32, payload: ShareCreatePayload, actor: &Actor) -> Result<String, GlobalError> {
+        let expires_at = match payload.expires_in {
+            Some(minutes) if minutes <= 0 => return Err(ShareError::InvalidExpiry.into()),
+            Some(minutes) => Some((chrono::Utc::now() + chrono::Duration::minutes(minutes)).naive_utc()),
+            None => None,
+        };
+        let link_id = Self::new_link_id();
+        let txn = self.state.db_conn.begin().await?;
+        let recorded = self.insert_link(&txn, docuser_id, payload, &link_id, expires_at, actor).await?;
+        txn.commit().await?;
+        events::send(&self.state, recorded).await;
+        Ok(link_id)
+    }
+
+    // the link with its audit entry and event in the caller's transaction, the publish scheduler inserts it with the schedule's claim
+    pub async fn insert_link(&self, txn: &DatabaseTransaction, docuser_id: i32, payload: ShareCreatePayload, link_id: &str, expires_at: Option<chrono::NaiveDateTime>, actor: &Actor) -> Result<events::Recorded, GlobalError> {
+        if payload.max_views.is_some_and(|max_views| max_views < 1) {
+            return Err(ShareError::InvalidMaxViews.into());
+        }
+        if payload.password.is_some() && !payload.allowed_domains.is_empty() {
+            return Err(ShareError::InvalidProtection.into());
+        }
+        let password_hash = match payload.password {
+            Some(password) if password.is_empty() => return Err(ShareError::InvalidProtection.into()),
+            Some(password) => Some(create_hash(password.as_bytes()).map_err(AuthError::from)?),
+            None => None,
+        };
+        let allowed_domains = payload.allowed_domains.iter()
+            .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+            .collect::<Vec<_>>();
+        if allowed_domains.iter().any(|domain| domain.is_empty()) {
+            return Err(ShareError::InvalidProtection.into());
+        }
+
+        let res = entity::docorg_scope::Entity::find()
+            .filter(entity::docorg_scope::Column::DocorgId.eq(payload.doc_id))
+            .filter(entity::docorg_scope::Column::ScopeId.is_in(payload.scope_ids.clone()))
+            .join(JoinType::LeftJoin, entity::docorg_scope::Relation::Docorg.def())
+            .join(JoinType::LeftJoin, entity::docorg::Relation::Docuser.def())
+            .filter(entity::docuser::Column::Id.eq(docuser_id))
+            .column_as(entity::docorg::Column::Id, "id")
+            .columns([entity::docorg::Column::Raw, entity::docorg::Column::DocuserId, entity::docorg::Column::Status])
+            .into_model::<DocorgWithScope>()
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotExist)?;
+
+        let convertres = entity::convert::Entity::find_by_id((payload.doc_id, payload.c_type))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(DocumentError::DocumentNotConverted)?;
+        match convertres.status {
+            0 => return Err(DocumentError::ConvertPending.into()),
+            2 => return Err(DocumentError::ConvertFailed.into()),
+            _ => {}
+        }
+
+        let protected = password_hash.is_some() || !allowed_domains.is_empty();
+        let new_link = entity::share_link::ActiveModel {
+            link_id: Set(link_id.to_string()),
+            docorg_id: Set(res.id),
+            docuser_id: Set(docuser_id),
+            scope_id: Set(res.scope_id),
+            c_type: Set(payload.c_type),
+            expires_at: Set(expires_at),
+            max_views: Set(payload.max_views),
+            password_hash: Set(password_hash),
+            allowed_domains: Set((!allowed_domains.is_empty()).then(|| serde_json::json!(allowed_domains))),
+            ..Default::default()
+        };
+        entity::share_link::Entity::insert(new_link).exec(txn).await?;
+        actor.entry("document.publish")
+            .targets([res.id])
+            .scopes([res.scope_id])
+            .summary(serde_json::json!({
+                "link_id": link_id,
+                "c_type": payload.c_type,
+                "expires_at": expires_at,
+                "max_views": payload.max_views,
+                "protected": protected,
+            }))
+            .record(txn)
+            .await?;
+        Ok(events::record(txn, docuser_id, ServerEvent::DocumentPublished { doc_id: res.id, link_id: link_id.to_string() }).await?)
+    }
+
+    /*
+     * count a view of the link and return it.
+     * protected links need the token of the unlock cookie, no view is counted without it.
+     * the view is counted only if the link is still valid, in a single statement
+     * so that concurrent readers can not go over max_views.
+     */
+    pub async fn resolve(&self, link_id: &str, referrer: Option<String>, unlock_token: Option<&str>) -> Result<entity::share_link::Model, GlobalError> {
+        let link = self.find_open(link_id).await?;
+        if !is_unlocked(unlock_token, link_id) {
+            if link.password_hash.is_some() {
+                return Err(ShareError::PasswordRequired.into());
+            }
+            if !allowed_domains(&link.allowed_domains).is_empty() {
+                return Err(ShareError::EmailVerificationRequired.into());
+            }
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let res = entity::share_link::Entity::update_many()
+            .col_expr(entity::share_link::Column::Views, Expr::col(entity::share_link::Column::Views).add(1))
+            .filter(entity::share_link::Column::LinkId.eq(link_id))
+            .filter(entity::share_link::Column::Revoked.eq(false))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::ExpiresAt.is_null())
+                .add(entity::share_link::Column::ExpiresAt.gt(now)))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::MaxViews.is_null())
+                .add(Expr::col(entity::share_link::Column::Views).lt(Expr::col(entity::share_link::Column::MaxViews))))
+            .exec(&self.state.db_conn)
+            .await?;
+        if res.rows_affected == 0 {
+            // revoked or expired in the meantime, or out of views
+            return Err(match self.find_open(link_id).await {
+                Err(err) => err,
+                Ok(_) => ShareError::ViewLimitReached.into(),
+            });
+        }
+
+        let access = entity::share_access::ActiveModel {
+            share_link_id: Set(link.id),
+            referrer: Set(referrer),
+            ..Default::default()
+        };
+        entity::share_access::Entity::insert(access).exec(&self.state.db_conn).await?;
+        Ok(link)
+    }
+
+    // links that can still be opened
+    pub async fn list(&self, docuser_id: i32, doc_id: i32) -> Result<Vec<ShareLinkItem>, GlobalError> {
+        let links = entity::share_link::Entity::find()
+            .filter(entity::share_link::Column::DocorgId.eq(doc_id))
+            .filter(entity::share_link::Column::DocuserId.eq(docuser_id))
+            .filter(entity::share_link::Column::Revoked.eq(false))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::ExpiresAt.is_null())
+                .add(entity::share_link::Column::ExpiresAt.gt(chrono::Utc::now().naive_utc())))
+            .filter(Condition::any()
+                .add(entity::share_link::Column::MaxViews.is_null())
+                .add(Expr::col(entity::share_link::Column::Views).lt(Expr::col(entity::share_link::Column::MaxViews))))
+            .order_by_desc(entity::share_link::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(links.into_iter().map(|link| link.into()).collect())
+    }
+
+    pub async fn revoke(&self, docuser_id: i32, link_id: &str, actor: &Actor) -> Result<(), GlobalError> {
+        let link = self.get(docuser_id, link_id).await?;
+        let (doc_id, scope_id) = (link.docorg_id, link.scope_id);
+        let mut link: entity::share_link::ActiveModel = link.into();
+        link.revoked = Set(true);
+        let txn = self.state.db_conn.begin().await?;
+        link.update(&txn).await?;
+        actor.entry("document.unpublish")
+            .targets([doc_id])
+            .scopes([scope_id])
+            .summary(serde_json::json!({ "link_id": link_id }))
+            .record(&txn)
+            .await?;
+        let recorded = events::record(&txn, docuser_id, ServerEvent::DocumentUnpublished { doc_id, link_id: link_id.to_string() }).await?;
+        txn.commit().await?;
+        events::send(&self.state, recorded).await;
+        Ok(())
+    }
+
+    pub async fn access(&self, docuser_id: i32, link_id: &str) -> Result<Vec<ShareAccessItem>, GlobalError> {
+        let link = self.get(docuser_id, link_id).await?;
+        let accesses = entity::share_access::Entity::find()
+            .filter(entity::share_access::Column::ShareLinkId.eq(link.id))
+            .order_by_desc(entity::share_access::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(accesses.into_iter().map(|access| ShareAccessItem {
+            referrer: access.referrer,
+            accessed_at: access.accessed_at,
+        }).collect())
+    }
+
+    /*
+     * unlocking, every failure(and every mailed code) counts against the
+     * same limiter as login, keyed by the reader's ip and the link.
+     * a successful check returns the token of the unlock cookie.
+     */
+    pub async fn unlock(&self, link_id: &str, password: &str, ip: &str) -> Result<String, GlobalError> {
+        let attempt_key = format!("share:{}:{}", ip, link_id);
+        redis_check_attempts(self.state.clone(), &attempt_key).await?;
+
+        let link = self.find_open(link_id).await?;
+        let password_hash = link.password_hash.ok_or(ShareError::InvalidProtection)?;
+        if verify_password(&password_hash, password.as_bytes()).is_err() {
+            redis_add_attempt(self.state.clone(), &attempt_key).await?;
+            return Err(ShareError::InvalidPassword.into());
+        }
+        redis_reset_attempts(self.state.clone(), &attempt_key).await?;
+        Self::issue_unlock_token(link_id)
+    }
+
+    pub async fn request_code(&self, link_id: &str, email: &str, ip: &str) -> Result<(), GlobalError> {
+        let attempt_key = format!("share:{}:{}", ip, link_id);
+        redis_check_attempts(self.state.clone(), &attempt_key).await?;
+        redis_add_attempt(self.state.clone(), &attempt_key).await?;
+
+        let link = self.find_open(link_id).await?;
+        let domains = allowed_domains(&link.allowed_domains);
+        if domains.is_empty() {
+            return Err(ShareError::InvalidProtection.into());
+        }
+        let email = email.trim().to_lowercase();
+        match email.rsplit_once('@') {
+            Some((local, domain)) if !local.is_empty() && domains.iter().any(|allowed| allowed == domain) => {},
+            _ => return Err(ShareError::DomainNotAllowed.into()),
+        }
+
+        let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+        let mut schema = ShareCode::new(RedisSchemaHeader {
+            key: format!("{}:{}", link_id, email),
+            expire_at: Some((chrono::Utc::now() + chrono::Duration::minutes(CODE_DUR_MINUTES)).timestamp() as usize),
+            con: self.state.redis_conn.clone(),
+        });
+        schema.set_code(code.clone()).flush().await?;
+
+        mail::send(
+            &email,
+            "docuvault verification code",
+            &format!("Your code to open the shared document is {}.\nIt expires in {} minutes.", code, CODE_DUR_MINUTES),
+        ).await.map_err(|e| match e {
+            MailError::InvalidRecipient => ShareError::InvalidEmail.into(),
+            MailError::NotConfigured | MailError::SendFailed => ShareError::MailNotSent.into(),
+        })
+    }
+
+    pub async fn verify_code(&self, link_id: &str, email: &str, code: &str, ip: &str) -> Result<String, GlobalError> {
+        let attempt_key = format!("share:{}:{}", ip, link_id);
+        redis_check_attempts(self.state.clone(), &attempt_key).await?;
+
+        self.find_open(link_id).await?;
+        let mut schema = ShareCode::new(RedisSchemaHeader {
+            key: format!("{}:{}", link_id, email.trim().to_lowercase()),
+            expire_at: None,
+            con: self.state.redis_conn.clone(),
+        });
+        schema.get_code().await?;
+        if schema.code.as_deref() != Some(code.trim()) {
+            redis_add_attempt(self.state.clone(), &attempt_key).await?;
+            return Err(ShareError::InvalidCode.into());
+        }
+        schema.del_all().await?;
+        redis_reset_attempts(self.state.clone(), &attempt_key).await?;
+        Self::issue_unlock_token(link_id)
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum SiteError {
+    SiteNotExist,
+}
+impl IntoResponse for SiteError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::SiteNotExist => (StatusCode::BAD_REQUEST, "target site not exists."),
+        };
+        res.into_response()
+    }
+}
+impl From<SiteError> for GlobalError {
+    fn from(value: SiteError) -> Self {
+        Self::Site(value)
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::SiteService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<SiteService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(SiteService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/save", post(save))
+        .route("/build", post(build))
+        .route("/status", post(status))
+        .route("/list", post(list))
+        .route("/delete", post(delete))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn save(State(state): State<ServiceState<SiteService>>, claims: Claims, Json(payload): Json<SiteSavePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let site_id = state.service.save(claims.user_id, payload).await?;
+    Ok(Json(SiteSaveResponse { site_id }))
+}
+// the site is built in background, poll /status until it is done
+async fn build(State(state): State<ServiceState<SiteService>>, claims: Claims, Json(payload): Json<SitePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.build(claims.user_id, payload.site_id).await?;
+    Ok(())
+}
+async fn status(State(state): State<ServiceState<SiteService>>, claims: Claims, Json(payload): Json<SitePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.status(claims.user_id, payload.site_id).await?;
+    Ok(Json(res))
+}
+async fn list(State(state): State<ServiceState<SiteService>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.list(claims.user_id).await?;
+    Ok(Json(res))
+}
+async fn delete(State(state): State<ServiceState<SiteService>>, claims: Claims, Json(payload): Json<SitePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.site_id).await?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+// site.status
+pub enum SiteStatus {
+    PENDING = 0,
+    RUNNING = 1,
+    DONE = 2,
+    FAILED = 3,
+}
+
+// save
+#[derive(Debug, Deserialize)]
+pub struct SiteSavePayload {
+    pub scope_id: i32,
+    #[serde(default)]
+    pub auto_build: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct SiteSaveResponse {
+    pub site_id: i32,
+}
+
+// build, status, delete
+#[derive(Debug, Deserialize)]
+pub struct SitePayload {
+    pub site_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct SiteItem {
+    pub id: i32,
+    pub scope_id: i32,
+    pub auto_build: bool,
+    pub status: i32,
+    // download through /file/:object_id, kept until the next build is done
+    pub object_id: Option<String>,
+    pub requested_at: chrono::NaiveDateTime,
+    pub built_at: Option<chrono::NaiveDateTime>,
+}
+impl From<crate::entity::site::Model> for SiteItem {
+    fn from(value: crate::entity::site::Model) -> Self {
+        Self {
+            id: value.id,
+            scope_id: value.scope_id,
+            auto_build: value.auto_build,
+            status: value.status,
+            object_id: value.object_id,
+            requested_at: value.requested_at,
+            built_at: value.built_at,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use sea_orm::{entity::*, query::*};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, background::site, grpc::delete::{delete_client::DeleteClient, DeleteRequest}}, routes::error::GlobalError};
+
+use super::{object::*, error::SiteError};
+
+#[derive(Clone, Debug)]
+pub struct SiteService {
+    state: AppState,
+}
+impl SiteService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    async fn get(&self, docuser_id: i32, site_id: i32) -> Result<entity::site::Model, GlobalError> {
+        let site = entity::site::Entity::find_by_id(site_id)
+            .filter(entity::site::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(SiteError::SiteNotExist)?;
+        Ok(site)
+    }
+
+    // one site per scope, saving again only changes the settings and triggers a build
+    pub async fn save(&self, docuser_id: i32, payload: SiteSavePayload) -> Result<i32, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &[payload.scope_id], docuser_id).await?;
+
+        let site = entity::site::Entity::find()
+            .filter(entity::site::Column::ScopeId.eq(payload.scope_id))
+            .one(&self.state.db_conn)
+            .await?;
+        let site_id = match site {
+            Some(site) => {
+                let mut site: entity::site::ActiveModel = site.into();
+                site.auto_build = Set(payload.auto_build);
+                site.update(&self.state.db_conn).await?.id
+            },
+            None => {
+                let new_site = entity::site::ActiveModel {
+                    docuser_id: Set(docuser_id),
+                    scope_id: Set(payload.scope_id),
+                    auto_build: Set(payload.auto_build),
+                    status: Set(SiteStatus::PENDING as i32),
+                    ..Default::default()
+                };
+                entity::site::Entity::insert(new_site).exec(&self.state.db_conn).await?.last_insert_id
+            },
+        };
+        site::request(&self.state, site_id).await?;
+        Ok(site_id)
+    }
+
+    pub async fn build(&self, docuser_id: i32, site_id: i32) -> Result<(), GlobalError> {
+        let site = self.get(docuser_id, site_id).await?;
+        site::request(&self.state, site.id).await?;
+        Ok(())
+    }
+
+    pub async fn status(&self, docuser_id: i32, site_id: i32) -> Result<SiteItem, GlobalError> {
+        Ok(self.get(docuser_id, site_id).await?.into())
+    }
+
+    pub async fn list(&self, docuser_id: i32) -> Result<Vec<SiteItem>, GlobalError> {
+        let sites = entity::site::Entity::find()
+            .filter(entity::site::Column::DocuserId.eq(docuser_id))
+            .order_by_asc(entity::site::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(sites.into_iter().map(|site| site.into()).collect())
+    }
+
+    pub async fn delete(&self, docuser_id: i32, site_id: i32) -> Result<(), GlobalError> {
+        let site = self.get(docuser_id, site_id).await?;
+        if let Some(object_id) = site.object_id.clone() {
+            let file_proxy_addr = self.state.file_proxy_addr.lock().await.clone();
+            let mut delete_client = DeleteClient::connect(file_proxy_addr).await?;
+            delete_client.delete(tonic::Request::new(DeleteRequest { object_ids: vec![object_id] })).await?;
+        }
+        site.delete(&self.state.db_conn).await?;
+        Ok(())
+    }
+}
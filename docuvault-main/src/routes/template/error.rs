@@ -0,0 +1,25 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum TemplateError {
+    TemplateNotExist,
+    EmptyName,
+    PendingDocumentExists,
+}
+impl IntoResponse for TemplateError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::TemplateNotExist => (StatusCode::BAD_REQUEST, "target template not exists."),
+            Self::EmptyName => (StatusCode::BAD_REQUEST, "template name must not be empty."),
+            Self::PendingDocumentExists => (StatusCode::CONFLICT, "pending document exists, set overwrite to replace it."),
+        };
+        res.into_response()
+    }
+}
+impl From<TemplateError> for GlobalError {
+    fn from(value: TemplateError) -> Self {
+        Self::Template(value)
+    }
+}
@@ -0,0 +1,52 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{Router, routing::post, extract::{State, ConnectInfo}, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::audit::Actor};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::TemplateService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<TemplateService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(TemplateService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/create", post(create))
+        .route("/list", post(list))
+        .route("/delete", post(delete))
+        .route("/instantiate", post(instantiate))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+async fn create(State(state): State<ServiceState<TemplateService>>, claims: Claims, Json(payload): Json<TemplateCreatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let template_id = state.service.create(claims.user_id, payload).await?;
+    Ok(Json(TemplateCreateResponse { template_id }))
+}
+async fn list(State(state): State<ServiceState<TemplateService>>, claims: Claims, Json(payload): Json<TemplateListPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.list(claims.user_id, &payload.scope_ids[..]).await?;
+    Ok(Json(res))
+}
+async fn delete(State(state): State<ServiceState<TemplateService>>, claims: Claims, Json(payload): Json<TemplateDeletePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.template_id).await?;
+    Ok(())
+}
+// renders the template into the pending document, which is then completed through /document/create
+async fn instantiate(State(state): State<ServiceState<TemplateService>>, claims: Claims, ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(payload): Json<TemplateInstantiatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.instantiate(claims.user_id, payload, &Actor::new(claims.user_id, addr)).await?;
+    Ok(Json(res))
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// create
+#[derive(Debug, Deserialize)]
+pub struct TemplateCreatePayload {
+    pub name: String,
+    pub raw: String,
+    // share the template with a scope instead of keeping it private
+    pub scope_id: Option<i32>,
+    pub tags: Vec<String>,
+    pub scope_ids: Vec<i32>,
+    pub seq_id: Option<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct TemplateCreateResponse {
+    pub template_id: i32,
+}
+
+// list
+#[derive(Debug, Deserialize)]
+pub struct TemplateListPayload {
+    pub scope_ids: Vec<i32>,
+}
+#[derive(Debug, Serialize)]
+pub struct TemplateItem {
+    pub id: i32,
+    pub docuser_id: i32,
+    pub scope_id: Option<i32>,
+    pub name: String,
+    pub raw: String,
+    pub tags: Vec<String>,
+    pub scope_ids: Vec<i32>,
+    pub seq_id: Option<i32>,
+    pub prompts: Vec<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+// delete
+#[derive(Debug, Deserialize)]
+pub struct TemplateDeletePayload {
+    pub template_id: i32,
+}
+
+// instantiate
+#[derive(Debug, Deserialize)]
+pub struct TemplateInstantiatePayload {
+    pub template_id: i32,
+    // prompt label -> answer
+    #[serde(default)]
+    pub answers: HashMap<String, String>,
+    // replace the current pending document if there is one
+    #[serde(default)]
+    pub overwrite: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct TemplateInstantiateResponse {
+    pub raw: String,
+    pub tags: Vec<String>,
+    pub scope_ids: Vec<i32>,
+    pub seq_id: Option<i32>,
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use sea_orm::{entity::*, query::*};
+
+use crate::{AppState, entity, modules::{audit::Actor, redis::redis_does_docuser_have_scope, template::{self, TemplateUser}}, routes::{error::GlobalError, resource::error::ResourceError, document::{service::DocumentService, object::PendingCreatePayload}}};
+
+use super::{object::*, error::TemplateError};
+
+#[derive(Clone, Debug)]
+pub struct TemplateService {
+    state: AppState,
+    document_service: DocumentService,
+}
+impl TemplateService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+            document_service: DocumentService::new(shared_state),
+        }
+    }
+
+    async fn check_user_has_seq(&self, docuser_id: i32, seq_id: Option<i32>) -> Result<(), GlobalError> {
+        if let Some(seq_id) = seq_id {
+            let seq = entity::sequence::Entity::find_by_id(seq_id)
+                .filter(entity::sequence::Column::DocuserId.eq(docuser_id))
+                .one(&self.state.db_conn)
+                .await?;
+            if seq.is_none() {
+                return Err(ResourceError::SequenceNotExist.into());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn create(&self, docuser_id: i32, payload: TemplateCreatePayload) -> Result<i32, GlobalError> {
+        if payload.name.trim().is_empty() {
+            return Err(TemplateError::EmptyName.into());
+        }
+        let mut scope_ids = payload.scope_ids.clone();
+        if let Some(scope_id) = payload.scope_id {
+            scope_ids.push(scope_id);
+        }
+        redis_does_docuser_have_scope(self.state.clone(), &scope_ids[..], docuser_id).await?;
+        self.check_user_has_seq(docuser_id, payload.seq_id).await?;
+
+        let tags = payload.tags.into_iter().map(|tag| tag.trim().to_lowercase()).filter(|tag| !tag.is_empty()).collect::<Vec<_>>();
+        let new_template = entity::template::ActiveModel {
+            docuser_id: Set(docuser_id),
+            scope_id: Set(payload.scope_id),
+            name: Set(payload.name.trim().to_string()),
+            raw: Set(payload.raw),
+            tags: Set(serde_json::json!(tags)),
+            scope_ids: Set(serde_json::json!(payload.scope_ids)),
+            seq_id: Set(payload.seq_id),
+            ..Default::default()
+        };
+        let res = entity::template::Entity::insert(new_template).exec(&self.state.db_conn).await?;
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn list(&self, docuser_id: i32, scope_ids: &[i32]) -> Result<Vec<TemplateItem>, GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), scope_ids, docuser_id).await?;
+
+        let mut cond = Condition::any().add(entity::template::Column::DocuserId.eq(docuser_id));
+        if !scope_ids.is_empty() {
+            cond = cond.add(entity::template::Column::ScopeId.is_in(scope_ids.to_vec()));
+        }
+        let res = entity::template::Entity::find()
+            .filter(cond)
+            .order_by_asc(entity::template::Column::Name)
+            .all(&self.state.db_conn)
+            .await?;
+
+        Ok(res.into_iter().map(|m| TemplateItem {
+            prompts: template::prompts(&m.raw),
+            tags: serde_json::from_value(m.tags).unwrap_or_default(),
+            scope_ids: serde_json::from_value(m.scope_ids).unwrap_or_default(),
+            id: m.id,
+            docuser_id: m.docuser_id,
+            scope_id: m.scope_id,
+            name: m.name,
+            raw: m.raw,
+            seq_id: m.seq_id,
+            created_at: m.created_at,
+        }).collect())
+    }
+
+    pub async fn delete(&self, docuser_id: i32, template_id: i32) -> Result<(), GlobalError> {
+        // only the author can delete a template, even a shared one
+        let res = entity::template::Entity::delete_many()
+            .filter(entity::template::Column::Id.eq(template_id))
+            .filter(entity::template::Column::DocuserId.eq(docuser_id))
+            .exec(&self.state.db_conn)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(TemplateError::TemplateNotExist.into());
+        }
+        Ok(())
+    }
+
+    async fn get_visible(&self, docuser_id: i32, template_id: i32) -> Result<entity::template::Model, GlobalError> {
+        let res = entity::template::Entity::find_by_id(template_id)
+            .one(&self.state.db_conn)
+            .await?;
+        let res = match res {
+            Some(res) => res,
+            None => return Err(TemplateError::TemplateNotExist.into()),
+        };
+        if res.docuser_id == docuser_id {
+            return Ok(res);
+        }
+        match res.scope_id {
+            Some(scope_id) if redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await.is_ok() => Ok(res),
+            _ => Err(TemplateError::TemplateNotExist.into()),
+        }
+    }
+
+    pub async fn instantiate(&self, docuser_id: i32, payload: TemplateInstantiatePayload, actor: &Actor) -> Result<TemplateInstantiateResponse, GlobalError> {
+        let target = self.get_visible(docuser_id, payload.template_id).await?;
+
+        let user = entity::docuser::Entity::find_by_id(docuser_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(GlobalError::InternalServerError)?;
+        let user = TemplateUser {
+            id: user.id,
+            nickname: user.nickname,
+            email: user.email,
+        };
+        let raw = template::render(&target.raw, &user, &payload.answers);
+
+        /*
+         * instantiated template becomes the pending document of the user
+         */
+        let pending = self.document_service.get_pending_document_records(docuser_id).await?;
+        let pending_payload = PendingCreatePayload { raw: raw.clone() };
+        match pending.len() {
+            0 => { self.document_service.create_or_get_pending_document(docuser_id, pending_payload, actor).await?; },
+            _ if payload.overwrite => { self.document_service.overwrite_pending_document(docuser_id, pending_payload, actor).await?; },
+            _ => return Err(TemplateError::PendingDocumentExists.into()),
+        }
+
+        /*
+         * the scopes and sequence are the author's, a shared template only suggests
+         * the ones the instantiating user has as well
+         */
+        let mut scope_ids = Vec::new();
+        for scope_id in serde_json::from_value::<Vec<i32>>(target.scope_ids).unwrap_or_default() {
+            if redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await.is_ok() {
+                scope_ids.push(scope_id);
+            }
+        }
+        let seq_id = match self.check_user_has_seq(docuser_id, target.seq_id).await {
+            Ok(()) => target.seq_id,
+            Err(_) => None,
+        };
+
+        Ok(TemplateInstantiateResponse {
+            raw,
+            tags: serde_json::from_value(target.tags).unwrap_or_default(),
+            scope_ids,
+            seq_id,
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum ThemeError {
+    ThemeNotExist,
+    EmptyName,
+    InvalidTemplate,
+}
+impl IntoResponse for ThemeError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::ThemeNotExist => (StatusCode::BAD_REQUEST, "target theme not exists."),
+            Self::EmptyName => (StatusCode::BAD_REQUEST, "theme name must not be empty."),
+            Self::InvalidTemplate => (StatusCode::BAD_REQUEST, "theme template does not render."),
+        };
+        res.into_response()
+    }
+}
+impl From<ThemeError> for GlobalError {
+    fn from(value: ThemeError) -> Self {
+        Self::Theme(value)
+    }
+}
+impl From<tera::Error> for ThemeError {
+    fn from(value: tera::Error) -> Self {
+        dbg!(value);
+        ThemeError::InvalidTemplate
+    }
+}
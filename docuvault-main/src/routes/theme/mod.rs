@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState, modules::theme};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::ThemeService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<ThemeService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(ThemeService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/save", post(save))
+        .route("/list", post(list))
+        .route("/get", post(get))
+        .route("/delete", post(delete))
+        .route("/assign", post(assign))
+        .route("/default", post(default))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// creates a theme, or replaces the given one
+async fn save(State(state): State<ServiceState<ThemeService>>, claims: Claims, Json(payload): Json<ThemeSavePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let theme_id = state.service.save(claims.user_id, payload).await?;
+    Ok(Json(ThemeSaveResponse { theme_id }))
+}
+async fn list(State(state): State<ServiceState<ThemeService>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.list(claims.user_id).await?;
+    Ok(Json(res))
+}
+async fn get(State(state): State<ServiceState<ThemeService>>, claims: Claims, Json(payload): Json<ThemePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let res = state.service.get(claims.user_id, payload.theme_id).await?;
+    Ok(Json(res))
+}
+async fn delete(State(state): State<ServiceState<ThemeService>>, claims: Claims, Json(payload): Json<ThemePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.theme_id).await?;
+    Ok(())
+}
+// theme_id null goes back to the built-in theme
+async fn assign(State(state): State<ServiceState<ThemeService>>, claims: Claims, Json(payload): Json<ThemeAssignPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.assign(claims.user_id, payload.scope_id, payload.theme_id).await?;
+    Ok(())
+}
+// built-in theme, a starting point for custom ones
+async fn default(_claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(ThemeSource {
+        layout: theme::LAYOUT.to_string(),
+        css: theme::STYLE.to_string(),
+    }))
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * layout is a tera template rendered with `page` and `css`, see modules::theme.
+ * {% include "meta.html" %} adds the title, description, OpenGraph and Twitter tags.
+ */
+
+// save
+#[derive(Debug, Deserialize)]
+pub struct ThemeSavePayload {
+    pub theme_id: Option<i32>,
+    pub name: String,
+    pub layout: String,
+    #[serde(default)]
+    pub css: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ThemeSaveResponse {
+    pub theme_id: i32,
+}
+
+// list
+#[derive(Debug, Serialize)]
+pub struct ThemeItem {
+    pub id: i32,
+    pub name: String,
+    pub scope_ids: Vec<i32>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+// get, delete
+#[derive(Debug, Deserialize)]
+pub struct ThemePayload {
+    pub theme_id: i32,
+}
+#[derive(Debug, Serialize)]
+pub struct ThemeDetail {
+    pub id: i32,
+    pub name: String,
+    pub layout: String,
+    pub css: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+// assign
+#[derive(Debug, Deserialize)]
+pub struct ThemeAssignPayload {
+    pub scope_id: i32,
+    pub theme_id: Option<i32>,
+}
+
+// default
+#[derive(Debug, Serialize)]
+pub struct ThemeSource {
+    pub layout: String,
+    pub css: String,
+}
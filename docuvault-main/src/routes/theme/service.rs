@@ -0,0 +1,113 @@
+use sea_orm::{entity::*, query::*, sea_query::Expr};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, theme}, routes::error::GlobalError};
+
+use super::{object::*, error::ThemeError};
+
+#[derive(Clone, Debug)]
+pub struct ThemeService {
+    state: AppState,
+}
+impl ThemeService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    async fn find(&self, docuser_id: i32, theme_id: i32) -> Result<entity::theme::Model, GlobalError> {
+        let theme = entity::theme::Entity::find_by_id(theme_id)
+            .filter(entity::theme::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(ThemeError::ThemeNotExist)?;
+        Ok(theme)
+    }
+
+    pub async fn save(&self, docuser_id: i32, payload: ThemeSavePayload) -> Result<i32, GlobalError> {
+        if payload.name.trim().is_empty() {
+            return Err(ThemeError::EmptyName.into());
+        }
+        theme::validate(&payload.layout, &payload.css).map_err(ThemeError::from)?;
+
+        match payload.theme_id {
+            Some(theme_id) => {
+                let mut theme: entity::theme::ActiveModel = self.find(docuser_id, theme_id).await?.into();
+                theme.name = Set(payload.name.trim().to_string());
+                theme.layout = Set(payload.layout);
+                theme.css = Set(payload.css);
+                theme.updated_at = Set(chrono::Utc::now().naive_utc());
+                theme.update(&self.state.db_conn).await?;
+                Ok(theme_id)
+            },
+            None => {
+                let new_theme = entity::theme::ActiveModel {
+                    docuser_id: Set(docuser_id),
+                    name: Set(payload.name.trim().to_string()),
+                    layout: Set(payload.layout),
+                    css: Set(payload.css),
+                    ..Default::default()
+                };
+                let res = entity::theme::Entity::insert(new_theme).exec(&self.state.db_conn).await?;
+                Ok(res.last_insert_id)
+            },
+        }
+    }
+
+    pub async fn list(&self, docuser_id: i32) -> Result<Vec<ThemeItem>, GlobalError> {
+        let themes = entity::theme::Entity::find()
+            .filter(entity::theme::Column::DocuserId.eq(docuser_id))
+            .order_by_asc(entity::theme::Column::Name)
+            .all(&self.state.db_conn)
+            .await?;
+        let scopes = entity::scope::Entity::find()
+            .filter(entity::scope::Column::DocuserId.eq(docuser_id))
+            .filter(entity::scope::Column::ThemeId.is_not_null())
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(themes.into_iter().map(|theme| ThemeItem {
+            scope_ids: scopes.iter().filter(|scope| scope.theme_id == Some(theme.id)).map(|scope| scope.id).collect(),
+            id: theme.id,
+            name: theme.name,
+            updated_at: theme.updated_at,
+        }).collect())
+    }
+
+    pub async fn get(&self, docuser_id: i32, theme_id: i32) -> Result<ThemeDetail, GlobalError> {
+        let theme = self.find(docuser_id, theme_id).await?;
+        Ok(ThemeDetail {
+            id: theme.id,
+            name: theme.name,
+            layout: theme.layout,
+            css: theme.css,
+            created_at: theme.created_at,
+            updated_at: theme.updated_at,
+        })
+    }
+
+    // scopes using the theme go back to the built-in one(on delete set null)
+    pub async fn delete(&self, docuser_id: i32, theme_id: i32) -> Result<(), GlobalError> {
+        let res = entity::theme::Entity::delete_many()
+            .filter(entity::theme::Column::Id.eq(theme_id))
+            .filter(entity::theme::Column::DocuserId.eq(docuser_id))
+            .exec(&self.state.db_conn)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(ThemeError::ThemeNotExist.into());
+        }
+        Ok(())
+    }
+
+    pub async fn assign(&self, docuser_id: i32, scope_id: i32, theme_id: Option<i32>) -> Result<(), GlobalError> {
+        redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+        if let Some(theme_id) = theme_id {
+            self.find(docuser_id, theme_id).await?;
+        }
+        entity::scope::Entity::update_many()
+            .col_expr(entity::scope::Column::ThemeId, Expr::value(theme_id))
+            .filter(entity::scope::Column::Id.eq(scope_id))
+            .exec(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+}
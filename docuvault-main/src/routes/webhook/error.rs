@@ -0,0 +1,27 @@
+use axum::{response::IntoResponse, http::StatusCode};
+
+use crate::routes::error::GlobalError;
+
+#[derive(Debug)]
+pub enum WebhookError {
+    WebhookNotExist,
+    DeliveryNotExist,
+    InvalidUrl,
+    UnknownEvent,
+}
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> axum::response::Response {
+        let res = match self {
+            Self::WebhookNotExist => (StatusCode::BAD_REQUEST, "webhook does not exist."),
+            Self::DeliveryNotExist => (StatusCode::BAD_REQUEST, "delivery does not exist."),
+            Self::InvalidUrl => (StatusCode::BAD_REQUEST, "url must be an absolute http or https url."),
+            Self::UnknownEvent => (StatusCode::BAD_REQUEST, "unknown event."),
+        };
+        res.into_response()
+    }
+}
+impl From<WebhookError> for GlobalError {
+    fn from(value: WebhookError) -> Self {
+        Self::Webhook(value)
+    }
+}
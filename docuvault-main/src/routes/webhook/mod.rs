@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post, extract::State, Json, response::IntoResponse, http::{Method, header, HeaderValue}};
+use tower_http::cors::CorsLayer;
+
+use crate::{AppState, common::object::ServiceState};
+
+pub mod error;
+pub mod object;
+use object::*;
+pub mod service;
+use service::WebhookService;
+
+use super::error::GlobalError;
+use super::auth::object::Claims;
+
+/*
+ * outgoing webhooks of a user, for all of their documents or the ones of a scope.
+ * deliveries are queued and retried by background::webhooks, /deliveries is their log.
+ */
+pub fn create_router(shared_state: AppState) -> Router {
+    let service_state: ServiceState<WebhookService> = ServiceState {
+        global_state: shared_state.clone(),
+        service: Arc::new(WebhookService::new(shared_state.clone())),
+    };
+    Router::new()
+        .route("/new", post(new))
+        .route("/update", post(update))
+        .route("/delete", post(delete))
+        .route("/list", post(list))
+        .route("/deliveries", post(deliveries))
+        .route("/test", post(test))
+        .route("/redeliver", post(redeliver))
+        .layer(
+            CorsLayer::new()
+                .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
+                .allow_methods([Method::OPTIONS, Method::POST])
+                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+                .allow_credentials(true)
+            )
+        .with_state(service_state)
+}
+// the secret is only shown here and when rotated
+async fn new(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookNewPayload>) -> Result<impl IntoResponse, GlobalError> {
+    let (webhook_id, secret) = state.service.new_webhook(claims.user_id, payload).await?;
+    Ok(Json(WebhookNewResponse { webhook_id, secret }))
+}
+async fn update(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookUpdatePayload>) -> Result<impl IntoResponse, GlobalError> {
+    let secret = state.service.update(claims.user_id, payload).await?;
+    Ok(Json(WebhookUpdateResponse { secret }))
+}
+async fn delete(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookDeletePayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.delete(claims.user_id, payload.webhook_id).await?;
+    Ok(())
+}
+async fn list(State(state): State<ServiceState<WebhookService>>, claims: Claims) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.list(claims.user_id).await?))
+}
+// newest first
+async fn deliveries(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookDeliveriesPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.deliveries(claims.user_id, payload).await?))
+}
+// sends a ping right away and answers with how it went, failures are retried like any delivery
+async fn test(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookTestPayload>) -> Result<impl IntoResponse, GlobalError> {
+    Ok(Json(state.service.test(claims.user_id, payload.webhook_id).await?))
+}
+// queue a delivery again with fresh attempts, e.g. one from the dead letters
+async fn redeliver(State(state): State<ServiceState<WebhookService>>, claims: Claims, Json(payload): Json<WebhookRedeliverPayload>) -> Result<impl IntoResponse, GlobalError> {
+    state.service.redeliver(claims.user_id, payload.delivery_id).await?;
+    Ok(())
+}
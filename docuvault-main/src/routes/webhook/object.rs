@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+// webhook_delivery.status
+pub enum WebhookDeliveryStatus {
+    PENDING = 0,
+    DELIVERED = 1,
+    // failed, tried again at next_attempt_at
+    RETRYING = 2,
+    // failed modules::webhook::MAX_ATTEMPTS times, left for /redeliver
+    DEAD = 3,
+}
+
+// new
+#[derive(Debug, Deserialize)]
+pub struct WebhookNewPayload {
+    pub url: String,
+    // only events of the scope's documents and sequences, otherwise all of the user's
+    pub scope_id: Option<i32>,
+    // names as in modules::webhook::EVENTS, empty for all of them
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+#[derive(Debug, Serialize)]
+pub struct WebhookNewResponse {
+    pub webhook_id: i32,
+    pub secret: String,
+}
+
+// update, fields left out stay as they are
+#[derive(Debug, Deserialize)]
+pub struct WebhookUpdatePayload {
+    pub webhook_id: i32,
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+    #[serde(default)]
+    pub rotate_secret: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct WebhookUpdateResponse {
+    // the new secret when rotated
+    pub secret: Option<String>,
+}
+
+// delete
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeletePayload {
+    pub webhook_id: i32,
+}
+
+// list
+#[derive(Debug, Serialize)]
+pub struct WebhookItem {
+    pub id: i32,
+    pub scope_id: Option<i32>,
+    pub url: String,
+    pub events: serde_json::Value,
+    pub active: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+impl From<crate::entity::webhook::Model> for WebhookItem {
+    fn from(value: crate::entity::webhook::Model) -> Self {
+        Self {
+            id: value.id,
+            scope_id: value.scope_id,
+            url: value.url,
+            events: value.events,
+            active: value.active,
+            created_at: value.created_at,
+        }
+    }
+}
+
+// deliveries
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesPayload {
+    pub webhook_id: i32,
+    pub status: Option<i32>,
+    // id of the last delivery of the previous page
+    pub before: Option<i32>,
+    pub limit: Option<u64>,
+}
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryItem {
+    pub id: i32,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: i32,
+    pub attempts: i32,
+    pub next_attempt_at: chrono::NaiveDateTime,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub delivered_at: Option<chrono::NaiveDateTime>,
+}
+impl From<crate::entity::webhook_delivery::Model> for WebhookDeliveryItem {
+    fn from(value: crate::entity::webhook_delivery::Model) -> Self {
+        Self {
+            id: value.id,
+            event: value.event,
+            payload: value.payload,
+            status: value.status,
+            attempts: value.attempts,
+            next_attempt_at: value.next_attempt_at,
+            response_status: value.response_status,
+            error: value.error,
+            created_at: value.created_at,
+            delivered_at: value.delivered_at,
+        }
+    }
+}
+
+// test
+#[derive(Debug, Deserialize)]
+pub struct WebhookTestPayload {
+    pub webhook_id: i32,
+}
+
+// redeliver
+#[derive(Debug, Deserialize)]
+pub struct WebhookRedeliverPayload {
+    pub delivery_id: i32,
+}
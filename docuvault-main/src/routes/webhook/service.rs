@@ -0,0 +1,151 @@
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::{entity::*, query::*};
+
+use crate::{AppState, entity, modules::{redis::redis_does_docuser_have_scope, background::webhooks, webhook}, routes::error::GlobalError};
+
+use super::{object::*, error::WebhookError};
+
+const DELIVERIES_LIMIT: u64 = 50;
+const MAX_DELIVERIES_LIMIT: u64 = 200;
+
+#[derive(Clone, Debug)]
+pub struct WebhookService {
+    state: AppState,
+}
+impl WebhookService {
+    pub fn new(shared_state: AppState) -> Self {
+        Self {
+            state: shared_state.clone(),
+        }
+    }
+
+    async fn owned(&self, docuser_id: i32, webhook_id: i32) -> Result<entity::webhook::Model, GlobalError> {
+        let hook = entity::webhook::Entity::find_by_id(webhook_id)
+            .filter(entity::webhook::Column::DocuserId.eq(docuser_id))
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(WebhookError::WebhookNotExist)?;
+        Ok(hook)
+    }
+
+    fn validate(url: &str, events: &[String]) -> Result<(), WebhookError> {
+        if !webhook::valid_url(url) {
+            return Err(WebhookError::InvalidUrl);
+        }
+        if events.iter().any(|event| !webhook::EVENTS.contains(&event.as_str())) {
+            return Err(WebhookError::UnknownEvent);
+        }
+        Ok(())
+    }
+
+    pub async fn new_webhook(&self, docuser_id: i32, payload: WebhookNewPayload) -> Result<(i32, String), GlobalError> {
+        let url = payload.url.trim().to_string();
+        Self::validate(&url, &payload.events)?;
+        if let Some(scope_id) = payload.scope_id {
+            redis_does_docuser_have_scope(self.state.clone(), &[scope_id], docuser_id).await?;
+        }
+
+        let secret = webhook::new_secret();
+        let new_hook = entity::webhook::ActiveModel {
+            docuser_id: Set(docuser_id),
+            scope_id: Set(payload.scope_id),
+            url: Set(url),
+            secret: Set(secret.clone()),
+            events: Set(serde_json::json!(payload.events)),
+            ..Default::default()
+        };
+        let hook = new_hook.insert(&self.state.db_conn).await?;
+        Ok((hook.id, secret))
+    }
+
+    pub async fn update(&self, docuser_id: i32, payload: WebhookUpdatePayload) -> Result<Option<String>, GlobalError> {
+        let hook = self.owned(docuser_id, payload.webhook_id).await?;
+        let url = payload.url.map_or_else(|| hook.url.clone(), |url| url.trim().to_string());
+        Self::validate(&url, payload.events.as_deref().unwrap_or_default())?;
+
+        let mut hook: entity::webhook::ActiveModel = hook.into();
+        hook.url = Set(url);
+        if let Some(events) = payload.events {
+            hook.events = Set(serde_json::json!(events));
+        }
+        if let Some(active) = payload.active {
+            hook.active = Set(active);
+        }
+        let secret = payload.rotate_secret.then(webhook::new_secret);
+        if let Some(secret) = &secret {
+            hook.secret = Set(secret.clone());
+        }
+        hook.update(&self.state.db_conn).await?;
+        Ok(secret)
+    }
+
+    // its deliveries go with it
+    pub async fn delete(&self, docuser_id: i32, webhook_id: i32) -> Result<(), GlobalError> {
+        let hook = self.owned(docuser_id, webhook_id).await?;
+        entity::webhook::Entity::delete_by_id(hook.id)
+            .exec(&self.state.db_conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list(&self, docuser_id: i32) -> Result<Vec<WebhookItem>, GlobalError> {
+        let hooks = entity::webhook::Entity::find()
+            .filter(entity::webhook::Column::DocuserId.eq(docuser_id))
+            .order_by_asc(entity::webhook::Column::Id)
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(hooks.into_iter().map(|hook| hook.into()).collect())
+    }
+
+    pub async fn deliveries(&self, docuser_id: i32, payload: WebhookDeliveriesPayload) -> Result<Vec<WebhookDeliveryItem>, GlobalError> {
+        let hook = self.owned(docuser_id, payload.webhook_id).await?;
+        let deliveries = entity::webhook_delivery::Entity::find()
+            .filter(entity::webhook_delivery::Column::WebhookId.eq(hook.id))
+            .apply_if(payload.status, |query, status| query.filter(entity::webhook_delivery::Column::Status.eq(status)))
+            .apply_if(payload.before, |query, before| query.filter(entity::webhook_delivery::Column::Id.lt(before)))
+            .order_by_desc(entity::webhook_delivery::Column::Id)
+            .limit(payload.limit.unwrap_or(DELIVERIES_LIMIT).min(MAX_DELIVERIES_LIMIT))
+            .all(&self.state.db_conn)
+            .await?;
+        Ok(deliveries.into_iter().map(|delivery| delivery.into()).collect())
+    }
+
+    /*
+     * the ping is queued like any delivery but claimed from the start(next attempt past now),
+     * so the worker leaves the first attempt to this request.
+     */
+    pub async fn test(&self, docuser_id: i32, webhook_id: i32) -> Result<WebhookDeliveryItem, GlobalError> {
+        let hook = self.owned(docuser_id, webhook_id).await?;
+        let now = chrono::Utc::now().naive_utc();
+        let id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(24).map(char::from).collect();
+        let data = serde_json::json!({ "type": webhook::TEST_EVENT, "webhook_id": hook.id });
+        let delivery = entity::webhook_delivery::ActiveModel {
+            webhook_id: Set(hook.id),
+            event: Set(webhook::TEST_EVENT.to_string()),
+            payload: Set(webhook::payload(&id, webhook::TEST_EVENT, now, data)),
+            status: Set(WebhookDeliveryStatus::PENDING as i32),
+            attempts: Set(0),
+            next_attempt_at: Set(now + chrono::Duration::minutes(5)),
+            created_at: Set(now),
+            ..Default::default()
+        };
+        let delivery = delivery.insert(&self.state.db_conn).await?;
+        Ok(webhooks::attempt(&self.state, delivery).await?.into())
+    }
+
+    pub async fn redeliver(&self, docuser_id: i32, delivery_id: i32) -> Result<(), GlobalError> {
+        let delivery = entity::webhook_delivery::Entity::find_by_id(delivery_id)
+            .one(&self.state.db_conn)
+            .await?
+            .ok_or(WebhookError::DeliveryNotExist)?;
+        self.owned(docuser_id, delivery.webhook_id).await
+            .map_err(|_| WebhookError::DeliveryNotExist)?;
+
+        let mut delivery: entity::webhook_delivery::ActiveModel = delivery.into();
+        delivery.status = Set(WebhookDeliveryStatus::PENDING as i32);
+        delivery.attempts = Set(0);
+        delivery.next_attempt_at = Set(chrono::Utc::now().naive_utc());
+        delivery.update(&self.state.db_conn).await?;
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
-use tonic::{Response, Status, Request};
+use tonic::{Response, Status, Request, Streaming};
 
 use crate::{AppState, module::obj_hash::hash_to_limit};
 
-use self::download::{DownloadRequest, DownloadResponse, download_server::Download, download_client::DownloadClient};
+use self::download::{DownloadRequest, DownloadResponse, DownloadChunk, download_server::Download, download_client::DownloadClient};
 
 pub mod download {
     use tonic::include_proto;
@@ -15,6 +15,8 @@ pub struct DownloadService {
 
 #[tonic::async_trait]
 impl Download for DownloadService {
+    type DownloadStreamStream = Streaming<DownloadChunk>;
+
     async fn download(&self, request: Request<DownloadRequest>) -> Result<Response<DownloadResponse>, Status> {
         let req = request.into_inner();
         let addr = self.state.file_server_addr.lock().await[hash_to_limit(self.state.file_server_num as u64, &req.object_id)];
@@ -22,6 +24,15 @@ impl Download for DownloadService {
         let mut download_client = DownloadClient::connect(addr).await.unwrap();
         let res = download_client.download(Request::new(req)).await?;
 
+        return Ok(res);
+    }
+    async fn download_stream(&self, request: Request<DownloadRequest>) -> Result<Response<Self::DownloadStreamStream>, Status> {
+        let req = request.into_inner();
+        let addr = self.state.file_server_addr.lock().await[hash_to_limit(self.state.file_server_num as u64, &req.object_id)];
+
+        let mut download_client = DownloadClient::connect(addr).await.unwrap();
+        let res = download_client.download_stream(Request::new(req)).await?;
+
         return Ok(res);
     }
 }